@@ -0,0 +1,162 @@
+//! CPI interface to the Clone program's `aggregator_swap`.
+//!
+//! This crate only depends on `solana-program` and `borsh`, so routers can
+//! call into Clone without building against the program or its Anchor
+//! version. The instruction layout is part of the program's interface and
+//! only changes together with this crate, and the program's integration tests
+//! build their swaps through this crate.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    declare_id,
+    instruction::{AccountMeta, Instruction},
+    program::{get_return_data, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+declare_id!("C1onEW2kPetmHmwe74YC1ESx3LnFEpVau6g2pg4fHycr");
+
+pub const CLONE_PROGRAM_SEED: &str = "clone";
+pub const POOLS_SEED: &str = "pools";
+pub const ORACLES_SEED: &str = "oracles";
+
+/// Anchor discriminator of `aggregator_swap`.
+pub const AGGREGATOR_SWAP_DISCRIMINATOR: [u8; 8] = [80, 210, 254, 12, 80, 229, 144, 251];
+
+pub fn clone_address() -> Pubkey {
+    Pubkey::find_program_address(&[CLONE_PROGRAM_SEED.as_ref()], &ID).0
+}
+
+pub fn pools_address() -> Pubkey {
+    Pubkey::find_program_address(&[POOLS_SEED.as_ref()], &ID).0
+}
+
+pub fn oracles_address() -> Pubkey {
+    Pubkey::find_program_address(&[ORACLES_SEED.as_ref()], &ID).0
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct AggregatorSwapArgs {
+    pub pool_index: u8,
+    pub quantity: u64,
+    pub quantity_is_input: bool,
+    pub quantity_is_collateral: bool,
+    /// Minimum output for exact input swaps, maximum input otherwise.
+    pub result_threshold: u64,
+}
+
+/// Amounts moved by a swap, read from the program's return data.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct SwapResult {
+    pub input: u64,
+    pub output: u64,
+    pub liquidity_fees_paid: u64,
+    pub treasury_fees_paid: u64,
+}
+
+/// Staking accounts of a swap. `user_staking_account` applies the user's fee
+/// tier to the swap, and `staking_reward_vault`, stored in the staking
+/// program's `clone_staking` account, takes the stakers' share of treasury
+/// fees. Without the vault the treasury keeps all fees.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StakingAccounts {
+    pub clone_staking: Pubkey,
+    pub user_staking_account: Option<Pubkey>,
+    pub clone_staking_program: Pubkey,
+    pub staking_reward_vault: Option<Pubkey>,
+}
+
+/// Accounts of `aggregator_swap`. `collateral_mint` and `collateral_vault` are
+/// stored in the program's `Clone` account. Of the treasury accounts, only
+/// the one for the side fees are paid in is required: onAssets when buying
+/// onAssets, collateral when selling them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AggregatorSwapAccounts {
+    pub user: Pubkey,
+    pub user_collateral_token_account: Pubkey,
+    pub user_onasset_token_account: Pubkey,
+    pub onasset_mint: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_vault: Pubkey,
+    pub treasury_onasset_token_account: Option<Pubkey>,
+    pub treasury_collateral_token_account: Option<Pubkey>,
+    pub token_program: Pubkey,
+    pub staking: Option<StakingAccounts>,
+}
+
+impl AggregatorSwapAccounts {
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        // Anchor passes the program id in place of a missing optional account.
+        let optional = |account: Option<Pubkey>, is_writable: bool| match account {
+            Some(account) if is_writable => AccountMeta::new(account, false),
+            Some(account) => AccountMeta::new_readonly(account, false),
+            None => AccountMeta::new_readonly(ID, false),
+        };
+        vec![
+            AccountMeta::new_readonly(self.user, true),
+            AccountMeta::new(clone_address(), false),
+            AccountMeta::new(pools_address(), false),
+            AccountMeta::new(oracles_address(), false),
+            AccountMeta::new(self.user_collateral_token_account, false),
+            AccountMeta::new(self.user_onasset_token_account, false),
+            AccountMeta::new(self.onasset_mint, false),
+            AccountMeta::new_readonly(self.collateral_mint, false),
+            AccountMeta::new(self.collateral_vault, false),
+            optional(self.treasury_onasset_token_account, true),
+            optional(self.treasury_collateral_token_account, true),
+            AccountMeta::new_readonly(self.token_program, false),
+            optional(self.staking.map(|staking| staking.clone_staking), false),
+            optional(
+                self.staking
+                    .and_then(|staking| staking.user_staking_account),
+                false,
+            ),
+            optional(
+                self.staking.map(|staking| staking.clone_staking_program),
+                false,
+            ),
+            optional(
+                self.staking
+                    .and_then(|staking| staking.staking_reward_vault),
+                true,
+            ),
+        ]
+    }
+}
+
+pub fn aggregator_swap_instruction(
+    accounts: &AggregatorSwapAccounts,
+    args: AggregatorSwapArgs,
+) -> Instruction {
+    let mut data = AGGREGATOR_SWAP_DISCRIMINATOR.to_vec();
+    data.extend(args.try_to_vec().unwrap());
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(),
+        data,
+    }
+}
+
+/// Swaps through Clone from within another program. `account_infos` must
+/// hold every account in `accounts` plus the Clone program; `signer_seeds`
+/// sign for `user` when it's a PDA.
+pub fn aggregator_swap<'info>(
+    accounts: &AggregatorSwapAccounts,
+    args: AggregatorSwapArgs,
+    account_infos: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<SwapResult, ProgramError> {
+    invoke_signed(
+        &aggregator_swap_instruction(accounts, args),
+        account_infos,
+        signer_seeds,
+    )?;
+    match get_return_data() {
+        Some((program_id, data)) if program_id == ID => {
+            SwapResult::try_from_slice(&data).map_err(|_| ProgramError::InvalidAccountData)
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
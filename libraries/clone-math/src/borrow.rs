@@ -0,0 +1,99 @@
+use crate::error::*;
+use crate::oracle::{check_feed_update, OraclePrice};
+use rust_decimal::prelude::*;
+
+pub fn check_mint_collateral_sufficient(
+    pool_oracle: &OraclePrice,
+    collateral_oracle: &OraclePrice,
+    asset_amount_borrowed: Decimal,
+    min_overcollateral_ratio: Decimal,
+    collateralization_ratio: Decimal,
+    collateral_amount: Decimal,
+    slot: u64,
+) -> Result<()> {
+    check_feed_update(pool_oracle, slot)?;
+    check_feed_update(collateral_oracle, slot)?;
+    let pool_price = pool_oracle
+        .get_price()?
+        .checked_div(collateral_oracle.get_price()?)
+        .ok_or(MathError::CheckedMathError)?;
+
+    let numerator = collateral_amount
+        .checked_mul(collateralization_ratio)
+        .ok_or(MathError::CheckedMathError)?;
+    let denominator = pool_price
+        .checked_mul(asset_amount_borrowed)
+        .ok_or(MathError::CheckedMathError)?;
+    if asset_amount_borrowed != Decimal::ZERO
+        && numerator
+            .checked_div(denominator)
+            .ok_or(MathError::CheckedMathError)?
+            < min_overcollateral_ratio
+    {
+        return Err(MathError::InvalidMintCollateralRatio);
+    }
+
+    Ok(())
+}
+
+/// Collateral value over borrowed value, after applying the collateral's
+/// `collateralization_ratio`. This is the ratio `check_mint_collateral_sufficient`
+/// compares to the pool's minimum; `None` when nothing is borrowed.
+pub fn calculate_borrow_collateral_ratio(
+    pool_oracle: &OraclePrice,
+    collateral_oracle: &OraclePrice,
+    asset_amount_borrowed: Decimal,
+    collateralization_ratio: Decimal,
+    collateral_amount: Decimal,
+    slot: u64,
+) -> Result<Option<Decimal>> {
+    check_feed_update(pool_oracle, slot)?;
+    check_feed_update(collateral_oracle, slot)?;
+    if asset_amount_borrowed.is_zero() {
+        return Ok(None);
+    }
+    let pool_price = pool_oracle
+        .get_price()?
+        .checked_div(collateral_oracle.get_price()?)
+        .ok_or(MathError::CheckedMathError)?;
+
+    let numerator = collateral_amount
+        .checked_mul(collateralization_ratio)
+        .ok_or(MathError::CheckedMathError)?;
+    let denominator = pool_price
+        .checked_mul(asset_amount_borrowed)
+        .ok_or(MathError::CheckedMathError)?;
+    Ok(Some(
+        numerator
+            .checked_div(denominator)
+            .ok_or(MathError::CheckedMathError)?,
+    ))
+}
+
+/// Onasset oracle price above which a borrow position falls below
+/// `min_overcollateral_ratio` and can be liquidated, with the collateral price
+/// held fixed. `None` when nothing is borrowed.
+pub fn calculate_borrow_liquidation_price(
+    collateral_oracle: &OraclePrice,
+    asset_amount_borrowed: Decimal,
+    min_overcollateral_ratio: Decimal,
+    collateralization_ratio: Decimal,
+    collateral_amount: Decimal,
+) -> Result<Option<Decimal>> {
+    if asset_amount_borrowed.is_zero() {
+        return Ok(None);
+    }
+    let collateral_price = collateral_oracle.get_price()?;
+    let numerator = collateral_amount
+        .checked_mul(collateralization_ratio)
+        .and_then(|value| value.checked_mul(collateral_price))
+        .ok_or(MathError::CheckedMathError)?;
+    let denominator = asset_amount_borrowed
+        .checked_mul(min_overcollateral_ratio)
+        .ok_or(MathError::CheckedMathError)?;
+    Ok(Some(
+        numerator
+            .checked_div(denominator)
+            .ok_or(MathError::CheckedMathError)?,
+    ))
+}
@@ -0,0 +1,20 @@
+use crate::error::*;
+use core::convert::TryInto;
+use rust_decimal::prelude::*;
+
+/// Parameters of the collateral token that the math depends on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CollateralParams {
+    pub scale: u8,
+    pub collateralization_ratio: u8,
+}
+
+impl CollateralParams {
+    pub fn to_collateral_decimal<T: TryInto<i64>>(&self, value: T) -> Result<Decimal> {
+        if let Ok(num) = TryInto::<i64>::try_into(value) {
+            Ok(Decimal::new(num, self.scale.into()))
+        } else {
+            Err(MathError::InvalidConversion)
+        }
+    }
+}
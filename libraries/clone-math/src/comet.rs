@@ -0,0 +1,440 @@
+use crate::collateral::CollateralParams;
+use crate::decimal::{
+    rescale_down, rescale_up, to_clone_decimal, to_pct_decimal, to_ratio_decimal, CLONE_TOKEN_SCALE,
+};
+use crate::error::*;
+use crate::oracle::{check_feed_update, OraclePrice};
+use crate::pool::PoolState;
+use rust_decimal::prelude::*;
+
+/// Liquidity a comet has committed to a single pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PositionState {
+    pub committed_collateral_liquidity: u64,
+    pub collateral_ild_rebate: i64,
+    pub onasset_ild_rebate: i64,
+}
+
+pub fn calculate_liquidity_proportion_from_committed_usd(
+    position_committed_usd: Decimal,
+    total_committed_usd: Decimal,
+) -> Result<Decimal> {
+    if total_committed_usd > Decimal::ZERO {
+        Ok(position_committed_usd
+            .checked_div(total_committed_usd)
+            .ok_or(MathError::CheckedMathError)?)
+    } else {
+        Ok(Decimal::ZERO)
+    }
+}
+
+pub fn calculate_liquidity_proportion_from_collateral(
+    collateral_liquidity_value: Decimal,
+    collateral_amm_value: Decimal,
+) -> Result<Decimal> {
+    let denominator = collateral_amm_value
+        .checked_add(collateral_liquidity_value)
+        .ok_or(MathError::CheckedMathError)?;
+    collateral_liquidity_value
+        .checked_div(denominator)
+        .ok_or(MathError::CheckedMathError)
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthScore {
+    pub score: Decimal,
+    pub effective_collateral: Decimal,
+    pub total_il_term: Decimal,
+    pub total_position_term: Decimal,
+}
+
+impl HealthScore {
+    pub fn is_healthy(&self) -> bool {
+        self.score.is_sign_positive()
+    }
+}
+
+/// ILD moved from the pool onto a position's rebate when `collateral_amount`
+/// of liquidity is committed. The rebate is credited to the user, so it
+/// rounds down.
+pub fn calculate_committed_liquidity_ild(
+    collateral_amount: u64,
+    pool: &PoolState,
+    collateral: &CollateralParams,
+) -> Result<(Decimal, Decimal)> {
+    let proportion_value = liquidity_proportion_of_pool(collateral_amount, pool, collateral)?;
+    let collateral_ild = rescale_down(
+        collateral
+            .to_collateral_decimal(pool.collateral_ild)?
+            .checked_mul(proportion_value)
+            .ok_or(MathError::CheckedMathError)?,
+        collateral.scale.into(),
+    );
+    let onasset_ild = rescale_down(
+        to_clone_decimal(pool.onasset_ild)?
+            .checked_mul(proportion_value)
+            .ok_or(MathError::CheckedMathError)?,
+        CLONE_TOKEN_SCALE,
+    );
+    Ok((collateral_ild, onasset_ild))
+}
+
+/// ILD claimed from the pool by a position withdrawing `collateral_amount`
+/// of liquidity. The claim becomes the position's debt, so it rounds up.
+pub fn calculate_withdrawn_liquidity_ild(
+    collateral_amount: u64,
+    pool: &PoolState,
+    collateral: &CollateralParams,
+) -> Result<(Decimal, Decimal)> {
+    let proportion_value = liquidity_proportion_of_pool(collateral_amount, pool, collateral)?;
+    let collateral_ild = rescale_up(
+        collateral
+            .to_collateral_decimal(pool.collateral_ild)?
+            .checked_mul(proportion_value)
+            .ok_or(MathError::CheckedMathError)?,
+        collateral.scale.into(),
+    );
+    let onasset_ild = rescale_up(
+        to_clone_decimal(pool.onasset_ild)?
+            .checked_mul(proportion_value)
+            .ok_or(MathError::CheckedMathError)?,
+        CLONE_TOKEN_SCALE,
+    );
+    Ok((collateral_ild, onasset_ild))
+}
+
+fn liquidity_proportion_of_pool(
+    collateral_amount: u64,
+    pool: &PoolState,
+    collateral: &CollateralParams,
+) -> Result<Decimal> {
+    let committed_collateral_value =
+        collateral.to_collateral_decimal(pool.committed_collateral_liquidity)?;
+    if committed_collateral_value > Decimal::ZERO {
+        collateral
+            .to_collateral_decimal(collateral_amount)?
+            .checked_div(committed_collateral_value)
+            .ok_or(MathError::CheckedMathError)
+    } else {
+        Ok(Decimal::ZERO)
+    }
+}
+
+pub fn calculate_liquidity_position_loss(
+    liquidity_position: &PositionState,
+    pool: &PoolState,
+    onasset_oracle: &OraclePrice,
+    collateral_oracle: &OraclePrice,
+    collateral: &CollateralParams,
+) -> Result<(Decimal, Decimal)> {
+    let position_committed_collateral_liquidity =
+        collateral.to_collateral_decimal(liquidity_position.committed_collateral_liquidity)?;
+    let total_committed_collateral_liquidity =
+        collateral.to_collateral_decimal(pool.committed_collateral_liquidity)?;
+
+    let proportional_value = if total_committed_collateral_liquidity > Decimal::ZERO {
+        position_committed_collateral_liquidity
+            .checked_div(total_committed_collateral_liquidity)
+            .ok_or(MathError::CheckedMathError)?
+    } else {
+        Decimal::ZERO
+    };
+
+    let collateral_ild_share = rescale_up(
+        collateral
+            .to_collateral_decimal(pool.collateral_ild)?
+            .checked_mul(proportional_value)
+            .ok_or(MathError::CheckedMathError)?
+            .checked_sub(
+                collateral.to_collateral_decimal(liquidity_position.collateral_ild_rebate)?,
+            )
+            .ok_or(MathError::CheckedMathError)?,
+        collateral.scale.into(),
+    );
+    let onasset_ild_share = rescale_up(
+        to_clone_decimal(pool.onasset_ild)?
+            .checked_mul(proportional_value)
+            .ok_or(MathError::CheckedMathError)?
+            .checked_sub(to_clone_decimal(liquidity_position.onasset_ild_rebate)?)
+            .ok_or(MathError::CheckedMathError)?,
+        CLONE_TOKEN_SCALE,
+    );
+
+    let pool_price = onasset_oracle
+        .get_price()?
+        .checked_div(collateral_oracle.get_price()?)
+        .ok_or(MathError::CheckedMathError)?;
+
+    let impermanent_loss = collateral_ild_share
+        .max(Decimal::ZERO)
+        .checked_add(
+            pool_price
+                .checked_mul(onasset_ild_share.max(Decimal::ZERO))
+                .ok_or(MathError::CheckedMathError)?,
+        )
+        .ok_or(MathError::CheckedMathError)?;
+    let impermanent_loss_term = impermanent_loss
+        .checked_mul(to_pct_decimal(pool.il_health_score_coefficient)?)
+        .ok_or(MathError::CheckedMathError)?;
+    let position_term = position_committed_collateral_liquidity
+        .checked_mul(to_pct_decimal(pool.position_health_score_coefficient)?)
+        .ok_or(MathError::CheckedMathError)?;
+
+    Ok((impermanent_loss_term, position_term))
+}
+
+/// Health score of a comet at `slot`. `positions` yields every open position
+/// together with its pool and the pool's onAsset oracle; all oracles must have
+/// been updated in `slot`.
+pub fn calculate_health_score<I>(
+    collateral_amount: u64,
+    positions: I,
+    collateral_oracle: &OraclePrice,
+    collateral: &CollateralParams,
+    slot: u64,
+) -> Result<HealthScore>
+where
+    I: IntoIterator<Item = (PositionState, PoolState, OraclePrice)>,
+{
+    check_feed_update(collateral_oracle, slot)?;
+
+    let mut total_il_term = Decimal::zero();
+    let mut total_position_term = Decimal::zero();
+
+    for (liquidity_position, pool, onasset_oracle) in positions {
+        check_feed_update(&onasset_oracle, slot)?;
+        let (impermanent_loss_term, position_term) = calculate_liquidity_position_loss(
+            &liquidity_position,
+            &pool,
+            &onasset_oracle,
+            collateral_oracle,
+            collateral,
+        )?;
+
+        total_il_term = total_il_term
+            .checked_add(impermanent_loss_term)
+            .ok_or(MathError::CheckedMathError)?;
+        total_position_term = total_position_term
+            .checked_add(position_term)
+            .ok_or(MathError::CheckedMathError)?;
+    }
+
+    let effective_collateral = collateral
+        .to_collateral_decimal(collateral_amount)?
+        .checked_mul(to_ratio_decimal(collateral.collateralization_ratio)?)
+        .ok_or(MathError::CheckedMathError)?;
+
+    let score = if total_il_term.is_zero() && total_position_term.is_zero() {
+        Decimal::new(100, 0)
+    } else {
+        let summed_terms = total_il_term
+            .checked_add(total_position_term)
+            .ok_or(MathError::CheckedMathError)?;
+        let inner_term = summed_terms
+            .checked_div(effective_collateral)
+            .ok_or(MathError::CheckedMathError)?;
+
+        Decimal::ONE_HUNDRED
+            .checked_mul(
+                Decimal::ONE
+                    .checked_sub(inner_term)
+                    .ok_or(MathError::CheckedMathError)?,
+            )
+            .ok_or(MathError::CheckedMathError)?
+    };
+
+    Ok(HealthScore {
+        score,
+        effective_collateral,
+        total_il_term,
+        total_position_term,
+    })
+}
+
+/// Onasset oracle price at which the comet's health score reaches zero, with
+/// every other price held fixed. A position's impermanent loss term grows
+/// linearly with the onAsset price through its onAsset ILD share, so the
+/// result is `None` when that share isn't positive. Comets that are already
+/// unhealthy get a price above the current one, which is floored at zero.
+pub fn calculate_position_liquidation_price(
+    health_score: &HealthScore,
+    liquidity_position: &PositionState,
+    pool: &PoolState,
+    onasset_oracle: &OraclePrice,
+    collateral_oracle: &OraclePrice,
+    collateral: &CollateralParams,
+) -> Result<Option<Decimal>> {
+    let onasset_ild_share = calculate_ild_share(liquidity_position, pool, collateral)?
+        .onasset_ild_share
+        .max(Decimal::ZERO);
+    let il_coefficient = to_pct_decimal(pool.il_health_score_coefficient)?;
+    if onasset_ild_share.is_zero() || il_coefficient.is_zero() {
+        return Ok(None);
+    }
+
+    let collateral_price = collateral_oracle.get_price()?;
+    let pool_price = onasset_oracle
+        .get_price()?
+        .checked_div(collateral_price)
+        .ok_or(MathError::CheckedMathError)?;
+
+    let health_margin = health_score
+        .effective_collateral
+        .checked_sub(health_score.total_il_term)
+        .and_then(|margin| margin.checked_sub(health_score.total_position_term))
+        .ok_or(MathError::CheckedMathError)?;
+    let price_change = health_margin
+        .checked_div(
+            il_coefficient
+                .checked_mul(onasset_ild_share)
+                .ok_or(MathError::CheckedMathError)?,
+        )
+        .ok_or(MathError::CheckedMathError)?;
+
+    let liquidation_price = pool_price
+        .checked_add(price_change)
+        .and_then(|price| price.checked_mul(collateral_price))
+        .ok_or(MathError::CheckedMathError)?;
+    Ok(Some(liquidation_price.max(Decimal::ZERO)))
+}
+
+/// ILD shares are debt owed by the position when positive and rewards owed to
+/// it when negative, so every term rounds up.
+pub struct ILDShare {
+    pub collateral_ild_claim: Decimal,
+    pub onasset_ild_claim: Decimal,
+    pub collateral_ild_share: Decimal,
+    pub onasset_ild_share: Decimal,
+}
+
+pub fn calculate_ild_share(
+    liquidity_position: &PositionState,
+    pool: &PoolState,
+    collateral: &CollateralParams,
+) -> Result<ILDShare> {
+    let position_committed_collateral_liquidity = collateral
+        .to_collateral_decimal(liquidity_position.committed_collateral_liquidity)
+        .map_err(|_| MathError::IntTypeConversionError)?;
+    let total_committed_collateral_liquidity = collateral
+        .to_collateral_decimal(pool.committed_collateral_liquidity)
+        .map_err(|_| MathError::IntTypeConversionError)?;
+
+    let claimable_ratio = if total_committed_collateral_liquidity > Decimal::ZERO {
+        position_committed_collateral_liquidity
+            .checked_div(total_committed_collateral_liquidity)
+            .ok_or(MathError::CheckedMathError)?
+    } else {
+        Decimal::ZERO
+    };
+
+    let collateral_ild_claim = rescale_up(
+        collateral
+            .to_collateral_decimal(pool.collateral_ild)?
+            .checked_mul(claimable_ratio)
+            .ok_or(MathError::CheckedMathError)?,
+        collateral.scale.into(),
+    );
+    let onasset_ild_claim = rescale_up(
+        to_clone_decimal(pool.onasset_ild)?
+            .checked_mul(claimable_ratio)
+            .ok_or(MathError::CheckedMathError)?,
+        CLONE_TOKEN_SCALE,
+    );
+
+    let collateral_ild_share = rescale_up(
+        collateral_ild_claim
+            .checked_sub(
+                collateral.to_collateral_decimal(liquidity_position.collateral_ild_rebate)?,
+            )
+            .ok_or(MathError::CheckedMathError)?,
+        collateral.scale.into(),
+    );
+    let onasset_ild_share = rescale_up(
+        onasset_ild_claim
+            .checked_sub(to_clone_decimal(liquidity_position.onasset_ild_rebate)?)
+            .ok_or(MathError::CheckedMathError)?,
+        CLONE_TOKEN_SCALE,
+    );
+
+    Ok(ILDShare {
+        collateral_ild_claim,
+        onasset_ild_claim,
+        collateral_ild_share,
+        onasset_ild_share,
+    })
+}
+
+/// Changes to a position's collateral and onAsset ILD rebates that settle one
+/// side's ILD debt with the other side's rewards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ILDNetting {
+    pub collateral_rebate_delta: Decimal,
+    pub onasset_rebate_delta: Decimal,
+}
+
+/// Settles as much of a position's positive ILD share as its negative share
+/// on the other side covers, exchanged at `onasset_price / collateral_price`.
+/// The rewards given up round up and the debt settled rounds down.
+pub fn calculate_ild_netting(
+    ild_share: &ILDShare,
+    onasset_price: Decimal,
+    collateral_price: Decimal,
+    collateral: &CollateralParams,
+) -> Result<ILDNetting> {
+    let collateral_scale = collateral.scale.into();
+    let price = onasset_price
+        .checked_div(collateral_price)
+        .ok_or(MathError::CheckedMathError)?;
+    let collateral_ild_share = ild_share.collateral_ild_share;
+    let onasset_ild_share = ild_share.onasset_ild_share;
+
+    if collateral_ild_share < Decimal::ZERO && onasset_ild_share > Decimal::ZERO {
+        let collateral_reward = collateral_ild_share.abs();
+        let onasset_debt_value = rescale_up(
+            onasset_ild_share
+                .checked_mul(price)
+                .ok_or(MathError::CheckedMathError)?,
+            collateral_scale,
+        );
+        let (collateral_used, onasset_settled) = if collateral_reward >= onasset_debt_value {
+            (onasset_debt_value, onasset_ild_share)
+        } else {
+            let onasset_settled = rescale_down(
+                collateral_reward
+                    .checked_div(price)
+                    .ok_or(MathError::CheckedMathError)?,
+                CLONE_TOKEN_SCALE,
+            );
+            (collateral_reward, onasset_settled)
+        };
+        Ok(ILDNetting {
+            collateral_rebate_delta: -collateral_used,
+            onasset_rebate_delta: onasset_settled,
+        })
+    } else if onasset_ild_share < Decimal::ZERO && collateral_ild_share > Decimal::ZERO {
+        let onasset_reward = onasset_ild_share.abs();
+        let onasset_reward_value = onasset_reward
+            .checked_mul(price)
+            .ok_or(MathError::CheckedMathError)?;
+        let (onasset_used, collateral_settled) = if onasset_reward_value >= collateral_ild_share {
+            let onasset_used = rescale_up(
+                collateral_ild_share
+                    .checked_div(price)
+                    .ok_or(MathError::CheckedMathError)?,
+                CLONE_TOKEN_SCALE,
+            );
+            (onasset_used.min(onasset_reward), collateral_ild_share)
+        } else {
+            (
+                onasset_reward,
+                rescale_down(onasset_reward_value, collateral_scale),
+            )
+        };
+        Ok(ILDNetting {
+            collateral_rebate_delta: collateral_settled,
+            onasset_rebate_delta: -onasset_used,
+        })
+    } else {
+        Ok(ILDNetting::default())
+    }
+}
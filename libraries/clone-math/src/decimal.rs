@@ -0,0 +1,57 @@
+use crate::error::*;
+use core::convert::TryInto;
+use rust_decimal::prelude::*;
+
+pub const CLONE_TOKEN_SCALE: u32 = 8;
+pub const PERCENT_SCALE: u32 = 2;
+pub const RATIO_SCALE: u32 = 2;
+pub const BPS_SCALE: u32 = 4;
+
+pub fn to_scaled_decimal<T: TryInto<i64>>(value: T, scale: u32) -> Result<Decimal> {
+    Ok(Decimal::new(
+        value
+            .try_into()
+            .map_err(|_| MathError::IntTypeConversionError)?,
+        scale,
+    ))
+}
+
+pub fn to_clone_decimal<T: TryInto<i64>>(value: T) -> Result<Decimal> {
+    to_scaled_decimal(value, CLONE_TOKEN_SCALE)
+}
+
+pub fn to_pct_decimal<T: TryInto<i64>>(value: T) -> Result<Decimal> {
+    to_scaled_decimal(value, PERCENT_SCALE)
+}
+
+pub fn to_ratio_decimal<T: TryInto<i64>>(value: T) -> Result<Decimal> {
+    to_scaled_decimal(value, RATIO_SCALE)
+}
+
+pub fn to_bps_decimal<T: TryInto<i64>>(value: T) -> Result<Decimal> {
+    to_scaled_decimal(value, BPS_SCALE)
+}
+
+pub fn rescale_toward_zero(decimal: Decimal, scale: u32) -> Decimal {
+    let mut rounded_decimal = decimal.round_dp_with_strategy(scale, RoundingStrategy::ToZero);
+    rounded_decimal.rescale(scale);
+    rounded_decimal
+}
+
+/// Rescales toward negative infinity. Used for amounts the protocol pays out
+/// or credits to users, so rounding never works in the user's favor.
+pub fn rescale_down(decimal: Decimal, scale: u32) -> Decimal {
+    let mut rounded_decimal =
+        decimal.round_dp_with_strategy(scale, RoundingStrategy::ToNegativeInfinity);
+    rounded_decimal.rescale(scale);
+    rounded_decimal
+}
+
+/// Rescales toward positive infinity. Used for amounts users owe the
+/// protocol, so rounding never works in the user's favor.
+pub fn rescale_up(decimal: Decimal, scale: u32) -> Decimal {
+    let mut rounded_decimal =
+        decimal.round_dp_with_strategy(scale, RoundingStrategy::ToPositiveInfinity);
+    rounded_decimal.rescale(scale);
+    rounded_decimal
+}
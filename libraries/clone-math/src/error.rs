@@ -0,0 +1,28 @@
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    CheckedMathError,
+    IntTypeConversionError,
+    InvalidConversion,
+    OutdatedOracle,
+    InvalidMintCollateralRatio,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            MathError::CheckedMathError => "Checked Math Error",
+            MathError::IntTypeConversionError => "Int Type Conversion Error",
+            MathError::InvalidConversion => "Invalid Conversion",
+            MathError::OutdatedOracle => "Outdated Oracle",
+            MathError::InvalidMintCollateralRatio => "Invalid Mint Collateral Ratio",
+        };
+        f.write_str(msg)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MathError {}
+
+pub type Result<T> = core::result::Result<T, MathError>;
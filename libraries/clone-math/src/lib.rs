@@ -0,0 +1,27 @@
+//! Core math of the Clone protocol.
+//!
+//! Everything in this crate is a pure function of its inputs: no program
+//! state, sysvars or clock access. The on-chain program and off-chain clients
+//! both go through these functions, so quotes computed off-chain match the
+//! program's results exactly. Build with `default-features = false` for
+//! `no_std` targets.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod borrow;
+pub mod collateral;
+pub mod comet;
+pub mod decimal;
+pub mod error;
+pub mod oracle;
+pub mod pool;
+pub mod rewards;
+
+pub use borrow::*;
+pub use collateral::*;
+pub use comet::*;
+pub use error::*;
+pub use oracle::*;
+pub use pool::*;
+pub use rewards::*;
+
+pub use rust_decimal::Decimal;
@@ -0,0 +1,68 @@
+use crate::error::*;
+use rust_decimal::prelude::*;
+
+/// Price as stored by the program's oracle cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub expo: u8,
+    pub rescale_factor: u8,
+    pub last_update_slot: u64,
+}
+
+impl OraclePrice {
+    pub fn get_price(&self) -> Result<Decimal> {
+        let mut price = Decimal::new(self.price, self.expo.into());
+        if self.rescale_factor != 0 {
+            price = price
+                .checked_div(Decimal::new(1, self.rescale_factor.into()))
+                .ok_or(MathError::CheckedMathError)?;
+        }
+        Ok(price)
+    }
+}
+
+/// Prices must have been refreshed in the slot they are used in.
+pub fn check_feed_update(oracle: &OraclePrice, slot: u64) -> Result<()> {
+    if oracle.last_update_slot != slot {
+        return Err(MathError::OutdatedOracle);
+    }
+    Ok(())
+}
+
+/// Slots after which an oracle's tracked volatility has decayed to half.
+pub const VOLATILITY_HALF_LIFE_SLOTS: u64 = 150;
+
+/// Volatility, in bps, tracked for an oracle once `price` replaces
+/// `previous`: the larger of the price change and `previous_volatility_bps`
+/// decayed over the slots since `previous` was read. Decaying by slot rather
+/// than by update keeps repeated updates from flattening it.
+pub fn calculate_volatility_bps(
+    previous: &OraclePrice,
+    previous_volatility_bps: u16,
+    price: &OraclePrice,
+) -> Result<u16> {
+    let elapsed_slots = price
+        .last_update_slot
+        .saturating_sub(previous.last_update_slot);
+    let decayed_volatility_bps = (previous_volatility_bps as u128)
+        .checked_mul(VOLATILITY_HALF_LIFE_SLOTS as u128)
+        .ok_or(MathError::CheckedMathError)?
+        / (VOLATILITY_HALF_LIFE_SLOTS as u128 + elapsed_slots as u128);
+
+    if previous.price <= 0 {
+        return Ok(decayed_volatility_bps as u16);
+    }
+    let previous_price = previous.get_price()?;
+    let change_bps = price
+        .get_price()?
+        .checked_sub(previous_price)
+        .and_then(|change| change.abs().checked_div(previous_price))
+        .and_then(|change| change.checked_mul(Decimal::from(10_000)))
+        .ok_or(MathError::CheckedMathError)?
+        .ceil()
+        .to_u16()
+        .unwrap_or(u16::MAX);
+
+    Ok(change_bps.max(decayed_volatility_bps as u16))
+}
@@ -0,0 +1,663 @@
+use crate::collateral::CollateralParams;
+use crate::decimal::{
+    rescale_down, rescale_toward_zero, rescale_up, to_bps_decimal, to_clone_decimal,
+    to_pct_decimal, CLONE_TOKEN_SCALE,
+};
+use crate::error::*;
+use core::convert::TryInto;
+use rust_decimal::prelude::*;
+
+/// Invariant a pool's swaps trade along. Both curves are centered at the
+/// oracle price, where the two sides of the JIT pool hold the same value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Curve {
+    /// `pool_collateral * pool_onasset = k`.
+    #[default]
+    ConstantProduct,
+    /// Curve's StableSwap invariant over the value of each side, which
+    /// trades closer to the center price the larger the pool's
+    /// `amplification_coefficient`.
+    StableSwap,
+}
+
+/// Newton's method steps allowed when solving the StableSwap invariant.
+const STABLESWAP_MAX_ITERATIONS: usize = 255;
+
+/// Change between steps, relative to the invariant, at which the StableSwap
+/// solution is taken to have converged.
+fn stableswap_tolerance() -> Decimal {
+    Decimal::new(1, 20)
+}
+
+/// AMM state of a single pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolState {
+    pub committed_collateral_liquidity: u64,
+    pub collateral_ild: i64,
+    pub onasset_ild: i64,
+    pub liquidity_trading_fee_bps: u16,
+    pub treasury_trading_fee_bps: u16,
+    pub il_health_score_coefficient: u16,
+    pub position_health_score_coefficient: u16,
+    pub curve: Curve,
+    /// StableSwap `A`, unused by the constant product curve.
+    pub amplification_coefficient: u16,
+    pub dynamic_fee: DynamicFeeParams,
+}
+
+/// Admin-set parameters of a pool's dynamic trading fee, which raises the
+/// liquidity fee while the onAsset's price moves or the pool is skewed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DynamicFeeParams {
+    /// Percent of the onAsset oracle's tracked volatility charged on top.
+    pub volatility_fee_coefficient: u16,
+    /// Percent of the pool's skew, the value of its onAsset ILD over its
+    /// committed collateral, charged on top.
+    pub imbalance_fee_coefficient: u16,
+    /// Cap on the liquidity and treasury fees together, in bps. Zero turns
+    /// dynamic fees off.
+    pub max_trading_fee_bps: u16,
+}
+
+#[derive(Default, Debug)]
+pub struct SwapSummary {
+    pub result: Decimal,
+    pub liquidity_fees_paid: Decimal,
+    pub treasury_fees_paid: Decimal,
+}
+
+/// How a swap settles: its treasury fees split between the treasury and
+/// stakers, and the ILD it leaves the pool with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapSettlement {
+    /// The treasury's part of the fees, in the swap's output token.
+    pub treasury_fees: u64,
+    /// The stakers' part of the fees, always paid in collateral.
+    pub staking_fees: u64,
+    pub onasset_ild_delta: i64,
+    pub collateral_ild_delta: i64,
+}
+
+impl PoolState {
+    pub fn calculate_jit_pool(
+        &self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        collateral: &CollateralParams,
+    ) -> Result<(Decimal, Decimal)> {
+        let committed_collateral_liquidity =
+            collateral.to_collateral_decimal(self.committed_collateral_liquidity)?;
+        let onasset_ild = to_clone_decimal(self.onasset_ild)?;
+        let center_price = onasset_price
+            .checked_div(collateral_price)
+            .ok_or(MathError::CheckedMathError)?;
+        let pool_collateral = collateral.to_collateral_decimal(
+            TryInto::<i64>::try_into(self.committed_collateral_liquidity)
+                .map_err(|_| MathError::IntTypeConversionError)?
+                .checked_sub(self.collateral_ild)
+                .ok_or(MathError::CheckedMathError)?,
+        )?;
+        let pool_onasset = rescale_toward_zero(
+            (committed_collateral_liquidity
+                .checked_div(center_price)
+                .ok_or(MathError::CheckedMathError)?)
+            .checked_sub(onasset_ild)
+            .ok_or(MathError::CheckedMathError)?,
+            CLONE_TOKEN_SCALE,
+        );
+        Ok((pool_collateral, pool_onasset))
+    }
+
+    /// `A * n^n` of the StableSwap invariant for two tokens.
+    fn stableswap_amplification(&self) -> Result<Decimal> {
+        if self.amplification_coefficient == 0 {
+            return Err(MathError::CheckedMathError);
+        }
+        Ok(Decimal::from(self.amplification_coefficient as u64 * 4))
+    }
+
+    /// The pool's invariant: `k` for the constant product curve and `D`, in
+    /// collateral, for StableSwap. Swaps never decrease it.
+    pub fn calculate_invariant(
+        &self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        collateral: &CollateralParams,
+    ) -> Result<Decimal> {
+        let (pool_collateral, pool_onasset) =
+            self.calculate_jit_pool(onasset_price, collateral_price, collateral)?;
+        match self.curve {
+            Curve::ConstantProduct => pool_onasset
+                .checked_mul(pool_collateral)
+                .ok_or(MathError::CheckedMathError),
+            Curve::StableSwap => {
+                let center_price = onasset_price
+                    .checked_div(collateral_price)
+                    .ok_or(MathError::CheckedMathError)?;
+                calculate_stableswap_invariant(
+                    pool_collateral,
+                    pool_onasset
+                        .checked_mul(center_price)
+                        .ok_or(MathError::CheckedMathError)?,
+                    self.stableswap_amplification()?,
+                )
+            }
+        }
+    }
+
+    /// Marginal price of the onAsset in collateral, which moves away from
+    /// the oracle price as ILD builds up.
+    pub fn calculate_pool_price(
+        &self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        collateral: &CollateralParams,
+    ) -> Result<Decimal> {
+        let (pool_collateral, pool_onasset) =
+            self.calculate_jit_pool(onasset_price, collateral_price, collateral)?;
+        match self.curve {
+            Curve::ConstantProduct => pool_collateral
+                .checked_div(pool_onasset)
+                .ok_or(MathError::CheckedMathError),
+            Curve::StableSwap => {
+                let center_price = onasset_price
+                    .checked_div(collateral_price)
+                    .ok_or(MathError::CheckedMathError)?;
+                let amplification = self.stableswap_amplification()?;
+                let pool_onasset_value = pool_onasset
+                    .checked_mul(center_price)
+                    .ok_or(MathError::CheckedMathError)?;
+                let invariant = calculate_stableswap_invariant(
+                    pool_collateral,
+                    pool_onasset_value,
+                    amplification,
+                )?;
+                let x = normalize(pool_collateral, invariant)?;
+                let y = normalize(pool_onasset_value, invariant)?;
+                // Ratio of the invariant's partial derivatives in y and x.
+                let four_xy = Decimal::from(4)
+                    .checked_mul(x)
+                    .and_then(|four_xy| four_xy.checked_mul(y))
+                    .ok_or(MathError::CheckedMathError)?;
+                let d_y = Decimal::ONE
+                    .checked_div(four_xy.checked_mul(y).ok_or(MathError::CheckedMathError)?)
+                    .and_then(|d_y| d_y.checked_add(amplification))
+                    .ok_or(MathError::CheckedMathError)?;
+                let d_x = Decimal::ONE
+                    .checked_div(four_xy.checked_mul(x).ok_or(MathError::CheckedMathError)?)
+                    .and_then(|d_x| d_x.checked_add(amplification))
+                    .ok_or(MathError::CheckedMathError)?;
+                center_price
+                    .checked_mul(d_y)
+                    .and_then(|price| price.checked_div(d_x))
+                    .ok_or(MathError::CheckedMathError)
+            }
+        }
+    }
+
+    /// Reserve of the other side of the JIT pool once one side moves to
+    /// `new_reserve` along the pool's curve.
+    fn calculate_other_reserve(
+        &self,
+        pool_collateral: Decimal,
+        pool_onasset: Decimal,
+        center_price: Decimal,
+        new_reserve: Decimal,
+        new_reserve_is_collateral: bool,
+    ) -> Result<Decimal> {
+        match self.curve {
+            Curve::ConstantProduct => pool_onasset
+                .checked_mul(pool_collateral)
+                .and_then(|invariant| invariant.checked_div(new_reserve))
+                .ok_or(MathError::CheckedMathError),
+            Curve::StableSwap => {
+                // The invariant is taken over the value of each side, in
+                // collateral at the center price.
+                let amplification = self.stableswap_amplification()?;
+                let invariant = calculate_stableswap_invariant(
+                    pool_collateral,
+                    pool_onasset
+                        .checked_mul(center_price)
+                        .ok_or(MathError::CheckedMathError)?,
+                    amplification,
+                )?;
+                if new_reserve_is_collateral {
+                    calculate_stableswap_reserve(new_reserve, invariant, amplification)?
+                        .checked_div(center_price)
+                        .ok_or(MathError::CheckedMathError)
+                } else {
+                    calculate_stableswap_reserve(
+                        new_reserve
+                            .checked_mul(center_price)
+                            .ok_or(MathError::CheckedMathError)?,
+                        invariant,
+                        amplification,
+                    )
+                }
+            }
+        }
+    }
+
+    /// Liquidity and treasury fees charged on a swap, the overrides
+    /// replacing the pool's own. With dynamic fees on, the liquidity fee
+    /// grows with `volatility_bps` and the pool's skew, never pushing the
+    /// total past `max_trading_fee_bps`.
+    pub fn calculate_trading_fees(
+        &self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        collateral: &CollateralParams,
+        volatility_bps: u16,
+        override_liquidity_trading_fee: Option<Decimal>,
+        override_treasury_trading_fee: Option<Decimal>,
+    ) -> Result<(Decimal, Decimal)> {
+        let liquidity_trading_fee = match override_liquidity_trading_fee {
+            Some(fee) => fee,
+            None => to_bps_decimal(self.liquidity_trading_fee_bps)?,
+        };
+        let treasury_trading_fee = match override_treasury_trading_fee {
+            Some(fee) => fee,
+            None => to_bps_decimal(self.treasury_trading_fee_bps)?,
+        };
+        let params = &self.dynamic_fee;
+        if params.max_trading_fee_bps == 0 || self.committed_collateral_liquidity == 0 {
+            return Ok((liquidity_trading_fee, treasury_trading_fee));
+        }
+
+        let committed_collateral_liquidity =
+            collateral.to_collateral_decimal(self.committed_collateral_liquidity)?;
+        let skew = to_clone_decimal(self.onasset_ild)?
+            .abs()
+            .checked_mul(onasset_price)
+            .and_then(|value| value.checked_div(collateral_price))
+            .and_then(|value| value.checked_div(committed_collateral_liquidity))
+            .ok_or(MathError::CheckedMathError)?;
+        let volatility_fee = to_bps_decimal(volatility_bps)?
+            .checked_mul(to_pct_decimal(params.volatility_fee_coefficient)?)
+            .ok_or(MathError::CheckedMathError)?;
+        let imbalance_fee = skew
+            .checked_mul(to_pct_decimal(params.imbalance_fee_coefficient)?)
+            .ok_or(MathError::CheckedMathError)?;
+        let headroom = to_bps_decimal(params.max_trading_fee_bps)?
+            .checked_sub(liquidity_trading_fee)
+            .and_then(|headroom| headroom.checked_sub(treasury_trading_fee))
+            .ok_or(MathError::CheckedMathError)?
+            .max(Decimal::ZERO);
+        let dynamic_fee = volatility_fee
+            .checked_add(imbalance_fee)
+            .ok_or(MathError::CheckedMathError)?
+            .min(headroom);
+
+        Ok((
+            liquidity_trading_fee
+                .checked_add(dynamic_fee)
+                .ok_or(MathError::CheckedMathError)?,
+            treasury_trading_fee,
+        ))
+    }
+
+    // This function calculate either the resultant amount received or
+    // required as input into the pool depending on the `quantity` specifications.
+    // Fees are calculated as well and are always of the output type of the swap.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_swap(
+        &self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        quantity: Decimal,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+        collateral: &CollateralParams,
+        override_liquidity_trading_fee: Option<Decimal>,
+        override_treasury_trading_fee: Option<Decimal>,
+    ) -> Result<SwapSummary> {
+        let (pool_collateral, pool_onasset) =
+            self.calculate_jit_pool(onasset_price, collateral_price, collateral)?;
+        let center_price = onasset_price
+            .checked_div(collateral_price)
+            .ok_or(MathError::CheckedMathError)?;
+        let default_liquidity_trading_fee = to_bps_decimal(self.liquidity_trading_fee_bps)?;
+        let default_treasury_trading_fee = to_bps_decimal(self.treasury_trading_fee_bps)?;
+        let liquidity_trading_fee =
+            override_liquidity_trading_fee.unwrap_or(default_liquidity_trading_fee);
+        let treasury_trading_fee =
+            override_treasury_trading_fee.unwrap_or(default_treasury_trading_fee);
+        if quantity_is_input {
+            let (i_pool, o_pool, o_scale) = if quantity_is_collateral {
+                (pool_collateral, pool_onasset, CLONE_TOKEN_SCALE)
+            } else {
+                (pool_onasset, pool_collateral, collateral.scale.into())
+            };
+            // o_pool - new_o_pool(i_pool + quantity) = output_before_fees
+            // The output is paid to the user and rounds down, fees round up.
+            let output_before_fees = rescale_down(
+                o_pool
+                    .checked_sub(
+                        self.calculate_other_reserve(
+                            pool_collateral,
+                            pool_onasset,
+                            center_price,
+                            i_pool
+                                .checked_add(quantity)
+                                .ok_or(MathError::CheckedMathError)?,
+                            quantity_is_collateral,
+                        )?,
+                    )
+                    .ok_or(MathError::CheckedMathError)?,
+                o_scale,
+            );
+            let liquidity_fees_paid = rescale_up(
+                output_before_fees
+                    .checked_mul(liquidity_trading_fee)
+                    .ok_or(MathError::CheckedMathError)?,
+                o_scale,
+            );
+            let treasury_fees_paid = rescale_up(
+                output_before_fees
+                    .checked_mul(treasury_trading_fee)
+                    .ok_or(MathError::CheckedMathError)?,
+                o_scale,
+            );
+            let result = rescale_down(
+                output_before_fees
+                    .checked_sub(
+                        liquidity_fees_paid
+                            .checked_add(treasury_fees_paid)
+                            .ok_or(MathError::CheckedMathError)?,
+                    )
+                    .ok_or(MathError::CheckedMathError)?,
+                o_scale,
+            );
+            Ok(SwapSummary {
+                result,
+                liquidity_fees_paid,
+                treasury_fees_paid,
+            })
+        } else {
+            let (o_pool, i_pool, i_scale, o_scale) = if quantity_is_collateral {
+                (
+                    pool_collateral,
+                    pool_onasset,
+                    CLONE_TOKEN_SCALE,
+                    collateral.scale.into(),
+                )
+            } else {
+                (
+                    pool_onasset,
+                    pool_collateral,
+                    collateral.scale.into(),
+                    CLONE_TOKEN_SCALE,
+                )
+            };
+            // The required input is owed by the user, so it and the fees round up.
+            let output_before_fees = rescale_up(
+                quantity
+                    .checked_div(
+                        Decimal::ONE
+                            .checked_sub(
+                                liquidity_trading_fee
+                                    .checked_add(treasury_trading_fee)
+                                    .ok_or(MathError::CheckedMathError)?,
+                            )
+                            .ok_or(MathError::CheckedMathError)?,
+                    )
+                    .ok_or(MathError::CheckedMathError)?,
+                o_scale,
+            );
+            // new_i_pool(o_pool - output_before_fees) - i_pool = result
+            let result = rescale_up(
+                self.calculate_other_reserve(
+                    pool_collateral,
+                    pool_onasset,
+                    center_price,
+                    o_pool
+                        .checked_sub(output_before_fees)
+                        .ok_or(MathError::CheckedMathError)?,
+                    quantity_is_collateral,
+                )?
+                .checked_sub(i_pool)
+                .ok_or(MathError::CheckedMathError)?,
+                i_scale,
+            );
+            let liquidity_fees_paid = rescale_up(
+                output_before_fees
+                    .checked_mul(liquidity_trading_fee)
+                    .ok_or(MathError::CheckedMathError)?,
+                o_scale,
+            );
+            let treasury_fees_paid = rescale_up(
+                output_before_fees
+                    .checked_mul(treasury_trading_fee)
+                    .ok_or(MathError::CheckedMathError)?,
+                o_scale,
+            );
+            Ok(SwapSummary {
+                result,
+                liquidity_fees_paid,
+                treasury_fees_paid,
+            })
+        }
+    }
+
+    /// Settles a swap of `input` for `output` that charged `treasury_fees`,
+    /// `treasury_fee_share_bps` of which go to stakers. Stakers are always
+    /// paid in collateral: an onAsset share stays in the pool, which pays it
+    /// out valued at the lower of the oracle price and its own marginal
+    /// price, so paying stakers never lowers the pool's invariant. Shares
+    /// worth less than the smallest collateral unit stay with the treasury.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_swap_settlement(
+        &self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        collateral: &CollateralParams,
+        input: u64,
+        output: u64,
+        input_is_collateral: bool,
+        treasury_fees: u64,
+        treasury_fee_share_bps: u16,
+    ) -> Result<SwapSettlement> {
+        let to_signed = |amount: u64| -> Result<i64> {
+            amount
+                .try_into()
+                .map_err(|_| MathError::IntTypeConversionError)
+        };
+        let staking_share: u64 = (treasury_fees as u128)
+            .checked_mul(treasury_fee_share_bps.into())
+            .ok_or(MathError::CheckedMathError)?
+            .checked_div(10_000)
+            .ok_or(MathError::CheckedMathError)?
+            .try_into()
+            .map_err(|_| MathError::IntTypeConversionError)?;
+        let treasury_share = treasury_fees
+            .checked_sub(staking_share)
+            .ok_or(MathError::CheckedMathError)?;
+
+        if !input_is_collateral {
+            // Fees are owed in collateral, the treasury and stakers both
+            // paid from the vault.
+            return Ok(SwapSettlement {
+                treasury_fees: treasury_share,
+                staking_fees: staking_share,
+                onasset_ild_delta: to_signed(input)?
+                    .checked_neg()
+                    .ok_or(MathError::CheckedMathError)?,
+                collateral_ild_delta: to_signed(
+                    output
+                        .checked_add(treasury_fees)
+                        .ok_or(MathError::CheckedMathError)?,
+                )?,
+            });
+        }
+
+        let collateral_ild_delta = to_signed(input)?
+            .checked_neg()
+            .ok_or(MathError::CheckedMathError)?;
+        let onasset_ild_delta = to_signed(
+            output
+                .checked_add(treasury_share)
+                .ok_or(MathError::CheckedMathError)?,
+        )?;
+        let staking_fees: u64 = if staking_share == 0 {
+            0
+        } else {
+            let pool = PoolState {
+                onasset_ild: self
+                    .onasset_ild
+                    .checked_add(onasset_ild_delta)
+                    .ok_or(MathError::CheckedMathError)?,
+                collateral_ild: self
+                    .collateral_ild
+                    .checked_add(collateral_ild_delta)
+                    .ok_or(MathError::CheckedMathError)?,
+                ..*self
+            };
+            let price = pool
+                .calculate_pool_price(onasset_price, collateral_price, collateral)?
+                .min(
+                    onasset_price
+                        .checked_div(collateral_price)
+                        .ok_or(MathError::CheckedMathError)?,
+                );
+            rescale_toward_zero(
+                to_clone_decimal(staking_share)?
+                    .checked_mul(price)
+                    .ok_or(MathError::CheckedMathError)?,
+                collateral.scale.into(),
+            )
+            .mantissa()
+            .try_into()
+            .map_err(|_| MathError::IntTypeConversionError)?
+        };
+
+        if staking_fees == 0 {
+            return Ok(SwapSettlement {
+                treasury_fees,
+                staking_fees,
+                onasset_ild_delta: to_signed(
+                    output
+                        .checked_add(treasury_fees)
+                        .ok_or(MathError::CheckedMathError)?,
+                )?,
+                collateral_ild_delta,
+            });
+        }
+        Ok(SwapSettlement {
+            treasury_fees: treasury_share,
+            staking_fees,
+            onasset_ild_delta,
+            collateral_ild_delta: collateral_ild_delta
+                .checked_add(to_signed(staking_fees)?)
+                .ok_or(MathError::CheckedMathError)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.committed_collateral_liquidity == 0
+            && self.onasset_ild == 0
+            && self.collateral_ild == 0
+    }
+}
+
+fn normalize(value: Decimal, invariant: Decimal) -> Result<Decimal> {
+    value
+        .checked_div(invariant)
+        .ok_or(MathError::CheckedMathError)
+}
+
+/// StableSwap invariant `D` of reserves worth `x` and `y`, solving
+/// `amplification * (x + y) + D = amplification * D + D^3 / (4 * x * y)`.
+/// Reserves are normalized by their sum so intermediate values stay near
+/// one whatever the pool's size.
+pub fn calculate_stableswap_invariant(
+    x: Decimal,
+    y: Decimal,
+    amplification: Decimal,
+) -> Result<Decimal> {
+    if x <= Decimal::ZERO || y <= Decimal::ZERO {
+        return Err(MathError::CheckedMathError);
+    }
+    let sum = x.checked_add(y).ok_or(MathError::CheckedMathError)?;
+    let x = normalize(x, sum)?;
+    let y = normalize(y, sum)?;
+    let two = Decimal::from(2);
+    let three = Decimal::from(3);
+    let amplification_less_one = amplification
+        .checked_sub(Decimal::ONE)
+        .ok_or(MathError::CheckedMathError)?;
+
+    let mut d = Decimal::ONE;
+    for _ in 0..STABLESWAP_MAX_ITERATIONS {
+        // d_p = d^3 / (4 * x * y)
+        let d_p = d
+            .checked_div(two.checked_mul(x).ok_or(MathError::CheckedMathError)?)
+            .and_then(|d_p| d_p.checked_mul(d))
+            .and_then(|d_p| d_p.checked_div(two.checked_mul(y)?))
+            .and_then(|d_p| d_p.checked_mul(d))
+            .ok_or(MathError::CheckedMathError)?;
+        // d = d * (amplification + 2 * d_p) / ((amplification - 1) * d + 3 * d_p)
+        let numerator = two
+            .checked_mul(d_p)
+            .and_then(|numerator| numerator.checked_add(amplification))
+            .ok_or(MathError::CheckedMathError)?;
+        let denominator = amplification_less_one
+            .checked_mul(d)
+            .and_then(|denominator| denominator.checked_add(three.checked_mul(d_p)?))
+            .ok_or(MathError::CheckedMathError)?;
+        let previous = d;
+        d = d
+            .checked_mul(numerator)
+            .and_then(|d| d.checked_div(denominator))
+            .ok_or(MathError::CheckedMathError)?;
+        if (d - previous).abs() <= stableswap_tolerance() {
+            return d.checked_mul(sum).ok_or(MathError::CheckedMathError);
+        }
+    }
+    Err(MathError::CheckedMathError)
+}
+
+/// Value of one side of a StableSwap pool with invariant `invariant` when the
+/// other side is worth `x`.
+pub fn calculate_stableswap_reserve(
+    x: Decimal,
+    invariant: Decimal,
+    amplification: Decimal,
+) -> Result<Decimal> {
+    if x <= Decimal::ZERO || invariant <= Decimal::ZERO {
+        return Err(MathError::CheckedMathError);
+    }
+    let x = normalize(x, invariant)?;
+    // With D = 1: y^2 + (b - 1) * y = c, where b = x + 1 / amplification
+    // and c = 1 / (4 * x * amplification).
+    let b = Decimal::ONE
+        .checked_div(amplification)
+        .and_then(|b| b.checked_add(x))
+        .ok_or(MathError::CheckedMathError)?;
+    let c = Decimal::from(4)
+        .checked_mul(x)
+        .and_then(|c| c.checked_mul(amplification))
+        .and_then(|c| Decimal::ONE.checked_div(c))
+        .ok_or(MathError::CheckedMathError)?;
+
+    let mut y = Decimal::ONE;
+    for _ in 0..STABLESWAP_MAX_ITERATIONS {
+        // y = (y^2 + c) / (2 * y + b - 1)
+        let previous = y;
+        y = y
+            .checked_mul(y)
+            .and_then(|y_squared| y_squared.checked_add(c))
+            .and_then(|numerator| {
+                numerator.checked_div(
+                    Decimal::from(2)
+                        .checked_mul(y)?
+                        .checked_add(b)?
+                        .checked_sub(Decimal::ONE)?,
+                )
+            })
+            .ok_or(MathError::CheckedMathError)?;
+        if (y - previous).abs() <= stableswap_tolerance() {
+            return y.checked_mul(invariant).ok_or(MathError::CheckedMathError);
+        }
+    }
+    Err(MathError::CheckedMathError)
+}
@@ -0,0 +1,69 @@
+use crate::error::*;
+use core::convert::TryInto;
+
+/// Scale of reward indices, in reward tokens per unit of committed
+/// collateral liquidity.
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000;
+
+/// Liquidity mining rewards a pool emits to its liquidity providers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RewardState {
+    /// Reward tokens emitted per slot.
+    pub emission_rate: u64,
+    /// Funded reward tokens not emitted yet.
+    pub unemitted_rewards: u64,
+    /// Rewards emitted per unit of committed liquidity, scaled by
+    /// `REWARD_INDEX_SCALE`.
+    pub reward_index: u128,
+    pub last_update_slot: u64,
+}
+
+impl RewardState {
+    /// Emits the rewards for the slots since the last update pro rata to
+    /// `committed_collateral_liquidity`. Nothing is emitted while the pool
+    /// has no liquidity, and never more than was funded.
+    pub fn accrue(&self, committed_collateral_liquidity: u64, slot: u64) -> Result<RewardState> {
+        let mut state = RewardState {
+            last_update_slot: slot.max(self.last_update_slot),
+            ..*self
+        };
+        if committed_collateral_liquidity == 0 {
+            return Ok(state);
+        }
+
+        let elapsed_slots = slot.saturating_sub(self.last_update_slot);
+        let emitted = (self.emission_rate as u128)
+            .checked_mul(elapsed_slots as u128)
+            .ok_or(MathError::CheckedMathError)?
+            .min(self.unemitted_rewards as u128);
+        let index_delta = emitted
+            .checked_mul(REWARD_INDEX_SCALE)
+            .ok_or(MathError::CheckedMathError)?
+            / committed_collateral_liquidity as u128;
+
+        state.unemitted_rewards -= emitted as u64;
+        state.reward_index = self
+            .reward_index
+            .checked_add(index_delta)
+            .ok_or(MathError::CheckedMathError)?;
+        Ok(state)
+    }
+}
+
+/// Rewards a position with `committed_collateral_liquidity` earned since it
+/// last recorded `position_reward_index`, rounded down.
+pub fn calculate_accrued_rewards(
+    committed_collateral_liquidity: u64,
+    reward_index: u128,
+    position_reward_index: u128,
+) -> Result<u64> {
+    let index_delta = reward_index
+        .checked_sub(position_reward_index)
+        .ok_or(MathError::CheckedMathError)?;
+    ((committed_collateral_liquidity as u128)
+        .checked_mul(index_delta)
+        .ok_or(MathError::CheckedMathError)?
+        / REWARD_INDEX_SCALE)
+        .try_into()
+        .map_err(|_| MathError::IntTypeConversionError)
+}
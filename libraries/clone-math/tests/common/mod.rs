@@ -0,0 +1,56 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use clone_math::*;
+use std::convert::TryInto;
+
+pub fn to_amount(decimal: Decimal) -> u64 {
+    decimal.mantissa().try_into().unwrap()
+}
+
+pub fn to_signed_amount(decimal: Decimal) -> i64 {
+    decimal.mantissa().try_into().unwrap()
+}
+
+/// Share of treasury fees a swap pays to stakers, with the prices the swap
+/// was made at.
+#[derive(Clone, Debug)]
+pub struct TreasuryFeeShare {
+    pub treasury_fee_share_bps: u16,
+    pub onasset_price: Decimal,
+    pub collateral_price: Decimal,
+    pub collateral: CollateralParams,
+}
+
+/// Applies a swap's ILD changes to `pool` the way the `swap` instruction
+/// does and returns the (input, output) amounts.
+pub fn apply_swap(
+    pool: &mut PoolState,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+    summary: &SwapSummary,
+    fee_share: &TreasuryFeeShare,
+) -> (u64, u64) {
+    let result = to_amount(summary.result);
+    let (input, output) = if quantity_is_input {
+        (quantity, result)
+    } else {
+        (result, quantity)
+    };
+    let settlement = pool
+        .calculate_swap_settlement(
+            fee_share.onasset_price,
+            fee_share.collateral_price,
+            &fee_share.collateral,
+            input,
+            output,
+            quantity_is_input == quantity_is_collateral,
+            to_amount(summary.treasury_fees_paid),
+            fee_share.treasury_fee_share_bps,
+        )
+        .unwrap();
+    pool.onasset_ild += settlement.onasset_ild_delta;
+    pool.collateral_ild += settlement.collateral_ild_delta;
+    (input, output)
+}
@@ -0,0 +1,156 @@
+//! Checks the dynamic trading fee and the oracle volatility it reads.
+
+use clone_math::*;
+
+const COLLATERAL: CollateralParams = CollateralParams {
+    scale: 7,
+    collateralization_ratio: 100,
+};
+const SLOT: u64 = 1_000;
+
+fn onasset_price() -> Decimal {
+    Decimal::TEN
+}
+
+fn collateral_price() -> Decimal {
+    Decimal::ONE
+}
+
+fn bps(value: i64) -> Decimal {
+    Decimal::new(value, 4)
+}
+
+/// Pool with 10,000 collateral committed, `onasset_ild` in onAssets.
+fn pool(onasset_ild: i64, dynamic_fee: DynamicFeeParams) -> PoolState {
+    PoolState {
+        committed_collateral_liquidity: 100_000_000_000,
+        onasset_ild: onasset_ild * 100_000_000,
+        liquidity_trading_fee_bps: 15,
+        treasury_trading_fee_bps: 10,
+        il_health_score_coefficient: 100,
+        position_health_score_coefficient: 100,
+        dynamic_fee,
+        ..PoolState::default()
+    }
+}
+
+fn trading_fees(pool: &PoolState, volatility_bps: u16) -> (Decimal, Decimal) {
+    pool.calculate_trading_fees(
+        onasset_price(),
+        collateral_price(),
+        &COLLATERAL,
+        volatility_bps,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+fn oracle(price: i64, last_update_slot: u64) -> OraclePrice {
+    OraclePrice {
+        price,
+        expo: 2,
+        last_update_slot,
+        ..OraclePrice::default()
+    }
+}
+
+#[test]
+fn test_dynamic_fee_is_off_without_a_cap() {
+    let pool = pool(
+        50,
+        DynamicFeeParams {
+            volatility_fee_coefficient: 100,
+            imbalance_fee_coefficient: 100,
+            max_trading_fee_bps: 0,
+        },
+    );
+    assert_eq!(trading_fees(&pool, 500), (bps(15), bps(10)));
+}
+
+#[test]
+fn test_dynamic_fee_components() {
+    let params = DynamicFeeParams {
+        volatility_fee_coefficient: 25,
+        imbalance_fee_coefficient: 10,
+        max_trading_fee_bps: 1_000,
+    };
+    // A quarter of 200 bps of volatility.
+    assert_eq!(trading_fees(&pool(0, params), 200), (bps(65), bps(10)));
+    // A tenth of a 5% skew, whichever way the pool leans.
+    assert_eq!(trading_fees(&pool(50, params), 0), (bps(65), bps(10)));
+    assert_eq!(trading_fees(&pool(-50, params), 0), (bps(65), bps(10)));
+    assert_eq!(trading_fees(&pool(50, params), 200), (bps(115), bps(10)));
+}
+
+#[test]
+fn test_dynamic_fee_is_capped() {
+    let pool = pool(
+        50,
+        DynamicFeeParams {
+            volatility_fee_coefficient: 25,
+            imbalance_fee_coefficient: 10,
+            max_trading_fee_bps: 30,
+        },
+    );
+    assert_eq!(trading_fees(&pool, 200), (bps(20), bps(10)));
+
+    // Fees already past the cap aren't lowered to it.
+    let fees = pool
+        .calculate_trading_fees(
+            onasset_price(),
+            collateral_price(),
+            &COLLATERAL,
+            200,
+            Some(bps(40)),
+            None,
+        )
+        .unwrap();
+    assert_eq!(fees, (bps(40), bps(10)));
+}
+
+#[test]
+fn test_volatility_tracks_price_changes() {
+    let previous = oracle(1_000, SLOT);
+    // A 2% move.
+    assert_eq!(
+        calculate_volatility_bps(&previous, 0, &oracle(1_020, SLOT + 1)).unwrap(),
+        200
+    );
+    assert_eq!(
+        calculate_volatility_bps(&previous, 0, &oracle(980, SLOT + 1)).unwrap(),
+        200
+    );
+    // Smaller moves don't replace a larger recent one.
+    assert_eq!(
+        calculate_volatility_bps(&previous, 300, &oracle(1_020, SLOT)).unwrap(),
+        300
+    );
+    // Nothing to compare against before the first update.
+    assert_eq!(
+        calculate_volatility_bps(&oracle(0, 0), 0, &oracle(1_000, SLOT)).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_volatility_decays_by_slot() {
+    let previous = oracle(1_000, SLOT);
+    assert_eq!(
+        calculate_volatility_bps(
+            &previous,
+            200,
+            &oracle(1_000, SLOT + VOLATILITY_HALF_LIFE_SLOTS)
+        )
+        .unwrap(),
+        100
+    );
+
+    // Updating again and again within a slot leaves it untouched.
+    let mut volatility_bps = 200;
+    for _ in 0..10 {
+        volatility_bps =
+            calculate_volatility_bps(&previous, volatility_bps, &oracle(1_000, SLOT)).unwrap();
+    }
+    assert_eq!(volatility_bps, 200);
+}
@@ -0,0 +1,89 @@
+//! Checks the netting of a position's ILD debt against its rewards.
+
+use clone_math::decimal::CLONE_TOKEN_SCALE;
+use clone_math::*;
+
+const COLLATERAL: CollateralParams = CollateralParams {
+    scale: 7,
+    collateralization_ratio: 100,
+};
+
+fn ild_share(collateral_ild_share: i64, onasset_ild_share: i64) -> ILDShare {
+    ILDShare {
+        collateral_ild_claim: Decimal::ZERO,
+        onasset_ild_claim: Decimal::ZERO,
+        collateral_ild_share: Decimal::new(collateral_ild_share, COLLATERAL.scale.into()),
+        onasset_ild_share: Decimal::new(onasset_ild_share, CLONE_TOKEN_SCALE),
+    }
+}
+
+fn netting(
+    collateral_ild_share: i64,
+    onasset_ild_share: i64,
+    onasset_price: Decimal,
+) -> (i64, i64) {
+    let netting = calculate_ild_netting(
+        &ild_share(collateral_ild_share, onasset_ild_share),
+        onasset_price,
+        Decimal::ONE,
+        &COLLATERAL,
+    )
+    .unwrap();
+    (
+        netting.collateral_rebate_delta.mantissa() as i64,
+        netting.onasset_rebate_delta.mantissa() as i64,
+    )
+}
+
+#[test]
+fn test_collateral_rewards_settle_onasset_ild() {
+    // 20 collateral of rewards against 1 onAsset of debt at a price of 10.
+    assert_eq!(
+        netting(-200_000_000, 100_000_000, Decimal::TEN),
+        (-100_000_000, 100_000_000)
+    );
+    // 5 collateral of rewards settle half an onAsset.
+    assert_eq!(
+        netting(-50_000_000, 100_000_000, Decimal::TEN),
+        (-50_000_000, 50_000_000)
+    );
+}
+
+#[test]
+fn test_onasset_rewards_settle_collateral_ild() {
+    assert_eq!(
+        netting(50_000_000, -100_000_000, Decimal::TEN),
+        (50_000_000, -50_000_000)
+    );
+    assert_eq!(
+        netting(200_000_000, -100_000_000, Decimal::TEN),
+        (100_000_000, -100_000_000)
+    );
+}
+
+#[test]
+fn test_netting_needs_opposite_shares() {
+    for (collateral_ild_share, onasset_ild_share) in [(-100, -100), (100, 100), (0, 100), (-100, 0)]
+    {
+        assert_eq!(
+            netting(collateral_ild_share, onasset_ild_share, Decimal::TEN),
+            (0, 0)
+        );
+    }
+}
+
+#[test]
+fn test_netting_rounds_against_the_position() {
+    let price = Decimal::new(3, 0);
+    // One unit of collateral reward can't settle a whole unit of onAsset
+    // debt worth three units of collateral.
+    let (collateral_delta, onasset_delta) = netting(-1, 1_000, price);
+    assert_eq!(collateral_delta, -1);
+    assert!(onasset_delta * 3 <= 10);
+
+    // Settling a single unit of collateral debt uses up a rounded up
+    // amount of onAsset rewards.
+    let (collateral_delta, onasset_delta) = netting(1, -1_000, price);
+    assert_eq!(collateral_delta, 1);
+    assert_eq!(onasset_delta, -4);
+}
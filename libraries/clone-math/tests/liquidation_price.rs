@@ -0,0 +1,147 @@
+//! Checks that the liquidation prices reported to clients are the prices at
+//! which the program's own checks start failing.
+
+use clone_math::decimal::{to_clone_decimal, to_ratio_decimal};
+use clone_math::*;
+
+const COLLATERAL: CollateralParams = CollateralParams {
+    scale: 7,
+    collateralization_ratio: 100,
+};
+const SLOT: u64 = 7;
+
+fn oracle(price: i64, expo: u8) -> OraclePrice {
+    OraclePrice {
+        price,
+        expo,
+        last_update_slot: SLOT,
+        ..OraclePrice::default()
+    }
+}
+
+fn to_oracle(price: Decimal) -> OraclePrice {
+    let price = price.normalize();
+    oracle(price.mantissa() as i64, price.scale() as u8)
+}
+
+#[test]
+fn test_comet_health_is_zero_at_liquidation_price() {
+    let pool = PoolState {
+        committed_collateral_liquidity: 10_000_000_000,
+        collateral_ild: -2_000_000_000,
+        onasset_ild: 1_000_000_000,
+        liquidity_trading_fee_bps: 15,
+        treasury_trading_fee_bps: 10,
+        il_health_score_coefficient: 100,
+        position_health_score_coefficient: 100,
+        ..PoolState::default()
+    };
+    let position = PositionState {
+        committed_collateral_liquidity: pool.committed_collateral_liquidity,
+        ..PositionState::default()
+    };
+    let collateral_oracle = oracle(1, 0);
+    let health_at = |onasset_oracle: &OraclePrice| {
+        calculate_health_score(
+            50_000_000_000,
+            [(position, pool, *onasset_oracle)],
+            &collateral_oracle,
+            &COLLATERAL,
+            SLOT,
+        )
+        .unwrap()
+    };
+
+    let onasset_oracle = oracle(1_000_000_000, 8);
+    let health_score = health_at(&onasset_oracle);
+    assert!(health_score.is_healthy());
+    let liquidation_price = calculate_position_liquidation_price(
+        &health_score,
+        &position,
+        &pool,
+        &onasset_oracle,
+        &collateral_oracle,
+        &COLLATERAL,
+    )
+    .unwrap()
+    .unwrap();
+    // 5,000 of collateral covers the 1,000 position term and 10 onAssets of
+    // impermanent loss up to 400.00.
+    assert_eq!(liquidation_price, Decimal::new(400, 0));
+    assert!(health_at(&to_oracle(liquidation_price)).score.is_zero());
+
+    // Collateral owed to the position doesn't move with the onAsset price.
+    let pool = PoolState {
+        onasset_ild: -1_000_000_000,
+        ..pool
+    };
+    assert_eq!(
+        calculate_position_liquidation_price(
+            &health_score,
+            &position,
+            &pool,
+            &onasset_oracle,
+            &collateral_oracle,
+            &COLLATERAL,
+        )
+        .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_borrow_is_liquidatable_above_liquidation_price() {
+    let collateral_oracle = oracle(1, 0);
+    let borrowed = to_clone_decimal(5_000_000_000u64).unwrap();
+    let collateral_amount = COLLATERAL.to_collateral_decimal(10_000_000_000u64).unwrap();
+    let min_ratio = to_ratio_decimal(150).unwrap();
+    let collateralization_ratio = to_ratio_decimal(COLLATERAL.collateralization_ratio).unwrap();
+
+    let liquidation_price = calculate_borrow_liquidation_price(
+        &collateral_oracle,
+        borrowed,
+        min_ratio,
+        collateralization_ratio,
+        collateral_amount,
+    )
+    .unwrap()
+    .unwrap();
+    // 50 onAssets against 1,000 of collateral stay at 150% up to 13.33...
+    assert_eq!(liquidation_price.round_dp(2), Decimal::new(1_333, 2));
+
+    let check_at = |price: Decimal| {
+        check_mint_collateral_sufficient(
+            &to_oracle(price),
+            &collateral_oracle,
+            borrowed,
+            min_ratio,
+            collateralization_ratio,
+            collateral_amount,
+            SLOT,
+        )
+    };
+    assert!(check_at(Decimal::new(1_333, 2)).is_ok());
+    assert!(check_at(Decimal::new(1_334, 2)).is_err());
+
+    let ratio = calculate_borrow_collateral_ratio(
+        &oracle(1_000_000_000, 8),
+        &collateral_oracle,
+        borrowed,
+        collateralization_ratio,
+        collateral_amount,
+        SLOT,
+    )
+    .unwrap();
+    assert_eq!(ratio, Some(Decimal::new(2, 0)));
+    assert_eq!(
+        calculate_borrow_liquidation_price(
+            &collateral_oracle,
+            Decimal::ZERO,
+            min_ratio,
+            collateralization_ratio,
+            collateral_amount,
+        )
+        .unwrap(),
+        None
+    );
+}
@@ -0,0 +1,527 @@
+//! Property tests for the swap and ILD math.
+//!
+//! Every property is a plain `check_*` function over concrete inputs. proptest
+//! records failing inputs under `proptest-regressions/` and replays them on the
+//! next run; to keep one permanently, paste the printed `Market` and arguments
+//! into a `#[test]` in the `regressions` module at the bottom of this file.
+
+mod common;
+
+use clone_math::decimal::{rescale_toward_zero, to_clone_decimal, CLONE_TOKEN_SCALE};
+use clone_math::*;
+use common::*;
+use proptest::prelude::*;
+
+#[derive(Clone, Debug)]
+struct Market {
+    pool: PoolState,
+    onasset_price: Decimal,
+    collateral_price: Decimal,
+    collateral: CollateralParams,
+    treasury_fee_share_bps: u16,
+}
+
+impl Market {
+    fn fee_share(&self) -> TreasuryFeeShare {
+        TreasuryFeeShare {
+            treasury_fee_share_bps: self.treasury_fee_share_bps,
+            onasset_price: self.onasset_price,
+            collateral_price: self.collateral_price,
+            collateral: self.collateral,
+        }
+    }
+
+    fn jit_pool(&self, pool: &PoolState) -> (Decimal, Decimal) {
+        pool.calculate_jit_pool(self.onasset_price, self.collateral_price, &self.collateral)
+            .unwrap()
+    }
+
+    fn invariant(&self, pool: &PoolState) -> Decimal {
+        pool.calculate_invariant(self.onasset_price, self.collateral_price, &self.collateral)
+            .unwrap()
+    }
+
+    fn swap(
+        &self,
+        pool: &PoolState,
+        quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+    ) -> Option<SwapSummary> {
+        let quantity = if quantity_is_collateral {
+            self.collateral.to_collateral_decimal(quantity).unwrap()
+        } else {
+            to_clone_decimal(quantity).unwrap()
+        };
+        pool.calculate_swap(
+            self.onasset_price,
+            self.collateral_price,
+            quantity,
+            quantity_is_input,
+            quantity_is_collateral,
+            &self.collateral,
+            None,
+            None,
+        )
+        .ok()
+    }
+
+    /// Reserve of the quantity's token that a swap of that quantity can
+    /// draw on or add to, in base units.
+    fn reserve(&self, quantity_is_collateral: bool) -> u64 {
+        let (pool_collateral, pool_onasset) = self.jit_pool(&self.pool);
+        to_amount(if quantity_is_collateral {
+            pool_collateral
+        } else {
+            pool_onasset
+        })
+    }
+}
+
+fn market() -> impl Strategy<Value = Market> {
+    (
+        6u8..=9,
+        1_000u64..=1_000_000_000,
+        10_000i64..=1_000_000_000_000,
+        90_000_000i64..=110_000_000,
+        -5_000i64..=5_000,
+        -5_000i64..=5_000,
+        0u16..=300,
+        0u16..=300,
+        prop_oneof![Just(0u16), 1u16..=u16::MAX],
+        prop_oneof![Just(0u16), 1u16..=10_000],
+    )
+        .prop_map(
+            |(
+                scale,
+                committed_units,
+                onasset_price,
+                collateral_price,
+                collateral_ild_bps,
+                onasset_ild_bps,
+                liquidity_trading_fee_bps,
+                treasury_trading_fee_bps,
+                amplification_coefficient,
+                treasury_fee_share_bps,
+            )| {
+                let collateral = CollateralParams {
+                    scale,
+                    collateralization_ratio: 100,
+                };
+                let onasset_price = Decimal::new(onasset_price, 8);
+                let collateral_price = Decimal::new(collateral_price, 8);
+                let committed_collateral_liquidity = committed_units * 10u64.pow(scale.into());
+                let committed_onasset = collateral
+                    .to_collateral_decimal(committed_collateral_liquidity)
+                    .unwrap()
+                    * collateral_price
+                    / onasset_price;
+                let collateral_ild =
+                    committed_collateral_liquidity as i64 / 10_000 * collateral_ild_bps;
+                let onasset_ild = to_signed_amount(rescale_toward_zero(
+                    committed_onasset * Decimal::new(onasset_ild_bps, 4),
+                    CLONE_TOKEN_SCALE,
+                ));
+                Market {
+                    pool: PoolState {
+                        committed_collateral_liquidity,
+                        collateral_ild,
+                        onasset_ild,
+                        liquidity_trading_fee_bps,
+                        treasury_trading_fee_bps,
+                        il_health_score_coefficient: 100,
+                        position_health_score_coefficient: 100,
+                        // A zero coefficient stands for the constant product curve.
+                        curve: if amplification_coefficient == 0 {
+                            Curve::ConstantProduct
+                        } else {
+                            Curve::StableSwap
+                        },
+                        amplification_coefficient,
+                        ..PoolState::default()
+                    },
+                    onasset_price,
+                    collateral_price,
+                    collateral,
+                    treasury_fee_share_bps,
+                }
+            },
+        )
+}
+
+/// Scales `raw` into a quantity between one base unit and a tenth of the
+/// relevant reserve.
+fn quantity_for(market: &Market, raw: u64, quantity_is_collateral: bool) -> u64 {
+    let max_quantity = (market.reserve(quantity_is_collateral) / 10).max(1);
+    1 + raw % max_quantity
+}
+
+fn check_fees_not_negative(
+    market: &Market,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+) {
+    if let Some(summary) = market.swap(
+        &market.pool,
+        quantity,
+        quantity_is_input,
+        quantity_is_collateral,
+    ) {
+        assert!(
+            summary.liquidity_fees_paid >= Decimal::ZERO,
+            "{:?}",
+            summary
+        );
+        assert!(summary.treasury_fees_paid >= Decimal::ZERO, "{:?}", summary);
+    }
+}
+
+fn check_invariant_not_decreasing(
+    market: &Market,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+) {
+    let Some(summary) = market.swap(
+        &market.pool,
+        quantity,
+        quantity_is_input,
+        quantity_is_collateral,
+    ) else {
+        return;
+    };
+    if summary.result <= Decimal::ZERO {
+        return;
+    }
+    let invariant = market.invariant(&market.pool);
+    let mut pool = market.pool;
+    apply_swap(
+        &mut pool,
+        quantity,
+        quantity_is_input,
+        quantity_is_collateral,
+        &summary,
+        &market.fee_share(),
+    );
+    // The StableSwap invariant is solved numerically, so it's only
+    // reproducible up to the solver's tolerance.
+    let tolerance = match pool.curve {
+        Curve::ConstantProduct => Decimal::ZERO,
+        Curve::StableSwap => invariant * Decimal::new(1, 20),
+    };
+    assert!(
+        market.invariant(&pool) >= invariant - tolerance,
+        "{:?}",
+        summary
+    );
+}
+
+fn check_round_trip_not_profitable(market: &Market, quantity: u64, quantity_is_collateral: bool) {
+    let Some(summary) = market.swap(&market.pool, quantity, true, quantity_is_collateral) else {
+        return;
+    };
+    if summary.result <= Decimal::ZERO {
+        return;
+    }
+    let mut pool = market.pool;
+    let (_, received) = apply_swap(
+        &mut pool,
+        quantity,
+        true,
+        quantity_is_collateral,
+        &summary,
+        &market.fee_share(),
+    );
+
+    // Buy back exactly what was sold, paying with what was received.
+    let Some(reverse) = market.swap(&pool, quantity, false, quantity_is_collateral) else {
+        return;
+    };
+    if reverse.result <= Decimal::ZERO {
+        return;
+    }
+    let paid = to_amount(reverse.result);
+    assert!(paid >= received, "paid {} < received {}", paid, received);
+}
+
+fn check_ild_shares_sum_to_pool(market: &Market, weights: &[u64]) {
+    let pool = &market.pool;
+    let total_weight: u64 = weights.iter().sum();
+    let mut remaining = pool.committed_collateral_liquidity;
+    let mut collateral_claims = Decimal::ZERO;
+    let mut onasset_claims = Decimal::ZERO;
+    for (index, weight) in weights.iter().enumerate() {
+        let committed_collateral_liquidity = if index + 1 == weights.len() {
+            remaining
+        } else {
+            (pool.committed_collateral_liquidity as u128 * *weight as u128 / total_weight as u128)
+                as u64
+        };
+        remaining -= committed_collateral_liquidity;
+        let position = PositionState {
+            committed_collateral_liquidity,
+            ..PositionState::default()
+        };
+        let share = calculate_ild_share(&position, pool, &market.collateral).unwrap();
+        assert_eq!(share.collateral_ild_claim, share.collateral_ild_share);
+        assert_eq!(share.onasset_ild_claim, share.onasset_ild_share);
+        collateral_claims += share.collateral_ild_claim;
+        onasset_claims += share.onasset_ild_claim;
+    }
+
+    // Each claim rounds up by less than one base unit.
+    let num_positions = weights.len() as i64;
+    let collateral_ild = market
+        .collateral
+        .to_collateral_decimal(pool.collateral_ild)
+        .unwrap();
+    let collateral_unit = Decimal::new(1, market.collateral.scale.into());
+    assert!(collateral_claims >= collateral_ild);
+    assert!(collateral_claims <= collateral_ild + collateral_unit * Decimal::from(num_positions));
+    let onasset_ild = to_clone_decimal(pool.onasset_ild).unwrap();
+    let onasset_unit = Decimal::new(1, CLONE_TOKEN_SCALE);
+    assert!(onasset_claims >= onasset_ild);
+    assert!(onasset_claims <= onasset_ild + onasset_unit * Decimal::from(num_positions));
+}
+
+fn check_position_losses_cover_pool_loss(market: &Market, weights: &[u64]) {
+    let pool = &market.pool;
+    let onasset_oracle = OraclePrice {
+        price: market.onasset_price.mantissa() as i64,
+        expo: market.onasset_price.scale() as u8,
+        ..OraclePrice::default()
+    };
+    let collateral_oracle = OraclePrice {
+        price: market.collateral_price.mantissa() as i64,
+        expo: market.collateral_price.scale() as u8,
+        ..OraclePrice::default()
+    };
+    let loss = |committed_collateral_liquidity| {
+        calculate_liquidity_position_loss(
+            &PositionState {
+                committed_collateral_liquidity,
+                ..PositionState::default()
+            },
+            pool,
+            &onasset_oracle,
+            &collateral_oracle,
+            &market.collateral,
+        )
+        .unwrap()
+    };
+
+    let total_weight: u64 = weights.iter().sum();
+    let mut remaining = pool.committed_collateral_liquidity;
+    let mut il_terms = Decimal::ZERO;
+    let mut position_terms = Decimal::ZERO;
+    for (index, weight) in weights.iter().enumerate() {
+        let committed_collateral_liquidity = if index + 1 == weights.len() {
+            remaining
+        } else {
+            (pool.committed_collateral_liquidity as u128 * *weight as u128 / total_weight as u128)
+                as u64
+        };
+        remaining -= committed_collateral_liquidity;
+        let (il_term, position_term) = loss(committed_collateral_liquidity);
+        assert!(il_term >= Decimal::ZERO);
+        assert!(position_term >= Decimal::ZERO);
+        il_terms += il_term;
+        position_terms += position_term;
+    }
+
+    let (pool_il_term, pool_position_term) = loss(pool.committed_collateral_liquidity);
+    assert!(il_terms >= pool_il_term);
+    assert_eq!(position_terms, pool_position_term);
+}
+
+proptest! {
+    #[test]
+    fn fees_are_never_negative(
+        market in market(),
+        raw_quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+    ) {
+        let quantity = quantity_for(&market, raw_quantity, quantity_is_collateral);
+        check_fees_not_negative(&market, quantity, quantity_is_input, quantity_is_collateral);
+    }
+
+    #[test]
+    fn invariant_never_decreases(
+        market in market(),
+        raw_quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+    ) {
+        let quantity = quantity_for(&market, raw_quantity, quantity_is_collateral);
+        check_invariant_not_decreasing(&market, quantity, quantity_is_input, quantity_is_collateral);
+    }
+
+    #[test]
+    fn round_trip_is_never_profitable(
+        market in market(),
+        raw_quantity: u64,
+        quantity_is_collateral: bool,
+    ) {
+        let quantity = quantity_for(&market, raw_quantity, quantity_is_collateral);
+        check_round_trip_not_profitable(&market, quantity, quantity_is_collateral);
+    }
+
+    #[test]
+    fn ild_shares_sum_to_pool_totals(
+        market in market(),
+        weights in prop::collection::vec(1u64..=1_000, 1..=10),
+    ) {
+        check_ild_shares_sum_to_pool(&market, &weights);
+    }
+
+    #[test]
+    fn position_losses_cover_pool_loss(
+        market in market(),
+        weights in prop::collection::vec(1u64..=1_000, 1..=10),
+    ) {
+        check_position_losses_cover_pool_loss(&market, &weights);
+    }
+}
+
+mod regressions {
+    use super::*;
+
+    fn market(
+        collateral_ild: i64,
+        onasset_ild: i64,
+        liquidity_trading_fee_bps: u16,
+        treasury_trading_fee_bps: u16,
+    ) -> Market {
+        Market {
+            pool: PoolState {
+                committed_collateral_liquidity: 1_000_000_000,
+                collateral_ild,
+                onasset_ild,
+                liquidity_trading_fee_bps,
+                treasury_trading_fee_bps,
+                il_health_score_coefficient: 100,
+                position_health_score_coefficient: 100,
+                ..PoolState::default()
+            },
+            onasset_price: Decimal::new(1_234_567_891, 8),
+            collateral_price: Decimal::ONE,
+            collateral: CollateralParams {
+                scale: 7,
+                collateralization_ratio: 100,
+            },
+            treasury_fee_share_bps: 0,
+        }
+    }
+
+    #[test]
+    fn single_unit_swaps() {
+        let market = market(0, 0, 15, 10);
+        for quantity_is_input in [true, false] {
+            for quantity_is_collateral in [true, false] {
+                check_fees_not_negative(&market, 1, quantity_is_input, quantity_is_collateral);
+                check_invariant_not_decreasing(
+                    &market,
+                    1,
+                    quantity_is_input,
+                    quantity_is_collateral,
+                );
+            }
+            check_round_trip_not_profitable(&market, 1, quantity_is_input);
+        }
+    }
+
+    #[test]
+    fn swaps_sharing_treasury_fees() {
+        for treasury_fee_share_bps in [1, 5_000, 10_000] {
+            let mut market = market(12_345_678, -98_765, 15, 10);
+            market.treasury_fee_share_bps = treasury_fee_share_bps;
+            for quantity_is_input in [true, false] {
+                for quantity_is_collateral in [true, false] {
+                    for quantity in [1, 777, 10_000_000] {
+                        check_invariant_not_decreasing(
+                            &market,
+                            quantity,
+                            quantity_is_input,
+                            quantity_is_collateral,
+                        );
+                    }
+                }
+                check_round_trip_not_profitable(&market, 777, quantity_is_input);
+            }
+        }
+    }
+
+    #[test]
+    fn stakers_share_of_pool_trading_below_oracle() {
+        // The pool's onAsset is worth a fraction of its oracle price, so
+        // paying stakers the oracle value of their share would drain it.
+        let mut market = market(414_000_000, -1_170_000_000_000, 0, 1);
+        market.onasset_price = Decimal::new(1, 4);
+        market.collateral_price = Decimal::new(9, 1);
+        market.collateral.scale = 6;
+        market.treasury_fee_share_bps = 321;
+        for quantity_is_input in [true, false] {
+            for quantity_is_collateral in [true, false] {
+                check_invariant_not_decreasing(
+                    &market,
+                    10_000_000,
+                    quantity_is_input,
+                    quantity_is_collateral,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn swaps_without_fees() {
+        let market = market(12_345_678, -98_765, 0, 0);
+        for quantity_is_input in [true, false] {
+            for quantity_is_collateral in [true, false] {
+                check_invariant_not_decreasing(
+                    &market,
+                    777,
+                    quantity_is_input,
+                    quantity_is_collateral,
+                );
+            }
+            check_round_trip_not_profitable(&market, 777, quantity_is_input);
+        }
+    }
+
+    #[test]
+    fn stableswap_swaps() {
+        for amplification_coefficient in [1, 100, u16::MAX] {
+            let mut market = market(12_345_678, -98_765, 15, 10);
+            market.pool.curve = Curve::StableSwap;
+            market.pool.amplification_coefficient = amplification_coefficient;
+            for quantity_is_input in [true, false] {
+                for quantity_is_collateral in [true, false] {
+                    for quantity in [1, 777, 10_000_000] {
+                        check_fees_not_negative(
+                            &market,
+                            quantity,
+                            quantity_is_input,
+                            quantity_is_collateral,
+                        );
+                        check_invariant_not_decreasing(
+                            &market,
+                            quantity,
+                            quantity_is_input,
+                            quantity_is_collateral,
+                        );
+                    }
+                }
+                check_round_trip_not_profitable(&market, 777, quantity_is_input);
+            }
+        }
+    }
+
+    #[test]
+    fn uneven_ild_split() {
+        let market = market(123_456_789, -9_876_543, 15, 10);
+        check_ild_shares_sum_to_pool(&market, &[1, 2, 3, 997]);
+        check_position_losses_cover_pool_loss(&market, &[1, 2, 3, 997]);
+    }
+}
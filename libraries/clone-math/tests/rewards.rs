@@ -0,0 +1,126 @@
+//! Checks the reward-per-share accounting of liquidity mining rewards.
+
+use clone_math::*;
+use proptest::prelude::*;
+
+const SLOT: u64 = 100;
+
+fn rewards(emission_rate: u64, unemitted_rewards: u64) -> RewardState {
+    RewardState {
+        emission_rate,
+        unemitted_rewards,
+        last_update_slot: SLOT,
+        ..RewardState::default()
+    }
+}
+
+#[test]
+fn test_rewards_accrue_pro_rata() {
+    let state = rewards(1_000, 1_000_000).accrue(4_000, SLOT + 10).unwrap();
+    assert_eq!(state.unemitted_rewards, 990_000);
+    assert_eq!(state.last_update_slot, SLOT + 10);
+
+    // Three quarters and a quarter of the committed liquidity.
+    assert_eq!(
+        calculate_accrued_rewards(3_000, state.reward_index, 0).unwrap(),
+        7_500
+    );
+    assert_eq!(
+        calculate_accrued_rewards(1_000, state.reward_index, 0).unwrap(),
+        2_500
+    );
+    // Positions only earn from the index they last recorded.
+    assert_eq!(
+        calculate_accrued_rewards(1_000, state.reward_index, state.reward_index).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_rewards_are_capped_by_funding() {
+    let state = rewards(1_000, 5_000).accrue(1_000, SLOT + 10).unwrap();
+    assert_eq!(state.unemitted_rewards, 0);
+    assert_eq!(
+        calculate_accrued_rewards(1_000, state.reward_index, 0).unwrap(),
+        5_000
+    );
+}
+
+#[test]
+fn test_rewards_wait_for_liquidity() {
+    let state = rewards(1_000, 1_000_000).accrue(0, SLOT + 10).unwrap();
+    assert_eq!(state.unemitted_rewards, 1_000_000);
+    assert_eq!(state.reward_index, 0);
+    assert_eq!(state.last_update_slot, SLOT + 10);
+
+    // Accruing twice within a slot emits once.
+    let state = state.accrue(1_000, SLOT + 11).unwrap();
+    assert_eq!(state, state.accrue(1_000, SLOT + 11).unwrap());
+    assert_eq!(state.unemitted_rewards, 999_000);
+}
+
+#[test]
+fn test_reward_index_keeps_large_deltas() {
+    // A whole u64 of rewards over a single unit of liquidity moves the index
+    // far past u64::MAX in one accrual.
+    let state = rewards(u64::MAX, u64::MAX).accrue(1, SLOT + 1).unwrap();
+    assert_eq!(state.reward_index, u64::MAX as u128 * REWARD_INDEX_SCALE);
+    assert_eq!(
+        calculate_accrued_rewards(1, state.reward_index, 0).unwrap(),
+        u64::MAX
+    );
+    assert!(calculate_accrued_rewards(1, 0, state.reward_index).is_err());
+}
+
+/// Replays `steps` of (slots elapsed, position, new liquidity) against a pool
+/// whose positions start with `liquidities`, settling each position before
+/// its liquidity changes, and checks that the positions never claim more
+/// than the pool emitted.
+fn check_claims_within_emissions(
+    emission_rate: u64,
+    funding: u64,
+    liquidities: &[u64],
+    steps: &[(u64, usize, u64)],
+) {
+    let mut state = rewards(emission_rate, funding);
+    let mut positions: Vec<(u64, u128)> = liquidities.iter().map(|l| (*l, 0)).collect();
+    let mut claimed = 0u128;
+    let mut slot = SLOT;
+    let settle = |state: &RewardState, position: &(u64, u128)| {
+        calculate_accrued_rewards(position.0, state.reward_index, position.1).unwrap() as u128
+    };
+
+    for (elapsed_slots, index, liquidity) in steps {
+        slot += elapsed_slots;
+        state = state
+            .accrue(positions.iter().map(|(l, _)| l).sum(), slot)
+            .unwrap();
+        let position = &mut positions[index % liquidities.len()];
+        claimed += settle(&state, position);
+        *position = (*liquidity, state.reward_index);
+    }
+    state = state
+        .accrue(positions.iter().map(|(l, _)| l).sum(), slot + 1)
+        .unwrap();
+    claimed += positions
+        .iter()
+        .map(|position| settle(&state, position))
+        .sum::<u128>();
+
+    assert!(claimed <= (funding - state.unemitted_rewards) as u128);
+}
+
+proptest! {
+    #[test]
+    fn claimed_rewards_never_exceed_emissions(
+        emission_rate in 0u64..=1_000_000_000_000,
+        funding: u64,
+        liquidities in prop::collection::vec(0u64..=1_000_000_000_000_000, 1..=8),
+        steps in prop::collection::vec(
+            (0u64..=1_000, any::<usize>(), 0u64..=1_000_000_000_000_000),
+            0..=32,
+        ),
+    ) {
+        check_claims_within_emissions(emission_rate, funding, &liquidities, &steps);
+    }
+}
@@ -0,0 +1,303 @@
+//! Checks that rounding always favors the protocol: repeating small
+//! operations must never leave a user with more than they started with.
+
+mod common;
+
+use clone_math::decimal::{rescale_down, rescale_up, to_clone_decimal, CLONE_TOKEN_SCALE};
+use clone_math::*;
+use common::*;
+
+const COLLATERAL: CollateralParams = CollateralParams {
+    scale: 7,
+    collateralization_ratio: 100,
+};
+const ITERATIONS: u64 = 200;
+const TREASURY_FEE_SHARE_BPS: u16 = 2_500;
+
+fn onasset_price() -> Decimal {
+    Decimal::new(1_234_567_891, 8)
+}
+
+fn collateral_price() -> Decimal {
+    Decimal::ONE
+}
+
+fn pool(collateral_ild: i64, onasset_ild: i64) -> PoolState {
+    PoolState {
+        committed_collateral_liquidity: 10_000_000_000_000,
+        collateral_ild,
+        onasset_ild,
+        liquidity_trading_fee_bps: 15,
+        treasury_trading_fee_bps: 10,
+        il_health_score_coefficient: 100,
+        position_health_score_coefficient: 100,
+        ..PoolState::default()
+    }
+}
+
+fn to_quantity(amount: u64, quantity_is_collateral: bool) -> Decimal {
+    if quantity_is_collateral {
+        COLLATERAL.to_collateral_decimal(amount).unwrap()
+    } else {
+        to_clone_decimal(amount).unwrap()
+    }
+}
+
+/// Runs a swap the way the `swap` instruction does, applying the ILD changes
+/// to `pool`. Returns the (input, output) amounts, or `None` if the
+/// instruction would reject the swap.
+fn swap(
+    pool: &mut PoolState,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+) -> Option<(u64, u64)> {
+    let summary = pool
+        .calculate_swap(
+            onasset_price(),
+            collateral_price(),
+            to_quantity(quantity, quantity_is_collateral),
+            quantity_is_input,
+            quantity_is_collateral,
+            &COLLATERAL,
+            None,
+            None,
+        )
+        .unwrap();
+    if quantity == 0
+        || summary.result <= Decimal::ZERO
+        || summary.liquidity_fees_paid <= Decimal::ZERO
+        || summary.treasury_fees_paid <= Decimal::ZERO
+    {
+        return None;
+    }
+    Some(apply_swap(
+        pool,
+        quantity,
+        quantity_is_input,
+        quantity_is_collateral,
+        &summary,
+        &TreasuryFeeShare {
+            treasury_fee_share_bps: TREASURY_FEE_SHARE_BPS,
+            onasset_price: onasset_price(),
+            collateral_price: collateral_price(),
+            collateral: COLLATERAL,
+        },
+    ))
+}
+
+#[test]
+fn test_rescale_direction() {
+    let value = Decimal::new(-15, 1);
+    assert_eq!(rescale_down(value, 0), Decimal::new(-2, 0));
+    assert_eq!(rescale_up(value, 0), Decimal::new(-1, 0));
+    let value = Decimal::new(15, 1);
+    assert_eq!(rescale_down(value, 0), Decimal::new(1, 0));
+    assert_eq!(rescale_up(value, 0), Decimal::new(2, 0));
+    assert_eq!(rescale_up(Decimal::new(2, 0), 3).scale(), 3);
+}
+
+#[test]
+fn test_swap_exact_input_round_trip_extracts_no_dust() {
+    for quantity_is_collateral in [true, false] {
+        let mut pool = pool(0, 0);
+        for quantity in 1..=ITERATIONS {
+            let Some((_, received)) = swap(&mut pool, quantity, true, quantity_is_collateral)
+            else {
+                continue;
+            };
+            if let Some((_, returned)) = swap(&mut pool, received, true, !quantity_is_collateral) {
+                assert!(returned <= quantity, "{} > {}", returned, quantity);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_swap_exact_output_round_trip_extracts_no_dust() {
+    for quantity_is_collateral in [true, false] {
+        let mut pool = pool(0, 0);
+        for quantity in 1..=ITERATIONS {
+            // Buy `quantity` exactly, then sell the purchase back.
+            let Some((paid, _)) = swap(&mut pool, quantity, false, quantity_is_collateral) else {
+                continue;
+            };
+            if let Some((_, returned)) = swap(&mut pool, quantity, true, quantity_is_collateral) {
+                assert!(returned <= paid, "{} > {}", returned, paid);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_swap_rounds_fees_up_and_output_down() {
+    let pool = pool(0, 0);
+    for quantity in 1..=ITERATIONS {
+        let summary = pool
+            .calculate_swap(
+                onasset_price(),
+                collateral_price(),
+                to_quantity(quantity, true),
+                true,
+                true,
+                &COLLATERAL,
+                None,
+                None,
+            )
+            .unwrap();
+        let output_before_fees =
+            summary.result + summary.liquidity_fees_paid + summary.treasury_fees_paid;
+        assert!(summary.liquidity_fees_paid >= output_before_fees * Decimal::new(15, 4));
+        assert!(summary.treasury_fees_paid >= output_before_fees * Decimal::new(10, 4));
+    }
+}
+
+fn add_liquidity(pool: &mut PoolState, position: &mut PositionState, amount: u64) {
+    let (collateral_ild, onasset_ild) =
+        calculate_committed_liquidity_ild(amount, pool, &COLLATERAL).unwrap();
+    pool.committed_collateral_liquidity += amount;
+    pool.collateral_ild += to_signed_amount(collateral_ild);
+    pool.onasset_ild += to_signed_amount(onasset_ild);
+    position.committed_collateral_liquidity += amount;
+    position.collateral_ild_rebate += to_signed_amount(collateral_ild);
+    position.onasset_ild_rebate += to_signed_amount(onasset_ild);
+}
+
+fn withdraw_liquidity(pool: &mut PoolState, position: &mut PositionState, amount: u64) {
+    let (collateral_ild, onasset_ild) =
+        calculate_withdrawn_liquidity_ild(amount, pool, &COLLATERAL).unwrap();
+    pool.committed_collateral_liquidity -= amount;
+    pool.collateral_ild -= to_signed_amount(collateral_ild);
+    pool.onasset_ild -= to_signed_amount(onasset_ild);
+    position.committed_collateral_liquidity -= amount;
+    position.collateral_ild_rebate -= to_signed_amount(collateral_ild);
+    position.onasset_ild_rebate -= to_signed_amount(onasset_ild);
+}
+
+#[test]
+fn test_liquidity_round_trip_extracts_no_dust() {
+    for (collateral_ild, onasset_ild) in
+        [(123_457, 98_765), (-123_457, -98_765), (123_457, -98_765)]
+    {
+        for amount in [1, 7, 81, ITERATIONS, 1_000] {
+            // Committing in one go and withdrawing unit by unit, and the
+            // reverse, must both leave the emptied position without a reward.
+            for add_in_units in [false, true] {
+                let mut pool = pool(collateral_ild, onasset_ild);
+                pool.committed_collateral_liquidity = 1_000_003;
+                let mut position = PositionState::default();
+                if add_in_units {
+                    for _ in 0..amount {
+                        add_liquidity(&mut pool, &mut position, 1);
+                    }
+                    withdraw_liquidity(&mut pool, &mut position, amount);
+                } else {
+                    add_liquidity(&mut pool, &mut position, amount);
+                    for _ in 0..amount {
+                        withdraw_liquidity(&mut pool, &mut position, 1);
+                    }
+                }
+
+                let share = calculate_ild_share(&position, &pool, &COLLATERAL).unwrap();
+                assert!(share.collateral_ild_share >= Decimal::ZERO);
+                assert!(share.onasset_ild_share >= Decimal::ZERO);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ild_shares_cover_pool_ild() {
+    for (collateral_ild, onasset_ild) in
+        [(1_234_567_891, 987_654_321), (-1_234_567_891, -987_654_321)]
+    {
+        let pool = pool(collateral_ild, onasset_ild);
+        for num_positions in [3u64, 7, 13] {
+            let committed = pool.committed_collateral_liquidity / num_positions;
+            let mut collateral_shares = Decimal::ZERO;
+            let mut onasset_shares = Decimal::ZERO;
+            for index in 0..num_positions {
+                let position = PositionState {
+                    committed_collateral_liquidity: if index + 1 == num_positions {
+                        pool.committed_collateral_liquidity - committed * (num_positions - 1)
+                    } else {
+                        committed
+                    },
+                    ..PositionState::default()
+                };
+                let share = calculate_ild_share(&position, &pool, &COLLATERAL).unwrap();
+                collateral_shares += share.collateral_ild_share;
+                onasset_shares += share.onasset_ild_share;
+            }
+            // Debt is never under-collected and rewards are never over-paid.
+            assert!(collateral_shares >= COLLATERAL.to_collateral_decimal(collateral_ild).unwrap());
+            assert!(onasset_shares >= to_clone_decimal(onasset_ild).unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_position_loss_rounds_up() {
+    let pool = pool(1_234_567_891, 987_654_321);
+    let onasset_oracle = OraclePrice {
+        price: 1_234_567_891,
+        expo: 8,
+        ..OraclePrice::default()
+    };
+    let collateral_oracle = OraclePrice {
+        price: 1,
+        ..OraclePrice::default()
+    };
+    for committed in 1..=ITERATIONS {
+        let position = PositionState {
+            committed_collateral_liquidity: committed,
+            ..PositionState::default()
+        };
+        let (il_term, _) = calculate_liquidity_position_loss(
+            &position,
+            &pool,
+            &onasset_oracle,
+            &collateral_oracle,
+            &COLLATERAL,
+        )
+        .unwrap();
+        let ratio = COLLATERAL.to_collateral_decimal(committed).unwrap()
+            / COLLATERAL
+                .to_collateral_decimal(pool.committed_collateral_liquidity)
+                .unwrap();
+        let exact_loss = COLLATERAL
+            .to_collateral_decimal(pool.collateral_ild)
+            .unwrap()
+            * ratio
+            + onasset_price() * to_clone_decimal(pool.onasset_ild).unwrap() * ratio;
+        assert!(il_term >= exact_loss);
+    }
+}
+
+#[test]
+fn test_wrap_unwrap_round_trip_extracts_no_dust() {
+    for underlying_scale in [6u32, 9, 12] {
+        for amount in 1..=ITERATIONS as i64 {
+            let onasset = rescale_down(Decimal::new(amount, underlying_scale), CLONE_TOKEN_SCALE);
+            let unwrapped = rescale_down(onasset, underlying_scale);
+            assert!(unwrapped.mantissa() <= amount.into());
+        }
+    }
+}
+
+#[test]
+fn test_liquidator_rewards_round_down() {
+    let fee = Decimal::new(50, 4);
+    let pool_price = onasset_price() / collateral_price();
+    let mut total_reward = Decimal::ZERO;
+    let mut total_burned = Decimal::ZERO;
+    for amount in 1..=ITERATIONS {
+        let burn_amount = to_clone_decimal(amount).unwrap();
+        total_reward += rescale_down(
+            (Decimal::ONE + fee) * pool_price * burn_amount,
+            COLLATERAL.scale.into(),
+        );
+        total_burned += burn_amount;
+    }
+    assert!(total_reward <= (Decimal::ONE + fee) * pool_price * total_burned);
+}
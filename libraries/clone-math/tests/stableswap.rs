@@ -0,0 +1,144 @@
+//! Checks the StableSwap curve against the constant product curve it
+//! replaces for tightly pegged onAssets.
+
+use clone_math::*;
+
+const COLLATERAL: CollateralParams = CollateralParams {
+    scale: 7,
+    collateralization_ratio: 100,
+};
+const SLOT: u64 = 7;
+
+fn onasset_price() -> Decimal {
+    Decimal::new(100_100_000, 8)
+}
+
+fn collateral_price() -> Decimal {
+    Decimal::ONE
+}
+
+fn pool(curve: Curve, collateral_ild: i64, onasset_ild: i64) -> PoolState {
+    PoolState {
+        committed_collateral_liquidity: 10_000_000_000,
+        collateral_ild,
+        onasset_ild,
+        liquidity_trading_fee_bps: 15,
+        treasury_trading_fee_bps: 10,
+        il_health_score_coefficient: 100,
+        position_health_score_coefficient: 100,
+        curve,
+        amplification_coefficient: 100,
+        ..PoolState::default()
+    }
+}
+
+fn sell_collateral(pool: &PoolState, collateral_amount: u64) -> SwapSummary {
+    pool.calculate_swap(
+        onasset_price(),
+        collateral_price(),
+        COLLATERAL.to_collateral_decimal(collateral_amount).unwrap(),
+        true,
+        true,
+        &COLLATERAL,
+        None,
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_stableswap_has_less_price_impact() {
+    let constant_product = pool(Curve::ConstantProduct, 0, 0);
+    let stableswap = pool(Curve::StableSwap, 0, 0);
+    // 10% of the pool's collateral.
+    let quantity = 1_000_000_000;
+    let constant_product_output = sell_collateral(&constant_product, quantity).result;
+    let stableswap_output = sell_collateral(&stableswap, quantity).result;
+    assert!(stableswap_output > constant_product_output);
+
+    // Apart from fees the trade stays within 0.1% of the oracle price.
+    let at_center = COLLATERAL.to_collateral_decimal(quantity).unwrap() / onasset_price()
+        * (Decimal::ONE - Decimal::new(25, 4));
+    assert!(stableswap_output <= at_center);
+    assert!(stableswap_output >= at_center * Decimal::new(999, 3));
+}
+
+#[test]
+fn test_stableswap_pool_price_follows_ild() {
+    let center_price = onasset_price() / collateral_price();
+    for curve in [Curve::ConstantProduct, Curve::StableSwap] {
+        let balanced = pool(curve, 0, 0)
+            .calculate_pool_price(onasset_price(), collateral_price(), &COLLATERAL)
+            .unwrap();
+        assert_eq!(balanced.round_dp(6), center_price);
+
+        // Onassets bought out of the pool raise its price.
+        let bought = pool(curve, -1_000_000_000, 9_980_000_000)
+            .calculate_pool_price(onasset_price(), collateral_price(), &COLLATERAL)
+            .unwrap();
+        assert!(bought > center_price);
+    }
+
+    // The StableSwap pool moves less for the same imbalance.
+    let price_after = |curve| {
+        pool(curve, -1_000_000_000, 9_980_000_000)
+            .calculate_pool_price(onasset_price(), collateral_price(), &COLLATERAL)
+            .unwrap()
+    };
+    assert!(price_after(Curve::StableSwap) < price_after(Curve::ConstantProduct));
+}
+
+#[test]
+fn test_health_score_is_independent_of_curve() {
+    let onasset_oracle = OraclePrice {
+        price: onasset_price().mantissa() as i64,
+        expo: onasset_price().scale() as u8,
+        last_update_slot: SLOT,
+        ..OraclePrice::default()
+    };
+    let collateral_oracle = OraclePrice {
+        price: 1,
+        last_update_slot: SLOT,
+        ..OraclePrice::default()
+    };
+    let health_score = |curve| {
+        let pool = pool(curve, -1_000_000_000, 9_980_000_000);
+        let position = PositionState {
+            committed_collateral_liquidity: pool.committed_collateral_liquidity,
+            ..PositionState::default()
+        };
+        calculate_health_score(
+            20_000_000_000,
+            [(position, pool, onasset_oracle)],
+            &collateral_oracle,
+            &COLLATERAL,
+            SLOT,
+        )
+        .unwrap()
+        .score
+    };
+    // ILD records what the pool paid out, whichever curve priced the trades.
+    assert_eq!(
+        health_score(Curve::ConstantProduct),
+        health_score(Curve::StableSwap)
+    );
+}
+
+#[test]
+fn test_stableswap_requires_amplification() {
+    let mut stableswap = pool(Curve::StableSwap, 0, 0);
+    stableswap.amplification_coefficient = 0;
+    assert!(matches!(
+        stableswap.calculate_swap(
+            onasset_price(),
+            collateral_price(),
+            Decimal::ONE,
+            true,
+            true,
+            &COLLATERAL,
+            None,
+            None,
+        ),
+        Err(MathError::CheckedMathError)
+    ));
+}
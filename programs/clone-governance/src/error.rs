@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum GovernanceError {
+    #[msg("Input is invalid!")]
+    InvalidInput,
+
+    #[msg("Bump not found")]
+    BumpNotFound,
+
+    #[msg("Checked Math Error")]
+    CheckedMathError,
+
+    #[msg("Not enough stake from before the proposal!")]
+    InsufficientVotingPower,
+
+    #[msg("Voting has ended!")]
+    VotingClosed,
+
+    #[msg("Voting and execution delay haven't ended!")]
+    ExecutionDelayNotElapsed,
+
+    #[msg("Proposal didn't pass!")]
+    ProposalNotPassed,
+
+    #[msg("Proposal was already executed!")]
+    ProposalAlreadyExecuted,
+
+    #[msg("Expected account not found")]
+    ExpectedAccountNotFound,
+}
@@ -0,0 +1,25 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+// PROPOSAL EVENTS
+
+#[event]
+pub struct ProposalCreated {
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    pub voting_end_slot: u64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub index: u64,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub weight: u64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub index: u64,
+}
@@ -0,0 +1,36 @@
+use crate::states::*;
+use crate::GOVERNANCE_SEED;
+use anchor_lang::prelude::*;
+use clone::cpi::accounts::AcceptAdmin;
+use clone::program::Clone as CloneProgram;
+
+#[derive(Accounts)]
+pub struct AcceptCloneAdmin<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED.as_ref()],
+        bump = governance.bump,
+    )]
+    pub governance: Account<'info, Governance>,
+    pub clone_program: Program<'info, CloneProgram>,
+    /// CHECK: Validated by the clone program.
+    #[account(mut)]
+    pub clone: UncheckedAccount<'info>,
+}
+
+/// Completes the handover of `Clone.admin` once the admin made the
+/// governance PDA its pending admin. Anyone may call it.
+pub fn execute(ctx: Context<AcceptCloneAdmin>) -> Result<()> {
+    let seeds = &[&[
+        GOVERNANCE_SEED.as_ref(),
+        bytemuck::bytes_of(&ctx.accounts.governance.bump),
+    ][..]];
+    let cpi_accounts = AcceptAdmin {
+        pending_admin: ctx.accounts.governance.to_account_info(),
+        clone: ctx.accounts.clone.to_account_info(),
+    };
+    clone::cpi::accept_admin(CpiContext::new_with_signer(
+        ctx.accounts.clone_program.to_account_info(),
+        cpi_accounts,
+        seeds,
+    ))
+}
@@ -0,0 +1,65 @@
+use crate::error::*;
+use crate::events::*;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use clone_staking::states::User;
+
+pub const VOTE_SEED: &str = "vote";
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    #[account(
+        seeds = [clone_staking::USER_SEED.as_ref(), voter.key.as_ref()],
+        bump,
+        seeds::program = clone_staking::ID,
+    )]
+    pub voter_staking_account: Account<'info, User>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        space = 8 + 9,
+        seeds = [VOTE_SEED.as_ref(), proposal.key().as_ref(), voter.key.as_ref()],
+        bump,
+        payer = voter
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Votes the voter's snapshotted stake for or against the proposal. The vote
+/// record makes a second vote fail.
+pub fn execute(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    require!(
+        Clock::get()?.slot < proposal.voting_end_slot,
+        GovernanceError::VotingClosed
+    );
+
+    let weight = proposal.voting_power(&ctx.accounts.voter_staking_account);
+    require!(weight > 0, GovernanceError::InsufficientVotingPower);
+
+    let votes = if approve {
+        &mut proposal.votes_for
+    } else {
+        &mut proposal.votes_against
+    };
+    *votes = votes
+        .checked_add(weight)
+        .ok_or(error!(GovernanceError::CheckedMathError))?;
+
+    let vote_record = &mut ctx.accounts.vote_record;
+    vote_record.approve = approve;
+    vote_record.weight = weight;
+
+    emit!(VoteCast {
+        index: proposal.index,
+        voter: ctx.accounts.voter.key(),
+        approve,
+        weight,
+    });
+
+    Ok(())
+}
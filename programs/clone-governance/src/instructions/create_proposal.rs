@@ -0,0 +1,71 @@
+use crate::error::*;
+use crate::events::*;
+use crate::states::*;
+use crate::GOVERNANCE_SEED;
+use anchor_lang::prelude::*;
+use clone_staking::states::User;
+
+pub const PROPOSAL_SEED: &str = "proposal";
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(
+        seeds = [clone_staking::USER_SEED.as_ref(), proposer.key.as_ref()],
+        bump,
+        seeds::program = clone_staking::ID,
+    )]
+    pub proposer_staking_account: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [GOVERNANCE_SEED.as_ref()],
+        bump = governance.bump,
+    )]
+    pub governance: Account<'info, Governance>,
+    // Leaves 64 bytes for the action.
+    #[account(
+        init,
+        space = 8 + 137,
+        seeds = [PROPOSAL_SEED.as_ref(), &governance.num_proposals.to_le_bytes()],
+        bump,
+        payer = proposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Opens voting on `action` for `voting_period_slots`, counting stake
+/// added before this slot.
+pub fn execute(ctx: Context<CreateProposal>, action: ProposalAction) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let governance = &mut ctx.accounts.governance;
+    let proposal = &mut ctx.accounts.proposal;
+
+    proposal.index = governance.num_proposals;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.action = action;
+    proposal.snapshot_slot = current_slot;
+    proposal.voting_end_slot = current_slot
+        .checked_add(governance.voting_period_slots)
+        .ok_or(error!(GovernanceError::CheckedMathError))?;
+    require!(
+        proposal.voting_power(&ctx.accounts.proposer_staking_account)
+            >= governance.proposal_threshold,
+        GovernanceError::InsufficientVotingPower
+    );
+
+    governance.num_proposals = governance
+        .num_proposals
+        .checked_add(1)
+        .ok_or(error!(GovernanceError::CheckedMathError))?;
+
+    emit!(ProposalCreated {
+        index: proposal.index,
+        proposer: proposal.proposer,
+        action: proposal.action.clone(),
+        voting_end_slot: proposal.voting_end_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::error::*;
+use crate::events::*;
+use crate::states::*;
+use crate::GOVERNANCE_SEED;
+use anchor_lang::prelude::*;
+use clone::cpi::accounts::{UpdateCloneParameters, UpdateOracles, UpdatePoolParameters};
+use clone::program::Clone as CloneProgram;
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED.as_ref()],
+        bump = governance.bump,
+    )]
+    pub governance: Account<'info, Governance>,
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    pub clone_program: Program<'info, CloneProgram>,
+    /// CHECK: Validated by the clone program.
+    #[account(mut)]
+    pub clone: UncheckedAccount<'info>,
+    /// CHECK: Validated by the clone program, only needed for pool parameters.
+    #[account(mut)]
+    pub pools: Option<UncheckedAccount<'info>>,
+    /// CHECK: Validated by the clone program, only needed for oracle updates.
+    #[account(mut)]
+    pub oracles: Option<UncheckedAccount<'info>>,
+}
+
+/// Carries out a passed proposal once the execution delay has elapsed, with
+/// the governance PDA signing as `Clone.admin`. Anyone may call it.
+pub fn execute(ctx: Context<ExecuteProposal>) -> Result<()> {
+    let governance = &ctx.accounts.governance;
+    let proposal = &mut ctx.accounts.proposal;
+
+    require!(!proposal.executed, GovernanceError::ProposalAlreadyExecuted);
+    let executable_slot = proposal
+        .voting_end_slot
+        .checked_add(governance.execution_delay_slots)
+        .ok_or(error!(GovernanceError::CheckedMathError))?;
+    require!(
+        Clock::get()?.slot >= executable_slot,
+        GovernanceError::ExecutionDelayNotElapsed
+    );
+    require!(
+        proposal.has_passed(governance.quorum),
+        GovernanceError::ProposalNotPassed
+    );
+    proposal.executed = true;
+
+    let seeds = &[&[
+        GOVERNANCE_SEED.as_ref(),
+        bytemuck::bytes_of(&governance.bump),
+    ][..]];
+    let cpi_program = ctx.accounts.clone_program.to_account_info();
+    let auth = governance.to_account_info();
+    let clone = ctx.accounts.clone.to_account_info();
+
+    match proposal.action.clone() {
+        ProposalAction::UpdatePoolParameters { index, params } => {
+            let pools = ctx
+                .accounts
+                .pools
+                .as_ref()
+                .ok_or(error!(GovernanceError::ExpectedAccountNotFound))?;
+            let cpi_accounts = UpdatePoolParameters {
+                auth,
+                clone,
+                pools: pools.to_account_info(),
+            };
+            clone::cpi::update_pool_parameters(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds),
+                index,
+                params,
+            )?;
+        }
+        ProposalAction::UpdateCloneParameters { params } => {
+            let cpi_accounts = UpdateCloneParameters { admin: auth, clone };
+            clone::cpi::update_clone_parameters(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds),
+                params,
+            )?;
+        }
+        ProposalAction::UpdateOracles { params } => {
+            let oracles = ctx
+                .accounts
+                .oracles
+                .as_ref()
+                .ok_or(error!(GovernanceError::ExpectedAccountNotFound))?;
+            let cpi_accounts = UpdateOracles {
+                auth,
+                clone,
+                oracles: oracles.to_account_info(),
+            };
+            clone::cpi::update_oracles(
+                CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds),
+                params,
+            )?;
+        }
+    }
+
+    emit!(ProposalExecuted {
+        index: proposal.index,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::error::*;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+pub const GOVERNANCE_SEED: &str = "governance";
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + 41,
+        seeds = [GOVERNANCE_SEED.as_ref()],
+        bump,
+        payer = payer
+    )]
+    pub governance: Account<'info, Governance>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Sets up the governance PDA, which only acts once `Clone.admin` is
+/// handed over to it through `accept_clone_admin`.
+pub fn execute(
+    ctx: Context<Initialize>,
+    voting_period_slots: u64,
+    execution_delay_slots: u64,
+    quorum: u64,
+    proposal_threshold: u64,
+) -> Result<()> {
+    require!(
+        voting_period_slots > 0 && quorum > 0 && proposal_threshold > 0,
+        GovernanceError::InvalidInput
+    );
+
+    let governance = &mut ctx.accounts.governance;
+    governance.bump = *ctx
+        .bumps
+        .get("governance")
+        .ok_or(error!(GovernanceError::BumpNotFound))?;
+    governance.voting_period_slots = voting_period_slots;
+    governance.execution_delay_slots = execution_delay_slots;
+    governance.quorum = quorum;
+    governance.proposal_threshold = proposal_threshold;
+
+    Ok(())
+}
@@ -0,0 +1,11 @@
+pub mod accept_clone_admin;
+pub mod cast_vote;
+pub mod create_proposal;
+pub mod execute_proposal;
+pub mod initialize;
+
+pub use accept_clone_admin::*;
+pub use cast_vote::*;
+pub use create_proposal::*;
+pub use execute_proposal::*;
+pub use initialize::*;
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod states;
+
+pub use instructions::*;
+use states::ProposalAction;
+
+declare_id!("Bthaq1xj3KNSo2cJqcqSqr7BJbkCc8mNxoeMSRdB7mV2");
+
+#[program]
+pub mod clone_governance {
+    use super::*;
+
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        voting_period_slots: u64,
+        execution_delay_slots: u64,
+        quorum: u64,
+        proposal_threshold: u64,
+    ) -> Result<()> {
+        instructions::initialize::execute(
+            ctx,
+            voting_period_slots,
+            execution_delay_slots,
+            quorum,
+            proposal_threshold,
+        )
+    }
+
+    pub fn accept_clone_admin(ctx: Context<AcceptCloneAdmin>) -> Result<()> {
+        instructions::accept_clone_admin::execute(ctx)
+    }
+
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: ProposalAction) -> Result<()> {
+        instructions::create_proposal::execute(ctx, action)
+    }
+
+    pub fn cast_vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+        instructions::cast_vote::execute(ctx, approve)
+    }
+
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        instructions::execute_proposal::execute(ctx)
+    }
+}
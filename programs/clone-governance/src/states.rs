@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use clone::instructions::{CloneParameters, PoolParameters, UpdateOracleParameters};
+use clone_staking::states::User;
+
+/// A Clone admin instruction carried out by a passed proposal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ProposalAction {
+    UpdatePoolParameters { index: u8, params: PoolParameters },
+    UpdateCloneParameters { params: CloneParameters },
+    UpdateOracles { params: UpdateOracleParameters },
+}
+
+#[account]
+#[derive(Default)]
+pub struct Governance {
+    pub bump: u8,
+    pub voting_period_slots: u64,
+    // Slots between the end of voting and execution.
+    pub execution_delay_slots: u64,
+    // Votes in favor a proposal needs to pass.
+    pub quorum: u64,
+    // Stake needed to create a proposal.
+    pub proposal_threshold: u64,
+    pub num_proposals: u64,
+}
+
+#[account]
+pub struct Proposal {
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    // Stake added at or after this slot doesn't vote on the proposal.
+    pub snapshot_slot: u64,
+    pub voting_end_slot: u64,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub executed: bool,
+}
+
+impl Proposal {
+    /// Only stake held throughout since the snapshot counts, so tokens can't
+    /// be unstaked and restaked from another wallet to vote twice, and
+    /// top-ups after the snapshot never add to it.
+    pub fn voting_power(&self, staker: &User) -> u64 {
+        staker.staked_since(self.snapshot_slot)
+    }
+
+    pub fn has_passed(&self, quorum: u64) -> bool {
+        self.votes_for >= quorum && self.votes_for > self.votes_against
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct VoteRecord {
+    pub approve: bool,
+    pub weight: u64,
+}
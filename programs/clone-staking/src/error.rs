@@ -13,4 +13,22 @@ pub enum CloneStakingError {
 
     #[msg("Checked Math Error")]
     CheckedMathError,
+
+    #[msg("Unbonding queue is full!")]
+    UnbondingQueueFull,
+
+    #[msg("Stake is locked!")]
+    StakeLocked,
+
+    #[msg("Delegate list is full!")]
+    DelegateListFull,
+
+    #[msg("Tiers must be sorted by stake requirement with decreasing fees below 100%!")]
+    InvalidTierSchedule,
+
+    #[msg("User account still holds stake or rewards!")]
+    UserAccountNotEmpty,
+
+    #[msg("Account already migrated!")]
+    AccountAlreadyMigrated,
 }
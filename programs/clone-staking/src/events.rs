@@ -0,0 +1,46 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+// DELEGATION EVENTS
+
+#[event]
+pub struct DelegateAdded {
+    pub user: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct DelegateRemoved {
+    pub user: Pubkey,
+    pub delegate: Pubkey,
+}
+
+// STAKE EVENTS
+
+#[event]
+pub struct StakeAdded {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub staked_tokens: u64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub staked_tokens: u64,
+    pub unlock_slot: u64,
+}
+
+#[event]
+pub struct StakeWithdrawn {
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+// TIER EVENTS
+
+#[event]
+pub struct TiersUpdated {
+    pub tiers: Vec<Tier>,
+}
@@ -0,0 +1,25 @@
+use crate::states::*;
+use crate::CLONE_STAKING_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(address = clone_staking.pending_admin)]
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+}
+
+/// Second step of an admin handover, after the admin set `pending_admin`
+/// through `update_staking_params`.
+pub fn execute(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let clone_staking = &mut ctx.accounts.clone_staking;
+    clone_staking.admin = clone_staking.pending_admin;
+    clone_staking.pending_admin = Pubkey::default();
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::error::*;
+use crate::events::*;
+use crate::states::*;
+use crate::USER_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct AddDelegate<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, User>,
+}
+
+/// Lets `delegate` swap on `clone` with the user's fee tier.
+pub fn execute(ctx: Context<AddDelegate>, delegate: Pubkey) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+
+    require!(
+        delegate != ctx.accounts.user.key() && !user_account.is_delegate(&delegate),
+        CloneStakingError::InvalidInput
+    );
+    let num_delegates = user_account.num_delegates as usize;
+    require!(
+        num_delegates < MAX_DELEGATES,
+        CloneStakingError::DelegateListFull
+    );
+    user_account.delegates[num_delegates] = delegate;
+    user_account.num_delegates += 1;
+
+    emit!(DelegateAdded {
+        user: ctx.accounts.user.key(),
+        delegate,
+    });
+
+    Ok(())
+}
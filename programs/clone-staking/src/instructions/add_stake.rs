@@ -1,10 +1,12 @@
 use crate::error::*;
+use crate::events::*;
 use crate::states::*;
 use crate::CLONE_STAKING_SEED;
 use anchor_lang::prelude::*;
 use anchor_spl::token::*;
 
 pub const USER_SEED: &str = "user";
+pub const USER_ACCOUNT_SPACE: usize = 8 + 451;
 
 #[derive(Accounts)]
 #[instruction(amount: u64)]
@@ -13,17 +15,19 @@ pub struct AddStake<'info> {
     pub user: Signer<'info>,
     #[account(
         init_if_needed,
-        space = 8 + 16,
+        space = USER_ACCOUNT_SPACE,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
         payer = user
     )]
     pub user_account: Account<'info, User>,
     #[account(
+        mut,
         seeds = [CLONE_STAKING_SEED.as_ref()],
         bump,
         has_one = cln_token_mint,
         has_one = cln_token_vault,
+        has_one = reward_vault,
 
     )]
     pub clone_staking: Account<'info, CloneStaking>,
@@ -34,6 +38,8 @@ pub struct AddStake<'info> {
         address = clone_staking.cln_token_vault
     )]
     pub cln_token_vault: Account<'info, TokenAccount>,
+    #[account(address = clone_staking.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
     #[account(
         mut,
         token::mint = cln_token_mint,
@@ -46,9 +52,13 @@ pub struct AddStake<'info> {
 }
 
 pub fn execute(ctx: Context<AddStake>, amount: u64) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
     // Initialize user account if needed.
     let user_account = &mut ctx.accounts.user_account;
-    let clone_staking = &ctx.accounts.clone_staking;
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+    clone_staking.settle_rewards(user_account, current_slot)?;
 
     if amount > 0 {
         // Transfer cln from user to vault
@@ -66,16 +76,19 @@ pub fn execute(ctx: Context<AddStake>, amount: u64) -> Result<()> {
         transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
 
         // Update user account
+        user_account.record_stake_change(current_slot);
         user_account.staked_tokens = user_account
             .staked_tokens
             .checked_add(amount)
             .ok_or(error!(CloneStakingError::CheckedMathError))?;
 
-        let current_slot = Clock::get()?.slot;
-        user_account.min_slot_withdrawal = current_slot
-            .checked_add(clone_staking.staking_period_slots)
-            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        emit!(StakeAdded {
+            user: ctx.accounts.user.key(),
+            amount,
+            staked_tokens: user_account.staked_tokens,
+        });
     }
+    clone_staking.update_reward_weight(user_account, current_slot)?;
 
     Ok(())
 }
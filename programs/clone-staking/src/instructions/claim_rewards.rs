@@ -0,0 +1,76 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{CLONE_STAKING_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = reward_vault,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    #[account(
+        mut,
+        address = clone_staking.reward_vault
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = clone_staking.reward_mint,
+        token::authority = user,
+    )]
+    pub user_reward_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn execute(ctx: Context<ClaimRewards>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let user_account = &mut ctx.accounts.user_account;
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+    clone_staking.settle_rewards(user_account, current_slot)?;
+    clone_staking.update_reward_weight(user_account, current_slot)?;
+
+    let amount = user_account.unclaimed_rewards;
+    require!(amount > 0, CloneStakingError::InvalidInput);
+    user_account.unclaimed_rewards = 0;
+    clone_staking.distributed_rewards = clone_staking
+        .distributed_rewards
+        .checked_sub(amount)
+        .ok_or(error!(CloneStakingError::CheckedMathError))?;
+
+    let seeds = &[&[
+        CLONE_STAKING_SEED.as_ref(),
+        bytemuck::bytes_of(&ctx.accounts.clone_staking.bump),
+    ][..]];
+    // Transfer rewards from vault to user
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.reward_vault.to_account_info().clone(),
+        to: ctx
+            .accounts
+            .user_reward_token_account
+            .to_account_info()
+            .clone(),
+        authority: ctx.accounts.clone_staking.to_account_info().clone(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+
+    transfer(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds),
+        amount,
+    )?;
+
+    Ok(())
+}
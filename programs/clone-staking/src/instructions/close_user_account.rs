@@ -0,0 +1,48 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{CLONE_STAKING_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+
+#[derive(Accounts)]
+pub struct CloseUserAccount<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+        close = user
+    )]
+    pub user_account: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = reward_vault,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    #[account(address = clone_staking.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+}
+
+/// Closes the staking account of a user with nothing staked, unbonding or
+/// left to claim, returning its rent.
+pub fn execute(ctx: Context<CloseUserAccount>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let user_account = &mut ctx.accounts.user_account;
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+    clone_staking.settle_rewards(user_account, current_slot)?;
+    clone_staking.update_reward_weight(user_account, current_slot)?;
+
+    require!(
+        user_account.staked_tokens == 0
+            && user_account.num_unbonding_lots == 0
+            && user_account.unclaimed_rewards == 0,
+        CloneStakingError::UserAccountNotEmpty
+    );
+
+    Ok(())
+}
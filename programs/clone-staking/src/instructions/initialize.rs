@@ -27,6 +27,13 @@ pub struct Initialize<'info> {
         token::authority = clone_staking,
     )]
     pub cln_token_vault: Account<'info, TokenAccount>,
+    /// CHECK: Admin responsibility
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        token::mint = reward_mint,
+        token::authority = clone_staking,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
     pub rent: Sysvar<'info, Rent>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
@@ -37,6 +44,8 @@ pub fn execute(ctx: Context<Initialize>, staking_period_slots: u64) -> Result<()
     clone_staking.admin = ctx.accounts.admin.key();
     clone_staking.cln_token_mint = ctx.accounts.cln_token_mint.key();
     clone_staking.cln_token_vault = ctx.accounts.cln_token_vault.key();
+    clone_staking.reward_mint = ctx.accounts.reward_mint.key();
+    clone_staking.reward_vault = ctx.accounts.reward_vault.key();
     clone_staking.staking_period_slots = staking_period_slots;
     clone_staking.bump = *ctx
         .bumps
@@ -0,0 +1,51 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{CLONE_STAKING_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+
+#[derive(Accounts)]
+#[instruction(lock_end_slot: u64)]
+pub struct LockStake<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = reward_vault,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    #[account(address = clone_staking.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+}
+
+/// Locks the user's stake until `lock_end_slot` in vote-escrow mode. Locks
+/// can only be extended, up to `max_lock_slots` ahead, and locked tokens
+/// can't be unstaked.
+pub fn execute(ctx: Context<LockStake>, lock_end_slot: u64) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let user_account = &mut ctx.accounts.user_account;
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    require!(
+        clone_staking.max_lock_slots > 0
+            && lock_end_slot > current_slot
+            && lock_end_slot >= user_account.lock_end_slot
+            && lock_end_slot - current_slot <= clone_staking.max_lock_slots,
+        CloneStakingError::InvalidInput
+    );
+
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+    clone_staking.settle_rewards(user_account, current_slot)?;
+
+    user_account.lock_end_slot = lock_end_slot;
+    clone_staking.update_reward_weight(user_account, current_slot)?;
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{CLONE_STAKING_SEED, USER_ACCOUNT_SPACE, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::*;
+
+/// Size of user accounts created before rewards, holding only
+/// `staked_tokens` and `min_slot_withdrawal`.
+pub const LEGACY_USER_ACCOUNT_SPACE: usize = 8 + 16;
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct MigrateUserAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Only accounts in the original layout are migrated.
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub user_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = reward_vault,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    #[account(address = clone_staking.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a user account created before rewards to the current layout and
+/// gives its stake a reward weight. The original fields keep their offsets
+/// and the new ones start zeroed. Anyone may call it, paying the extra rent.
+pub fn execute(ctx: Context<MigrateUserAccount>, _user: Pubkey) -> Result<()> {
+    let account = ctx.accounts.user_account.to_account_info();
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    require!(
+        account.data_len() == LEGACY_USER_ACCOUNT_SPACE,
+        CloneStakingError::AccountAlreadyMigrated
+    );
+
+    let rent_delta = Rent::get()?
+        .minimum_balance(USER_ACCOUNT_SPACE)
+        .saturating_sub(account.lamports());
+    if rent_delta > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent_delta,
+        )?;
+    }
+    account.realloc(USER_ACCOUNT_SPACE, true)?;
+
+    let mut user_account = Account::<User>::try_from(&account)?;
+    // Stake earns nothing for the time before migration.
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+    user_account.reward_per_token_checkpoint = clone_staking.reward_per_token;
+    clone_staking.update_reward_weight(&mut user_account, Clock::get()?.slot)?;
+    user_account.exit(&crate::ID)?;
+
+    Ok(())
+}
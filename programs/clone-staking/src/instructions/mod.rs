@@ -1,9 +1,33 @@
+pub mod accept_admin;
+pub mod add_delegate;
 pub mod add_stake;
+pub mod claim_rewards;
+pub mod close_user_account;
 pub mod initialize;
+pub mod lock_stake;
+pub mod migrate_user_account;
+pub mod refresh_reward_weight;
+pub mod remove_delegate;
+pub mod replace_tiers;
+pub mod request_unstake;
+pub mod set_reward_vault;
 pub mod update_staking_params;
+pub mod update_vote_escrow;
 pub mod withdraw_stake;
 
+pub use accept_admin::*;
+pub use add_delegate::*;
 pub use add_stake::*;
+pub use claim_rewards::*;
+pub use close_user_account::*;
 pub use initialize::*;
+pub use lock_stake::*;
+pub use migrate_user_account::*;
+pub use refresh_reward_weight::*;
+pub use remove_delegate::*;
+pub use replace_tiers::*;
+pub use request_unstake::*;
+pub use set_reward_vault::*;
 pub use update_staking_params::*;
+pub use update_vote_escrow::*;
 pub use withdraw_stake::*;
@@ -0,0 +1,38 @@
+use crate::states::*;
+use crate::{CLONE_STAKING_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct RefreshRewardWeight<'info> {
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = reward_vault,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    #[account(address = clone_staking.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+}
+
+/// Brings a staker's reward weight down to their decayed effective stake.
+/// Anyone may call it.
+pub fn execute(ctx: Context<RefreshRewardWeight>, _user: Pubkey) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let user_account = &mut ctx.accounts.user_account;
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+    clone_staking.settle_rewards(user_account, current_slot)?;
+    clone_staking.update_reward_weight(user_account, current_slot)?;
+
+    Ok(())
+}
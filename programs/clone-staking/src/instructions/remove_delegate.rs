@@ -0,0 +1,39 @@
+use crate::error::*;
+use crate::events::*;
+use crate::states::*;
+use crate::USER_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct RemoveDelegate<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, User>,
+}
+
+pub fn execute(ctx: Context<RemoveDelegate>, delegate: Pubkey) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let num_delegates = user_account.num_delegates as usize;
+
+    let index = user_account.delegates[..num_delegates]
+        .iter()
+        .position(|key| *key == delegate)
+        .ok_or(error!(CloneStakingError::InvalidInput))?;
+    user_account
+        .delegates
+        .copy_within(index + 1..num_delegates, index);
+    user_account.delegates[num_delegates - 1] = Pubkey::default();
+    user_account.num_delegates -= 1;
+
+    emit!(DelegateRemoved {
+        user: ctx.accounts.user.key(),
+        delegate,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use crate::error::*;
+use crate::events::*;
+use crate::states::*;
+use crate::CLONE_STAKING_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(tiers: Vec<Tier>)]
+pub struct ReplaceTiers<'info> {
+    #[account(address = clone_staking.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = admin
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+}
+
+/// Replaces the whole tier schedule, rejecting it unless it's valid as a
+/// whole.
+pub fn execute(ctx: Context<ReplaceTiers>, tiers: Vec<Tier>) -> Result<()> {
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    require!(tiers.len() <= MAX_TIERS, CloneStakingError::InvalidInput);
+    clone_staking.tiers = [Tier::default(); MAX_TIERS];
+    clone_staking.tiers[..tiers.len()].copy_from_slice(&tiers);
+    clone_staking.num_tiers = tiers.len() as u8;
+    clone_staking.validate_tiers()?;
+
+    emit!(TiersUpdated { tiers });
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::error::*;
+use crate::events::*;
+use crate::states::*;
+use crate::{CLONE_STAKING_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct RequestUnstake<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, User>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = reward_vault,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    #[account(address = clone_staking.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+}
+
+/// Moves `amount` out of the user's stake into an unbonding lot that
+/// `withdraw_stake` releases after the staking period. Unbonding tokens
+/// don't count toward fee tiers or earn rewards.
+pub fn execute(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    let user_account = &mut ctx.accounts.user_account;
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    require!(
+        amount > 0 && amount <= user_account.staked_tokens,
+        CloneStakingError::InvalidInput
+    );
+    require!(
+        current_slot >= user_account.lock_end_slot,
+        CloneStakingError::StakeLocked
+    );
+
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+    clone_staking.settle_rewards(user_account, current_slot)?;
+
+    user_account.record_stake_change(current_slot);
+    user_account.staked_tokens = user_account
+        .staked_tokens
+        .checked_sub(amount)
+        .ok_or(error!(CloneStakingError::CheckedMathError))?;
+    clone_staking.update_reward_weight(user_account, current_slot)?;
+
+    // Stake from before unbonding lots still waits out its original period.
+    let unlock_slot = current_slot
+        .checked_add(clone_staking.staking_period_slots)
+        .ok_or(error!(CloneStakingError::CheckedMathError))?
+        .max(user_account.min_slot_withdrawal);
+    user_account.add_unbonding_lot(amount, unlock_slot)?;
+
+    emit!(UnstakeRequested {
+        user: ctx.accounts.user.key(),
+        amount,
+        staked_tokens: user_account.staked_tokens,
+        unlock_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::error::*;
+use crate::states::*;
+use crate::CLONE_STAKING_SEED;
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+
+#[derive(Accounts)]
+pub struct SetRewardVault<'info> {
+    #[account(address = clone_staking.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = admin
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    /// CHECK: Admin responsibility
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        token::mint = reward_mint,
+        token::authority = clone_staking,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+}
+
+/// Points rewards at a new vault, which deployments from before rewards
+/// need. Rewards already distributed must be claimed from the old vault
+/// first.
+pub fn execute(ctx: Context<SetRewardVault>) -> Result<()> {
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    require!(
+        clone_staking.distributed_rewards == 0,
+        CloneStakingError::InvalidInput
+    );
+    clone_staking.reward_mint = ctx.accounts.reward_mint.key();
+    clone_staking.reward_vault = ctx.accounts.reward_vault.key();
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use crate::error::*;
+use crate::events::*;
 use crate::states::*;
 use crate::CLONE_STAKING_SEED;
 use anchor_lang::prelude::*;
@@ -14,6 +16,12 @@ pub enum Parameters {
         lp_trading_fee_bps: u16,
         treasury_trading_fee_bps: u16,
     },
+    Rewards {
+        treasury_fee_share_bps: u16,
+    },
+    PendingAdmin {
+        pending_admin: Pubkey,
+    },
 }
 
 #[derive(Accounts)]
@@ -46,13 +54,31 @@ pub fn execute(ctx: Context<UpdateStakingParams>, params: Parameters) -> Result<
             lp_trading_fee_bps,
             treasury_trading_fee_bps,
         } => {
+            require!(
+                index < num_tiers && num_tiers as usize <= MAX_TIERS,
+                CloneStakingError::InvalidInput
+            );
             clone_staking.num_tiers = num_tiers;
             clone_staking.tiers[index as usize] = Tier {
                 stake_requirement,
                 lp_trading_fee_bps,
                 treasury_trading_fee_bps,
             };
+            clone_staking.validate_tiers()?;
+            emit!(TiersUpdated {
+                tiers: clone_staking.active_tiers(),
+            });
+        }
+        Parameters::Rewards {
+            treasury_fee_share_bps,
+        } => {
+            require!(
+                treasury_fee_share_bps <= 10_000,
+                CloneStakingError::InvalidInput
+            );
+            clone_staking.treasury_fee_share_bps = treasury_fee_share_bps;
         }
+        Parameters::PendingAdmin { pending_admin } => clone_staking.pending_admin = pending_admin,
     }
 
     Ok(())
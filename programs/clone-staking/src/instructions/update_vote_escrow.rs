@@ -0,0 +1,44 @@
+use crate::error::*;
+use crate::states::*;
+use crate::CLONE_STAKING_SEED;
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+
+#[derive(Accounts)]
+#[instruction(max_lock_slots: u64)]
+pub struct UpdateVoteEscrow<'info> {
+    #[account(address = clone_staking.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        has_one = admin,
+        has_one = reward_vault,
+    )]
+    pub clone_staking: Account<'info, CloneStaking>,
+    #[account(address = clone_staking.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+}
+
+/// Sets the longest lock in vote-escrow mode, zero turning the mode off.
+/// Weights follow lazily: the next time a staker's account is touched, their
+/// rewards up to now are settled under the old mode and their weight is
+/// recomputed under the new one. Anyone can do so through
+/// `refresh_reward_weight`.
+pub fn execute(ctx: Context<UpdateVoteEscrow>, max_lock_slots: u64) -> Result<()> {
+    let clone_staking = &mut ctx.accounts.clone_staking;
+
+    clone_staking.update_rewards(ctx.accounts.reward_vault.amount)?;
+
+    clone_staking.vote_escrow_epoch = clone_staking
+        .vote_escrow_epoch
+        .checked_add(1)
+        .ok_or(error!(CloneStakingError::CheckedMathError))?;
+    clone_staking.previous_max_lock_slots = clone_staking.max_lock_slots;
+    clone_staking.mode_change_slot = Clock::get()?.slot;
+    clone_staking.mode_change_reward_per_token = clone_staking.reward_per_token;
+    clone_staking.max_lock_slots = max_lock_slots;
+
+    Ok(())
+}
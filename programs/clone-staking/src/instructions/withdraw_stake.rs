@@ -1,11 +1,11 @@
 use crate::error::*;
+use crate::events::*;
 use crate::states::*;
 use crate::{CLONE_STAKING_SEED, USER_SEED};
 use anchor_lang::prelude::*;
 use anchor_spl::token::*;
 
 #[derive(Accounts)]
-#[instruction(amount: u64)]
 pub struct WithdrawStake<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
@@ -40,19 +40,17 @@ pub struct WithdrawStake<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn execute(ctx: Context<WithdrawStake>, amount: u64) -> Result<()> {
+/// Releases every unbonding lot whose staking period has ended.
+pub fn execute(ctx: Context<WithdrawStake>) -> Result<()> {
     let current_slot = Clock::get()?.slot;
     let user_account = &mut ctx.accounts.user_account;
     let clone_staking = &ctx.accounts.clone_staking;
 
+    let amount = user_account.release_unbonded_lots(current_slot)?;
     require!(
-        current_slot >= user_account.min_slot_withdrawal,
+        amount > 0,
         CloneStakingError::CannotWithdrawBeforeStakingPeriod
     );
-    require!(
-        amount > 0 && amount <= user_account.staked_tokens,
-        CloneStakingError::InvalidInput
-    );
 
     let seeds = &[&[
         CLONE_STAKING_SEED.as_ref(),
@@ -74,11 +72,11 @@ pub fn execute(ctx: Context<WithdrawStake>, amount: u64) -> Result<()> {
         CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds),
         amount,
     )?;
-    // Update user account
-    user_account.staked_tokens = user_account
-        .staked_tokens
-        .checked_sub(amount)
-        .ok_or(error!(CloneStakingError::CheckedMathError))?;
+
+    emit!(StakeWithdrawn {
+        user: ctx.accounts.user.key(),
+        amount,
+    });
 
     Ok(())
 }
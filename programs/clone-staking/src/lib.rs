@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
 mod error;
+pub mod events;
 pub mod instructions;
 pub mod states;
 
 pub use instructions::*;
+use states::Tier;
 
 declare_id!("42L6bfEYntcmqVcFvHywitcaHhXF9rjYq9C9p9iWQ2X2");
 
@@ -19,8 +21,40 @@ pub mod clone_staking {
         instructions::add_stake::execute(ctx, amount)
     }
 
-    pub fn withdraw_stake(ctx: Context<WithdrawStake>, amount: u64) -> Result<()> {
-        instructions::withdraw_stake::execute(ctx, amount)
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+        instructions::request_unstake::execute(ctx, amount)
+    }
+
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        instructions::withdraw_stake::execute(ctx)
+    }
+
+    pub fn lock_stake(ctx: Context<LockStake>, lock_end_slot: u64) -> Result<()> {
+        instructions::lock_stake::execute(ctx, lock_end_slot)
+    }
+
+    pub fn refresh_reward_weight(ctx: Context<RefreshRewardWeight>, user: Pubkey) -> Result<()> {
+        instructions::refresh_reward_weight::execute(ctx, user)
+    }
+
+    pub fn add_delegate(ctx: Context<AddDelegate>, delegate: Pubkey) -> Result<()> {
+        instructions::add_delegate::execute(ctx, delegate)
+    }
+
+    pub fn remove_delegate(ctx: Context<RemoveDelegate>, delegate: Pubkey) -> Result<()> {
+        instructions::remove_delegate::execute(ctx, delegate)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::claim_rewards::execute(ctx)
+    }
+
+    pub fn close_user_account(ctx: Context<CloseUserAccount>) -> Result<()> {
+        instructions::close_user_account::execute(ctx)
+    }
+
+    pub fn replace_tiers(ctx: Context<ReplaceTiers>, tiers: Vec<Tier>) -> Result<()> {
+        instructions::replace_tiers::execute(ctx, tiers)
     }
 
     pub fn update_staking_params(
@@ -29,4 +63,20 @@ pub mod clone_staking {
     ) -> Result<()> {
         instructions::update_staking_params::execute(ctx, params)
     }
+
+    pub fn update_vote_escrow(ctx: Context<UpdateVoteEscrow>, max_lock_slots: u64) -> Result<()> {
+        instructions::update_vote_escrow::execute(ctx, max_lock_slots)
+    }
+
+    pub fn set_reward_vault(ctx: Context<SetRewardVault>) -> Result<()> {
+        instructions::set_reward_vault::execute(ctx)
+    }
+
+    pub fn migrate_user_account(ctx: Context<MigrateUserAccount>, user: Pubkey) -> Result<()> {
+        instructions::migrate_user_account::execute(ctx, user)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin::execute(ctx)
+    }
 }
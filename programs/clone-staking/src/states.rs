@@ -1,6 +1,11 @@
+use crate::error::*;
 use anchor_lang::prelude::*;
 
 pub const MAX_TIERS: usize = 16;
+pub const MAX_UNBONDING_LOTS: usize = 8;
+pub const MAX_DELEGATES: usize = 4;
+pub const MAX_STAKE_CHECKPOINTS: usize = 8;
+pub const REWARD_PER_TOKEN_SCALE: u128 = 1_000_000_000_000;
 
 #[account]
 pub struct CloneStaking {
@@ -11,6 +16,26 @@ pub struct CloneStaking {
     pub bump: u8,
     pub num_tiers: u8,
     pub tiers: [Tier; MAX_TIERS],
+    // The fields below fit in the space reserved at initialization, so they
+    // read as zero on accounts created before them.
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    // Share of treasury fees `clone` swaps pay to the reward vault.
+    pub treasury_fee_share_bps: u16,
+    pub total_reward_weight: u64,
+    pub reward_per_token: u128,
+    // Reward vault tokens already distributed to stakers and not yet claimed.
+    pub distributed_rewards: u64,
+    // Longest lock in vote-escrow mode, zero when staked tokens count as is.
+    pub max_lock_slots: u64,
+    // Set by the admin, becomes admin once it accepts.
+    pub pending_admin: Pubkey,
+    // Counts vote-escrow mode changes. Stakers' rewards up to the latest one
+    // are settled under the `max_lock_slots` it replaced, as of its slot.
+    pub vote_escrow_epoch: u64,
+    pub previous_max_lock_slots: u64,
+    pub mode_change_slot: u64,
+    pub mode_change_reward_per_token: u128,
 }
 
 impl CloneStaking {
@@ -26,6 +51,158 @@ impl CloneStaking {
             });
         fees
     }
+
+    pub fn active_tiers(&self) -> Vec<Tier> {
+        self.tiers[..self.num_tiers as usize].to_vec()
+    }
+
+    /// Checks that tiers are sorted by strictly increasing stake requirement,
+    /// that their fees never increase, and that a tier's fees stay below 100%.
+    pub fn validate_tiers(&self) -> Result<()> {
+        let tiers = &self.tiers[..self.num_tiers as usize];
+        require!(
+            tiers.iter().all(|tier| {
+                (tier.lp_trading_fee_bps as u32 + tier.treasury_trading_fee_bps as u32) < 10_000
+            }) && tiers.windows(2).all(|pair| {
+                pair[0].stake_requirement < pair[1].stake_requirement
+                    && pair[0].lp_trading_fee_bps >= pair[1].lp_trading_fee_bps
+                    && pair[0].treasury_trading_fee_bps >= pair[1].treasury_trading_fee_bps
+            }),
+            CloneStakingError::InvalidTierSchedule
+        );
+        Ok(())
+    }
+
+    /// Stake that counts toward fee tiers and rewards. In vote-escrow mode
+    /// it's the staked tokens scaled by the time left on the user's lock, so
+    /// it decays linearly to zero at unlock.
+    pub fn effective_stake(&self, user: &User, slot: u64) -> u64 {
+        effective_stake(user, slot, self.max_lock_slots)
+    }
+
+    /// Sets the user's reward weight to their effective stake. Their rewards
+    /// must be settled first. Recorded weights fall behind decaying stake
+    /// until refreshed, which anyone can do through `refresh_reward_weight`.
+    pub fn update_reward_weight(&mut self, user: &mut User, slot: u64) -> Result<()> {
+        let reward_weight = self.effective_stake(user, slot);
+        self.total_reward_weight = self
+            .total_reward_weight
+            .checked_sub(user.reward_weight)
+            .and_then(|total| total.checked_add(reward_weight))
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        user.reward_weight = reward_weight;
+        user.vote_escrow_epoch = self.vote_escrow_epoch;
+        Ok(())
+    }
+
+    /// Credits the user's rewards since their last checkpoint. They earn on
+    /// the lower of their recorded weight and their effective stake at
+    /// `slot`, so rewards stop at lock expiry even when the weight wasn't
+    /// refreshed. What the recorded weight would have earned beyond that goes
+    /// back to the vault for the next distribution. When the vote-escrow mode
+    /// changed since the weight was recorded, rewards up to the change are
+    /// capped under the previous mode. Must run before the user's
+    /// `reward_weight` changes.
+    pub fn settle_rewards(&mut self, user: &mut User, slot: u64) -> Result<()> {
+        let mode_change_reward_per_token = if user.vote_escrow_epoch == self.vote_escrow_epoch {
+            user.reward_per_token_checkpoint
+        } else {
+            self.mode_change_reward_per_token
+                .max(user.reward_per_token_checkpoint)
+        };
+        let (earned_before_change, forfeited_before_change) = split_rewards(
+            mode_change_reward_per_token
+                .checked_sub(user.reward_per_token_checkpoint)
+                .ok_or(error!(CloneStakingError::CheckedMathError))?,
+            user.reward_weight,
+            effective_stake(user, self.mode_change_slot, self.previous_max_lock_slots),
+        )?;
+        let (earned_since_change, forfeited_since_change) = split_rewards(
+            self.reward_per_token
+                .checked_sub(mode_change_reward_per_token)
+                .ok_or(error!(CloneStakingError::CheckedMathError))?,
+            user.reward_weight,
+            self.effective_stake(user, slot),
+        )?;
+        let earned = earned_before_change
+            .checked_add(earned_since_change)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        let forfeited = forfeited_before_change
+            .checked_add(forfeited_since_change)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+
+        user.unclaimed_rewards = user
+            .unclaimed_rewards
+            .checked_add(earned)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        user.reward_per_token_checkpoint = self.reward_per_token;
+        self.distributed_rewards = self
+            .distributed_rewards
+            .checked_sub(forfeited)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        Ok(())
+    }
+
+    /// Distributes reward vault tokens that arrived since the last update,
+    /// from treasury fees or deposits, over the stakers' reward weights. They
+    /// stay in the vault while there is no weight.
+    pub fn update_rewards(&mut self, reward_vault_amount: u64) -> Result<()> {
+        if self.total_reward_weight == 0 {
+            return Ok(());
+        }
+        let new_rewards = reward_vault_amount
+            .checked_sub(self.distributed_rewards)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        let reward_per_token_delta = (new_rewards as u128)
+            .checked_mul(REWARD_PER_TOKEN_SCALE)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?
+            / self.total_reward_weight as u128;
+        // Rounding dust is left for the next update.
+        let distributed: u64 = (reward_per_token_delta
+            .checked_mul(self.total_reward_weight as u128)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?
+            / REWARD_PER_TOKEN_SCALE)
+            .try_into()
+            .map_err(|_| CloneStakingError::CheckedMathError)?;
+
+        self.reward_per_token = self
+            .reward_per_token
+            .checked_add(reward_per_token_delta)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        self.distributed_rewards = self
+            .distributed_rewards
+            .checked_add(distributed)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?;
+        Ok(())
+    }
+}
+
+/// `user`'s staked tokens scaled by the time left on their lock when
+/// `max_lock_slots` is non-zero, see `CloneStaking::effective_stake`.
+fn effective_stake(user: &User, slot: u64, max_lock_slots: u64) -> u64 {
+    if max_lock_slots == 0 {
+        return user.staked_tokens;
+    }
+    let remaining_lock_slots = user.lock_end_slot.saturating_sub(slot).min(max_lock_slots);
+    (user.staked_tokens as u128 * remaining_lock_slots as u128 / max_lock_slots as u128) as u64
+}
+
+/// Splits what `weight` earned over `reward_per_token_delta` into the part
+/// credited, earned on at most `stake`, and the part forfeited.
+fn split_rewards(reward_per_token_delta: u128, weight: u64, stake: u64) -> Result<(u64, u64)> {
+    let rewards_for = |weight: u64| -> Result<u64> {
+        (reward_per_token_delta
+            .checked_mul(weight as u128)
+            .ok_or(error!(CloneStakingError::CheckedMathError))?
+            / REWARD_PER_TOKEN_SCALE)
+            .try_into()
+            .map_err(|_| error!(CloneStakingError::CheckedMathError))
+    };
+    let earned = rewards_for(weight.min(stake))?;
+    let forfeited = rewards_for(weight)?
+        .checked_sub(earned)
+        .ok_or(error!(CloneStakingError::CheckedMathError))?;
+    Ok((earned, forfeited))
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
@@ -45,9 +222,113 @@ impl Default for Tier {
     }
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Default)]
+pub struct UnbondingLot {
+    pub amount: u64,
+    pub unlock_slot: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq, Default)]
+pub struct StakeCheckpoint {
+    pub slot: u64,
+    pub staked_tokens: u64,
+}
+
 #[account]
 #[derive(Default)]
 pub struct User {
     pub staked_tokens: u64,
+    // Kept at its original offset. Stake added before unbonding lots existed
+    // can't be withdrawn before it.
     pub min_slot_withdrawal: u64,
+    pub reward_per_token_checkpoint: u128,
+    pub unclaimed_rewards: u64,
+    pub num_unbonding_lots: u8,
+    pub unbonding_lots: [UnbondingLot; MAX_UNBONDING_LOTS],
+    pub reward_weight: u64,
+    pub lock_end_slot: u64,
+    // Wallets that trade with this account's fee tier.
+    pub num_delegates: u8,
+    pub delegates: [Pubkey; MAX_DELEGATES],
+    // Stake held before each of the latest stake changes, so governance
+    // votes only count stake held since a proposal's snapshot.
+    pub num_stake_checkpoints: u8,
+    pub stake_checkpoints: [StakeCheckpoint; MAX_STAKE_CHECKPOINTS],
+    // `CloneStaking::vote_escrow_epoch` the reward weight was recorded in.
+    pub vote_escrow_epoch: u64,
+}
+
+impl User {
+    pub fn add_unbonding_lot(&mut self, amount: u64, unlock_slot: u64) -> Result<()> {
+        let num_lots = self.num_unbonding_lots as usize;
+        require!(
+            num_lots < MAX_UNBONDING_LOTS,
+            CloneStakingError::UnbondingQueueFull
+        );
+        self.unbonding_lots[num_lots] = UnbondingLot {
+            amount,
+            unlock_slot,
+        };
+        self.num_unbonding_lots += 1;
+        Ok(())
+    }
+
+    /// Records the stake held before it changes at `slot`. Once the history
+    /// is full its two oldest checkpoints merge into the later slot with the
+    /// lower stake, which only ever undercounts `staked_since`.
+    pub fn record_stake_change(&mut self, slot: u64) {
+        let mut num_checkpoints = self.num_stake_checkpoints as usize;
+        if num_checkpoints > 0 && self.stake_checkpoints[num_checkpoints - 1].slot == slot {
+            let checkpoint = &mut self.stake_checkpoints[num_checkpoints - 1];
+            checkpoint.staked_tokens = checkpoint.staked_tokens.min(self.staked_tokens);
+            return;
+        }
+        if num_checkpoints == MAX_STAKE_CHECKPOINTS {
+            let oldest = self.stake_checkpoints[0];
+            self.stake_checkpoints.copy_within(1.., 0);
+            self.stake_checkpoints[0].staked_tokens = self.stake_checkpoints[0]
+                .staked_tokens
+                .min(oldest.staked_tokens);
+            num_checkpoints -= 1;
+        }
+        self.stake_checkpoints[num_checkpoints] = StakeCheckpoint {
+            slot,
+            staked_tokens: self.staked_tokens,
+        };
+        self.num_stake_checkpoints = num_checkpoints as u8 + 1;
+    }
+
+    /// Lowest stake held from `slot` on, changes at `slot` included.
+    pub fn staked_since(&self, slot: u64) -> u64 {
+        self.stake_checkpoints[..self.num_stake_checkpoints as usize]
+            .iter()
+            .filter(|checkpoint| checkpoint.slot >= slot)
+            .fold(self.staked_tokens, |staked, checkpoint| {
+                staked.min(checkpoint.staked_tokens)
+            })
+    }
+
+    pub fn is_delegate(&self, wallet: &Pubkey) -> bool {
+        self.delegates[..self.num_delegates as usize].contains(wallet)
+    }
+
+    /// Removes the lots unlocked by `slot`, returning their total amount.
+    pub fn release_unbonded_lots(&mut self, slot: u64) -> Result<u64> {
+        let mut released: u64 = 0;
+        let mut num_lots = 0;
+        for index in 0..self.num_unbonding_lots as usize {
+            let lot = self.unbonding_lots[index];
+            if lot.unlock_slot <= slot {
+                released = released
+                    .checked_add(lot.amount)
+                    .ok_or(error!(CloneStakingError::CheckedMathError))?;
+            } else {
+                self.unbonding_lots[num_lots] = lot;
+                num_lots += 1;
+            }
+        }
+        self.unbonding_lots[num_lots..].fill(UnbondingLot::default());
+        self.num_unbonding_lots = num_lots as u8;
+        Ok(released)
+    }
 }
@@ -1,9 +1,7 @@
-use rust_decimal::prelude::*;
-
-pub static CLONE_TOKEN_SCALE: u32 = 8;
-pub static PERCENT_SCALE: u32 = 2;
-pub static RATIO_SCALE: u32 = 2;
-pub static BPS_SCALE: u32 = 4;
+pub use clone_math::decimal::{
+    rescale_down, rescale_toward_zero, rescale_up, BPS_SCALE, CLONE_TOKEN_SCALE, PERCENT_SCALE,
+    RATIO_SCALE,
+};
 
 #[macro_export]
 macro_rules! to_clone_decimal {
@@ -56,9 +54,3 @@ macro_rules! to_bps_decimal {
         )
     }};
 }
-
-pub fn rescale_toward_zero(decimal: Decimal, scale: u32) -> Decimal {
-    let mut rounded_decimal = decimal.round_dp_with_strategy(scale, RoundingStrategy::ToZero);
-    rounded_decimal.rescale(scale);
-    rounded_decimal
-}
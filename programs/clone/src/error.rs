@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use clone_math::MathError;
 
 #[error_code]
 pub enum CloneError {
@@ -157,6 +158,62 @@ pub enum CloneError {
     /// 38. Auth already exists
     #[msg("Failed to load switchboard")]
     FailedToLoadSwitchboard,
+
+    /// 39. Pool Array Full
+    #[msg("Pool Array Full")]
+    PoolArrayFull,
+
+    /// 40. Oracle Array Full
+    #[msg("Oracle Array Full")]
+    OracleArrayFull,
+
+    /// 41. Account already migrated
+    #[msg("Account Already Migrated")]
+    AccountAlreadyMigrated,
+
+    /// 42. Invalid swap route
+    #[msg("Invalid Swap Route")]
+    InvalidSwapRoute,
+
+    /// 43. Flash loan not repaid
+    #[msg("Flash Loan Not Repaid")]
+    FlashLoanNotRepaid,
+
+    /// 44. Invalid flash loan
+    #[msg("Invalid Flash Loan")]
+    InvalidFlashLoan,
+
+    /// 45. Transaction expired
+    #[msg("Transaction Expired")]
+    TransactionExpired,
+
+    /// 46. Invalid reward mint
+    #[msg("Invalid Reward Mint")]
+    InvalidRewardMint,
+
+    /// 47. Invalid multisig signers
+    #[msg("Invalid Multisig Signers")]
+    InvalidMultisigSigners,
+
+    /// 48. Not enough multisig approvals
+    #[msg("Not Enough Multisig Approvals")]
+    NotEnoughApprovals,
+
+    /// 49. Admin transaction already executed
+    #[msg("Admin Transaction Already Executed")]
+    AdminTransactionExecuted,
+
+    /// 50. Admin transaction predates the multisig signers
+    #[msg("Admin Transaction Predates The Multisig Signers")]
+    StaleAdminTransaction,
+
+    /// 51. Flash loans disabled
+    #[msg("Flash Loans Disabled")]
+    FlashLoansDisabled,
+
+    /// 52. Pool has liquidity
+    #[msg("Pool Has Liquidity")]
+    PoolHasLiquidity,
 }
 
 impl From<CloneError> for ProgramError {
@@ -165,6 +222,18 @@ impl From<CloneError> for ProgramError {
     }
 }
 
+impl From<MathError> for CloneError {
+    fn from(e: MathError) -> Self {
+        match e {
+            MathError::CheckedMathError => CloneError::CheckedMathError,
+            MathError::IntTypeConversionError => CloneError::IntTypeConversionError,
+            MathError::InvalidConversion => CloneError::InvalidConversion,
+            MathError::OutdatedOracle => CloneError::OutdatedOracle,
+            MathError::InvalidMintCollateralRatio => CloneError::InvalidMintCollateralRatio,
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! return_error_if_false {
     ($boolean:expr, $err:expr) => {
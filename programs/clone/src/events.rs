@@ -24,6 +24,27 @@ pub struct LiquidityDelta {
     pub onasset_ild_delta: i64,
 }
 
+// LP REWARDS COMPOUNDED EVENT
+#[event]
+pub struct LpRewardsCompounded {
+    pub event_id: u64,
+    pub user_address: Pubkey,
+    pub pool_index: u8,
+    pub collateral_compounded: u64,
+    pub onasset_compounded: u64,
+    pub committed_collateral_delta: u64,
+}
+
+// LP REWARDS NETTED EVENT
+#[event]
+pub struct LpRewardsNetted {
+    pub event_id: u64,
+    pub user_address: Pubkey,
+    pub pool_index: u8,
+    pub collateral_rebate_delta: i64,
+    pub onasset_rebate_delta: i64,
+}
+
 // POOL UPDATE
 #[event]
 pub struct PoolState {
@@ -0,0 +1,25 @@
+use crate::states::*;
+use crate::CLONE_PROGRAM_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(address = clone.pending_admin)]
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+}
+
+/// Second step of an admin handover, after the admin set `pending_admin`
+/// through `update_clone_parameters`.
+pub fn execute(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let clone = &mut ctx.accounts.clone;
+    clone.admin = clone.pending_admin;
+    clone.pending_admin = Pubkey::default();
+
+    Ok(())
+}
@@ -14,8 +14,9 @@ pub struct AddCollateralToBorrow<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
+        constraint = (borrow_index as u64) < user_account.load_user()?.header.num_borrows @ CloneError::InvalidInputPositionIndex,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
         bump = clone.bump,
@@ -38,7 +39,8 @@ pub struct AddCollateralToBorrow<'info> {
 pub fn execute(ctx: Context<AddCollateralToBorrow>, borrow_index: u8, amount: u64) -> Result<()> {
     return_error_if_false!(amount > 0, CloneError::InvalidTokenAmount);
 
-    let borrows = &mut ctx.accounts.user_account.borrows;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let borrows = &mut user_account.borrows;
 
     // add collateral amount to mint data
     //borrows[borrow_index as usize].collateral_amount += amount;
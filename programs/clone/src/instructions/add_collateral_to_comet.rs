@@ -16,7 +16,7 @@ pub struct AddCollateralToComet<'info> {
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -40,7 +40,8 @@ pub struct AddCollateralToComet<'info> {
 pub fn execute(ctx: Context<AddCollateralToComet>, amount: u64) -> Result<()> {
     return_error_if_false!(amount > 0, CloneError::InvalidTokenAmount);
 
-    let comet = &mut ctx.accounts.user_account.comet;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet = &mut user_account.header.comet;
 
     // send collateral from user to vault
     let cpi_accounts = Transfer {
@@ -1,24 +1,186 @@
 use crate::decimal::{rescale_toward_zero, CLONE_TOKEN_SCALE};
 use crate::error::*;
 use crate::events::*;
+use crate::instructions::reserve_user_capacity;
 use crate::math::*;
 use crate::states::*;
-use crate::{return_error_if_false, to_clone_decimal};
-use crate::{CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
+use crate::{
+    return_error_if_false, to_clone_decimal, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED,
+    USER_SEED,
+};
 use anchor_lang::prelude::*;
-use rust_decimal::prelude::*;
 use std::convert::TryInto;
 
+/// Optional limits on the state a comet instruction executes in, so a
+/// transaction that lands late fails instead of executing on worse terms.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CometBounds {
+    /// Last slot the instruction may execute in.
+    pub expiry_slot: Option<u64>,
+    /// Range of the pool price, in collateral per onAsset with
+    /// `CLONE_TOKEN_SCALE` decimals.
+    pub min_pool_price: Option<u64>,
+    pub max_pool_price: Option<u64>,
+    /// Most collateral and onAsset ILD the position may be left with.
+    pub max_collateral_ild: Option<u64>,
+    pub max_onasset_ild: Option<u64>,
+    /// Lowest health score, with `CLONE_TOKEN_SCALE` decimals, the comet may
+    /// be left with.
+    pub min_health_score: Option<u64>,
+}
+
+pub fn check_expiry_slot(expiry_slot: Option<u64>, slot: u64) -> Result<()> {
+    return_error_if_false!(
+        expiry_slot.map_or(true, |expiry_slot| slot <= expiry_slot),
+        CloneError::TransactionExpired
+    );
+    Ok(())
+}
+
+/// Checks `bounds` against the comet once the instruction has updated it.
+/// Oracles are only needed for the pool price and health score bounds.
+#[allow(clippy::too_many_arguments)]
+pub fn check_comet_bounds(
+    bounds: &CometBounds,
+    comet: &Comet,
+    comet_positions: &[LiquidityPosition],
+    comet_position_index: usize,
+    pools: &Pools,
+    oracles: Option<&Oracles>,
+    collateral: &Collateral,
+    slot: u64,
+) -> Result<()> {
+    check_expiry_slot(bounds.expiry_slot, slot)?;
+
+    let position = &comet_positions[comet_position_index];
+    let ild_share = calculate_ild_share(position, pools, collateral)?;
+    if let Some(max_collateral_ild) = bounds.max_collateral_ild {
+        return_error_if_false!(
+            ild_share.collateral_ild_share
+                <= collateral.to_collateral_decimal(max_collateral_ild)?,
+            CloneError::SlippageToleranceExceeded
+        );
+    }
+    if let Some(max_onasset_ild) = bounds.max_onasset_ild {
+        return_error_if_false!(
+            ild_share.onasset_ild_share <= to_clone_decimal!(max_onasset_ild),
+            CloneError::SlippageToleranceExceeded
+        );
+    }
+
+    let checks_pool_price = bounds.min_pool_price.is_some() || bounds.max_pool_price.is_some();
+    if !checks_pool_price && bounds.min_health_score.is_none() {
+        return Ok(());
+    }
+    let oracles = oracles.ok_or(error!(CloneError::ExpectedAccountNotFound))?;
+
+    if checks_pool_price {
+        let pool = &pools.pools[position.pool_index as usize];
+        let pool_oracle = &oracles.oracles[pool.asset_info.oracle_info_index as usize];
+        let collateral_oracle = &oracles.oracles[collateral.oracle_info_index as usize];
+        check_feed_update(pool_oracle, slot)?;
+        check_feed_update(collateral_oracle, slot)?;
+        let onasset_price = pool_oracle.get_price()?;
+        let collateral_price = collateral_oracle.get_price()?;
+        // An empty pool would trade at the oracle price.
+        let pool_price = if pool.committed_collateral_liquidity == 0 {
+            onasset_price
+                .checked_div(collateral_price)
+                .ok_or(error!(CloneError::CheckedMathError))?
+        } else {
+            pool.calculate_pool_price(onasset_price, collateral_price, collateral)?
+        };
+        if let Some(min_pool_price) = bounds.min_pool_price {
+            return_error_if_false!(
+                pool_price >= to_clone_decimal!(min_pool_price),
+                CloneError::SlippageToleranceExceeded
+            );
+        }
+        if let Some(max_pool_price) = bounds.max_pool_price {
+            return_error_if_false!(
+                pool_price <= to_clone_decimal!(max_pool_price),
+                CloneError::SlippageToleranceExceeded
+            );
+        }
+    }
+
+    if let Some(min_health_score) = bounds.min_health_score {
+        let health_score =
+            calculate_health_score(comet, comet_positions, pools, oracles, collateral, slot)?;
+        return_error_if_false!(
+            health_score.score >= to_clone_decimal!(min_health_score),
+            CloneError::SlippageToleranceExceeded
+        );
+    }
+
+    Ok(())
+}
+
+/// Commits `collateral_amount` of liquidity from `position` to `pool`, moving
+/// the pool's ILD on that liquidity onto the position's rebates so it starts
+/// out with none. Returns the collateral and onAsset ILD deltas.
+pub fn commit_liquidity(
+    pool: &mut Pool,
+    position: &mut LiquidityPosition,
+    collateral_amount: u64,
+    collateral: &Collateral,
+    slot: u64,
+) -> Result<(i64, i64)> {
+    pool.accrue_rewards(slot)?;
+    position.accrue_rewards(pool)?;
+
+    let (collateral_ild, onasset_ild) =
+        calculate_committed_liquidity_ild(collateral_amount, pool, collateral)?;
+    let collateral_ild_delta: i64 = collateral_ild
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
+    let onasset_ild_delta: i64 = onasset_ild
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
+
+    position.committed_collateral_liquidity = position
+        .committed_collateral_liquidity
+        .checked_add(collateral_amount)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+    position.collateral_ild_rebate = position
+        .collateral_ild_rebate
+        .checked_add(collateral_ild_delta)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+    position.onasset_ild_rebate = position
+        .onasset_ild_rebate
+        .checked_add(onasset_ild_delta)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    pool.committed_collateral_liquidity = pool
+        .committed_collateral_liquidity
+        .checked_add(collateral_amount)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+    pool.onasset_ild = pool
+        .onasset_ild
+        .checked_add(onasset_ild_delta)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+    pool.collateral_ild = pool
+        .collateral_ild
+        .checked_add(collateral_ild_delta)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    Ok((collateral_ild_delta, onasset_ild_delta))
+}
+
 #[derive(Accounts)]
 #[instruction(pool_index: u8, collateral_amount: u64)]
 pub struct AddLiquidityToComet<'info> {
     pub user: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -30,115 +192,94 @@ pub struct AddLiquidityToComet<'info> {
         seeds = [POOLS_SEED.as_ref()],
         bump,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn execute(
     ctx: Context<AddLiquidityToComet>,
     pool_index: u8,
     collateral_amount: u64,
+    bounds: CometBounds,
 ) -> Result<()> {
     return_error_if_false!(collateral_amount > 0, CloneError::InvalidTokenAmount);
 
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    return_error_if_false!(
+        (pool_index as u64) < pools.num_pools,
+        CloneError::PoolNotFound
+    );
     return_error_if_false!(
         pools.pools[pool_index as usize].status == Status::Active,
         CloneError::StatusPreventsAction
     );
-    let oracles = &ctx.accounts.oracles;
-    let comet = &mut ctx.accounts.user_account.comet;
-    let pool = &pools.pools[pool_index as usize];
-    let committed_collateral_value =
-        collateral.to_collateral_decimal(pool.committed_collateral_liquidity)?;
-    let collateral_liquidity_value = collateral.to_collateral_decimal(collateral_amount)?;
-
-    let proportion_value = if committed_collateral_value > Decimal::ZERO {
-        collateral_liquidity_value
-            .checked_div(committed_collateral_value)
-            .ok_or(error!(CloneError::CheckedMathError))?
-    } else {
-        Decimal::ZERO
-    };
-
-    let collateral_ild = rescale_toward_zero(
-        collateral
-            .to_collateral_decimal(pool.collateral_ild)?
-            .checked_mul(proportion_value)
-            .ok_or(error!(CloneError::CheckedMathError))?,
-        collateral
-            .scale
-            .try_into()
-            .map_err(|_| CloneError::IntTypeConversionError)?,
-    );
-    let collateral_ild_delta: i64 = collateral_ild
-        .mantissa()
-        .try_into()
-        .map_err(|_| CloneError::IntTypeConversionError)?;
-    let onasset_ild = rescale_toward_zero(
-        to_clone_decimal!(pool.onasset_ild)
-            .checked_mul(proportion_value)
-            .ok_or(error!(CloneError::CheckedMathError))?,
-        CLONE_TOKEN_SCALE,
-    );
-    let onasset_ild_delta: i64 = onasset_ild
-        .mantissa()
-        .try_into()
-        .map_err(|_| CloneError::IntTypeConversionError)?;
+    let oracles = ctx.accounts.oracles.load()?;
 
     // find the index of the position within the comet position
-    if let Some((position_index, _)) = comet
-        .positions
-        .iter()
-        .enumerate()
-        .find(|(_, position)| position.pool_index == pool_index)
-    {
-        comet.positions[position_index].committed_collateral_liquidity = comet.positions
-            [position_index]
-            .committed_collateral_liquidity
-            .checked_add(collateral_amount)
-            .ok_or(error!(CloneError::CheckedMathError))?;
-        comet.positions[position_index].collateral_ild_rebate = comet.positions[position_index]
-            .collateral_ild_rebate
-            .checked_add(collateral_ild_delta)
-            .ok_or(error!(CloneError::CheckedMathError))?;
-        comet.positions[position_index].onasset_ild_rebate = comet.positions[position_index]
-            .onasset_ild_rebate
-            .checked_add(onasset_ild_delta)
-            .ok_or(error!(CloneError::CheckedMathError))?;
-    } else {
-        comet.positions.push(LiquidityPosition {
-            pool_index,
-            committed_collateral_liquidity: collateral_amount,
-            collateral_ild_rebate: collateral_ild_delta,
-            onasset_ild_rebate: onasset_ild_delta,
-        });
+    let existing_position_index = {
+        let user_account = ctx.accounts.user_account.load_user()?;
+        user_account.comet_positions[..user_account.header.comet.num_positions as usize]
+            .iter()
+            .position(|position| position.pool_index == pool_index)
+    };
+    if existing_position_index.is_none() {
+        reserve_user_capacity(
+            &ctx.accounts.user_account,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            0,
+            1,
+        )?;
     }
 
-    // Update pool
-    pools.pools[pool_index as usize].committed_collateral_liquidity = pools.pools
-        [pool_index as usize]
-        .committed_collateral_liquidity
-        .checked_add(collateral_amount)
-        .ok_or(error!(CloneError::CheckedMathError))?;
-    pools.pools[pool_index as usize].onasset_ild = pools.pools[pool_index as usize]
-        .onasset_ild
-        .checked_add(onasset_ild_delta)
-        .ok_or(error!(CloneError::CheckedMathError))?;
-    pools.pools[pool_index as usize].collateral_ild = pools.pools[pool_index as usize]
-        .collateral_ild
-        .checked_add(collateral_ild_delta)
-        .ok_or(error!(CloneError::CheckedMathError))?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let position_index = match existing_position_index {
+        Some(position_index) => position_index,
+        None => {
+            user_account.add_comet_position(LiquidityPosition {
+                pool_index,
+                ..LiquidityPosition::default()
+            });
+            user_account.header.comet.num_positions as usize - 1
+        }
+    };
+
+    let slot = Clock::get()?.slot;
+    let (collateral_ild_delta, onasset_ild_delta) = commit_liquidity(
+        &mut pools.pools[pool_index as usize],
+        &mut user_account.comet_positions[position_index],
+        collateral_amount,
+        collateral,
+        slot,
+    )?;
 
-    let health_score = calculate_health_score(comet, pools, oracles, collateral)?;
+    let health_score = calculate_health_score(
+        &user_account.header.comet,
+        &user_account.comet_positions,
+        &pools,
+        &oracles,
+        collateral,
+        slot,
+    )?;
 
     return_error_if_false!(health_score.is_healthy(), CloneError::HealthScoreTooLow);
+    check_comet_bounds(
+        &bounds,
+        &user_account.header.comet,
+        &user_account.comet_positions,
+        position_index,
+        &pools,
+        Some(&oracles),
+        collateral,
+        slot,
+    )?;
 
     emit!(LiquidityDelta {
         event_id: ctx.accounts.clone.event_counter,
@@ -29,7 +29,7 @@ pub struct AddPool<'info> {
         seeds = [POOLS_SEED.as_ref()],
         bump,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mint::decimals = TryInto::<u8>::try_into(CLONE_TOKEN_SCALE).map_err(|_| CloneError::IntTypeConversionError)?,
         mint::authority = clone,
@@ -74,7 +74,7 @@ pub fn execute(
         CloneError::InvalidOvercollateralizationRatios
     );
     // append pool to list
-    ctx.accounts.pools.pools.push(Pool {
+    ctx.accounts.pools.load_mut()?.append_pool(Pool {
         underlying_asset_token_account: ctx
             .accounts
             .underlying_asset_token_account
@@ -87,7 +87,8 @@ pub fn execute(
         committed_collateral_liquidity: 0,
         collateral_ild: 0,
         onasset_ild: 0,
-    });
+        ..Pool::default()
+    })?;
 
     Ok(())
 }
@@ -0,0 +1,154 @@
+use crate::error::*;
+use crate::instructions::{
+    can_use_staking_account, process_swap, staking_reward_share, staking_tier_fees, SwapResult,
+    SwapTokenAccounts,
+};
+use crate::states::*;
+use crate::{CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+use clone_staking::{
+    program::CloneStaking as CloneStakingProgram,
+    states::{CloneStaking, User as UserStaking},
+    CLONE_STAKING_SEED,
+};
+
+/// `swap` for routers and other programs. User token accounts can be any
+/// account of the right mint owned by `user`, which may be a PDA signing
+/// through CPI, and only the treasury account of the side fees are paid in is
+/// needed. The amounts moved are returned as a `SwapResult`.
+#[derive(Accounts)]
+#[instruction(
+    pool_index: u8,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+    result_threshold: u64
+)]
+pub struct AggregatorSwap<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        mut,
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::InvalidInputPositionIndex,
+        constraint = pools.load()?.pools[pool_index as usize].status == Status::Active @ CloneError::StatusPreventsAction,
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        mut,
+        seeds = [ORACLES_SEED.as_ref()],
+        bump,
+    )]
+    pub oracles: AccountLoader<'info, Oracles>,
+    #[account(
+        mut,
+        token::mint = collateral_mint,
+        token::authority = user
+    )]
+    pub user_collateral_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = onasset_mint,
+        token::authority = user
+    )]
+    pub user_onasset_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        address = pools.load()?.pools[pool_index as usize].asset_info.onasset_mint,
+    )]
+    pub onasset_mint: Box<Account<'info, Mint>>,
+    #[account(
+        address = clone.collateral.mint
+    )]
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        address = clone.collateral.vault
+    )]
+    pub collateral_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = onasset_mint,
+        token::authority = clone.treasury_address
+    )]
+    pub treasury_onasset_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = collateral_mint,
+        token::authority = clone.treasury_address
+    )]
+    pub treasury_collateral_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        seeds::program = clone_staking_program.clone().ok_or(error!(CloneError::ExpectedAccountNotFound))?.key(),
+    )]
+    pub clone_staking: Option<Account<'info, CloneStaking>>,
+    #[account(
+        constraint = can_use_staking_account(&user_staking_account, user.key) @ CloneError::Unauthorized,
+    )]
+    pub user_staking_account: Option<Account<'info, UserStaking>>,
+    pub clone_staking_program: Option<Program<'info, CloneStakingProgram>>,
+    #[account(
+        mut,
+        address = clone_staking.as_ref().ok_or(error!(CloneError::ExpectedAccountNotFound))?.reward_vault,
+        token::mint = collateral_mint,
+    )]
+    pub staking_reward_vault: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+pub fn execute(
+    ctx: Context<AggregatorSwap>,
+    pool_index: u8,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+    result_threshold: u64,
+) -> Result<SwapResult> {
+    let fee_overrides = staking_tier_fees(
+        &ctx.accounts.clone_staking,
+        &ctx.accounts.user_staking_account,
+    )?;
+    let token_accounts = SwapTokenAccounts {
+        user: ctx.accounts.user.to_account_info(),
+        user_collateral_token_account: ctx.accounts.user_collateral_token_account.to_account_info(),
+        user_onasset_token_account: ctx.accounts.user_onasset_token_account.to_account_info(),
+        onasset_mint: ctx.accounts.onasset_mint.to_account_info(),
+        collateral_vault: ctx.accounts.collateral_vault.to_account_info(),
+        treasury_onasset_token_account: ctx
+            .accounts
+            .treasury_onasset_token_account
+            .as_ref()
+            .map(|account| account.to_account_info()),
+        treasury_collateral_token_account: ctx
+            .accounts
+            .treasury_collateral_token_account
+            .as_ref()
+            .map(|account| account.to_account_info()),
+        staking_reward_share: staking_reward_share(
+            &ctx.accounts.clone_staking,
+            &ctx.accounts.staking_reward_vault,
+        ),
+        token_program: ctx.accounts.token_program.to_account_info(),
+    };
+    process_swap(
+        &mut ctx.accounts.clone,
+        &ctx.accounts.pools,
+        &ctx.accounts.oracles,
+        token_accounts,
+        pool_index,
+        quantity,
+        quantity_is_input,
+        quantity_is_collateral,
+        result_threshold,
+        fee_overrides,
+    )
+}
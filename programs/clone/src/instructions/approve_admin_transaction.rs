@@ -0,0 +1,31 @@
+use crate::error::*;
+use crate::states::*;
+use crate::ADMIN_MULTISIG_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ApproveAdminTransaction<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [ADMIN_MULTISIG_SEED.as_ref()],
+        bump = admin_multisig.bump,
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+    #[account(
+        mut,
+        constraint = !admin_transaction.executed @ CloneError::AdminTransactionExecuted,
+        constraint = admin_transaction.signer_set_version == admin_multisig.signer_set_version @ CloneError::StaleAdminTransaction,
+    )]
+    pub admin_transaction: Box<Account<'info, AdminTransaction>>,
+}
+
+pub fn execute(ctx: Context<ApproveAdminTransaction>) -> Result<()> {
+    let signer_index = ctx
+        .accounts
+        .admin_multisig
+        .signer_index(ctx.accounts.signer.key)
+        .ok_or(error!(CloneError::Unauthorized))?;
+    ctx.accounts.admin_transaction.approve(signer_index);
+
+    Ok(())
+}
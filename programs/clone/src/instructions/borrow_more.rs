@@ -1,5 +1,6 @@
 use crate::error::*;
 use crate::events::*;
+use crate::instructions::{check_borrow_bounds, BorrowBounds};
 use crate::math::*;
 use crate::return_error_if_false;
 use crate::states::*;
@@ -18,8 +19,9 @@ pub struct BorrowMore<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
+        constraint = (borrow_index as u64) < user_account.load_user()?.header.num_borrows @ CloneError::InvalidInputPositionIndex,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -30,12 +32,12 @@ pub struct BorrowMore<'info> {
         seeds = [POOLS_SEED.as_ref()],
         bump
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
         associated_token::mint = onasset_mint,
@@ -44,13 +46,18 @@ pub struct BorrowMore<'info> {
     pub user_onasset_token_account: Account<'info, TokenAccount>,
     #[account(
         mut,
-        address = pools.pools[user_account.borrows[borrow_index as usize].pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[user_account.load_user()?.borrows[borrow_index as usize].pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     pub token_program: Program<'info, Token>,
 }
 
-pub fn execute(ctx: Context<BorrowMore>, borrow_index: u8, amount: u64) -> Result<()> {
+pub fn execute(
+    ctx: Context<BorrowMore>,
+    borrow_index: u8,
+    amount: u64,
+    bounds: BorrowBounds,
+) -> Result<()> {
     return_error_if_false!(amount > 0, CloneError::InvalidTokenAmount);
 
     let seeds = &[&[
@@ -59,9 +66,10 @@ pub fn execute(ctx: Context<BorrowMore>, borrow_index: u8, amount: u64) -> Resul
     ][..]];
 
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
-    let borrows = &mut ctx.accounts.user_account.borrows;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let borrows = &mut user_account.borrows;
 
     let pool_index = borrows[borrow_index as usize].pool_index;
     let pool = &pools.pools[pool_index as usize];
@@ -82,13 +90,26 @@ pub fn execute(ctx: Context<BorrowMore>, borrow_index: u8, amount: u64) -> Resul
         .ok_or(error!(CloneError::CheckedMathError))?;
 
     // ensure position sufficiently over collateralized and oracle prices are up to date
+    let borrowed_onasset = to_clone_decimal!(borrows[borrow_index as usize].borrowed_onasset);
+    let collateral_amount = collateral.to_collateral_decimal(borrow_position.collateral_amount)?;
+    let slot = Clock::get()?.slot;
     check_mint_collateral_sufficient(
         pool_oracle,
         collateral_oracle,
-        to_clone_decimal!(borrows[borrow_index as usize].borrowed_onasset),
+        borrowed_onasset,
         min_overcollateral_ratio,
         collateralization_ratio,
-        collateral.to_collateral_decimal(borrow_position.collateral_amount)?,
+        collateral_amount,
+        slot,
+    )?;
+    check_borrow_bounds(
+        &bounds,
+        pool_oracle,
+        collateral_oracle,
+        borrowed_onasset,
+        collateralization_ratio,
+        collateral_amount,
+        slot,
     )?;
 
     // mint onasset to the user
@@ -0,0 +1,80 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{return_error_if_false, CLONE_PROGRAM_SEED, POOLS_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, *};
+
+#[derive(Accounts)]
+#[instruction(comet_position_index: u8)]
+pub struct ClaimPoolRewards<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+        constraint = user_account.load_user()?.header.comet.num_positions > comet_position_index.into() @ CloneError::InvalidInputPositionIndex
+    )]
+    pub user_account: AccountLoader<'info, User>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        mut,
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        address = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].rewards.reward_mint @ CloneError::InvalidRewardMint,
+    )]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = clone
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user
+    )]
+    pub user_reward_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Pays out the liquidity mining rewards a comet position has earned.
+pub fn execute(ctx: Context<ClaimPoolRewards>, comet_position_index: u8) -> Result<()> {
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet_position = &mut user_account.comet_positions[comet_position_index as usize];
+    let pool = &mut pools.pools[comet_position.pool_index as usize];
+
+    pool.accrue_rewards(Clock::get()?.slot)?;
+    comet_position.accrue_rewards(pool)?;
+    let rewards = comet_position.unclaimed_rewards;
+    return_error_if_false!(rewards > 0, CloneError::InvalidTokenAmount);
+    comet_position.unclaimed_rewards = 0;
+
+    let seeds = &[&[
+        CLONE_PROGRAM_SEED.as_ref(),
+        bytemuck::bytes_of(&ctx.accounts.clone.bump),
+    ][..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                to: ctx.accounts.user_reward_token_account.to_account_info(),
+                authority: ctx.accounts.clone.to_account_info(),
+            },
+            seeds,
+        ),
+        rewards,
+    )?;
+
+    Ok(())
+}
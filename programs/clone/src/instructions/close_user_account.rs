@@ -1,5 +1,6 @@
+use crate::error::*;
 use crate::states::*;
-use crate::USER_SEED;
+use crate::{return_error_if_false, USER_SEED};
 use anchor_lang::prelude::*;
 use anchor_lang::AccountsClose;
 
@@ -11,7 +12,7 @@ pub struct CloseUserAccount<'info> {
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     /// CHECK: Should be a system owned address.
     #[account(mut)]
     pub destination: AccountInfo<'info>,
@@ -19,8 +20,13 @@ pub struct CloseUserAccount<'info> {
 }
 
 pub fn execute(ctx: Context<CloseUserAccount>) -> Result<()> {
-    let user_account = &ctx.accounts.user_account;
-    assert!(user_account.comet.is_empty() && user_account.borrows.is_empty());
+    {
+        let user_account = ctx.accounts.user_account.load()?;
+        return_error_if_false!(
+            user_account.comet.is_empty() && user_account.num_borrows == 0,
+            CloneError::RequireAllPositionsClosed
+        );
+    }
 
     ctx.accounts
         .user_account
@@ -15,9 +15,9 @@ pub struct CollectLpRewards<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
-        constraint = user_account.comet.positions.len() > comet_position_index.into() @ CloneError::InvalidInputPositionIndex
+        constraint = user_account.load_user()?.header.comet.num_positions > comet_position_index.into() @ CloneError::InvalidInputPositionIndex
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -27,9 +27,9 @@ pub struct CollectLpRewards<'info> {
     #[account(
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = pools.pools[user_account.comet.positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
+        constraint = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         address = clone.collateral.vault
@@ -37,7 +37,7 @@ pub struct CollectLpRewards<'info> {
     pub collateral_vault: Box<Account<'info, TokenAccount>>,
     #[account(
         mut,
-        address = pools.pools[user_account.comet.positions[comet_position_index as usize].pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -60,12 +60,13 @@ pub fn execute(ctx: Context<CollectLpRewards>, comet_position_index: u8) -> Resu
         CLONE_PROGRAM_SEED.as_ref(),
         bytemuck::bytes_of(&ctx.accounts.clone.bump),
     ][..]];
-    let pools = &ctx.accounts.pools;
-    let comet = &mut ctx.accounts.user_account.comet;
+    let pools = ctx.accounts.pools.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet_positions = &mut user_account.comet_positions;
 
-    let comet_position = comet.positions[comet_position_index as usize];
+    let comet_position = comet_positions[comet_position_index as usize];
 
-    let ild_share = calculate_ild_share(&comet_position, pools, &ctx.accounts.clone.collateral)?;
+    let ild_share = calculate_ild_share(&comet_position, &pools, &ctx.accounts.clone.collateral)?;
 
     if ild_share.collateral_ild_share < Decimal::ZERO {
         let collateral_reward = ild_share
@@ -76,7 +77,7 @@ pub fn execute(ctx: Context<CollectLpRewards>, comet_position_index: u8) -> Resu
             .map_err(|_| CloneError::IntTypeConversionError)?;
 
         // Update rebate amount such that the ild_share is now zero.
-        comet.positions[comet_position_index as usize].collateral_ild_rebate = comet.positions
+        comet_positions[comet_position_index as usize].collateral_ild_rebate = comet_positions
             [comet_position_index as usize]
             .collateral_ild_rebate
             .checked_sub(collateral_reward)
@@ -113,7 +114,7 @@ pub fn execute(ctx: Context<CollectLpRewards>, comet_position_index: u8) -> Resu
             .map_err(|_| CloneError::IntTypeConversionError)?;
 
         // Update rebate amount such that the ild_share is now zero.
-        comet.positions[comet_position_index as usize].onasset_ild_rebate = comet.positions
+        comet_positions[comet_position_index as usize].onasset_ild_rebate = comet_positions
             [comet_position_index as usize]
             .onasset_ild_rebate
             .checked_sub(onasset_reward)
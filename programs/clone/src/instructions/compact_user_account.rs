@@ -0,0 +1,36 @@
+use crate::states::*;
+use crate::USER_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CompactUserAccount<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+    )]
+    pub user_account: AccountLoader<'info, User>,
+}
+
+pub fn execute(ctx: Context<CompactUserAccount>) -> Result<()> {
+    let (num_borrows, num_positions) = {
+        let user_account = ctx.accounts.user_account.load()?;
+        (user_account.num_borrows, user_account.comet.num_positions)
+    };
+    ctx.accounts
+        .user_account
+        .resize_user(num_borrows, num_positions)?;
+
+    // Refund the rent freed by shrinking the account.
+    let account_info = ctx.accounts.user_account.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(account_info.data_len());
+    let excess_lamports = account_info.lamports().saturating_sub(rent_exempt_minimum);
+    if excess_lamports > 0 {
+        **account_info.try_borrow_mut_lamports()? -= excess_lamports;
+        **ctx.accounts.user.try_borrow_mut_lamports()? += excess_lamports;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use crate::decimal::rescale_toward_zero;
+use crate::error::*;
+use crate::events::*;
+use crate::instructions::commit_liquidity;
+use crate::math::*;
+use crate::states::*;
+use crate::{return_error_if_false, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use rust_decimal::prelude::*;
+use std::convert::TryInto;
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, comet_position_index: u8)]
+pub struct CompoundLpRewards<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.as_ref()],
+        bump,
+        constraint = (comet_position_index as u64) < user_account.load_user()?.header.comet.num_positions @ CloneError::InvalidInputPositionIndex,
+    )]
+    pub user_account: AccountLoader<'info, User>,
+    #[account(
+        mut,
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        mut,
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].status == Status::Active @ CloneError::StatusPreventsAction
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        seeds = [ORACLES_SEED.as_ref()],
+        bump,
+    )]
+    pub oracles: AccountLoader<'info, Oracles>,
+}
+
+/// Commits the LP rewards of a comet position, its negative collateral and
+/// onAsset ILD, back into the pool as liquidity instead of paying them out.
+/// OnAssets count at their oracle price. Anyone may call it for positions
+/// that opted in through `set_auto_compound`.
+pub fn execute(
+    ctx: Context<CompoundLpRewards>,
+    user: Pubkey,
+    comet_position_index: u8,
+) -> Result<()> {
+    let collateral = &ctx.accounts.clone.collateral;
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let position_index = comet_position_index as usize;
+    let comet_position = user_account.comet_positions[position_index];
+    return_error_if_false!(
+        ctx.accounts.signer.key() == user || comet_position.auto_compound != 0,
+        CloneError::Unauthorized
+    );
+
+    let ild_share = calculate_ild_share(&comet_position, &pools, collateral)?;
+    let collateral_reward = ild_share.collateral_ild_share.min(Decimal::ZERO).abs();
+    let onasset_reward = ild_share.onasset_ild_share.min(Decimal::ZERO).abs();
+
+    let pool_index = comet_position.pool_index as usize;
+    let oracle = &oracles.oracles[pools.pools[pool_index].asset_info.oracle_info_index as usize];
+    let collateral_oracle = &oracles.oracles[collateral.oracle_info_index as usize];
+    let slot = Clock::get()?.slot;
+    check_feed_update(oracle, slot)?;
+    check_feed_update(collateral_oracle, slot)?;
+    let onasset_price = oracle.get_price()?;
+    let collateral_price = collateral_oracle.get_price()?;
+    let onasset_reward_value = rescale_toward_zero(
+        onasset_reward
+            .checked_mul(onasset_price)
+            .and_then(|value| value.checked_div(collateral_price))
+            .ok_or(error!(CloneError::CheckedMathError))?,
+        collateral.scale.into(),
+    );
+    let compounded_liquidity: u64 = collateral_reward
+        .checked_add(onasset_reward_value)
+        .ok_or(error!(CloneError::CheckedMathError))?
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
+    return_error_if_false!(compounded_liquidity > 0, CloneError::InvalidTokenAmount);
+
+    let collateral_compounded: i64 = collateral_reward
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
+    let onasset_compounded: i64 = onasset_reward
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
+
+    // Update rebate amounts such that the rewards are now zero, as if they
+    // were collected.
+    let position = &mut user_account.comet_positions[position_index];
+    position.collateral_ild_rebate = position
+        .collateral_ild_rebate
+        .checked_sub(collateral_compounded)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+    position.onasset_ild_rebate = position
+        .onasset_ild_rebate
+        .checked_sub(onasset_compounded)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    let (collateral_ild_delta, onasset_ild_delta) = commit_liquidity(
+        &mut pools.pools[pool_index],
+        position,
+        compounded_liquidity,
+        collateral,
+        slot,
+    )?;
+
+    let health_score = calculate_health_score(
+        &user_account.header.comet,
+        &user_account.comet_positions,
+        &pools,
+        &oracles,
+        collateral,
+        slot,
+    )?;
+    return_error_if_false!(health_score.is_healthy(), CloneError::HealthScoreTooLow);
+
+    emit!(LiquidityDelta {
+        event_id: ctx.accounts.clone.event_counter,
+        user_address: user,
+        pool_index: comet_position.pool_index,
+        committed_collateral_delta: compounded_liquidity
+            .try_into()
+            .map_err(|_| CloneError::IntTypeConversionError)?,
+        collateral_ild_delta,
+        onasset_ild_delta,
+    });
+    emit!(LpRewardsCompounded {
+        event_id: ctx.accounts.clone.event_counter,
+        user_address: user,
+        pool_index: comet_position.pool_index,
+        collateral_compounded: collateral_compounded
+            .try_into()
+            .map_err(|_| CloneError::IntTypeConversionError)?,
+        onasset_compounded: onasset_compounded
+            .try_into()
+            .map_err(|_| CloneError::IntTypeConversionError)?,
+        committed_collateral_delta: compounded_liquidity,
+    });
+
+    ctx.accounts.clone.event_counter = ctx
+        .accounts
+        .clone
+        .event_counter
+        .checked_add(1)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use crate::error::*;
+use crate::return_error_if_false;
+use crate::states::*;
+use crate::ADMIN_MULTISIG_SEED;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+#[derive(Accounts)]
+pub struct ExecuteAdminTransaction<'info> {
+    // Not written back on exit, the transaction may update the multisig.
+    #[account(
+        seeds = [ADMIN_MULTISIG_SEED.as_ref()],
+        bump = admin_multisig.bump,
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+    #[account(
+        mut,
+        constraint = !admin_transaction.executed @ CloneError::AdminTransactionExecuted,
+        constraint = admin_transaction.signer_set_version == admin_multisig.signer_set_version @ CloneError::StaleAdminTransaction,
+    )]
+    pub admin_transaction: Box<Account<'info, AdminTransaction>>,
+}
+
+/// Signs the stored instruction with the multisig PDA once enough signers
+/// approved it. The remaining accounts are the instruction's accounts and
+/// program. Anyone may call it.
+pub fn execute(ctx: Context<ExecuteAdminTransaction>) -> Result<()> {
+    let admin_multisig = &ctx.accounts.admin_multisig;
+    let admin_transaction = &mut ctx.accounts.admin_transaction;
+    return_error_if_false!(
+        admin_transaction.num_approvals() >= admin_multisig.threshold,
+        CloneError::NotEnoughApprovals
+    );
+
+    // Persist before the CPI so the transaction can't be executed again
+    // from within it.
+    admin_transaction.executed = true;
+    admin_transaction.exit(&crate::ID)?;
+
+    let multisig_key = admin_multisig.key();
+    let instruction = Instruction {
+        program_id: admin_transaction.program_id,
+        accounts: admin_transaction
+            .accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer || account.pubkey == multisig_key,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: admin_transaction.data.clone(),
+    };
+    let seeds = &[&[
+        ADMIN_MULTISIG_SEED.as_ref(),
+        bytemuck::bytes_of(&admin_multisig.bump),
+    ][..]];
+    invoke_signed(&instruction, ctx.remaining_accounts, seeds)?;
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{instruction, return_error_if_false, CLONE_PROGRAM_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, *};
+use std::convert::TryInto;
+
+/// Fee owed on a flash loan or flash mint of `amount`, rounded up.
+pub fn calculate_flash_loan_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .and_then(|fee| fee.checked_add(9_999))
+        .ok_or(error!(CloneError::CheckedMathError))?
+        / 10_000;
+    fee.try_into()
+        .map_err(|_| error!(CloneError::IntTypeConversionError))
+}
+
+/// Arguments of the instruction at `index` if it's a top-level call to this
+/// program's instruction `T`.
+pub fn load_flash_loan_instruction<T: AnchorDeserialize + Discriminator>(
+    instructions_sysvar: &AccountInfo,
+    index: usize,
+) -> Result<Option<T>> {
+    let instruction = load_instruction_at_checked(index, instructions_sysvar)?;
+    if instruction.program_id != crate::ID
+        || instruction.data.get(..8) != Some(&T::discriminator()[..])
+    {
+        return Ok(None);
+    }
+    Ok(T::deserialize(&mut &instruction.data[8..]).ok())
+}
+
+/// Index of the instruction being executed. Introspection only sees
+/// top-level instructions, so it must be one: through CPI the caller's
+/// instruction would be checked in its place.
+pub fn current_flash_loan_instruction_index<T: AnchorDeserialize + Discriminator>(
+    instructions_sysvar: &AccountInfo,
+) -> Result<u8> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    return_error_if_false!(
+        load_flash_loan_instruction::<T>(instructions_sysvar, current_index as usize)?.is_some(),
+        CloneError::InvalidFlashLoan
+    );
+    current_index
+        .try_into()
+        .map_err(|_| error!(CloneError::InvalidFlashLoan))
+}
+
+/// Checks that an instruction `T` after the flash loan at `borrow_index`
+/// repays it.
+pub fn check_flash_loan_repaid<T: AnchorDeserialize + Discriminator>(
+    instructions_sysvar: &AccountInfo,
+    borrow_index: u8,
+    is_repayment: impl Fn(&T) -> bool,
+) -> Result<()> {
+    let mut index = borrow_index as usize + 1;
+    loop {
+        match load_flash_loan_instruction::<T>(instructions_sysvar, index) {
+            Ok(Some(repayment)) if is_repayment(&repayment) => return Ok(()),
+            Ok(_) => index += 1,
+            // Raised once `index` is past the last instruction.
+            Err(_) => return Err(error!(CloneError::FlashLoanNotRepaid)),
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct FlashLoanCollateral<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+        constraint = clone.flash_loan_fee_bps > 0 @ CloneError::FlashLoansDisabled,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        address = clone.collateral.mint
+    )]
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        address = clone.collateral.vault
+    )]
+    pub collateral_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = collateral_mint
+    )]
+    pub user_collateral_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Address checked, read through instruction introspection.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Lends `amount` of collateral from the vault. A later
+/// `repay_flash_loan_collateral` in the same transaction must return it
+/// together with the flash loan fee.
+pub fn execute(ctx: Context<FlashLoanCollateral>, amount: u64) -> Result<()> {
+    return_error_if_false!(amount > 0, CloneError::InvalidTokenAmount);
+
+    let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+    let borrow_index = current_flash_loan_instruction_index::<instruction::FlashLoanCollateral>(
+        &instructions_sysvar,
+    )?;
+    check_flash_loan_repaid::<instruction::RepayFlashLoanCollateral>(
+        &instructions_sysvar,
+        borrow_index,
+        |repayment| {
+            repayment.amount == amount && repayment.borrow_instruction_index == borrow_index
+        },
+    )?;
+
+    let seeds = &[&[
+        CLONE_PROGRAM_SEED.as_ref(),
+        bytemuck::bytes_of(&ctx.accounts.clone.bump),
+    ][..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.collateral_vault.to_account_info(),
+                to: ctx.accounts.user_collateral_token_account.to_account_info(),
+                authority: ctx.accounts.clone.to_account_info(),
+            },
+            seeds,
+        ),
+        amount,
+    )?;
+
+    Ok(())
+}
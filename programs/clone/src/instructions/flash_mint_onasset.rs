@@ -0,0 +1,79 @@
+use crate::error::*;
+use crate::instructions::{check_flash_loan_repaid, current_flash_loan_instruction_index};
+use crate::states::*;
+use crate::{instruction, return_error_if_false, CLONE_PROGRAM_SEED, POOLS_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, *};
+
+#[derive(Accounts)]
+#[instruction(pool_index: u8, amount: u64)]
+pub struct FlashMintOnasset<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+        constraint = clone.flash_loan_fee_bps > 0 @ CloneError::FlashLoansDisabled,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
+        constraint = pools.load()?.pools[pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction,
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        mut,
+        address = pools.load()?.pools[pool_index as usize].asset_info.onasset_mint,
+    )]
+    pub onasset_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = onasset_mint
+    )]
+    pub user_onasset_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Address checked, read through instruction introspection.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Mints `amount` of the pool's onAsset. A later `repay_flash_mint_onasset`
+/// in the same transaction must burn it and pay the flash loan fee.
+pub fn execute(ctx: Context<FlashMintOnasset>, pool_index: u8, amount: u64) -> Result<()> {
+    return_error_if_false!(amount > 0, CloneError::InvalidTokenAmount);
+
+    let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+    let borrow_index = current_flash_loan_instruction_index::<instruction::FlashMintOnasset>(
+        &instructions_sysvar,
+    )?;
+    check_flash_loan_repaid::<instruction::RepayFlashMintOnasset>(
+        &instructions_sysvar,
+        borrow_index,
+        |repayment| {
+            repayment.pool_index == pool_index
+                && repayment.amount == amount
+                && repayment.borrow_instruction_index == borrow_index
+        },
+    )?;
+
+    let seeds = &[&[
+        CLONE_PROGRAM_SEED.as_ref(),
+        bytemuck::bytes_of(&ctx.accounts.clone.bump),
+    ][..]];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.onasset_mint.to_account_info(),
+                to: ctx.accounts.user_onasset_token_account.to_account_info(),
+                authority: ctx.accounts.clone.to_account_info(),
+            },
+            seeds,
+        ),
+        amount,
+    )?;
+
+    Ok(())
+}
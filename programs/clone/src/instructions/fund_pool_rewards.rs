@@ -0,0 +1,81 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{CLONE_PROGRAM_SEED, POOLS_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, *};
+
+#[derive(Accounts)]
+#[instruction(pool_index: u8, emission_rate: u64, amount: u64)]
+pub struct FundPoolRewards<'info> {
+    #[account(address = clone.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+        has_one = admin
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        mut,
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        constraint = [Pubkey::default(), reward_mint.key()].contains(&pools.load()?.pools[pool_index as usize].rewards.reward_mint) @ CloneError::InvalidRewardMint,
+    )]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = admin
+    )]
+    pub admin_reward_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = clone
+    )]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Sets the rate the pool emits rewards to its liquidity providers at and
+/// adds `amount` to the rewards left to emit. The first funding fixes the
+/// pool's reward mint.
+pub fn execute(
+    ctx: Context<FundPoolRewards>,
+    pool_index: u8,
+    emission_rate: u64,
+    amount: u64,
+) -> Result<()> {
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    let pool = &mut pools.pools[pool_index as usize];
+
+    // Rewards up to now are emitted at the previous rate.
+    pool.accrue_rewards(Clock::get()?.slot)?;
+    pool.rewards.reward_mint = ctx.accounts.reward_mint.key();
+    pool.rewards.emission_rate = emission_rate;
+    pool.rewards.unemitted_rewards = pool
+        .rewards
+        .unemitted_rewards
+        .checked_add(amount)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    if amount > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.admin_reward_token_account.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use crate::decimal::{rescale_toward_zero, CLONE_TOKEN_SCALE};
+use crate::error::*;
+use crate::math::*;
+use crate::states::*;
+use crate::{
+    return_error_if_false, to_clone_decimal, to_ratio_decimal, CLONE_PROGRAM_SEED, ORACLES_SEED,
+    POOLS_SEED, USER_SEED,
+};
+use anchor_lang::prelude::*;
+use rust_decimal::prelude::*;
+use std::convert::TryInto;
+
+/// Health of one comet position. `liquidation_price` is the onAsset oracle
+/// price at which the comet's health score reaches zero, `None` when the
+/// position's health doesn't fall as the onAsset price rises.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct PositionHealth {
+    pub pool_index: u8,
+    pub il_term: i64,
+    pub position_term: i64,
+    pub liquidation_price: Option<u64>,
+}
+
+/// Health of one borrow position. `collateral_ratio` is compared against the
+/// pool's `min_overcollateral_ratio`, which `liquidation_price` is the onAsset
+/// oracle price for. Both are `None` when nothing is borrowed.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct BorrowHealth {
+    pub pool_index: u8,
+    pub collateral_ratio: Option<u64>,
+    pub liquidation_price: Option<u64>,
+}
+
+/// Health of a user's comet and borrows, returned by `get_user_health` as
+/// return data. Every value has `scale` decimals; prices are in the oracle's
+/// quote currency. `positions` and `borrows` are one page of the user's
+/// `num_positions` comet positions and `num_borrows` borrows.
+#[derive(Clone, PartialEq, Eq, Default, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct UserHealth {
+    pub scale: u32,
+    pub score: i64,
+    pub effective_collateral: i64,
+    pub total_il_term: i64,
+    pub total_position_term: i64,
+    pub num_positions: u64,
+    pub num_borrows: u64,
+    pub positions: Vec<PositionHealth>,
+    pub borrows: Vec<BorrowHealth>,
+}
+
+/// Most positions and borrows a `UserHealth` holds within the 1024 bytes of
+/// return data: 60 bytes of header, then 26 per position and 19 per borrow.
+pub const MAX_USER_HEALTH_ENTRIES: u8 = 37;
+
+#[derive(Accounts)]
+pub struct GetUserHealth<'info> {
+    /// CHECK: Only used to find the user account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+    )]
+    pub user_account: AccountLoader<'info, User>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        seeds = [ORACLES_SEED.as_ref()],
+        bump,
+    )]
+    pub oracles: AccountLoader<'info, Oracles>,
+}
+
+fn to_value(value: Decimal) -> Result<i64> {
+    rescale_toward_zero(value, CLONE_TOKEN_SCALE)
+        .mantissa()
+        .try_into()
+        .map_err(|_| error!(CloneError::IntTypeConversionError))
+}
+
+fn to_optional_value(value: Option<Decimal>) -> Result<Option<u64>> {
+    value
+        .map(|value| {
+            rescale_toward_zero(value, CLONE_TOKEN_SCALE)
+                .mantissa()
+                .try_into()
+                .map_err(|_| error!(CloneError::IntTypeConversionError))
+        })
+        .transpose()
+}
+
+/// Returns the user's health, with up to `limit` entries of detail: comet
+/// positions from `position_offset` first, then borrows from
+/// `borrow_offset`. Clients page through the rest with further calls.
+pub fn execute(
+    ctx: Context<GetUserHealth>,
+    position_offset: u8,
+    borrow_offset: u8,
+    limit: u8,
+) -> Result<UserHealth> {
+    return_error_if_false!(
+        limit <= MAX_USER_HEALTH_ENTRIES,
+        CloneError::InvalidValueRange
+    );
+    let collateral = &ctx.accounts.clone.collateral;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let user_account = ctx.accounts.user_account.load_user()?;
+    let slot = Clock::get()?.slot;
+
+    let comet = &user_account.header.comet;
+    let comet_positions = &user_account.comet_positions[..comet.num_positions as usize];
+    let health_score = calculate_health_score(
+        comet,
+        &user_account.comet_positions,
+        &pools,
+        &oracles,
+        collateral,
+        slot,
+    )?;
+
+    let positions = comet_positions
+        .iter()
+        .skip(position_offset.into())
+        .take(limit.into())
+        .map(|liquidity_position| -> Result<PositionHealth> {
+            let (il_term, position_term) = calculate_liquidity_position_loss(
+                &pools,
+                &oracles,
+                liquidity_position,
+                collateral,
+            )?;
+            let liquidation_price = calculate_position_liquidation_price(
+                &health_score,
+                liquidity_position,
+                &pools,
+                &oracles,
+                collateral,
+            )?;
+            Ok(PositionHealth {
+                pool_index: liquidity_position.pool_index,
+                il_term: to_value(il_term)?,
+                position_term: to_value(position_term)?,
+                liquidation_price: to_optional_value(liquidation_price)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let collateral_oracle = &oracles.oracles[collateral.oracle_info_index as usize];
+    let collateralization_ratio = to_ratio_decimal!(collateral.collateralization_ratio);
+    let borrows = user_account.borrows[..user_account.header.num_borrows as usize]
+        .iter()
+        .skip(borrow_offset.into())
+        .take(limit as usize - positions.len())
+        .map(|borrow| -> Result<BorrowHealth> {
+            let pool = &pools.pools[borrow.pool_index as usize];
+            let pool_oracle = &oracles.oracles[pool.asset_info.oracle_info_index as usize];
+            let borrowed_onasset = to_clone_decimal!(borrow.borrowed_onasset);
+            let collateral_amount = collateral.to_collateral_decimal(borrow.collateral_amount)?;
+
+            let collateral_ratio = calculate_borrow_collateral_ratio(
+                pool_oracle,
+                collateral_oracle,
+                borrowed_onasset,
+                collateralization_ratio,
+                collateral_amount,
+                slot,
+            )?;
+            let liquidation_price = calculate_borrow_liquidation_price(
+                collateral_oracle,
+                borrowed_onasset,
+                to_ratio_decimal!(pool.asset_info.min_overcollateral_ratio),
+                collateralization_ratio,
+                collateral_amount,
+            )?;
+            Ok(BorrowHealth {
+                pool_index: borrow.pool_index,
+                collateral_ratio: to_optional_value(collateral_ratio)?,
+                liquidation_price: to_optional_value(liquidation_price)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(UserHealth {
+        scale: CLONE_TOKEN_SCALE,
+        score: to_value(health_score.score)?,
+        effective_collateral: to_value(health_score.effective_collateral)?,
+        total_il_term: to_value(health_score.total_il_term)?,
+        total_position_term: to_value(health_score.total_position_term)?,
+        num_positions: comet.num_positions,
+        num_borrows: user_account.header.num_borrows,
+        positions,
+        borrows,
+    })
+}
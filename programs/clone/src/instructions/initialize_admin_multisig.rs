@@ -0,0 +1,43 @@
+use crate::error::*;
+use crate::states::*;
+use crate::CLONE_PROGRAM_SEED;
+use anchor_lang::prelude::*;
+
+pub const ADMIN_MULTISIG_SEED: &str = "admin-multisig";
+
+#[derive(Accounts)]
+#[instruction(signers: Vec<Pubkey>, threshold: u8)]
+pub struct InitializeAdminMultisig<'info> {
+    #[account(mut, address = clone.admin)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+        has_one = admin
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        init,
+        space = 8 + 335,
+        seeds = [ADMIN_MULTISIG_SEED.as_ref()],
+        bump,
+        payer = admin
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the multisig. It only takes over once the admin keys are set to
+/// its address.
+pub fn execute(
+    ctx: Context<InitializeAdminMultisig>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    let admin_multisig = &mut ctx.accounts.admin_multisig;
+    admin_multisig.bump = *ctx
+        .bumps
+        .get("admin_multisig")
+        .ok_or(error!(CloneError::BumpNotFound))?;
+    admin_multisig.set_signers(&signers, threshold)
+}
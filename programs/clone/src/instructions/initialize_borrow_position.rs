@@ -1,5 +1,6 @@
 use crate::error::*;
 use crate::events::*;
+use crate::instructions::{check_expiry_slot, reserve_user_capacity};
 use crate::math::*;
 use crate::return_error_if_false;
 use crate::states::*;
@@ -8,18 +9,76 @@ use crate::{
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount, Transfer};
+use rust_decimal::prelude::*;
 use std::convert::TryInto;
 
+/// Optional limits on the state a borrow instruction executes in, see
+/// `CometBounds`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct BorrowBounds {
+    /// Last slot the instruction may execute in.
+    pub expiry_slot: Option<u64>,
+    /// Highest oracle price of the onAsset, in collateral with
+    /// `CLONE_TOKEN_SCALE` decimals.
+    pub max_oracle_price: Option<u64>,
+    /// Lowest collateral ratio, with `CLONE_TOKEN_SCALE` decimals, the borrow
+    /// may be left with.
+    pub min_collateral_ratio: Option<u64>,
+}
+
+/// Checks `bounds` against a borrow position once the instruction has
+/// updated it to `borrowed_onasset` against `collateral_amount`.
+#[allow(clippy::too_many_arguments)]
+pub fn check_borrow_bounds(
+    bounds: &BorrowBounds,
+    pool_oracle: &OracleInfo,
+    collateral_oracle: &OracleInfo,
+    borrowed_onasset: Decimal,
+    collateralization_ratio: Decimal,
+    collateral_amount: Decimal,
+    slot: u64,
+) -> Result<()> {
+    check_expiry_slot(bounds.expiry_slot, slot)?;
+    if let Some(max_oracle_price) = bounds.max_oracle_price {
+        let oracle_price = pool_oracle
+            .get_price()?
+            .checked_div(collateral_oracle.get_price()?)
+            .ok_or(error!(CloneError::CheckedMathError))?;
+        return_error_if_false!(
+            oracle_price <= to_clone_decimal!(max_oracle_price),
+            CloneError::SlippageToleranceExceeded
+        );
+    }
+    if let Some(min_collateral_ratio) = bounds.min_collateral_ratio {
+        let collateral_ratio = calculate_borrow_collateral_ratio(
+            pool_oracle,
+            collateral_oracle,
+            borrowed_onasset,
+            collateralization_ratio,
+            collateral_amount,
+            slot,
+        )?;
+        let min_collateral_ratio = to_clone_decimal!(min_collateral_ratio);
+        return_error_if_false!(
+            collateral_ratio.map_or(true, |ratio| ratio >= min_collateral_ratio),
+            CloneError::SlippageToleranceExceeded
+        );
+    }
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(pool_index: u8, onasset_amount: u64, collateral_amount: u64)]
 pub struct InitializeBorrowPosition<'info> {
     pub user: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -30,14 +89,15 @@ pub struct InitializeBorrowPosition<'info> {
         mut,
         seeds = [POOLS_SEED.as_ref()],
         bump,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
         address = clone.collateral.vault
@@ -51,7 +111,7 @@ pub struct InitializeBorrowPosition<'info> {
     pub user_collateral_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
         mut,
-        address = pools.pools[pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -61,6 +121,7 @@ pub struct InitializeBorrowPosition<'info> {
     )]
     pub user_onasset_token_account: Box<Account<'info, TokenAccount>>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn execute(
@@ -68,6 +129,7 @@ pub fn execute(
     pool_index: u8,
     onasset_amount: u64,
     collateral_amount: u64,
+    bounds: BorrowBounds,
 ) -> Result<()> {
     return_error_if_false!(
         onasset_amount > 0 && collateral_amount > 0,
@@ -79,8 +141,8 @@ pub fn execute(
         bytemuck::bytes_of(&ctx.accounts.clone.bump),
     ][..]];
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
 
     let pool = &pools.pools[pool_index as usize];
     return_error_if_false!(
@@ -96,6 +158,7 @@ pub fn execute(
     let collateral_amount_value = collateral.to_collateral_decimal(collateral_amount)?;
     let onasset_amount_value = to_clone_decimal!(onasset_amount);
     // ensure position sufficiently over collateralized and oracle prices are up to date
+    let slot = Clock::get()?.slot;
     check_mint_collateral_sufficient(
         pool_oracle,
         collateral_oracle,
@@ -103,6 +166,16 @@ pub fn execute(
         min_overcollateral_ratio,
         collateralization_ratio,
         collateral_amount_value,
+        slot,
+    )?;
+    check_borrow_bounds(
+        &bounds,
+        pool_oracle,
+        collateral_oracle,
+        onasset_amount_value,
+        collateralization_ratio,
+        collateral_amount_value,
+        slot,
     )?;
 
     // lock user collateral in vault
@@ -139,14 +212,24 @@ pub fn execute(
     )?;
 
     // set mint position data
-    let user_account = &mut ctx.accounts.user_account;
-    user_account.borrows.push(Borrow {
-        collateral_amount,
-        pool_index: pool_index
-            .try_into()
-            .map_err(|_| CloneError::IntTypeConversionError)?,
-        borrowed_onasset: onasset_amount,
-    });
+    reserve_user_capacity(
+        &ctx.accounts.user_account,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        1,
+        0,
+    )?;
+    ctx.accounts
+        .user_account
+        .load_user_mut()?
+        .append_borrow(Borrow {
+            collateral_amount,
+            pool_index: pool_index
+                .try_into()
+                .map_err(|_| CloneError::IntTypeConversionError)?,
+            borrowed_onasset: onasset_amount,
+            ..Borrow::default()
+        });
 
     emit!(BorrowUpdate {
         event_id: ctx.accounts.clone.event_counter,
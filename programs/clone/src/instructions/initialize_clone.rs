@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::*;
 
 pub const CLONE_PROGRAM_SEED: &str = "clone";
+pub const CLONE_ACCOUNT_SPACE: usize = 8 + 504;
 
 #[derive(Accounts)]
 #[instruction(
@@ -12,6 +13,7 @@ pub const CLONE_PROGRAM_SEED: &str = "clone";
     treasury_address: Pubkey,
     collateral_oracle_index: u8,
     collateralization_ratio: u8,
+    flash_loan_fee_bps: u16,
 )]
 pub struct InitializeClone<'info> {
     #[account(mut)]
@@ -19,7 +21,7 @@ pub struct InitializeClone<'info> {
     #[account(
         init,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
-        space = 8 + 472,
+        space = CLONE_ACCOUNT_SPACE,
         bump,
         payer = admin
     )]
@@ -43,11 +45,16 @@ pub fn execute(
     treasury_address: Pubkey,
     collateral_oracle_index: u8,
     collateralization_ratio: u8,
+    flash_loan_fee_bps: u16,
 ) -> Result<()> {
     return_error_if_false!(
         comet_onasset_ild_liquidator_fee_bps < 10000 && borrow_liquidator_fee_bps < 10000,
         CloneError::InvalidValueRange
     );
+    return_error_if_false!(
+        flash_loan_fee_bps > 0 && flash_loan_fee_bps < 10000,
+        CloneError::InvalidValueRange
+    );
 
     // set manager data
     ctx.accounts.clone.admin = *ctx.accounts.admin.to_account_info().key;
@@ -67,6 +74,7 @@ pub fn execute(
         comet_collateral_ild_liquidator_fee_bps;
     ctx.accounts.clone.comet_onasset_ild_liquidator_fee_bps = comet_onasset_ild_liquidator_fee_bps;
     ctx.accounts.clone.borrow_liquidator_fee_bps = borrow_liquidator_fee_bps;
+    ctx.accounts.clone.flash_loan_fee_bps = flash_loan_fee_bps;
 
     Ok(())
 }
@@ -16,12 +16,12 @@ pub struct InitializeOracles<'info> {
     pub clone: Box<Account<'info, Clone>>,
     #[account(
         init,
-        space = 10240,
+        space = 8 + std::mem::size_of::<Oracles>(),
         seeds = [ORACLES_SEED.as_ref()],
         bump,
         payer = admin,
     )]
-    pub oracles: Account<'info, Oracles>,
+    pub oracles: AccountLoader<'info, Oracles>,
     pub system_program: Program<'info, System>,
 }
 
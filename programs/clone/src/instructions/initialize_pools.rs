@@ -16,12 +16,12 @@ pub struct InitializePools<'info> {
     pub clone: Box<Account<'info, Clone>>,
     #[account(
         init,
-        space = 10240,
+        space = 8 + std::mem::size_of::<Pools>(),
         seeds = [POOLS_SEED.as_ref()],
         bump,
         payer = admin,
     )]
-    pub pools: Account<'info, Pools>,
+    pub pools: AccountLoader<'info, Pools>,
     pub system_program: Program<'info, System>,
 }
 
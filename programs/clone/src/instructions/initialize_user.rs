@@ -1,5 +1,7 @@
+use crate::error::*;
 use crate::states::*;
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 pub const USER_SEED: &str = "user";
 
@@ -10,15 +12,70 @@ pub struct InitializeUser<'info> {
     pub payer: Signer<'info>,
     #[account(
         init,
-        space = 10240,
+        space = User::account_size(0, 0),
         seeds = [USER_SEED.as_ref(), authority.as_ref()],
         bump,
         payer = payer,
     )]
-    pub user_account: Account<'info, User>,
+    pub user_account: AccountLoader<'info, User>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn execute(_ctx: Context<InitializeUser>, _authority: Pubkey) -> Result<()> {
     Ok(())
 }
+
+/// Grows the user account so that it has room for `additional_borrows` more
+/// borrow positions and `additional_positions` more comet positions. The payer
+/// covers the extra rent.
+pub fn reserve_user_capacity<'info>(
+    user_account: &AccountLoader<'info, User>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    additional_borrows: u64,
+    additional_positions: u64,
+) -> Result<()> {
+    let (borrows_capacity, positions_capacity) = {
+        let user = user_account.load_user()?;
+        let required_borrows = user
+            .header
+            .num_borrows
+            .checked_add(additional_borrows)
+            .ok_or(error!(CloneError::CheckedMathError))?;
+        let required_positions = user
+            .header
+            .comet
+            .num_positions
+            .checked_add(additional_positions)
+            .ok_or(error!(CloneError::CheckedMathError))?;
+        if required_borrows <= user.header.borrows_capacity
+            && required_positions <= user.header.comet.positions_capacity
+        {
+            return Ok(());
+        }
+        (
+            user.header.borrows_capacity.max(required_borrows),
+            user.header.comet.positions_capacity.max(required_positions),
+        )
+    };
+    user_account.resize_user(borrows_capacity, positions_capacity)?;
+
+    let account_info = user_account.to_account_info();
+    let rent_delta = Rent::get()?
+        .minimum_balance(account_info.data_len())
+        .saturating_sub(account_info.lamports());
+    if rent_delta > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account_info,
+                },
+            ),
+            rent_delta,
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::decimal::rescale_toward_zero;
+use crate::decimal::rescale_down;
 use crate::error::*;
 use crate::events::*;
 use crate::math::*;
@@ -28,22 +28,23 @@ pub struct LiquidateBorrowPosition<'info> {
         seeds = [POOLS_SEED.as_ref()],
         bump,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
         seeds = [USER_SEED.as_ref(), user.as_ref()],
         bump,
+        constraint = (borrow_index as u64) < user_account.load_user()?.header.num_borrows @ CloneError::InvalidInputPositionIndex,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
-        address = pools.pools[user_account.borrows[borrow_index as usize].pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[user_account.load_user()?.borrows[borrow_index as usize].pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -90,11 +91,11 @@ pub fn execute(
     ][..]];
 
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
 
-    let borrows = &mut ctx.accounts.user_account.borrows;
-    let borrow_position = borrows[borrow_index as usize];
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let borrow_position = user_account.borrows[borrow_index as usize];
     let pool_index = borrow_position.pool_index as usize;
     let pool = &pools.pools[pool_index];
     return_error_if_false!(
@@ -120,6 +121,7 @@ pub fn execute(
         min_overcollateral_ratio,
         collateralization_ratio,
         collateral_position_amount,
+        Clock::get()?.slot,
     )
     .is_err();
     let is_in_liquidation_mode = pool.status == Status::Liquidation;
@@ -135,7 +137,7 @@ pub fn execute(
         .checked_div(collateral_oracle.get_price()?)
         .ok_or(error!(CloneError::CheckedMathError))?;
 
-    let collateral_reward = rescale_toward_zero(
+    let collateral_reward = rescale_down(
         (Decimal::one()
             .checked_add(borrow_liquidation_fee_rate)
             .ok_or(error!(CloneError::CheckedMathError))?)
@@ -191,6 +193,7 @@ pub fn execute(
     )?;
 
     // Update data
+    let borrows = &mut user_account.borrows;
     borrows[borrow_index as usize].borrowed_onasset = borrows[borrow_index as usize]
         .borrowed_onasset
         .checked_sub(burn_amount)
@@ -205,10 +208,8 @@ pub fn execute(
         )
         .ok_or(error!(CloneError::CheckedMathError))?;
 
-    // Remove position if empty
-    if borrows[borrow_index as usize].is_empty() {
-        borrows.remove(borrow_index as usize);
-    } else {
+    let position_closed = borrows[borrow_index as usize].is_empty();
+    if !position_closed {
         let borrowed_onasset = to_clone_decimal!(borrows[borrow_index as usize].borrowed_onasset);
         let collateral_amount = Decimal::new(
             borrows[borrow_index as usize]
@@ -254,6 +255,12 @@ pub fn execute(
             .try_into()
             .map_err(|_| CloneError::IntTypeConversionError)?)
     });
+
+    // Remove position if empty
+    if position_closed {
+        user_account.remove_borrow(borrow_index as usize);
+    }
+
     ctx.accounts.clone.event_counter = ctx
         .accounts
         .clone
@@ -1,4 +1,4 @@
-use crate::decimal::rescale_toward_zero;
+use crate::decimal::rescale_down;
 use crate::error::*;
 use crate::events::*;
 use crate::instructions::withdraw_liquidity;
@@ -20,8 +20,9 @@ pub struct LiquidateCometCollateralIld<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.as_ref()],
         bump,
+        constraint = (comet_position_index as u64) < user_account.load_user()?.header.comet.num_positions @ CloneError::InvalidInputPositionIndex,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -33,12 +34,12 @@ pub struct LiquidateCometCollateralIld<'info> {
         seeds = [POOLS_SEED.as_ref()],
         bump,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
         address = clone.collateral.mint
@@ -78,12 +79,14 @@ pub fn execute(
         bytemuck::bytes_of(&ctx.accounts.clone.bump),
     ][..]];
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
-    let comet = &mut ctx.accounts.user_account.comet;
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet = &mut user_account.header.comet;
+    let comet_positions = &mut user_account.comet_positions;
 
-    let comet_position = comet.positions[comet_position_index as usize];
-    let ild_share = calculate_ild_share(&comet_position, pools, collateral)?;
+    let comet_position = comet_positions[comet_position_index as usize];
+    let ild_share = calculate_ild_share(&comet_position, &pools, collateral)?;
     let pool_index = comet_position.pool_index as usize;
     let pool = &pools.pools[pool_index];
     return_error_if_false!(
@@ -96,7 +99,14 @@ pub fn execute(
         .map_err(|_| CloneError::IntTypeConversionError)?;
 
     let is_in_liquidation_mode = pool.status == Status::Liquidation;
-    let starting_health_score = calculate_health_score(comet, pools, oracles, collateral)?;
+    let starting_health_score = calculate_health_score(
+        comet,
+        comet_positions,
+        &pools,
+        &oracles,
+        collateral,
+        Clock::get()?.slot,
+    )?;
 
     return_error_if_false!(
         !starting_health_score.is_healthy() || is_in_liquidation_mode,
@@ -107,7 +117,7 @@ pub fn execute(
         // calculate reward for liquidator
         let liquidator_fee =
             to_bps_decimal!(ctx.accounts.clone.comet_collateral_ild_liquidator_fee_bps);
-        let collateral_reward: u64 = rescale_toward_zero(
+        let collateral_reward: u64 = rescale_down(
             liquidator_fee
                 .checked_mul(ild_share.collateral_ild_share)
                 .ok_or(error!(CloneError::CheckedMathError))?,
@@ -133,7 +143,7 @@ pub fn execute(
             .collateral_amount
             .checked_sub(collateral_reduction)
             .ok_or(error!(CloneError::CheckedMathError))?;
-        comet.positions[comet_position_index as usize].collateral_ild_rebate = comet.positions
+        comet_positions[comet_position_index as usize].collateral_ild_rebate = comet_positions
             [comet_position_index as usize]
             .collateral_ild_rebate
             .checked_add(
@@ -172,9 +182,9 @@ pub fn execute(
     // Withdraw liquidity position
     if comet_position.committed_collateral_liquidity > 0 {
         withdraw_liquidity(
-            pools,
-            oracles,
-            comet,
+            &mut pools,
+            &oracles,
+            comet_positions,
             collateral,
             comet_position_index,
             comet_position.committed_collateral_liquidity,
@@ -189,8 +199,8 @@ pub fn execute(
         .checked_add(1)
         .ok_or(error!(CloneError::CheckedMathError))?;
 
-    if comet.positions[comet_position_index as usize].is_empty() {
-        comet.positions.remove(comet_position_index as usize);
+    if comet_positions[comet_position_index as usize].is_empty() {
+        user_account.remove_comet_position(comet_position_index as usize);
     }
 
     Ok(())
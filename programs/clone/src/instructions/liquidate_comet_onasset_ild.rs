@@ -1,4 +1,4 @@
-use crate::decimal::rescale_toward_zero;
+use crate::decimal::rescale_down;
 use crate::error::*;
 use crate::instructions::withdraw_liquidity;
 use crate::math::*;
@@ -20,8 +20,9 @@ pub struct LiquidateCometOnassetIld<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.as_ref()],
         bump,
+        constraint = (comet_position_index as u64) < user_account.load_user()?.header.comet.num_positions @ CloneError::InvalidInputPositionIndex,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -33,15 +34,15 @@ pub struct LiquidateCometOnassetIld<'info> {
         seeds = [POOLS_SEED.as_ref()],
         bump,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
-        address = pools.pools[user_account.comet.positions[comet_position_index as usize].pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -87,12 +88,14 @@ pub fn execute(
         bytemuck::bytes_of(&ctx.accounts.clone.bump),
     ][..]];
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
-    let comet = &mut ctx.accounts.user_account.comet;
-    let comet_position = comet.positions[comet_position_index as usize];
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet = &mut user_account.header.comet;
+    let comet_positions = &mut user_account.comet_positions;
+    let comet_position = comet_positions[comet_position_index as usize];
     let authorized_amount = to_clone_decimal!(amount);
-    let ild_share = calculate_ild_share(&comet_position, pools, collateral)?;
+    let ild_share = calculate_ild_share(&comet_position, &pools, collateral)?;
     let pool_index = comet_position.pool_index as usize;
     let pool = &pools.pools[pool_index];
 
@@ -114,7 +117,14 @@ pub fn execute(
         .ok_or(error!(CloneError::CheckedMathError))?;
 
     let is_in_liquidation_mode = pool.status == Status::Liquidation;
-    let starting_health_score = calculate_health_score(comet, pools, oracles, collateral)?;
+    let starting_health_score = calculate_health_score(
+        comet,
+        comet_positions,
+        &pools,
+        &oracles,
+        collateral,
+        Clock::get()?.slot,
+    )?;
 
     return_error_if_false!(
         !starting_health_score.is_healthy() || is_in_liquidation_mode,
@@ -126,7 +136,7 @@ pub fn execute(
     let liquidator_fee = to_bps_decimal!(ctx.accounts.clone.comet_onasset_ild_liquidator_fee_bps);
 
     // calculate reward for liquidator
-    let collateral_reward = rescale_toward_zero(
+    let collateral_reward = rescale_down(
         Decimal::one()
             .checked_add(liquidator_fee)
             .ok_or(error!(CloneError::CheckedMathError))?
@@ -142,7 +152,7 @@ pub fn execute(
             .mantissa()
             .try_into()
             .map_err(|_| CloneError::IntTypeConversionError)?;
-        comet.positions[comet_position_index as usize].onasset_ild_rebate = comet.positions
+        comet_positions[comet_position_index as usize].onasset_ild_rebate = comet_positions
             [comet_position_index as usize]
             .onasset_ild_rebate
             .checked_add(ild_rebate_increase)
@@ -198,9 +208,9 @@ pub fn execute(
     // Withdraw liquidity position
     if comet_position.committed_collateral_liquidity > 0 {
         withdraw_liquidity(
-            pools,
-            oracles,
-            comet,
+            &mut pools,
+            &oracles,
+            comet_positions,
             collateral,
             comet_position_index,
             comet_position.committed_collateral_liquidity,
@@ -215,8 +225,8 @@ pub fn execute(
         .checked_add(1)
         .ok_or(error!(CloneError::CheckedMathError))?;
 
-    if comet.positions[comet_position_index as usize].is_empty() {
-        comet.positions.remove(comet_position_index as usize);
+    if comet_positions[comet_position_index as usize].is_empty() {
+        user_account.remove_comet_position(comet_position_index as usize);
     }
 
     Ok(())
@@ -0,0 +1,65 @@
+use crate::error::*;
+use crate::instructions::{migration_address, MigrationTarget};
+use crate::{return_error_if_false, CLONE_ACCOUNT_SPACE, CLONE_PROGRAM_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Size `Clone` accounts were created with before `pending_admin`.
+const CLONE_LAYOUT_V0_SPACE: usize = 8 + 472;
+
+#[derive(Accounts)]
+#[instruction(target: MigrationTarget)]
+pub struct MigrateAccountLayout<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: Read as raw bytes, it may itself be in an earlier layout.
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub clone: UncheckedAccount<'info>,
+    /// CHECK: Seeds and the layout are validated in the instruction.
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows `Clone` to fit the fields added after its creation. The signer pays
+/// for the extra space. `Pools`, `Oracles` and `User` accounts are written in
+/// their current layout by `migrate_to_zero_copy` and never need this.
+pub fn execute(ctx: Context<MigrateAccountLayout>, target: MigrationTarget) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+
+    // `admin` leads every layout of `Clone`.
+    let admin = Pubkey::try_from_slice(&ctx.accounts.clone.try_borrow_data()?[8..40])?;
+    return_error_if_false!(
+        account.key() == migration_address(target, ctx.accounts.signer.key, &admin)?,
+        CloneError::ExpectedAccountNotFound
+    );
+
+    return_error_if_false!(
+        target == MigrationTarget::Clone && account.data_len() == CLONE_LAYOUT_V0_SPACE,
+        CloneError::AccountAlreadyMigrated
+    );
+    // The new fields start zeroed.
+    account.realloc(CLONE_ACCOUNT_SPACE, true)?;
+
+    let rent_delta = Rent::get()?
+        .minimum_balance(account.data_len())
+        .saturating_sub(account.lamports());
+    if rent_delta > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.signer.to_account_info(),
+                    to: account,
+                },
+            ),
+            rent_delta,
+        )?;
+    }
+
+    Ok(())
+}
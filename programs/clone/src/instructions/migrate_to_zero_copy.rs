@@ -0,0 +1,291 @@
+use crate::error::*;
+use crate::states::*;
+use crate::{return_error_if_false, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use std::mem::size_of;
+
+/// Size every `Pools`, `Oracles` and `User` account was created with before
+/// the zero-copy layouts.
+const LEGACY_ACCOUNT_SPACE: usize = 10240;
+
+/// Account to be rewritten from the legacy Borsh layout into its zero-copy layout.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, AnchorDeserialize, AnchorSerialize)]
+pub enum MigrationTarget {
+    Pools,
+    Oracles,
+    User { authority: Pubkey },
+    // Only grown by `migrate_account_layout`, it was never stored in Borsh
+    // vectors.
+    Clone,
+}
+
+// Legacy (Borsh, Vec backed) layouts, kept only to read accounts created
+// before the zero-copy migration. Field order must match the old definitions.
+#[derive(AnchorDeserialize)]
+struct LegacyAssetInfo {
+    onasset_mint: Pubkey,
+    oracle_info_index: u8,
+    il_health_score_coefficient: u16,
+    position_health_score_coefficient: u16,
+    min_overcollateral_ratio: u16,
+    max_liquidation_overcollateral_ratio: u16,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyPool {
+    underlying_asset_token_account: Pubkey,
+    committed_collateral_liquidity: u64,
+    collateral_ild: i64,
+    onasset_ild: i64,
+    treasury_trading_fee_bps: u16,
+    liquidity_trading_fee_bps: u16,
+    asset_info: LegacyAssetInfo,
+    status: Status,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyPools {
+    pools: Vec<LegacyPool>,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyOracleInfo {
+    source: OracleSource,
+    address: Pubkey,
+    price: i64,
+    expo: u8,
+    status: Status,
+    last_update_slot: u64,
+    rescale_factor: u8,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyOracles {
+    oracles: Vec<LegacyOracleInfo>,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyBorrow {
+    pool_index: u8,
+    borrowed_onasset: u64,
+    collateral_amount: u64,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyLiquidityPosition {
+    pool_index: u8,
+    committed_collateral_liquidity: u64,
+    collateral_ild_rebate: i64,
+    onasset_ild_rebate: i64,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyComet {
+    collateral_amount: u64,
+    positions: Vec<LegacyLiquidityPosition>,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyUser {
+    borrows: Vec<LegacyBorrow>,
+    comet: LegacyComet,
+}
+
+/// Address of the account `target` refers to, provided `signer` may migrate
+/// it: the admin for the protocol accounts and the owner for user accounts.
+/// Anyone may grow `Clone`, whose admin may be a PDA unable to pay for it.
+pub fn migration_address(
+    target: MigrationTarget,
+    signer: &Pubkey,
+    admin: &Pubkey,
+) -> Result<Pubkey> {
+    let address = match target {
+        MigrationTarget::Pools => {
+            return_error_if_false!(signer == admin, CloneError::Unauthorized);
+            Pubkey::find_program_address(&[POOLS_SEED.as_ref()], &crate::ID).0
+        }
+        MigrationTarget::Oracles => {
+            return_error_if_false!(signer == admin, CloneError::Unauthorized);
+            Pubkey::find_program_address(&[ORACLES_SEED.as_ref()], &crate::ID).0
+        }
+        MigrationTarget::User { authority } => {
+            return_error_if_false!(*signer == authority, CloneError::Unauthorized);
+            Pubkey::find_program_address(&[USER_SEED.as_ref(), authority.as_ref()], &crate::ID).0
+        }
+        MigrationTarget::Clone => {
+            Pubkey::find_program_address(&[CLONE_PROGRAM_SEED.as_ref()], &crate::ID).0
+        }
+    };
+    Ok(address)
+}
+
+#[derive(Accounts)]
+#[instruction(target: MigrationTarget)]
+pub struct MigrateToZeroCopy<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    /// CHECK: Seeds and the legacy layout are validated in the instruction.
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn execute(ctx: Context<MigrateToZeroCopy>, target: MigrationTarget) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+
+    return_error_if_false!(
+        account.key()
+            == migration_address(target, ctx.accounts.signer.key, &ctx.accounts.clone.admin)?,
+        CloneError::ExpectedAccountNotFound
+    );
+    return_error_if_false!(
+        account.data_len() == LEGACY_ACCOUNT_SPACE,
+        CloneError::AccountAlreadyMigrated
+    );
+
+    // Read the legacy contents onto the heap, then clear everything after the discriminator.
+    let legacy_data = {
+        let mut data = account.try_borrow_mut_data()?;
+        let legacy_data = data[8..].to_vec();
+        data[8..].fill(0);
+        legacy_data
+    };
+
+    match target {
+        MigrationTarget::Pools => {
+            let legacy = LegacyPools::deserialize(&mut legacy_data.as_slice())?;
+            resize_migrated_account(
+                &account,
+                &ctx.accounts.signer,
+                &ctx.accounts.system_program,
+                8 + size_of::<Pools>(),
+            )?;
+            let loader = AccountLoader::<Pools>::try_from(&account)?;
+            let mut pools = loader.load_mut()?;
+            for pool in legacy.pools.into_iter() {
+                pools.append_pool(Pool {
+                    underlying_asset_token_account: pool.underlying_asset_token_account,
+                    committed_collateral_liquidity: pool.committed_collateral_liquidity,
+                    collateral_ild: pool.collateral_ild,
+                    onasset_ild: pool.onasset_ild,
+                    asset_info: AssetInfo {
+                        onasset_mint: pool.asset_info.onasset_mint,
+                        il_health_score_coefficient: pool.asset_info.il_health_score_coefficient,
+                        position_health_score_coefficient: pool
+                            .asset_info
+                            .position_health_score_coefficient,
+                        min_overcollateral_ratio: pool.asset_info.min_overcollateral_ratio,
+                        max_liquidation_overcollateral_ratio: pool
+                            .asset_info
+                            .max_liquidation_overcollateral_ratio,
+                        oracle_info_index: pool.asset_info.oracle_info_index,
+                        ..AssetInfo::default()
+                    },
+                    treasury_trading_fee_bps: pool.treasury_trading_fee_bps,
+                    liquidity_trading_fee_bps: pool.liquidity_trading_fee_bps,
+                    status: pool.status,
+                    ..Pool::default()
+                })?;
+            }
+        }
+        MigrationTarget::Oracles => {
+            let legacy = LegacyOracles::deserialize(&mut legacy_data.as_slice())?;
+            resize_migrated_account(
+                &account,
+                &ctx.accounts.signer,
+                &ctx.accounts.system_program,
+                8 + size_of::<Oracles>(),
+            )?;
+            let loader = AccountLoader::<Oracles>::try_from(&account)?;
+            let mut oracles = loader.load_mut()?;
+            for oracle in legacy.oracles.into_iter() {
+                oracles.append_oracle(OracleInfo {
+                    address: oracle.address,
+                    price: oracle.price,
+                    last_update_slot: oracle.last_update_slot,
+                    source: oracle.source,
+                    expo: oracle.expo,
+                    status: oracle.status,
+                    rescale_factor: oracle.rescale_factor,
+                    ..OracleInfo::default()
+                })?;
+            }
+        }
+        MigrationTarget::User { .. } => {
+            let legacy = LegacyUser::deserialize(&mut legacy_data.as_slice())?;
+            let borrows_capacity = legacy.borrows.len() as u64;
+            let positions_capacity = legacy.comet.positions.len() as u64;
+            resize_migrated_account(
+                &account,
+                &ctx.accounts.signer,
+                &ctx.accounts.system_program,
+                User::account_size(borrows_capacity, positions_capacity),
+            )?;
+            let loader = AccountLoader::<User>::try_from(&account)?;
+            {
+                let mut header = loader.load_mut()?;
+                header.borrows_capacity = borrows_capacity;
+                header.comet.positions_capacity = positions_capacity;
+                header.comet.collateral_amount = legacy.comet.collateral_amount;
+            }
+            let mut user_account = loader.load_user_mut()?;
+            for borrow in legacy.borrows.into_iter() {
+                user_account.append_borrow(Borrow {
+                    borrowed_onasset: borrow.borrowed_onasset,
+                    collateral_amount: borrow.collateral_amount,
+                    pool_index: borrow.pool_index,
+                    ..Borrow::default()
+                });
+            }
+            for position in legacy.comet.positions.into_iter() {
+                user_account.add_comet_position(LiquidityPosition {
+                    committed_collateral_liquidity: position.committed_collateral_liquidity,
+                    collateral_ild_rebate: position.collateral_ild_rebate,
+                    onasset_ild_rebate: position.onasset_ild_rebate,
+                    pool_index: position.pool_index,
+                    ..LiquidityPosition::default()
+                });
+            }
+        }
+        MigrationTarget::Clone => return Err(error!(CloneError::AccountAlreadyMigrated)),
+    }
+
+    Ok(())
+}
+
+/// Resizes a migrated account, the signer topping up the rent a larger
+/// account needs and receiving the rent a smaller one frees.
+pub fn resize_migrated_account<'info>(
+    account: &AccountInfo<'info>,
+    signer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+    if rent_exempt_minimum > lamports {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: signer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent_exempt_minimum - lamports,
+        )?;
+    } else if new_len < account.data_len() {
+        let excess_lamports = lamports - rent_exempt_minimum;
+        **account.try_borrow_mut_lamports()? -= excess_lamports;
+        **signer.try_borrow_mut_lamports()? += excess_lamports;
+    }
+    account.realloc(new_len, true)?;
+
+    Ok(())
+}
@@ -1,11 +1,23 @@
+pub mod accept_admin;
 pub mod add_collateral_to_borrow;
 pub mod add_collateral_to_comet;
 pub mod add_liquidity_to_comet;
 pub mod add_pool;
+pub mod aggregator_swap;
+pub mod approve_admin_transaction;
 pub mod borrow_more;
+pub mod claim_pool_rewards;
 pub mod close_user_account;
 pub mod collect_lp_rewards;
+pub mod compact_user_account;
+pub mod compound_lp_rewards;
 pub mod create_token_metadata;
+pub mod execute_admin_transaction;
+pub mod flash_loan_collateral;
+pub mod flash_mint_onasset;
+pub mod fund_pool_rewards;
+pub mod get_user_health;
+pub mod initialize_admin_multisig;
 pub mod initialize_borrow_position;
 pub mod initialize_clone;
 pub mod initialize_oracles;
@@ -14,12 +26,22 @@ pub mod initialize_user;
 pub mod liquidate_borrow_position;
 pub mod liquidate_comet_collateral_ild;
 pub mod liquidate_comet_onasset_ild;
+pub mod migrate_account_layout;
+pub mod migrate_to_zero_copy;
+pub mod net_lp_rewards;
 pub mod pay_borrow_debt;
 pub mod pay_impermanent_loss_debt;
+pub mod propose_admin_transaction;
+pub mod quote_swap;
 pub mod remove_comet_position;
 pub mod remove_pool;
+pub mod repay_flash_loan_collateral;
+pub mod repay_flash_mint_onasset;
+pub mod set_auto_compound;
 pub mod swap;
+pub mod swap_route;
 pub mod unwrap_onasset;
+pub mod update_admin_multisig;
 pub mod update_clone_parameters;
 pub mod update_oracles;
 pub mod update_pool_parameters;
@@ -29,14 +51,26 @@ pub mod withdraw_collateral_from_comet;
 pub mod withdraw_liquidity_from_comet;
 pub mod wrap_asset;
 
+pub use accept_admin::*;
 pub use add_collateral_to_borrow::*;
 pub use add_collateral_to_comet::*;
 pub use add_liquidity_to_comet::*;
 pub use add_pool::*;
+pub use aggregator_swap::*;
+pub use approve_admin_transaction::*;
 pub use borrow_more::*;
+pub use claim_pool_rewards::*;
 pub use close_user_account::*;
 pub use collect_lp_rewards::*;
+pub use compact_user_account::*;
+pub use compound_lp_rewards::*;
 pub use create_token_metadata::*;
+pub use execute_admin_transaction::*;
+pub use flash_loan_collateral::*;
+pub use flash_mint_onasset::*;
+pub use fund_pool_rewards::*;
+pub use get_user_health::*;
+pub use initialize_admin_multisig::*;
 pub use initialize_borrow_position::*;
 pub use initialize_clone::*;
 pub use initialize_oracles::*;
@@ -45,12 +79,22 @@ pub use initialize_user::*;
 pub use liquidate_borrow_position::*;
 pub use liquidate_comet_collateral_ild::*;
 pub use liquidate_comet_onasset_ild::*;
+pub use migrate_account_layout::*;
+pub use migrate_to_zero_copy::*;
+pub use net_lp_rewards::*;
 pub use pay_borrow_debt::*;
 pub use pay_impermanent_loss_debt::*;
+pub use propose_admin_transaction::*;
+pub use quote_swap::*;
 pub use remove_comet_position::*;
 pub use remove_pool::*;
+pub use repay_flash_loan_collateral::*;
+pub use repay_flash_mint_onasset::*;
+pub use set_auto_compound::*;
 pub use swap::*;
+pub use swap_route::*;
 pub use unwrap_onasset::*;
+pub use update_admin_multisig::*;
 pub use update_clone_parameters::*;
 pub use update_oracles::*;
 pub use update_pool_parameters::*;
@@ -0,0 +1,100 @@
+use crate::error::*;
+use crate::events::*;
+use crate::math::*;
+use crate::states::*;
+use crate::{return_error_if_false, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
+use anchor_lang::prelude::*;
+use rust_decimal::prelude::*;
+use std::convert::TryInto;
+
+#[derive(Accounts)]
+#[instruction(comet_position_index: u8)]
+pub struct NetLpRewards<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+        constraint = (comet_position_index as u64) < user_account.load_user()?.header.comet.num_positions @ CloneError::InvalidInputPositionIndex,
+    )]
+    pub user_account: AccountLoader<'info, User>,
+    #[account(
+        mut,
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        seeds = [ORACLES_SEED.as_ref()],
+        bump,
+    )]
+    pub oracles: AccountLoader<'info, Oracles>,
+}
+
+/// Settles a comet position's onAsset ILD with its collateral rewards, or
+/// its collateral ILD with its onAsset rewards, at the oracle price. No
+/// tokens move: the rewards are given up instead of collected and the debt
+/// is marked paid.
+pub fn execute(ctx: Context<NetLpRewards>, comet_position_index: u8) -> Result<()> {
+    let collateral = &ctx.accounts.clone.collateral;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet_position = &mut user_account.comet_positions[comet_position_index as usize];
+    let pool_index = comet_position.pool_index;
+
+    let pool = &pools.pools[comet_position.pool_index as usize];
+    let pool_oracle = &oracles.oracles[pool.asset_info.oracle_info_index as usize];
+    let collateral_oracle = &oracles.oracles[collateral.oracle_info_index as usize];
+    let slot = Clock::get()?.slot;
+    check_feed_update(pool_oracle, slot)?;
+    check_feed_update(collateral_oracle, slot)?;
+
+    let ild_share = calculate_ild_share(comet_position, &pools, collateral)?;
+    let netting = calculate_ild_netting(&ild_share, pool_oracle, collateral_oracle, collateral)?;
+    return_error_if_false!(
+        !netting.collateral_rebate_delta.is_zero() && !netting.onasset_rebate_delta.is_zero(),
+        CloneError::InvalidTokenAmount
+    );
+
+    let collateral_rebate_delta: i64 = netting
+        .collateral_rebate_delta
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
+    let onasset_rebate_delta: i64 = netting
+        .onasset_rebate_delta
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
+    comet_position.collateral_ild_rebate = comet_position
+        .collateral_ild_rebate
+        .checked_add(collateral_rebate_delta)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+    comet_position.onasset_ild_rebate = comet_position
+        .onasset_ild_rebate
+        .checked_add(onasset_rebate_delta)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    emit!(LpRewardsNetted {
+        event_id: ctx.accounts.clone.event_counter,
+        user_address: ctx.accounts.user.key(),
+        pool_index,
+        collateral_rebate_delta,
+        onasset_rebate_delta,
+    });
+    ctx.accounts.clone.event_counter = ctx
+        .accounts
+        .clone
+        .event_counter
+        .checked_add(1)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    Ok(())
+}
@@ -14,9 +14,9 @@ pub struct PayBorrowDebt<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.as_ref()],
         bump,
-        constraint = (borrow_index as usize) < user_account.borrows.len() @ CloneError::InvalidInputPositionIndex,
+        constraint = (borrow_index as u64) < user_account.load_user()?.header.num_borrows @ CloneError::InvalidInputPositionIndex,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -26,9 +26,9 @@ pub struct PayBorrowDebt<'info> {
     #[account(
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = pools.pools[user_account.borrows[borrow_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
+        constraint = pools.load()?.pools[user_account.load_user()?.borrows[borrow_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         constraint = payer_onasset_token_account.amount >= amount @ CloneError::InvalidTokenAccountBalance,
@@ -38,7 +38,7 @@ pub struct PayBorrowDebt<'info> {
     pub payer_onasset_token_account: Account<'info, TokenAccount>,
     #[account(
         mut,
-        address = pools.pools[user_account.borrows[borrow_index as usize].pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[user_account.load_user()?.borrows[borrow_index as usize].pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     pub token_program: Program<'info, Token>,
@@ -51,7 +51,8 @@ pub fn execute(
     amount: u64,
 ) -> Result<()> {
     return_error_if_false!(amount > 0, CloneError::InvalidTokenAmount);
-    let borrows = &mut ctx.accounts.user_account.borrows;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let borrows = &mut user_account.borrows;
     let borrow_position = borrows[borrow_index as usize];
     let amount_value = amount.min(borrow_position.borrowed_onasset);
 
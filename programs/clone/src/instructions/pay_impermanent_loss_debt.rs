@@ -1,8 +1,9 @@
 use crate::error::*;
 use crate::events::*;
+use crate::instructions::{check_comet_bounds, CometBounds};
 use crate::math::*;
 use crate::states::*;
-use crate::{return_error_if_false, CLONE_PROGRAM_SEED, POOLS_SEED, USER_SEED};
+use crate::{return_error_if_false, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, *};
 use rust_decimal::prelude::*;
@@ -23,9 +24,9 @@ pub struct PayImpermanentLossDebt<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.as_ref()],
         bump,
-        constraint = user_account.comet.positions.len() > comet_position_index.into() @ CloneError::InvalidInputPositionIndex
+        constraint = user_account.load_user()?.header.comet.num_positions > comet_position_index.into() @ CloneError::InvalidInputPositionIndex
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -35,9 +36,15 @@ pub struct PayImpermanentLossDebt<'info> {
     #[account(
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = pools.pools[user_account.comet.positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
+        constraint = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
+    /// Only needed for pool price and health score bounds.
+    #[account(
+        seeds = [ORACLES_SEED.as_ref()],
+        bump,
+    )]
+    pub oracles: Option<AccountLoader<'info, Oracles>>,
     #[account(
         address = clone.collateral.mint
     )]
@@ -49,7 +56,7 @@ pub struct PayImpermanentLossDebt<'info> {
     pub collateral_vault: Box<Account<'info, TokenAccount>>,
     #[account(
         mut,
-        address = pools.pools[user_account.comet.positions[comet_position_index as usize].pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -73,12 +80,15 @@ pub fn execute(
     comet_position_index: u8,
     amount: u64,
     payment_type: PaymentType,
+    bounds: CometBounds,
 ) -> Result<()> {
     return_error_if_false!(amount > 0, CloneError::InvalidTokenAmount);
-    let pools = &ctx.accounts.pools;
-    let comet = &mut ctx.accounts.user_account.comet;
+    let pools = ctx.accounts.pools.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet = &mut user_account.header.comet;
+    let comet_positions = &mut user_account.comet_positions;
 
-    let comet_position = comet.positions[comet_position_index as usize];
+    let comet_position = comet_positions[comet_position_index as usize];
     let ild_share = calculate_ild_share(&comet_position, &pools, &ctx.accounts.clone.collateral)?;
 
     match payment_type {
@@ -95,7 +105,7 @@ pub fn execute(
                 .map_err(|_| CloneError::IntTypeConversionError)?;
             let burn_amount = ild_share.min(amount);
 
-            comet.positions[comet_position_index as usize].onasset_ild_rebate = comet.positions
+            comet_positions[comet_position_index as usize].onasset_ild_rebate = comet_positions
                 [comet_position_index as usize]
                 .onasset_ild_rebate
                 .checked_add(
@@ -132,7 +142,7 @@ pub fn execute(
                 .map_err(|_| CloneError::IntTypeConversionError)?;
             let transfer_amount = ild_share.min(amount);
 
-            comet.positions[comet_position_index as usize].collateral_ild_rebate = comet.positions
+            comet_positions[comet_position_index as usize].collateral_ild_rebate = comet_positions
                 [comet_position_index as usize]
                 .collateral_ild_rebate
                 .checked_add(
@@ -170,7 +180,7 @@ pub fn execute(
                 .map_err(|_| CloneError::IntTypeConversionError)?;
             let from_wallet_amount = ild_share.min(amount).min(comet.collateral_amount);
 
-            comet.positions[comet_position_index as usize].collateral_ild_rebate = comet.positions
+            comet_positions[comet_position_index as usize].collateral_ild_rebate = comet_positions
                 [comet_position_index as usize]
                 .collateral_ild_rebate
                 .checked_add(
@@ -207,5 +217,22 @@ pub fn execute(
         }
     }
 
+    let oracles = ctx
+        .accounts
+        .oracles
+        .as_ref()
+        .map(|oracles| oracles.load())
+        .transpose()?;
+    check_comet_bounds(
+        &bounds,
+        &user_account.header.comet,
+        &user_account.comet_positions,
+        comet_position_index as usize,
+        &pools,
+        oracles.as_deref(),
+        &ctx.accounts.clone.collateral,
+        Clock::get()?.slot,
+    )?;
+
     Ok(())
 }
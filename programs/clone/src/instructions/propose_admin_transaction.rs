@@ -0,0 +1,60 @@
+use crate::error::*;
+use crate::states::*;
+use crate::ADMIN_MULTISIG_SEED;
+use anchor_lang::prelude::*;
+
+pub const ADMIN_TRANSACTION_SEED: &str = "admin-transaction";
+
+#[derive(Accounts)]
+#[instruction(
+    instruction_program_id: Pubkey,
+    instruction_accounts: Vec<TransactionAccount>,
+    instruction_data: Vec<u8>,
+)]
+pub struct ProposeAdminTransaction<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ADMIN_MULTISIG_SEED.as_ref()],
+        bump = admin_multisig.bump,
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+    #[account(
+        init,
+        space = AdminTransaction::space(instruction_accounts.len(), instruction_data.len()),
+        seeds = [ADMIN_TRANSACTION_SEED.as_ref(), &admin_multisig.num_transactions.to_le_bytes()],
+        bump,
+        payer = proposer
+    )]
+    pub admin_transaction: Box<Account<'info, AdminTransaction>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Stores an instruction for the multisig to sign, approved by the proposer.
+pub fn execute(
+    ctx: Context<ProposeAdminTransaction>,
+    instruction_program_id: Pubkey,
+    instruction_accounts: Vec<TransactionAccount>,
+    instruction_data: Vec<u8>,
+) -> Result<()> {
+    let admin_multisig = &mut ctx.accounts.admin_multisig;
+    let signer_index = admin_multisig
+        .signer_index(ctx.accounts.proposer.key)
+        .ok_or(error!(CloneError::Unauthorized))?;
+
+    let admin_transaction = &mut ctx.accounts.admin_transaction;
+    admin_transaction.index = admin_multisig.num_transactions;
+    admin_transaction.signer_set_version = admin_multisig.signer_set_version;
+    admin_transaction.program_id = instruction_program_id;
+    admin_transaction.accounts = instruction_accounts;
+    admin_transaction.data = instruction_data;
+    admin_transaction.approve(signer_index);
+
+    admin_multisig.num_transactions = admin_multisig
+        .num_transactions
+        .checked_add(1)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use crate::decimal::{rescale_toward_zero, CLONE_TOKEN_SCALE};
+use crate::error::*;
+use crate::instructions::{can_use_staking_account, staking_reward_share, staking_tier_fees};
+use crate::math::*;
+use crate::states::*;
+use crate::{
+    return_error_if_false, to_clone_decimal, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use clone_staking::{
+    program::CloneStaking as CloneStakingProgram,
+    states::{CloneStaking, User as UserStaking},
+    CLONE_STAKING_SEED,
+};
+use rust_decimal::prelude::*;
+use std::convert::TryInto;
+
+/// Outcome of a swap, returned by `quote_swap` as return data. Amounts are in
+/// token units, `pool_price` is the pool's collateral per onAsset after the
+/// trade with `pool_scale` decimals.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SwapQuote {
+    pub result: u64,
+    pub liquidity_fees_paid: u64,
+    pub treasury_fees_paid: u64,
+    pub pool_price: u64,
+    pub pool_scale: u32,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_index: u8,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+)]
+pub struct QuoteSwap<'info> {
+    /// CHECK: Only used to find the user's staking account.
+    pub user: UncheckedAccount<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::InvalidInputPositionIndex,
+        constraint = pools.load()?.pools[pool_index as usize].status == Status::Active @ CloneError::StatusPreventsAction,
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        seeds = [ORACLES_SEED.as_ref()],
+        bump,
+    )]
+    pub oracles: AccountLoader<'info, Oracles>,
+    #[account(
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        seeds::program = clone_staking_program.clone().ok_or(error!(CloneError::ExpectedAccountNotFound))?.key(),
+    )]
+    pub clone_staking: Option<Account<'info, CloneStaking>>,
+    #[account(
+        constraint = can_use_staking_account(&user_staking_account, user.key) @ CloneError::Unauthorized,
+    )]
+    pub user_staking_account: Option<Account<'info, UserStaking>>,
+    pub clone_staking_program: Option<Program<'info, CloneStakingProgram>>,
+    #[account(
+        address = clone_staking.as_ref().ok_or(error!(CloneError::ExpectedAccountNotFound))?.reward_vault,
+        token::mint = clone.collateral.mint,
+    )]
+    pub staking_reward_vault: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+pub fn execute(
+    ctx: Context<QuoteSwap>,
+    pool_index: u8,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+) -> Result<SwapQuote> {
+    return_error_if_false!(quantity > 0, CloneError::InvalidTokenAmount);
+
+    let collateral = &ctx.accounts.clone.collateral;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let pool = &pools.pools[pool_index as usize];
+    let pool_oracle = &oracles.oracles[pool.asset_info.oracle_info_index as usize];
+    let collateral_oracle = &oracles.oracles[collateral.oracle_info_index as usize];
+
+    let (override_liquidity_trading_fee, override_treasury_trading_fee) = staking_tier_fees(
+        &ctx.accounts.clone_staking,
+        &ctx.accounts.user_staking_account,
+    )?;
+
+    check_feed_update(&pool_oracle, Clock::get()?.slot)?;
+    check_feed_update(&collateral_oracle, Clock::get()?.slot)?;
+
+    return_error_if_false!(
+        pool.committed_collateral_liquidity > 0,
+        CloneError::PoolEmpty
+    );
+    let user_specified_quantity = if quantity_is_collateral {
+        collateral.to_collateral_decimal(quantity)?
+    } else {
+        to_clone_decimal!(quantity)
+    };
+
+    let (liquidity_trading_fee, treasury_trading_fee) = pool.calculate_trading_fees(
+        pool_oracle,
+        collateral_oracle,
+        collateral,
+        override_liquidity_trading_fee,
+        override_treasury_trading_fee,
+    )?;
+    let swap_summary = pool.calculate_swap(
+        pool_oracle.get_price()?,
+        collateral_oracle.get_price()?,
+        user_specified_quantity,
+        quantity_is_input,
+        quantity_is_collateral,
+        collateral,
+        Some(liquidity_trading_fee),
+        Some(treasury_trading_fee),
+    )?;
+
+    return_error_if_false!(
+        swap_summary.result > Decimal::ZERO
+            && swap_summary.liquidity_fees_paid > Decimal::ZERO
+            && swap_summary.treasury_fees_paid > Decimal::ZERO,
+        CloneError::InvalidTokenAmount
+    );
+
+    let to_amount = |value: Decimal| -> Result<u64> {
+        value
+            .mantissa()
+            .try_into()
+            .map_err(|_| error!(CloneError::IntTypeConversionError))
+    };
+    let result = to_amount(swap_summary.result)?;
+    let liquidity_fees_paid = to_amount(swap_summary.liquidity_fees_paid)?;
+    let treasury_fees_paid = to_amount(swap_summary.treasury_fees_paid)?;
+
+    // Settle the swap as `swap` would to find the post-trade price.
+    let (input, output) = if quantity_is_input {
+        (quantity, result)
+    } else {
+        (result, quantity)
+    };
+    let mut post_trade_pool = *pool;
+    post_trade_pool.settle_swap(
+        pool_oracle.get_price()?,
+        collateral_oracle.get_price()?,
+        collateral,
+        input,
+        output,
+        quantity_is_input == quantity_is_collateral,
+        treasury_fees_paid,
+        staking_reward_share(
+            &ctx.accounts.clone_staking,
+            &ctx.accounts.staking_reward_vault,
+        )
+        .map_or(0, |(_, treasury_fee_share_bps)| treasury_fee_share_bps),
+    )?;
+    let pool_price = rescale_toward_zero(
+        post_trade_pool.calculate_pool_price(
+            pool_oracle.get_price()?,
+            collateral_oracle.get_price()?,
+            collateral,
+        )?,
+        CLONE_TOKEN_SCALE,
+    );
+
+    Ok(SwapQuote {
+        result,
+        liquidity_fees_paid,
+        treasury_fees_paid,
+        pool_price: to_amount(pool_price)?,
+        pool_scale: pool_price.scale(),
+    })
+}
@@ -12,28 +12,24 @@ pub struct RemoveCometPosition<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
+        constraint = (comet_position_index as u64) < user_account.load_user()?.header.comet.num_positions @ CloneError::InvalidInputPositionIndex,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [POOLS_SEED.as_ref()],
         bump,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
 }
 
 pub fn execute(ctx: Context<RemoveCometPosition>, comet_position_index: u8) -> Result<()> {
-    let comet = &mut ctx.accounts.user_account.comet;
-    let comet_position = comet.positions[comet_position_index as usize];
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet_position = user_account.comet_positions[comet_position_index as usize];
 
-    return_error_if_false!(
-        comet_position.committed_collateral_liquidity == 0
-            && comet_position.onasset_ild_rebate == 0
-            && comet_position.collateral_ild_rebate == 0,
-        CloneError::CometNotEmpty
-    );
+    return_error_if_false!(comet_position.is_empty(), CloneError::CometNotEmpty);
 
-    comet.positions.remove(comet_position_index.into());
+    user_account.remove_comet_position(comet_position_index.into());
 
     Ok(())
 }
@@ -20,16 +20,16 @@ pub struct RemovePool<'info> {
         mut,
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = (pool_index as usize) < pools.pools.len() @ CloneError::PoolNotFound,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         address = underlying_asset_token_account.mint
     )]
     pub underlying_asset_mint: Box<Account<'info, Mint>>,
     #[account(
         mut,
-        address = pools.pools[pool_index as usize].underlying_asset_token_account,
+        address = pools.load()?.pools[pool_index as usize].underlying_asset_token_account,
     )]
     pub underlying_asset_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
@@ -47,11 +47,8 @@ pub fn execute(ctx: Context<RemovePool>, pool_index: u8) -> Result<()> {
         bytemuck::bytes_of(&ctx.accounts.clone.bump),
     ][..]];
 
-    let pools = &mut ctx.accounts.pools;
-    let pool = &pools.pools[pool_index as usize];
-
     return_error_if_false!(
-        pool.status == Status::Deprecation,
+        ctx.accounts.pools.load()?.pools[pool_index as usize].status == Status::Deprecation,
         CloneError::StatusPreventsAction
     );
 
@@ -82,7 +79,10 @@ pub fn execute(ctx: Context<RemovePool>, pool_index: u8) -> Result<()> {
         )?;
     }
 
-    pools.pools.remove(pool_index as usize);
+    ctx.accounts
+        .pools
+        .load_mut()?
+        .remove_pool(pool_index as usize);
 
     Ok(())
 }
@@ -0,0 +1,106 @@
+use crate::error::*;
+use crate::instructions::{
+    calculate_flash_loan_fee, current_flash_loan_instruction_index, load_flash_loan_instruction,
+};
+use crate::states::*;
+use crate::{instruction, return_error_if_false, CLONE_PROGRAM_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, *};
+
+#[derive(Accounts)]
+#[instruction(amount: u64, borrow_instruction_index: u8)]
+pub struct RepayFlashLoanCollateral<'info> {
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        address = clone.collateral.mint
+    )]
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        address = clone.collateral.vault
+    )]
+    pub collateral_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = collateral_mint,
+        token::authority = payer
+    )]
+    pub payer_collateral_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = clone.treasury_address
+    )]
+    pub treasury_collateral_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Address checked, read through instruction introspection.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Returns the collateral lent by the `flash_loan_collateral` instruction at
+/// `borrow_instruction_index` to the vault and pays the fee to the treasury.
+pub fn execute(
+    ctx: Context<RepayFlashLoanCollateral>,
+    amount: u64,
+    borrow_instruction_index: u8,
+) -> Result<()> {
+    let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+    let current_index = current_flash_loan_instruction_index::<
+        instruction::RepayFlashLoanCollateral,
+    >(&instructions_sysvar)?;
+    return_error_if_false!(
+        borrow_instruction_index < current_index,
+        CloneError::InvalidFlashLoan
+    );
+    let borrow = load_flash_loan_instruction::<instruction::FlashLoanCollateral>(
+        &instructions_sysvar,
+        borrow_instruction_index as usize,
+    )?
+    .ok_or(error!(CloneError::InvalidFlashLoan))?;
+    return_error_if_false!(borrow.amount == amount, CloneError::InvalidFlashLoan);
+
+    let fee = calculate_flash_loan_fee(amount, ctx.accounts.clone.flash_loan_fee_bps)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx
+                    .accounts
+                    .payer_collateral_token_account
+                    .to_account_info(),
+                to: ctx.accounts.collateral_vault.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    if fee > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx
+                        .accounts
+                        .payer_collateral_token_account
+                        .to_account_info(),
+                    to: ctx
+                        .accounts
+                        .treasury_collateral_token_account
+                        .to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::error::*;
+use crate::instructions::{
+    calculate_flash_loan_fee, current_flash_loan_instruction_index, load_flash_loan_instruction,
+};
+use crate::states::*;
+use crate::{instruction, return_error_if_false, CLONE_PROGRAM_SEED, POOLS_SEED};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, *};
+
+#[derive(Accounts)]
+#[instruction(pool_index: u8, amount: u64, borrow_instruction_index: u8)]
+pub struct RepayFlashMintOnasset<'info> {
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        mut,
+        address = pools.load()?.pools[pool_index as usize].asset_info.onasset_mint,
+    )]
+    pub onasset_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = onasset_mint,
+        token::authority = payer
+    )]
+    pub payer_onasset_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = onasset_mint,
+        associated_token::authority = clone.treasury_address
+    )]
+    pub treasury_onasset_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Address checked, read through instruction introspection.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Burns the onAssets minted by the `flash_mint_onasset` instruction at
+/// `borrow_instruction_index` and pays the fee to the treasury in onAssets.
+pub fn execute(
+    ctx: Context<RepayFlashMintOnasset>,
+    pool_index: u8,
+    amount: u64,
+    borrow_instruction_index: u8,
+) -> Result<()> {
+    let instructions_sysvar = ctx.accounts.instructions_sysvar.to_account_info();
+    let current_index = current_flash_loan_instruction_index::<instruction::RepayFlashMintOnasset>(
+        &instructions_sysvar,
+    )?;
+    return_error_if_false!(
+        borrow_instruction_index < current_index,
+        CloneError::InvalidFlashLoan
+    );
+    let borrow = load_flash_loan_instruction::<instruction::FlashMintOnasset>(
+        &instructions_sysvar,
+        borrow_instruction_index as usize,
+    )?
+    .ok_or(error!(CloneError::InvalidFlashLoan))?;
+    return_error_if_false!(
+        borrow.pool_index == pool_index && borrow.amount == amount,
+        CloneError::InvalidFlashLoan
+    );
+
+    let fee = calculate_flash_loan_fee(amount, ctx.accounts.clone.flash_loan_fee_bps)?;
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.onasset_mint.to_account_info(),
+                from: ctx.accounts.payer_onasset_token_account.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    if fee > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer_onasset_token_account.to_account_info(),
+                    to: ctx
+                        .accounts
+                        .treasury_onasset_token_account
+                        .to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use crate::error::*;
+use crate::states::*;
+use crate::USER_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(comet_position_index: u8)]
+pub struct SetAutoCompound<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_SEED.as_ref(), user.key.as_ref()],
+        bump,
+        constraint = (comet_position_index as u64) < user_account.load_user()?.header.comet.num_positions @ CloneError::InvalidInputPositionIndex,
+    )]
+    pub user_account: AccountLoader<'info, User>,
+}
+
+/// Lets keepers compound the position's LP rewards on the user's behalf.
+pub fn execute(
+    ctx: Context<SetAutoCompound>,
+    comet_position_index: u8,
+    enabled: bool,
+) -> Result<()> {
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    user_account.comet_positions[comet_position_index as usize].auto_compound = enabled.into();
+
+    Ok(())
+}
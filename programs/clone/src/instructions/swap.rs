@@ -37,16 +37,16 @@ pub struct Swap<'info> {
         mut,
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = (pool_index as usize) < pools.pools.len() @ CloneError::InvalidInputPositionIndex,
-        constraint = pools.pools[pool_index as usize].status == Status::Active @ CloneError::StatusPreventsAction,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::InvalidInputPositionIndex,
+        constraint = pools.load()?.pools[pool_index as usize].status == Status::Active @ CloneError::StatusPreventsAction,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
         associated_token::mint = collateral_mint,
@@ -61,7 +61,7 @@ pub struct Swap<'info> {
     pub user_onasset_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
         mut,
-        address = pools.pools[pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -94,12 +94,154 @@ pub struct Swap<'info> {
     )]
     pub clone_staking: Option<Account<'info, CloneStaking>>,
     #[account(
-        seeds = [USER_STAKING_SEED.as_ref(), user.key.as_ref()],
-        bump,
-        seeds::program = clone_staking_program.clone().ok_or(error!(CloneError::ExpectedAccountNotFound))?.key(),
+        constraint = can_use_staking_account(&user_staking_account, user.key) @ CloneError::Unauthorized,
     )]
     pub user_staking_account: Option<Account<'info, UserStaking>>,
     pub clone_staking_program: Option<Program<'info, CloneStakingProgram>>,
+    #[account(
+        mut,
+        address = clone_staking.as_ref().ok_or(error!(CloneError::ExpectedAccountNotFound))?.reward_vault,
+        token::mint = collateral_mint,
+    )]
+    pub staking_reward_vault: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+/// Whether `user` may trade with the fee tier of `user_staking_account`:
+/// their own staking account, or one that registered them as a delegate.
+pub fn can_use_staking_account(user_staking_account: &Account<UserStaking>, user: &Pubkey) -> bool {
+    user_staking_account.is_delegate(user)
+        || user_staking_account.key()
+            == Pubkey::find_program_address(
+                &[USER_STAKING_SEED.as_ref(), user.as_ref()],
+                &clone_staking::ID,
+            )
+            .0
+}
+
+/// Trading fees of the staking tier the user's effective stake qualifies for,
+/// overriding the pool's fees. `None` when no staking accounts are passed or
+/// no tier applies.
+pub fn staking_tier_fees(
+    clone_staking: &Option<Account<CloneStaking>>,
+    user_staking_account: &Option<Account<UserStaking>>,
+) -> Result<(Option<Decimal>, Option<Decimal>)> {
+    if let (Some(clone_staking), Some(user_staking_account)) = (clone_staking, user_staking_account)
+    {
+        let effective_stake =
+            clone_staking.effective_stake(user_staking_account, Clock::get()?.slot);
+        if let Some((lp_fees, treasury_fees)) = clone_staking.get_tier_fees(effective_stake) {
+            return Ok((
+                Some(to_bps_decimal!(lp_fees)),
+                Some(to_bps_decimal!(treasury_fees)),
+            ));
+        }
+    }
+    Ok((None, None))
+}
+
+/// Staking reward vault and the share of treasury fees it takes. `None`
+/// unless the vault is passed along with the staking account and the share
+/// is set, in which case the treasury keeps all fees.
+pub fn staking_reward_share<'info>(
+    clone_staking: &Option<Account<'info, CloneStaking>>,
+    staking_reward_vault: &Option<Box<Account<'info, TokenAccount>>>,
+) -> Option<(AccountInfo<'info>, u16)> {
+    match (clone_staking, staking_reward_vault) {
+        (Some(clone_staking), Some(staking_reward_vault))
+            if clone_staking.treasury_fee_share_bps > 0 =>
+        {
+            Some((
+                staking_reward_vault.to_account_info(),
+                clone_staking.treasury_fee_share_bps,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Pays a swap's treasury fees as `settlement` splits them. The treasury's
+/// part is paid in the swap's output token, minted for onAssets and taken
+/// from the vault for collateral, and stakers' part in collateral from the
+/// vault.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_treasury_fees<'info>(
+    token_program: AccountInfo<'info>,
+    onasset_mint: AccountInfo<'info>,
+    collateral_vault: AccountInfo<'info>,
+    treasury_token_account: AccountInfo<'info>,
+    staking_reward_vault: Option<AccountInfo<'info>>,
+    clone_info: AccountInfo<'info>,
+    seeds: &[&[&[u8]]],
+    settlement: &SwapSettlement,
+    input_is_collateral: bool,
+) -> Result<()> {
+    if input_is_collateral {
+        token::mint_to(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                MintTo {
+                    mint: onasset_mint,
+                    to: treasury_token_account,
+                    authority: clone_info.clone(),
+                },
+                seeds,
+            ),
+            settlement.treasury_fees,
+        )?;
+    } else {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                Transfer {
+                    from: collateral_vault.clone(),
+                    to: treasury_token_account,
+                    authority: clone_info.clone(),
+                },
+                seeds,
+            ),
+            settlement.treasury_fees,
+        )?;
+    }
+    if settlement.staking_fees > 0 {
+        let staking_reward_vault =
+            staking_reward_vault.ok_or(error!(CloneError::ExpectedAccountNotFound))?;
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program,
+                Transfer {
+                    from: collateral_vault,
+                    to: staking_reward_vault,
+                    authority: clone_info,
+                },
+                seeds,
+            ),
+            settlement.staking_fees,
+        )?;
+    }
+    Ok(())
+}
+
+/// Amounts moved by a swap, returned by `aggregator_swap` as return data.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct SwapResult {
+    pub input: u64,
+    pub output: u64,
+    pub liquidity_fees_paid: u64,
+    pub treasury_fees_paid: u64,
+}
+
+/// Token accounts a swap moves funds between. Treasury accounts only need to
+/// be present for the side the fees are paid in.
+pub struct SwapTokenAccounts<'info> {
+    pub user: AccountInfo<'info>,
+    pub user_collateral_token_account: AccountInfo<'info>,
+    pub user_onasset_token_account: AccountInfo<'info>,
+    pub onasset_mint: AccountInfo<'info>,
+    pub collateral_vault: AccountInfo<'info>,
+    pub treasury_onasset_token_account: Option<AccountInfo<'info>>,
+    pub treasury_collateral_token_account: Option<AccountInfo<'info>>,
+    pub staking_reward_share: Option<(AccountInfo<'info>, u16)>,
+    pub token_program: AccountInfo<'info>,
 }
 
 pub fn execute(
@@ -110,43 +252,72 @@ pub fn execute(
     quantity_is_collateral: bool,
     result_threshold: u64,
 ) -> Result<()> {
+    let fee_overrides = staking_tier_fees(
+        &ctx.accounts.clone_staking,
+        &ctx.accounts.user_staking_account,
+    )?;
+    let token_accounts = SwapTokenAccounts {
+        user: ctx.accounts.user.to_account_info(),
+        user_collateral_token_account: ctx.accounts.user_collateral_token_account.to_account_info(),
+        user_onasset_token_account: ctx.accounts.user_onasset_token_account.to_account_info(),
+        onasset_mint: ctx.accounts.onasset_mint.to_account_info(),
+        collateral_vault: ctx.accounts.collateral_vault.to_account_info(),
+        treasury_onasset_token_account: Some(
+            ctx.accounts
+                .treasury_onasset_token_account
+                .to_account_info(),
+        ),
+        treasury_collateral_token_account: Some(
+            ctx.accounts
+                .treasury_collateral_token_account
+                .to_account_info(),
+        ),
+        staking_reward_share: staking_reward_share(
+            &ctx.accounts.clone_staking,
+            &ctx.accounts.staking_reward_vault,
+        ),
+        token_program: ctx.accounts.token_program.to_account_info(),
+    };
+    process_swap(
+        &mut ctx.accounts.clone,
+        &ctx.accounts.pools,
+        &ctx.accounts.oracles,
+        token_accounts,
+        pool_index,
+        quantity,
+        quantity_is_input,
+        quantity_is_collateral,
+        result_threshold,
+        fee_overrides,
+    )?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_swap<'info>(
+    clone: &mut Account<'info, Clone>,
+    pools: &AccountLoader<'info, Pools>,
+    oracles: &AccountLoader<'info, Oracles>,
+    token_accounts: SwapTokenAccounts<'info>,
+    pool_index: u8,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+    result_threshold: u64,
+    fee_overrides: (Option<Decimal>, Option<Decimal>),
+) -> Result<SwapResult> {
     return_error_if_false!(quantity > 0, CloneError::InvalidTokenAmount);
 
-    let seeds = &[&[
-        CLONE_PROGRAM_SEED.as_ref(),
-        bytemuck::bytes_of(&ctx.accounts.clone.bump),
-    ][..]];
-    let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
+    let clone_info = clone.to_account_info();
+    let seeds = &[&[CLONE_PROGRAM_SEED.as_ref(), bytemuck::bytes_of(&clone.bump)][..]];
+    let collateral = &clone.collateral;
+    let mut pools = pools.load_mut()?;
+    let oracles = oracles.load()?;
     let pool = &pools.pools[pool_index as usize];
     let pool_oracle = &oracles.oracles[pool.asset_info.oracle_info_index as usize];
     let collateral_oracle = &oracles.oracles[collateral.oracle_info_index as usize];
-
-    let mut override_liquidity_trading_fee = None;
-    let mut override_treasury_trading_fee = None;
-
-    if ctx.accounts.clone_staking.is_some()
-        && ctx.accounts.user_staking_account.is_some()
-        && ctx.accounts.clone_staking_program.is_some()
-    {
-        let user_staking_account = ctx
-            .accounts
-            .user_staking_account
-            .as_ref()
-            .ok_or(error!(CloneError::ExpectedAccountNotFound))?;
-        let clone_staking = ctx
-            .accounts
-            .clone_staking
-            .as_ref()
-            .ok_or(error!(CloneError::ExpectedAccountNotFound))?;
-        if let Some((lp_fees, treasury_fees)) =
-            clone_staking.get_tier_fees(user_staking_account.staked_tokens)
-        {
-            override_liquidity_trading_fee = Some(to_bps_decimal!(lp_fees));
-            override_treasury_trading_fee = Some(to_bps_decimal!(treasury_fees));
-        }
-    }
+    let (override_liquidity_trading_fee, override_treasury_trading_fee) = fee_overrides;
 
     check_feed_update(&pool_oracle, Clock::get()?.slot)?;
     check_feed_update(&collateral_oracle, Clock::get()?.slot)?;
@@ -161,6 +332,13 @@ pub fn execute(
         to_clone_decimal!(quantity)
     };
 
+    let (liquidity_trading_fee, treasury_trading_fee) = pool.calculate_trading_fees(
+        pool_oracle,
+        collateral_oracle,
+        collateral,
+        override_liquidity_trading_fee,
+        override_treasury_trading_fee,
+    )?;
     let swap_summary = pool.calculate_swap(
         pool_oracle.get_price()?,
         collateral_oracle.get_price()?,
@@ -168,8 +346,8 @@ pub fn execute(
         quantity_is_input,
         quantity_is_collateral,
         collateral,
-        override_liquidity_trading_fee,
-        override_treasury_trading_fee,
+        Some(liquidity_trading_fee),
+        Some(treasury_trading_fee),
     )?;
 
     return_error_if_false!(
@@ -190,175 +368,119 @@ pub fn execute(
         .mantissa()
         .try_into()
         .map_err(|_| CloneError::IntTypeConversionError)?;
-    let mut input_is_collateral = false;
+    let input_is_collateral = quantity_is_input == quantity_is_collateral;
+    let (input, output) = if quantity_is_input {
+        return_error_if_false!(
+            result_amount >= result_threshold,
+            CloneError::SlippageToleranceExceeded
+        );
+        (quantity, result_amount)
+    } else {
+        return_error_if_false!(
+            result_amount <= result_threshold,
+            CloneError::SlippageToleranceExceeded
+        );
+        (result_amount, quantity)
+    };
 
-    let (onasset_ild_delta, collateral_ild_delta) = if (quantity_is_input && quantity_is_collateral)
-        || (!quantity_is_input && !quantity_is_collateral)
-    {
-        input_is_collateral = true;
-        // User transfers collateral to vault, mint onasset to user, mint onasset as fees
-        let (transfer_amount, mint_amount) = if quantity_is_input {
-            (quantity, result_amount)
-        } else {
-            (result_amount, quantity)
-        };
+    let settlement = pools.pools[pool_index as usize].settle_swap(
+        pool_oracle.get_price()?,
+        collateral_oracle.get_price()?,
+        collateral,
+        input,
+        output,
+        input_is_collateral,
+        treasury_fees,
+        token_accounts
+            .staking_reward_share
+            .as_ref()
+            .map_or(0, |(_, treasury_fee_share_bps)| *treasury_fee_share_bps),
+    )?;
+    let treasury_token_account = if input_is_collateral {
+        token_accounts.treasury_onasset_token_account.clone()
+    } else {
+        token_accounts.treasury_collateral_token_account.clone()
+    }
+    .ok_or(error!(CloneError::ExpectedAccountNotFound))?;
+
+    if input_is_collateral {
+        // User transfers collateral to vault, mint onasset to user.
         token::transfer(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info().clone(),
+                token_accounts.token_program.clone(),
                 Transfer {
-                    from: ctx
-                        .accounts
-                        .user_collateral_token_account
-                        .to_account_info()
-                        .clone(),
-                    to: ctx.accounts.collateral_vault.to_account_info().clone(),
-                    authority: ctx.accounts.user.to_account_info().clone(),
+                    from: token_accounts.user_collateral_token_account.clone(),
+                    to: token_accounts.collateral_vault.clone(),
+                    authority: token_accounts.user.clone(),
                 },
             ),
-            transfer_amount,
+            input,
         )?;
         token::mint_to(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info().clone(),
+                token_accounts.token_program.clone(),
                 MintTo {
-                    mint: ctx.accounts.onasset_mint.to_account_info().clone(),
-                    to: ctx
-                        .accounts
-                        .user_onasset_token_account
-                        .to_account_info()
-                        .clone(),
-                    authority: ctx.accounts.clone.to_account_info().clone(),
+                    mint: token_accounts.onasset_mint.clone(),
+                    to: token_accounts.user_onasset_token_account.clone(),
+                    authority: clone_info.clone(),
                 },
                 seeds,
             ),
-            mint_amount,
+            output,
         )?;
-        token::mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info().clone(),
-                MintTo {
-                    mint: ctx.accounts.onasset_mint.to_account_info().clone(),
-                    to: ctx
-                        .accounts
-                        .treasury_onasset_token_account
-                        .to_account_info()
-                        .clone(),
-                    authority: ctx.accounts.clone.to_account_info().clone(),
-                },
-                seeds,
-            ),
-            treasury_fees,
-        )?;
-
-        (
-            (mint_amount
-                .checked_add(treasury_fees)
-                .ok_or(error!(CloneError::CheckedMathError))?)
-            .try_into()
-            .map_err(|_| CloneError::IntTypeConversionError)?,
-            -(TryInto::<i64>::try_into(transfer_amount)
-                .map_err(|_| CloneError::IntTypeConversionError)?),
-        )
     } else {
-        // User burns onasset, transfer collateral from vault to user, transfer collateral as fees.
-        let (burn_amount, transfer_amount) = if quantity_is_input {
-            (quantity, result_amount)
-        } else {
-            (result_amount, quantity)
-        };
+        // User burns onasset, transfer collateral from vault to user.
         token::burn(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info().clone(),
+                token_accounts.token_program.clone(),
                 Burn {
-                    mint: ctx.accounts.onasset_mint.to_account_info().clone(),
-                    from: ctx
-                        .accounts
-                        .user_onasset_token_account
-                        .to_account_info()
-                        .clone(),
-                    authority: ctx.accounts.user.to_account_info().clone(),
+                    mint: token_accounts.onasset_mint.clone(),
+                    from: token_accounts.user_onasset_token_account.clone(),
+                    authority: token_accounts.user.clone(),
                 },
             ),
-            burn_amount,
+            input,
         )?;
         token::transfer(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info().clone(),
+                token_accounts.token_program.clone(),
                 Transfer {
-                    from: ctx.accounts.collateral_vault.to_account_info().clone(),
-                    to: ctx
-                        .accounts
-                        .user_collateral_token_account
-                        .to_account_info()
-                        .clone(),
-                    authority: ctx.accounts.clone.to_account_info().clone(),
+                    from: token_accounts.collateral_vault.clone(),
+                    to: token_accounts.user_collateral_token_account.clone(),
+                    authority: clone_info.clone(),
                 },
                 seeds,
             ),
-            transfer_amount,
+            output,
         )?;
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info().clone(),
-                Transfer {
-                    from: ctx.accounts.collateral_vault.to_account_info().clone(),
-                    to: ctx
-                        .accounts
-                        .treasury_collateral_token_account
-                        .to_account_info()
-                        .clone(),
-                    authority: ctx.accounts.clone.to_account_info().clone(),
-                },
-                seeds,
-            ),
-            treasury_fees,
-        )?;
-        (
-            -(TryInto::<i64>::try_into(burn_amount)
-                .map_err(|_| CloneError::IntTypeConversionError)?),
-            (transfer_amount
-                .checked_add(treasury_fees)
-                .ok_or(error!(CloneError::CheckedMathError))?
-                .try_into()
-                .map_err(|_| CloneError::IntTypeConversionError)?),
-        )
-    };
-
-    pools.pools[pool_index as usize].onasset_ild = pools.pools[pool_index as usize]
-        .onasset_ild
-        .checked_add(onasset_ild_delta)
-        .ok_or(error!(CloneError::CheckedMathError))?;
-    pools.pools[pool_index as usize].collateral_ild = pools.pools[pool_index as usize]
-        .collateral_ild
-        .checked_add(collateral_ild_delta)
-        .ok_or(error!(CloneError::CheckedMathError))?;
-
-    let (input, output) = if quantity_is_input {
-        return_error_if_false!(
-            result_amount >= result_threshold,
-            CloneError::SlippageToleranceExceeded
-        );
-        (quantity, result_amount)
-    } else {
-        return_error_if_false!(
-            result_amount <= result_threshold,
-            CloneError::SlippageToleranceExceeded
-        );
-        (result_amount, quantity)
-    };
+    }
+    pay_treasury_fees(
+        token_accounts.token_program.clone(),
+        token_accounts.onasset_mint.clone(),
+        token_accounts.collateral_vault.clone(),
+        treasury_token_account,
+        token_accounts
+            .staking_reward_share
+            .map(|(staking_reward_vault, _)| staking_reward_vault),
+        clone_info,
+        seeds,
+        &settlement,
+        input_is_collateral,
+    )?;
 
+    let liquidity_fees_paid: u64 = swap_summary
+        .liquidity_fees_paid
+        .mantissa()
+        .try_into()
+        .map_err(|_| CloneError::IntTypeConversionError)?;
     emit!(SwapEvent {
-        event_id: ctx.accounts.clone.event_counter,
-        user_address: ctx.accounts.user.key(),
+        event_id: clone.event_counter,
+        user_address: token_accounts.user.key(),
         pool_index,
         input_is_collateral,
         input,
         output,
-        trading_fee: swap_summary
-            .liquidity_fees_paid
-            .mantissa()
-            .try_into()
-            .map_err(|_| CloneError::IntTypeConversionError)?,
+        trading_fee: liquidity_fees_paid,
         treasury_fee: treasury_fees
     });
 
@@ -372,7 +494,7 @@ pub fn execute(
     );
 
     emit!(PoolState {
-        event_id: ctx.accounts.clone.event_counter,
+        event_id: clone.event_counter,
         pool_index,
         onasset_ild: pool.onasset_ild,
         collateral_ild: pool.collateral_ild,
@@ -383,12 +505,15 @@ pub fn execute(
             .map_err(|_| CloneError::IntTypeConversionError)?,
         pool_scale: pool_price.scale()
     });
-    ctx.accounts.clone.event_counter = ctx
-        .accounts
-        .clone
+    clone.event_counter = clone
         .event_counter
         .checked_add(1)
         .ok_or(error!(CloneError::CheckedMathError))?;
 
-    Ok(())
+    Ok(SwapResult {
+        input,
+        output,
+        liquidity_fees_paid,
+        treasury_fees_paid: treasury_fees,
+    })
 }
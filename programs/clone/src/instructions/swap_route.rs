@@ -0,0 +1,321 @@
+use crate::decimal::{rescale_toward_zero, CLONE_TOKEN_SCALE};
+use crate::error::*;
+use crate::events::*;
+use crate::instructions::{
+    can_use_staking_account, pay_treasury_fees, staking_reward_share, staking_tier_fees,
+};
+use crate::math::*;
+use crate::states::*;
+use crate::{
+    return_error_if_false, to_clone_decimal, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::*;
+use clone_staking::{
+    program::CloneStaking as CloneStakingProgram,
+    states::{CloneStaking, User as UserStaking},
+    CLONE_STAKING_SEED,
+};
+use rust_decimal::prelude::*;
+use std::convert::TryInto;
+
+fn is_valid_route(pools: &Pools, input_pool_index: u8, output_pool_index: u8) -> bool {
+    input_pool_index != output_pool_index
+        && (input_pool_index as u64) < pools.num_pools
+        && (output_pool_index as u64) < pools.num_pools
+}
+
+fn is_active_route(pools: &Pools, input_pool_index: u8, output_pool_index: u8) -> bool {
+    pools.pools[input_pool_index as usize].status == Status::Active
+        && pools.pools[output_pool_index as usize].status == Status::Active
+}
+
+/// Swaps the onAsset of one pool for the onAsset of another in a single
+/// instruction, selling into the collateral leg of the input pool and buying
+/// from the collateral leg of the output pool.
+#[derive(Accounts)]
+#[instruction(input_pool_index: u8, output_pool_index: u8)]
+pub struct SwapRoute<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CLONE_PROGRAM_SEED.as_ref()],
+        bump = clone.bump,
+    )]
+    pub clone: Box<Account<'info, Clone>>,
+    #[account(
+        mut,
+        seeds = [POOLS_SEED.as_ref()],
+        bump,
+        constraint = is_valid_route(&*pools.load()?, input_pool_index, output_pool_index) @ CloneError::InvalidSwapRoute,
+        constraint = is_active_route(&*pools.load()?, input_pool_index, output_pool_index) @ CloneError::StatusPreventsAction,
+    )]
+    pub pools: AccountLoader<'info, Pools>,
+    #[account(
+        mut,
+        seeds = [ORACLES_SEED.as_ref()],
+        bump,
+    )]
+    pub oracles: AccountLoader<'info, Oracles>,
+    #[account(
+        mut,
+        associated_token::mint = input_onasset_mint,
+        associated_token::authority = user
+    )]
+    pub user_input_onasset_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = output_onasset_mint,
+        associated_token::authority = user
+    )]
+    pub user_output_onasset_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        address = pools.load()?.pools[input_pool_index as usize].asset_info.onasset_mint,
+    )]
+    pub input_onasset_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        address = pools.load()?.pools[output_pool_index as usize].asset_info.onasset_mint,
+    )]
+    pub output_onasset_mint: Box<Account<'info, Mint>>,
+    #[account(
+        address = clone.collateral.mint
+    )]
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        address = clone.collateral.vault
+    )]
+    pub collateral_vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = output_onasset_mint,
+        associated_token::authority = clone.treasury_address
+    )]
+    pub treasury_output_onasset_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = clone.treasury_address
+    )]
+    pub treasury_collateral_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    #[account(
+        seeds = [CLONE_STAKING_SEED.as_ref()],
+        bump,
+        seeds::program = clone_staking_program.clone().ok_or(error!(CloneError::ExpectedAccountNotFound))?.key(),
+    )]
+    pub clone_staking: Option<Account<'info, CloneStaking>>,
+    #[account(
+        constraint = can_use_staking_account(&user_staking_account, user.key) @ CloneError::Unauthorized,
+    )]
+    pub user_staking_account: Option<Account<'info, UserStaking>>,
+    pub clone_staking_program: Option<Program<'info, CloneStakingProgram>>,
+    #[account(
+        mut,
+        address = clone_staking.as_ref().ok_or(error!(CloneError::ExpectedAccountNotFound))?.reward_vault,
+        token::mint = collateral_mint,
+    )]
+    pub staking_reward_vault: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+pub fn execute<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapRoute<'info>>,
+    input_pool_index: u8,
+    output_pool_index: u8,
+    quantity: u64,
+    result_threshold: u64,
+) -> Result<()> {
+    return_error_if_false!(quantity > 0, CloneError::InvalidTokenAmount);
+
+    let seeds = &[&[
+        CLONE_PROGRAM_SEED.as_ref(),
+        bytemuck::bytes_of(&ctx.accounts.clone.bump),
+    ][..]];
+    let collateral = &ctx.accounts.clone.collateral;
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let collateral_oracle = &oracles.oracles[collateral.oracle_info_index as usize];
+    let slot = Clock::get()?.slot;
+    check_feed_update(collateral_oracle, slot)?;
+
+    let (override_liquidity_trading_fee, override_treasury_trading_fee) = staking_tier_fees(
+        &ctx.accounts.clone_staking,
+        &ctx.accounts.user_staking_account,
+    )?;
+    let treasury_fee_share = staking_reward_share(
+        &ctx.accounts.clone_staking,
+        &ctx.accounts.staking_reward_vault,
+    );
+
+    let mut event_id = ctx.accounts.clone.event_counter;
+    let mut hop_input = quantity;
+    // The input pool takes the onAsset and the output pool the collateral
+    // it was sold for.
+    for (pool_index, input_is_collateral) in [(input_pool_index, false), (output_pool_index, true)]
+    {
+        let pool = &pools.pools[pool_index as usize];
+        let pool_oracle = &oracles.oracles[pool.asset_info.oracle_info_index as usize];
+        check_feed_update(pool_oracle, slot)?;
+        return_error_if_false!(
+            pool.committed_collateral_liquidity > 0,
+            CloneError::PoolEmpty
+        );
+
+        let input_quantity = if input_is_collateral {
+            collateral.to_collateral_decimal(hop_input)?
+        } else {
+            to_clone_decimal!(hop_input)
+        };
+        let (liquidity_trading_fee, treasury_trading_fee) = pool.calculate_trading_fees(
+            pool_oracle,
+            collateral_oracle,
+            collateral,
+            override_liquidity_trading_fee,
+            override_treasury_trading_fee,
+        )?;
+        let swap_summary = pool.calculate_swap(
+            pool_oracle.get_price()?,
+            collateral_oracle.get_price()?,
+            input_quantity,
+            true,
+            input_is_collateral,
+            collateral,
+            Some(liquidity_trading_fee),
+            Some(treasury_trading_fee),
+        )?;
+        return_error_if_false!(
+            swap_summary.result > Decimal::ZERO
+                && swap_summary.liquidity_fees_paid > Decimal::ZERO
+                && swap_summary.treasury_fees_paid > Decimal::ZERO,
+            CloneError::InvalidTokenAmount
+        );
+
+        let output: u64 = swap_summary
+            .result
+            .mantissa()
+            .try_into()
+            .map_err(|_| CloneError::IntTypeConversionError)?;
+        let treasury_fees: u64 = swap_summary
+            .treasury_fees_paid
+            .mantissa()
+            .try_into()
+            .map_err(|_| CloneError::IntTypeConversionError)?;
+        let settlement = pools.pools[pool_index as usize].settle_swap(
+            pool_oracle.get_price()?,
+            collateral_oracle.get_price()?,
+            collateral,
+            hop_input,
+            output,
+            input_is_collateral,
+            treasury_fees,
+            treasury_fee_share
+                .as_ref()
+                .map_or(0, |(_, treasury_fee_share_bps)| *treasury_fee_share_bps),
+        )?;
+        // The user's side of each hop settles after the route, and the
+        // collateral bought stays in the vault for the output pool.
+        pay_treasury_fees(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.output_onasset_mint.to_account_info(),
+            ctx.accounts.collateral_vault.to_account_info(),
+            if input_is_collateral {
+                ctx.accounts
+                    .treasury_output_onasset_token_account
+                    .to_account_info()
+            } else {
+                ctx.accounts
+                    .treasury_collateral_token_account
+                    .to_account_info()
+            },
+            treasury_fee_share
+                .clone()
+                .map(|(staking_reward_vault, _)| staking_reward_vault),
+            ctx.accounts.clone.to_account_info(),
+            seeds,
+            &settlement,
+            input_is_collateral,
+        )?;
+        let pool = &pools.pools[pool_index as usize];
+
+        emit!(SwapEvent {
+            event_id,
+            user_address: ctx.accounts.user.key(),
+            pool_index,
+            input_is_collateral,
+            input: hop_input,
+            output,
+            trading_fee: swap_summary
+                .liquidity_fees_paid
+                .mantissa()
+                .try_into()
+                .map_err(|_| CloneError::IntTypeConversionError)?,
+            treasury_fee: treasury_fees
+        });
+
+        let pool_price = rescale_toward_zero(
+            pool_oracle
+                .get_price()?
+                .checked_div(collateral_oracle.get_price()?)
+                .ok_or(error!(CloneError::CheckedMathError))?,
+            CLONE_TOKEN_SCALE,
+        );
+        emit!(PoolState {
+            event_id,
+            pool_index,
+            onasset_ild: pool.onasset_ild,
+            collateral_ild: pool.collateral_ild,
+            committed_collateral_liquidity: pool.committed_collateral_liquidity,
+            pool_price: pool_price
+                .mantissa()
+                .try_into()
+                .map_err(|_| CloneError::IntTypeConversionError)?,
+            pool_scale: pool_price.scale()
+        });
+
+        event_id = event_id
+            .checked_add(1)
+            .ok_or(error!(CloneError::CheckedMathError))?;
+        hop_input = output;
+    }
+
+    return_error_if_false!(
+        hop_input >= result_threshold,
+        CloneError::SlippageToleranceExceeded
+    );
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.input_onasset_mint.to_account_info(),
+                from: ctx
+                    .accounts
+                    .user_input_onasset_token_account
+                    .to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        quantity,
+    )?;
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.output_onasset_mint.to_account_info(),
+                to: ctx
+                    .accounts
+                    .user_output_onasset_token_account
+                    .to_account_info(),
+                authority: ctx.accounts.clone.to_account_info(),
+            },
+            seeds,
+        ),
+        hop_input,
+    )?;
+
+    ctx.accounts.clone.event_counter = event_id;
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::decimal::rescale_toward_zero;
+use crate::decimal::rescale_down;
 use crate::error::*;
 use crate::return_error_if_false;
 use crate::states::*;
@@ -20,11 +20,12 @@ pub struct UnwrapOnAsset<'info> {
     #[account(
         seeds = [POOLS_SEED.as_ref()],
         bump,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
-        address = pools.pools[pool_index as usize].underlying_asset_token_account,
+        address = pools.load()?.pools[pool_index as usize].underlying_asset_token_account,
     )]
     pub underlying_asset_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
@@ -39,7 +40,7 @@ pub struct UnwrapOnAsset<'info> {
     pub user_asset_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
         mut,
-        address = pools.pools[pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -52,7 +53,8 @@ pub struct UnwrapOnAsset<'info> {
 }
 
 pub fn execute(ctx: Context<UnwrapOnAsset>, amount: u64, pool_index: u8) -> Result<()> {
-    let pool = &ctx.accounts.pools.pools[pool_index as usize];
+    let pools = ctx.accounts.pools.load()?;
+    let pool = &pools.pools[pool_index as usize];
     return_error_if_false!(
         pool.status == Status::Active || pool.status == Status::Deprecation,
         CloneError::StatusPreventsAction
@@ -65,7 +67,7 @@ pub fn execute(ctx: Context<UnwrapOnAsset>, amount: u64, pool_index: u8) -> Resu
         .decimals
         .try_into()
         .map_err(|_| CloneError::IntTypeConversionError)?;
-    let unwrapped_amount = rescale_toward_zero(to_clone_decimal!(amount), underlying_mint_scale)
+    let unwrapped_amount = rescale_down(to_clone_decimal!(amount), underlying_mint_scale)
         .mantissa()
         .try_into()
         .map_err(|_| CloneError::IntTypeConversionError)?;
@@ -0,0 +1,33 @@
+use crate::error::*;
+use crate::states::*;
+use crate::ADMIN_MULTISIG_SEED;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(signers: Vec<Pubkey>, threshold: u8)]
+pub struct UpdateAdminMultisig<'info> {
+    // Only the multisig's own PDA can sign, through `execute_admin_transaction`.
+    #[account(
+        mut,
+        seeds = [ADMIN_MULTISIG_SEED.as_ref()],
+        bump = admin_multisig.bump,
+        constraint = admin_multisig.to_account_info().is_signer @ CloneError::Unauthorized
+    )]
+    pub admin_multisig: Box<Account<'info, AdminMultisig>>,
+}
+
+/// Replaces the signers and threshold, dropping any pending transactions.
+pub fn execute(
+    ctx: Context<UpdateAdminMultisig>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    let admin_multisig = &mut ctx.accounts.admin_multisig;
+    admin_multisig.set_signers(&signers, threshold)?;
+    admin_multisig.signer_set_version = admin_multisig
+        .signer_set_version
+        .checked_add(1)
+        .ok_or(error!(CloneError::CheckedMathError))?;
+
+    Ok(())
+}
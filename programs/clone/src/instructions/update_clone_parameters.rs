@@ -13,6 +13,8 @@ pub enum CloneParameters {
     TreasuryAddress { address: Pubkey },
     CollateralizationRatio { value: u8 },
     NonAuthLiquidationsEnabled { value: bool },
+    FlashLoanFee { value: u16 },
+    PendingAdmin { address: Pubkey },
 }
 
 #[derive(Accounts)]
@@ -85,6 +87,13 @@ pub fn execute(ctx: Context<UpdateCloneParameters>, params: CloneParameters) ->
         CloneParameters::NonAuthLiquidationsEnabled { value } => {
             clone.non_auth_liquidations_enabled = value;
         }
+        CloneParameters::FlashLoanFee { value } => {
+            return_error_if_false!(value < 10000, CloneError::InvalidValueRange);
+            clone.flash_loan_fee_bps = value;
+        }
+        CloneParameters::PendingAdmin { address } => {
+            clone.pending_admin = address;
+        }
     }
 
     Ok(())
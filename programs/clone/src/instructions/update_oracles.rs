@@ -37,7 +37,7 @@ pub struct UpdateOracles<'info> {
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
 }
 
 pub fn execute(ctx: Context<UpdateOracles>, params: UpdateOracleParameters) -> Result<()> {
@@ -47,7 +47,7 @@ pub fn execute(ctx: Context<UpdateOracles>, params: UpdateOracleParameters) -> R
     let is_admin = auth_key.eq(&clone.admin);
     let is_auth = clone_auth.iter().any(|auth| auth_key.eq(auth));
 
-    let oracles = &mut ctx.accounts.oracles.oracles;
+    let mut oracles = ctx.accounts.oracles.load_mut()?;
 
     match params {
         UpdateOracleParameters::Add {
@@ -56,17 +56,21 @@ pub fn execute(ctx: Context<UpdateOracles>, params: UpdateOracleParameters) -> R
             rescale_factor,
         } => {
             return_error_if_false!(is_admin, CloneError::Unauthorized);
-            oracles.push(OracleInfo {
+            oracles.append_oracle(OracleInfo {
                 source,
                 address,
                 status: Status::Active,
                 rescale_factor: rescale_factor.unwrap_or(OracleInfo::default().rescale_factor),
                 ..OracleInfo::default()
-            });
+            })?;
         }
         UpdateOracleParameters::Remove { index } => {
             return_error_if_false!(is_admin, CloneError::Unauthorized);
-            oracles.remove(index.into());
+            return_error_if_false!(
+                (index as u64) < oracles.num_oracles,
+                CloneError::InvalidOracleIndex
+            );
+            oracles.remove_oracle(index.into());
         }
         UpdateOracleParameters::Modify {
             index,
@@ -74,7 +78,11 @@ pub fn execute(ctx: Context<UpdateOracles>, params: UpdateOracleParameters) -> R
             source,
             status,
         } => {
-            let oracle = &mut oracles[index as usize];
+            return_error_if_false!(
+                (index as u64) < oracles.num_oracles,
+                CloneError::InvalidOracleIndex
+            );
+            let oracle = &mut oracles.oracles[index as usize];
             if let Some(addr) = address {
                 return_error_if_false!(is_admin, CloneError::Unauthorized);
                 oracle.address = addr;
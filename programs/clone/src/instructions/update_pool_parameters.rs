@@ -12,6 +12,11 @@ pub enum PoolParameters {
     MaxLiquidationOvercollateralRatio { value: u16 },
     IlHealthScoreCoefficient { value: u16 },
     PositionHealthScoreCoefficient { value: u16 },
+    AmplificationCoefficient { value: u16 },
+    Curve { value: Curve },
+    VolatilityFeeCoefficient { value: u16 },
+    ImbalanceFeeCoefficient { value: u16 },
+    MaxTradingFee { value: u16 },
 }
 
 #[derive(Accounts)]
@@ -30,9 +35,9 @@ pub struct UpdatePoolParameters<'info> {
         mut,
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = (index as usize) < pools.pools.len() @ CloneError::PoolNotFound,
+        constraint = (index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
 }
 
 pub fn execute(
@@ -44,7 +49,7 @@ pub fn execute(
     let clone_admin = ctx.accounts.clone.admin;
     let clone_auth = ctx.accounts.clone.auth.clone();
 
-    let pools = &mut ctx.accounts.pools;
+    let mut pools = ctx.accounts.pools.load_mut()?;
     let pool = &mut pools.pools[index as usize];
 
     let is_admin = auth_key == clone_admin;
@@ -91,12 +96,32 @@ pub fn execute(
         PoolParameters::PositionHealthScoreCoefficient { value } => {
             pool.asset_info.position_health_score_coefficient = value;
         }
+        PoolParameters::AmplificationCoefficient { value } => {
+            return_error_if_false!(pool.is_empty(), CloneError::PoolHasLiquidity);
+            pool.asset_info.amplification_coefficient = value;
+        }
+        PoolParameters::Curve { value } => {
+            return_error_if_false!(pool.is_empty(), CloneError::PoolHasLiquidity);
+            pool.curve = value;
+        }
+        PoolParameters::VolatilityFeeCoefficient { value } => {
+            pool.dynamic_fee.volatility_fee_coefficient = value;
+        }
+        PoolParameters::ImbalanceFeeCoefficient { value } => {
+            pool.dynamic_fee.imbalance_fee_coefficient = value;
+        }
+        PoolParameters::MaxTradingFee { value } => {
+            pool.dynamic_fee.max_trading_fee_bps = value;
+        }
     }
 
     return_error_if_false!(
         pool.asset_info.is_valid_overcollateral_ratios(),
         CloneError::InvalidOvercollateralizationRatios
     );
+    // StableSwap needs an amplification coefficient, so set it first.
+    return_error_if_false!(pool.is_valid_curve(), CloneError::InvalidValueRange);
+    return_error_if_false!(pool.is_valid_dynamic_fee(), CloneError::InvalidValueRange);
 
     Ok(())
 }
@@ -3,6 +3,7 @@ use crate::return_error_if_false;
 use crate::states::*;
 use crate::ORACLES_SEED;
 use anchor_lang::prelude::*;
+use clone_math::calculate_volatility_bps;
 use pyth_sdk_solana::state::SolanaPriceAccount;
 use std::convert::TryInto;
 use switchboard_solana::AggregatorAccountData;
@@ -15,20 +16,24 @@ pub struct UpdatePrices<'info> {
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
 }
 
 pub fn execute<'info>(
     ctx: Context<'_, '_, '_, 'info, UpdatePrices<'info>>,
     oracle_indices: Vec<u8>,
 ) -> Result<()> {
-    let oracles = &mut ctx.accounts.oracles.oracles;
+    let mut oracles = ctx.accounts.oracles.load_mut()?;
 
     // generate data from pyth oracle
     for (account_index, oracle_index) in oracle_indices.iter().enumerate() {
         let supplied_oracle_address = &ctx.remaining_accounts[account_index];
+        return_error_if_false!(
+            (*oracle_index as u64) < oracles.num_oracles,
+            CloneError::InvalidOracleIndex
+        );
         let oracle_index = *oracle_index as usize;
-        let oracle: &mut OracleInfo = &mut oracles[oracle_index];
+        let oracle: &mut OracleInfo = &mut oracles.oracles[oracle_index];
 
         return_error_if_false!(
             supplied_oracle_address.key().eq(&oracle.address),
@@ -91,11 +96,15 @@ pub fn execute<'info>(
 
         msg!("PRICE: {} {}", price, expo);
 
-        oracles[oracle_index].price = price;
-        oracles[oracle_index].expo = expo;
-        oracles[oracle_index].last_update_slot = Clock::get()?.slot;
+        let previous = oracle.oracle_price();
+        oracle.price = price;
+        oracle.expo = expo;
+        oracle.last_update_slot = Clock::get()?.slot;
+        oracle.volatility_bps =
+            calculate_volatility_bps(&previous, oracle.volatility_bps, &oracle.oracle_price())
+                .map_err(|e| error!(CloneError::from(e)))?;
 
-        msg!("UPDATED ORACLE: {:?}", oracles[oracle_index]);
+        msg!("UPDATED ORACLE: {:?}", oracles.oracles[oracle_index]);
     }
 
     Ok(())
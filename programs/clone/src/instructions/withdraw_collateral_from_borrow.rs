@@ -18,9 +18,9 @@ pub struct WithdrawCollateralFromBorrow<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
-        constraint = (borrow_index as usize) < user_account.borrows.len() @ CloneError::InvalidInputPositionIndex
+        constraint = (borrow_index as u64) < user_account.load_user()?.header.num_borrows @ CloneError::InvalidInputPositionIndex
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
         bump = clone.bump,
@@ -30,15 +30,15 @@ pub struct WithdrawCollateralFromBorrow<'info> {
         mut,
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = pools.pools[user_account.borrows[borrow_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
+        constraint = pools.load()?.pools[user_account.load_user()?.borrows[borrow_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
         address = clone.collateral.vault,
@@ -65,9 +65,10 @@ pub fn execute(
         bytemuck::bytes_of(&ctx.accounts.clone.bump),
     ][..]];
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
-    let borrows = &mut ctx.accounts.user_account.borrows;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let borrows = &mut user_account.borrows;
 
     let pool_index = borrows[borrow_index as usize].pool_index;
     let pool = &pools.pools[pool_index as usize];
@@ -94,6 +95,7 @@ pub fn execute(
         min_overcollateral_ratio,
         collateralization_ratio,
         collateral.to_collateral_decimal(borrow_position.collateral_amount)?,
+        Clock::get()?.slot,
     )?;
 
     // send collateral back to user
@@ -135,7 +137,7 @@ pub fn execute(
 
     // check to see if mint is empty, if so remove
     if borrow_position.is_empty() {
-        borrows.remove(borrow_index as usize);
+        user_account.remove_borrow(borrow_index as usize);
     }
 
     Ok(())
@@ -17,7 +17,7 @@ pub struct WithdrawCollateralFromComet<'info> {
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -29,13 +29,13 @@ pub struct WithdrawCollateralFromComet<'info> {
         seeds = [POOLS_SEED.as_ref()],
         bump,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
     #[account(
         mut,
         address = clone.collateral.vault
@@ -58,10 +58,11 @@ pub fn execute(ctx: Context<WithdrawCollateralFromComet>, collateral_amount: u64
         bytemuck::bytes_of(&ctx.accounts.clone.bump),
     ][..]];
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
+    let pools = ctx.accounts.pools.load()?;
+    let oracles = ctx.accounts.oracles.load()?;
 
-    let comet = &mut ctx.accounts.user_account.comet;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
+    let comet = &mut user_account.header.comet;
     let collateral_to_withdraw = collateral_amount.min(comet.collateral_amount);
 
     // update the collateral amount
@@ -85,7 +86,14 @@ pub fn execute(ctx: Context<WithdrawCollateralFromComet>, collateral_amount: u64
         CpiContext::new_with_signer(cpi_program, cpi_accounts, seeds),
         collateral_to_withdraw,
     )?;
-    let health_score = calculate_health_score(comet, pools, oracles, collateral)?;
+    let health_score = calculate_health_score(
+        comet,
+        &user_account.comet_positions,
+        &pools,
+        &oracles,
+        collateral,
+        Clock::get()?.slot,
+    )?;
 
     return_error_if_false!(health_score.is_healthy(), CloneError::HealthScoreTooLow);
 
@@ -1,9 +1,11 @@
 use crate::decimal::{rescale_toward_zero, CLONE_TOKEN_SCALE};
 use crate::error::*;
 use crate::events::*;
+use crate::instructions::{check_comet_bounds, CometBounds};
+use crate::math::*;
 use crate::return_error_if_false;
 use crate::states::*;
-use crate::{to_clone_decimal, CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
+use crate::{CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
 use anchor_lang::prelude::*;
 use std::convert::TryInto;
 
@@ -15,9 +17,9 @@ pub struct WithdrawLiquidityFromComet<'info> {
         mut,
         seeds = [USER_SEED.as_ref(), user.key.as_ref()],
         bump,
-        constraint = (comet_position_index as usize) < user_account.comet.positions.len() @ CloneError::InvalidInputPositionIndex
+        constraint = (comet_position_index as u64) < user_account.load_user()?.header.comet.num_positions @ CloneError::InvalidInputPositionIndex
     )]
-    pub user_account: Box<Account<'info, User>>,
+    pub user_account: AccountLoader<'info, User>,
     #[account(
         mut,
         seeds = [CLONE_PROGRAM_SEED.as_ref()],
@@ -28,21 +30,21 @@ pub struct WithdrawLiquidityFromComet<'info> {
         mut,
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = pools.pools[user_account.comet.positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
+        constraint = pools.load()?.pools[user_account.load_user()?.comet_positions[comet_position_index as usize].pool_index as usize].status != Status::Frozen @ CloneError::StatusPreventsAction
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
         seeds = [ORACLES_SEED.as_ref()],
         bump,
     )]
-    pub oracles: Box<Account<'info, Oracles>>,
+    pub oracles: AccountLoader<'info, Oracles>,
 }
 
 pub fn withdraw_liquidity(
     pools: &mut Pools,
     oracles: &Oracles,
-    comet: &mut Comet,
+    comet_positions: &mut [LiquidityPosition],
     collateral: &Collateral,
     comet_position_index: u8,
     collateral_amount: u64,
@@ -50,7 +52,7 @@ pub fn withdraw_liquidity(
     event_counter: u64,
 ) -> Result<()> {
     return_error_if_false!(collateral_amount > 0, CloneError::InvalidTokenAmount);
-    let comet_position = comet.positions[comet_position_index as usize];
+    let comet_position = comet_positions[comet_position_index as usize];
     let pool_index = comet_position.pool_index;
     let pool = &pools.pools[pool_index as usize];
     return_error_if_false!(
@@ -66,27 +68,12 @@ pub fn withdraw_liquidity(
     let collateral_value_to_withdraw =
         collateral_amount.min(comet_position.committed_collateral_liquidity);
 
-    let proportional_value = collateral
-        .to_collateral_decimal(collateral_value_to_withdraw)?
-        .checked_div(collateral.to_collateral_decimal(pool.committed_collateral_liquidity)?)
-        .ok_or(error!(CloneError::CheckedMathError))?;
+    pools.pools[pool_index as usize].accrue_rewards(Clock::get()?.slot)?;
+    let pool = &pools.pools[pool_index as usize];
+    comet_positions[comet_position_index as usize].accrue_rewards(pool)?;
 
-    let collateral_ild_claim = rescale_toward_zero(
-        collateral
-            .to_collateral_decimal(pool.collateral_ild)?
-            .checked_mul(proportional_value)
-            .ok_or(error!(CloneError::CheckedMathError))?,
-        collateral
-            .scale
-            .try_into()
-            .map_err(|_| CloneError::IntTypeConversionError)?,
-    );
-    let onasset_ild_claim = rescale_toward_zero(
-        to_clone_decimal!(pool.onasset_ild)
-            .checked_mul(proportional_value)
-            .ok_or(error!(CloneError::CheckedMathError))?,
-        CLONE_TOKEN_SCALE,
-    );
+    let (collateral_ild_claim, onasset_ild_claim) =
+        calculate_withdrawn_liquidity_ild(collateral_value_to_withdraw, pool, collateral)?;
 
     // Update pool values:
     pools.pools[pool_index as usize].onasset_ild = pools.pools[pool_index as usize]
@@ -113,7 +100,7 @@ pub fn withdraw_liquidity(
         .checked_sub(collateral_value_to_withdraw)
         .ok_or(error!(CloneError::CheckedMathError))?;
     // Update position values:
-    comet.positions[comet_position_index as usize].onasset_ild_rebate = comet.positions
+    comet_positions[comet_position_index as usize].onasset_ild_rebate = comet_positions
         [comet_position_index as usize]
         .onasset_ild_rebate
         .checked_sub(
@@ -123,7 +110,7 @@ pub fn withdraw_liquidity(
                 .map_err(|_| CloneError::IntTypeConversionError)?,
         )
         .ok_or(error!(CloneError::CheckedMathError))?;
-    comet.positions[comet_position_index as usize].collateral_ild_rebate = comet.positions
+    comet_positions[comet_position_index as usize].collateral_ild_rebate = comet_positions
         [comet_position_index as usize]
         .collateral_ild_rebate
         .checked_sub(
@@ -133,7 +120,7 @@ pub fn withdraw_liquidity(
                 .map_err(|_| CloneError::IntTypeConversionError)?,
         )
         .ok_or(error!(CloneError::CheckedMathError))?;
-    comet.positions[comet_position_index as usize].committed_collateral_liquidity = comet.positions
+    comet_positions[comet_position_index as usize].committed_collateral_liquidity = comet_positions
         [comet_position_index as usize]
         .committed_collateral_liquidity
         .checked_sub(collateral_value_to_withdraw)
@@ -186,21 +173,32 @@ pub fn execute(
     ctx: Context<WithdrawLiquidityFromComet>,
     comet_position_index: u8,
     amount: u64,
+    bounds: CometBounds,
 ) -> Result<()> {
     let collateral = &ctx.accounts.clone.collateral;
-    let pools = &mut ctx.accounts.pools;
-    let oracles = &ctx.accounts.oracles;
-    let comet = &mut ctx.accounts.user_account.comet;
+    let mut pools = ctx.accounts.pools.load_mut()?;
+    let oracles = ctx.accounts.oracles.load()?;
+    let mut user_account = ctx.accounts.user_account.load_user_mut()?;
     withdraw_liquidity(
-        pools,
-        oracles,
-        comet,
+        &mut pools,
+        &oracles,
+        &mut user_account.comet_positions,
         collateral,
         comet_position_index,
         amount,
         ctx.accounts.user.key(),
         ctx.accounts.clone.event_counter,
     )?;
+    check_comet_bounds(
+        &bounds,
+        &user_account.header.comet,
+        &user_account.comet_positions,
+        comet_position_index as usize,
+        &pools,
+        Some(&oracles),
+        collateral,
+        Clock::get()?.slot,
+    )?;
     ctx.accounts.clone.event_counter = ctx
         .accounts
         .clone
@@ -1,4 +1,4 @@
-use crate::decimal::{rescale_toward_zero, CLONE_TOKEN_SCALE};
+use crate::decimal::{rescale_down, CLONE_TOKEN_SCALE};
 use crate::error::*;
 use crate::states::*;
 use crate::{return_error_if_false, CLONE_PROGRAM_SEED, POOLS_SEED};
@@ -19,14 +19,14 @@ pub struct WrapAsset<'info> {
     #[account(
         seeds = [POOLS_SEED.as_ref()],
         bump,
-        constraint = (pool_index as usize) < pools.pools.len(),
-        constraint = pools.pools[pool_index as usize].status != Status::Frozen &&
-        pools.pools[pool_index as usize].status != Status::Deprecation @ CloneError::StatusPreventsAction,
+        constraint = (pool_index as u64) < pools.load()?.num_pools @ CloneError::PoolNotFound,
+        constraint = pools.load()?.pools[pool_index as usize].status != Status::Frozen &&
+        pools.load()?.pools[pool_index as usize].status != Status::Deprecation @ CloneError::StatusPreventsAction,
     )]
-    pub pools: Box<Account<'info, Pools>>,
+    pub pools: AccountLoader<'info, Pools>,
     #[account(
         mut,
-        address = pools.pools[pool_index as usize].underlying_asset_token_account,
+        address = pools.load()?.pools[pool_index as usize].underlying_asset_token_account,
     )]
     pub underlying_asset_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
@@ -41,7 +41,7 @@ pub struct WrapAsset<'info> {
     pub user_asset_token_account: Box<Account<'info, TokenAccount>>,
     #[account(
         mut,
-        address = pools.pools[pool_index as usize].asset_info.onasset_mint,
+        address = pools.load()?.pools[pool_index as usize].asset_info.onasset_mint,
     )]
     pub onasset_mint: Box<Account<'info, Mint>>,
     #[account(
@@ -62,7 +62,7 @@ pub fn execute(ctx: Context<WrapAsset>, amount: u64, _pool_index: u8) -> Result<
         .decimals
         .try_into()
         .map_err(|_| CloneError::IntTypeConversionError)?;
-    let onasset_amount = rescale_toward_zero(
+    let onasset_amount = rescale_down(
         Decimal::new(
             amount
                 .try_into()
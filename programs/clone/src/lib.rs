@@ -8,6 +8,7 @@ pub mod math;
 pub mod states;
 
 use instructions::*;
+use states::TransactionAccount;
 
 declare_id!("C1onEW2kPetmHmwe74YC1ESx3LnFEpVau6g2pg4fHycr");
 
@@ -25,6 +26,7 @@ pub mod clone {
         treasury_address: Pubkey,
         collateral_oracle_index: u8,
         collateralization_ratio: u8,
+        flash_loan_fee_bps: u16,
     ) -> Result<()> {
         instructions::initialize_clone::execute(
             ctx,
@@ -34,6 +36,7 @@ pub mod clone {
             treasury_address,
             collateral_oracle_index,
             collateralization_ratio,
+            flash_loan_fee_bps,
         )
     }
 
@@ -52,6 +55,10 @@ pub mod clone {
         instructions::update_clone_parameters::execute(ctx, params)
     }
 
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin::execute(ctx)
+    }
+
     pub fn update_pool_parameters(
         ctx: Context<UpdatePoolParameters>,
         index: u8,
@@ -105,12 +112,14 @@ pub mod clone {
         pool_index: u8,
         onasset_amount: u64,
         collateral_amount: u64,
+        bounds: BorrowBounds,
     ) -> Result<()> {
         instructions::initialize_borrow_position::execute(
             ctx,
             pool_index,
             onasset_amount,
             collateral_amount,
+            bounds,
         )
     }
 
@@ -139,8 +148,13 @@ pub mod clone {
         instructions::pay_borrow_debt::execute(ctx, user, borrow_index, amount)
     }
 
-    pub fn borrow_more(ctx: Context<BorrowMore>, borrow_index: u8, amount: u64) -> Result<()> {
-        instructions::borrow_more::execute(ctx, borrow_index, amount)
+    pub fn borrow_more(
+        ctx: Context<BorrowMore>,
+        borrow_index: u8,
+        amount: u64,
+        bounds: BorrowBounds,
+    ) -> Result<()> {
+        instructions::borrow_more::execute(ctx, borrow_index, amount, bounds)
     }
 
     pub fn add_collateral_to_comet(
@@ -161,16 +175,23 @@ pub mod clone {
         ctx: Context<AddLiquidityToComet>,
         pool_index: u8,
         collateral_amount: u64,
+        bounds: CometBounds,
     ) -> Result<()> {
-        instructions::add_liquidity_to_comet::execute(ctx, pool_index, collateral_amount)
+        instructions::add_liquidity_to_comet::execute(ctx, pool_index, collateral_amount, bounds)
     }
 
     pub fn withdraw_liquidity_from_comet(
         ctx: Context<WithdrawLiquidityFromComet>,
         comet_position_index: u8,
         amount: u64,
+        bounds: CometBounds,
     ) -> Result<()> {
-        instructions::withdraw_liquidity_from_comet::execute(ctx, comet_position_index, amount)
+        instructions::withdraw_liquidity_from_comet::execute(
+            ctx,
+            comet_position_index,
+            amount,
+            bounds,
+        )
     }
 
     pub fn liquidate_comet_collateral_ild(
@@ -206,12 +227,49 @@ pub mod clone {
         instructions::collect_lp_rewards::execute(ctx, comet_position_index)
     }
 
+    pub fn compound_lp_rewards(
+        ctx: Context<CompoundLpRewards>,
+        user: Pubkey,
+        comet_position_index: u8,
+    ) -> Result<()> {
+        instructions::compound_lp_rewards::execute(ctx, user, comet_position_index)
+    }
+
+    pub fn net_lp_rewards(ctx: Context<NetLpRewards>, comet_position_index: u8) -> Result<()> {
+        instructions::net_lp_rewards::execute(ctx, comet_position_index)
+    }
+
+    pub fn set_auto_compound(
+        ctx: Context<SetAutoCompound>,
+        comet_position_index: u8,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::set_auto_compound::execute(ctx, comet_position_index, enabled)
+    }
+
+    pub fn fund_pool_rewards(
+        ctx: Context<FundPoolRewards>,
+        pool_index: u8,
+        emission_rate: u64,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::fund_pool_rewards::execute(ctx, pool_index, emission_rate, amount)
+    }
+
+    pub fn claim_pool_rewards(
+        ctx: Context<ClaimPoolRewards>,
+        comet_position_index: u8,
+    ) -> Result<()> {
+        instructions::claim_pool_rewards::execute(ctx, comet_position_index)
+    }
+
     pub fn pay_impermanent_loss_debt(
         ctx: Context<PayImpermanentLossDebt>,
         user: Pubkey,
         comet_position_index: u8,
         amount: u64,
         payment_type: PaymentType,
+        bounds: CometBounds,
     ) -> Result<()> {
         instructions::pay_impermanent_loss_debt::execute(
             ctx,
@@ -219,6 +277,7 @@ pub mod clone {
             comet_position_index,
             amount,
             payment_type,
+            bounds,
         )
     }
 
@@ -226,6 +285,10 @@ pub mod clone {
         instructions::close_user_account::execute(ctx)
     }
 
+    pub fn compact_user_account(ctx: Context<CompactUserAccount>) -> Result<()> {
+        instructions::compact_user_account::execute(ctx)
+    }
+
     pub fn wrap_asset(ctx: Context<WrapAsset>, amount: u64, pool_index: u8) -> Result<()> {
         instructions::wrap_asset::execute(ctx, amount, pool_index)
     }
@@ -259,6 +322,99 @@ pub mod clone {
         )
     }
 
+    pub fn aggregator_swap(
+        ctx: Context<AggregatorSwap>,
+        pool_index: u8,
+        quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+        result_threshold: u64,
+    ) -> Result<SwapResult> {
+        instructions::aggregator_swap::execute(
+            ctx,
+            pool_index,
+            quantity,
+            quantity_is_input,
+            quantity_is_collateral,
+            result_threshold,
+        )
+    }
+
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapRoute<'info>>,
+        input_pool_index: u8,
+        output_pool_index: u8,
+        quantity: u64,
+        result_threshold: u64,
+    ) -> Result<()> {
+        instructions::swap_route::execute(
+            ctx,
+            input_pool_index,
+            output_pool_index,
+            quantity,
+            result_threshold,
+        )
+    }
+
+    pub fn flash_loan_collateral(ctx: Context<FlashLoanCollateral>, amount: u64) -> Result<()> {
+        instructions::flash_loan_collateral::execute(ctx, amount)
+    }
+
+    pub fn repay_flash_loan_collateral(
+        ctx: Context<RepayFlashLoanCollateral>,
+        amount: u64,
+        borrow_instruction_index: u8,
+    ) -> Result<()> {
+        instructions::repay_flash_loan_collateral::execute(ctx, amount, borrow_instruction_index)
+    }
+
+    pub fn flash_mint_onasset(
+        ctx: Context<FlashMintOnasset>,
+        pool_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::flash_mint_onasset::execute(ctx, pool_index, amount)
+    }
+
+    pub fn repay_flash_mint_onasset(
+        ctx: Context<RepayFlashMintOnasset>,
+        pool_index: u8,
+        amount: u64,
+        borrow_instruction_index: u8,
+    ) -> Result<()> {
+        instructions::repay_flash_mint_onasset::execute(
+            ctx,
+            pool_index,
+            amount,
+            borrow_instruction_index,
+        )
+    }
+
+    pub fn quote_swap(
+        ctx: Context<QuoteSwap>,
+        pool_index: u8,
+        quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+    ) -> Result<SwapQuote> {
+        instructions::quote_swap::execute(
+            ctx,
+            pool_index,
+            quantity,
+            quantity_is_input,
+            quantity_is_collateral,
+        )
+    }
+
+    pub fn get_user_health(
+        ctx: Context<GetUserHealth>,
+        position_offset: u8,
+        borrow_offset: u8,
+        limit: u8,
+    ) -> Result<UserHealth> {
+        instructions::get_user_health::execute(ctx, position_offset, borrow_offset, limit)
+    }
+
     pub fn create_token_metadata(
         ctx: Context<CreateTokenMetadata>,
         metadata_args: MetadataArgs,
@@ -269,4 +425,56 @@ pub mod clone {
     pub fn remove_pool(ctx: Context<RemovePool>, pool_index: u8) -> Result<()> {
         instructions::remove_pool::execute(ctx, pool_index)
     }
+
+    pub fn migrate_to_zero_copy(
+        ctx: Context<MigrateToZeroCopy>,
+        target: MigrationTarget,
+    ) -> Result<()> {
+        instructions::migrate_to_zero_copy::execute(ctx, target)
+    }
+
+    pub fn migrate_account_layout(
+        ctx: Context<MigrateAccountLayout>,
+        target: MigrationTarget,
+    ) -> Result<()> {
+        instructions::migrate_account_layout::execute(ctx, target)
+    }
+
+    pub fn initialize_admin_multisig(
+        ctx: Context<InitializeAdminMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::initialize_admin_multisig::execute(ctx, signers, threshold)
+    }
+
+    pub fn update_admin_multisig(
+        ctx: Context<UpdateAdminMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        instructions::update_admin_multisig::execute(ctx, signers, threshold)
+    }
+
+    pub fn propose_admin_transaction(
+        ctx: Context<ProposeAdminTransaction>,
+        instruction_program_id: Pubkey,
+        instruction_accounts: Vec<TransactionAccount>,
+        instruction_data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::propose_admin_transaction::execute(
+            ctx,
+            instruction_program_id,
+            instruction_accounts,
+            instruction_data,
+        )
+    }
+
+    pub fn approve_admin_transaction(ctx: Context<ApproveAdminTransaction>) -> Result<()> {
+        instructions::approve_admin_transaction::execute(ctx)
+    }
+
+    pub fn execute_admin_transaction(ctx: Context<ExecuteAdminTransaction>) -> Result<()> {
+        instructions::execute_admin_transaction::execute(ctx)
+    }
 }
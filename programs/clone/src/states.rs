@@ -1,12 +1,20 @@
-use crate::decimal::{rescale_toward_zero, CLONE_TOKEN_SCALE};
 use crate::error::CloneError;
-use crate::{to_bps_decimal, to_clone_decimal};
+use crate::{return_error_if_false, to_clone_decimal};
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use clone_math::{
+    calculate_accrued_rewards, CollateralParams, DynamicFeeParams, OraclePrice, PoolState,
+    PositionState, RewardState,
+};
 use rust_decimal::prelude::*;
+use std::cell::{Ref, RefMut};
 use std::convert::TryInto;
-use std::vec::Vec;
+use std::mem::size_of;
+
+pub use clone_math::{SwapSettlement, SwapSummary};
 
 #[derive(Clone, PartialEq, Copy, Eq, Debug, AnchorDeserialize, AnchorSerialize, Default)]
+#[repr(u8)]
 pub enum Status {
     Active = 0,
     #[default]
@@ -16,8 +24,17 @@ pub enum Status {
     Deprecation = 4,
 }
 
+/// Curve a pool's swaps trade along, see `clone_math::Curve`.
+#[derive(Clone, PartialEq, Copy, Eq, Debug, AnchorDeserialize, AnchorSerialize, Default)]
+#[repr(u8)]
+pub enum Curve {
+    #[default]
+    ConstantProduct = 0,
+    StableSwap = 1,
+}
+
 pub const NUM_POOLS: usize = 64;
-pub const NUM_BORROW_POSITIONS: usize = 24;
+pub const NUM_ORACLES: usize = 80;
 pub const NUM_AUTH: usize = 10;
 
 #[account]
@@ -33,39 +50,70 @@ pub struct Clone {
     pub treasury_address: Pubkey,
     pub event_counter: u64,
     pub non_auth_liquidations_enabled: bool,
+    // Fits in the space accounts were created with, so existing accounts
+    // read it as zero, which keeps flash loans disabled until the admin sets
+    // a fee.
+    pub flash_loan_fee_bps: u16,
+    // Set by the admin and accepted by its holder to hand over the admin key.
+    pub pending_admin: Pubkey,
 }
-#[account]
+#[account(zero_copy)]
 pub struct Pools {
-    pub pools: Vec<Pool>,
+    pub pools: [Pool; NUM_POOLS],
+    pub num_pools: u64,
 }
 
-impl Default for Pools {
-    fn default() -> Self {
-        Self { pools: Vec::new() }
+impl Pools {
+    pub fn append_pool(&mut self, pool: Pool) -> Result<()> {
+        let num_pools = self.num_pools as usize;
+        return_error_if_false!(num_pools < NUM_POOLS, CloneError::PoolArrayFull);
+        self.pools[num_pools] = pool;
+        self.num_pools += 1;
+        Ok(())
+    }
+
+    pub fn remove_pool(&mut self, index: usize) {
+        let num_pools = self.num_pools as usize;
+        self.pools.copy_within(index + 1..num_pools, index);
+        self.pools[num_pools - 1] = Pool::default();
+        self.num_pools -= 1;
     }
 }
 
-#[account]
+#[account(zero_copy)]
 pub struct Oracles {
-    pub oracles: Vec<OracleInfo>,
+    pub oracles: [OracleInfo; NUM_ORACLES],
+    pub num_oracles: u64,
 }
 
-impl Default for Oracles {
-    fn default() -> Self {
-        Self {
-            oracles: Vec::new(),
-        }
+impl Oracles {
+    pub fn append_oracle(&mut self, oracle: OracleInfo) -> Result<()> {
+        let num_oracles = self.num_oracles as usize;
+        return_error_if_false!(num_oracles < NUM_ORACLES, CloneError::OracleArrayFull);
+        self.oracles[num_oracles] = oracle;
+        self.num_oracles += 1;
+        Ok(())
+    }
+
+    pub fn remove_oracle(&mut self, index: usize) {
+        let num_oracles = self.num_oracles as usize;
+        self.oracles.copy_within(index + 1..num_oracles, index);
+        self.oracles[num_oracles - 1] = OracleInfo::default();
+        self.num_oracles -= 1;
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
+#[zero_copy]
+#[derive(PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
 pub struct AssetInfo {
     pub onasset_mint: Pubkey,
-    pub oracle_info_index: u8,
     pub il_health_score_coefficient: u16,
     pub position_health_score_coefficient: u16,
     pub min_overcollateral_ratio: u16,
     pub max_liquidation_overcollateral_ratio: u16,
+    pub oracle_info_index: u8,
+    pub padding: u8,
+    pub amplification_coefficient: u16,
 }
 
 impl AssetInfo {
@@ -75,83 +123,193 @@ impl AssetInfo {
     }
 }
 
-#[derive(Clone, PartialEq, Default, Eq, Debug, AnchorDeserialize, AnchorSerialize)]
+#[derive(Clone, Copy, PartialEq, Default, Eq, Debug, AnchorDeserialize, AnchorSerialize)]
+#[repr(u8)]
 pub enum OracleSource {
     #[default]
     PYTH,
     SWITCHBOARD,
 }
 
-#[derive(Clone, PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
+#[zero_copy]
+#[derive(PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
 pub struct OracleInfo {
-    pub source: OracleSource,
     pub address: Pubkey,
     pub price: i64,
+    pub last_update_slot: u64,
+    pub source: OracleSource,
     pub expo: u8,
     pub status: Status,
-    pub last_update_slot: u64,
     pub rescale_factor: u8,
+    /// Recent price movement in bps, see `clone_math::calculate_volatility_bps`.
+    pub volatility_bps: u16,
+    pub padding: [u8; 2],
 }
 
 impl OracleInfo {
-    pub fn get_price(&self) -> Result<Decimal> {
-        let mut price = Decimal::new(self.price, self.expo.into());
-        if self.rescale_factor != 0 {
-            price = price
-                .checked_div(Decimal::new(1, self.rescale_factor.into()))
-                .ok_or(error!(CloneError::CheckedMathError))?;
+    pub fn oracle_price(&self) -> OraclePrice {
+        OraclePrice {
+            price: self.price,
+            expo: self.expo,
+            rescale_factor: self.rescale_factor,
+            last_update_slot: self.last_update_slot,
         }
-        return Ok(price);
+    }
+
+    pub fn get_price(&self) -> Result<Decimal> {
+        self.oracle_price()
+            .get_price()
+            .map_err(|e| error!(CloneError::from(e)))
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
+#[zero_copy]
+#[derive(PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
 pub struct Pool {
     pub underlying_asset_token_account: Pubkey,
     pub committed_collateral_liquidity: u64,
     pub collateral_ild: i64,
     pub onasset_ild: i64,
+    pub asset_info: AssetInfo,
     pub treasury_trading_fee_bps: u16,
     pub liquidity_trading_fee_bps: u16,
-    pub asset_info: AssetInfo,
     pub status: Status,
+    pub curve: Curve,
+    pub dynamic_fee: DynamicFee,
+    pub rewards: PoolRewards,
+}
+
+/// See `clone_math::DynamicFeeParams`.
+#[zero_copy]
+#[derive(PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct DynamicFee {
+    pub volatility_fee_coefficient: u16,
+    pub imbalance_fee_coefficient: u16,
+    pub max_trading_fee_bps: u16,
+}
+
+/// A `u128` reward index stored as two `u64` words, which keeps the zero-copy
+/// layouts 8 byte aligned.
+#[zero_copy]
+#[derive(PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct RewardIndex {
+    pub low: u64,
+    pub high: u64,
+}
+
+impl From<u128> for RewardIndex {
+    fn from(index: u128) -> Self {
+        Self {
+            low: index as u64,
+            high: (index >> 64) as u64,
+        }
+    }
+}
+
+impl From<RewardIndex> for u128 {
+    fn from(index: RewardIndex) -> Self {
+        ((index.high as u128) << 64) | index.low as u128
+    }
+}
+
+/// Liquidity mining rewards funded by the admin, see `clone_math::RewardState`.
+/// They're held in the clone account's token account for `reward_mint`.
+#[zero_copy]
+#[derive(PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct PoolRewards {
+    /// Default until the pool is first funded.
+    pub reward_mint: Pubkey,
+    pub emission_rate: u64,
+    pub unemitted_rewards: u64,
+    pub reward_index: RewardIndex,
+    pub last_update_slot: u64,
 }
 
-#[derive(Default, Debug)]
-pub struct SwapSummary {
-    pub result: Decimal,
-    pub liquidity_fees_paid: Decimal,
-    pub treasury_fees_paid: Decimal,
+impl PoolRewards {
+    pub fn reward_state(&self) -> RewardState {
+        RewardState {
+            emission_rate: self.emission_rate,
+            unemitted_rewards: self.unemitted_rewards,
+            reward_index: self.reward_index.into(),
+            last_update_slot: self.last_update_slot,
+        }
+    }
 }
 
 impl Pool {
+    pub fn pool_state(&self) -> PoolState {
+        PoolState {
+            committed_collateral_liquidity: self.committed_collateral_liquidity,
+            collateral_ild: self.collateral_ild,
+            onasset_ild: self.onasset_ild,
+            liquidity_trading_fee_bps: self.liquidity_trading_fee_bps,
+            treasury_trading_fee_bps: self.treasury_trading_fee_bps,
+            il_health_score_coefficient: self.asset_info.il_health_score_coefficient,
+            position_health_score_coefficient: self.asset_info.position_health_score_coefficient,
+            curve: match self.curve {
+                Curve::ConstantProduct => clone_math::Curve::ConstantProduct,
+                Curve::StableSwap => clone_math::Curve::StableSwap,
+            },
+            amplification_coefficient: self.asset_info.amplification_coefficient,
+            dynamic_fee: DynamicFeeParams {
+                volatility_fee_coefficient: self.dynamic_fee.volatility_fee_coefficient,
+                imbalance_fee_coefficient: self.dynamic_fee.imbalance_fee_coefficient,
+                max_trading_fee_bps: self.dynamic_fee.max_trading_fee_bps,
+            },
+        }
+    }
+
+    /// Whether no liquidity is committed and no ILD is outstanding, so the
+    /// curve can change without moving the value of any position.
+    pub fn is_empty(&self) -> bool {
+        self.committed_collateral_liquidity == 0
+            && self.onasset_ild == 0
+            && self.collateral_ild == 0
+    }
+
+    pub fn is_valid_curve(&self) -> bool {
+        self.curve != Curve::StableSwap || self.asset_info.amplification_coefficient > 0
+    }
+
+    pub fn is_valid_dynamic_fee(&self) -> bool {
+        self.dynamic_fee.max_trading_fee_bps < 10_000
+    }
+
     pub fn calculate_jit_pool(
         &self,
         onasset_price: Decimal,
         collateral_price: Decimal,
         collateral: &Collateral,
     ) -> Result<(Decimal, Decimal)> {
-        let committed_collateral_liquidity =
-            collateral.to_collateral_decimal(self.committed_collateral_liquidity)?;
-        let onasset_ild = to_clone_decimal!(self.onasset_ild);
-        let center_price = onasset_price
-            .checked_div(collateral_price)
-            .ok_or(error!(CloneError::CheckedMathError))?;
-        let pool_collateral = collateral.to_collateral_decimal(
-            TryInto::<i64>::try_into(self.committed_collateral_liquidity)
-                .map_err(|_| CloneError::IntTypeConversionError)?
-                .checked_sub(self.collateral_ild)
-                .ok_or(error!(CloneError::CheckedMathError))?,
-        )?;
-        let pool_onasset = rescale_toward_zero(
-            (committed_collateral_liquidity
-                .checked_div(center_price)
-                .ok_or(error!(CloneError::CheckedMathError))?)
-            .checked_sub(onasset_ild)
-            .ok_or(error!(CloneError::CheckedMathError))?,
-            CLONE_TOKEN_SCALE,
-        );
-        Ok((pool_collateral, pool_onasset))
+        self.pool_state()
+            .calculate_jit_pool(
+                onasset_price,
+                collateral_price,
+                &collateral.collateral_params(),
+            )
+            .map_err(|e| error!(CloneError::from(e)))
+    }
+
+    /// Trading fees charged by the pool, including its dynamic fee driven by
+    /// the volatility tracked in `pool_oracle`.
+    pub fn calculate_trading_fees(
+        &self,
+        pool_oracle: &OracleInfo,
+        collateral_oracle: &OracleInfo,
+        collateral: &Collateral,
+        override_liquidity_trading_fee: Option<Decimal>,
+        override_treasury_trading_fee: Option<Decimal>,
+    ) -> Result<(Decimal, Decimal)> {
+        self.pool_state()
+            .calculate_trading_fees(
+                pool_oracle.get_price()?,
+                collateral_oracle.get_price()?,
+                &collateral.collateral_params(),
+                pool_oracle.volatility_bps,
+                override_liquidity_trading_fee,
+                override_treasury_trading_fee,
+            )
+            .map_err(|e| error!(CloneError::from(e)))
     }
 
     // This function calculate either the resultant amount received or
@@ -168,132 +326,90 @@ impl Pool {
         override_liquidity_trading_fee: Option<Decimal>,
         override_treasury_trading_fee: Option<Decimal>,
     ) -> Result<SwapSummary> {
-        let (pool_collateral, pool_onasset) =
-            self.calculate_jit_pool(onasset_price, collateral_price, collateral)?;
-        let invariant = pool_onasset
-            .checked_mul(pool_collateral)
+        self.pool_state()
+            .calculate_swap(
+                onasset_price,
+                collateral_price,
+                quantity,
+                quantity_is_input,
+                quantity_is_collateral,
+                &collateral.collateral_params(),
+                override_liquidity_trading_fee,
+                override_treasury_trading_fee,
+            )
+            .map_err(|e| error!(CloneError::from(e)))
+    }
+
+    pub fn calculate_pool_price(
+        &self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        collateral: &Collateral,
+    ) -> Result<Decimal> {
+        self.pool_state()
+            .calculate_pool_price(
+                onasset_price,
+                collateral_price,
+                &collateral.collateral_params(),
+            )
+            .map_err(|e| error!(CloneError::from(e)))
+    }
+
+    /// Settles a swap of `input` for `output`, splitting its treasury fees
+    /// with stakers and applying the ILD changes it makes, see
+    /// `clone_math::PoolState::calculate_swap_settlement`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn settle_swap(
+        &mut self,
+        onasset_price: Decimal,
+        collateral_price: Decimal,
+        collateral: &Collateral,
+        input: u64,
+        output: u64,
+        input_is_collateral: bool,
+        treasury_fees: u64,
+        treasury_fee_share_bps: u16,
+    ) -> Result<SwapSettlement> {
+        let settlement = self
+            .pool_state()
+            .calculate_swap_settlement(
+                onasset_price,
+                collateral_price,
+                &collateral.collateral_params(),
+                input,
+                output,
+                input_is_collateral,
+                treasury_fees,
+                treasury_fee_share_bps,
+            )
+            .map_err(|e| error!(CloneError::from(e)))?;
+        self.onasset_ild = self
+            .onasset_ild
+            .checked_add(settlement.onasset_ild_delta)
             .ok_or(error!(CloneError::CheckedMathError))?;
-        let default_liquidity_trading_fee = to_bps_decimal!(self.liquidity_trading_fee_bps);
-        let default_treasury_trading_fee = to_bps_decimal!(self.treasury_trading_fee_bps);
-        let liquidity_trading_fee =
-            override_liquidity_trading_fee.unwrap_or(default_liquidity_trading_fee);
-        let treasury_trading_fee =
-            override_treasury_trading_fee.unwrap_or(default_treasury_trading_fee);
-        if quantity_is_input {
-            let (i_pool, o_pool, o_scale) = if quantity_is_collateral {
-                (pool_collateral, pool_onasset, CLONE_TOKEN_SCALE)
-            } else {
-                (pool_onasset, pool_collateral, collateral.scale.into())
-            };
-            // o_pool - (invariant / (i_pool + quantity)) = output_before_fees
-            let output_before_fees = rescale_toward_zero(
-                o_pool
-                    .checked_sub(
-                        invariant
-                            .checked_div(
-                                i_pool
-                                    .checked_add(quantity)
-                                    .ok_or(error!(CloneError::CheckedMathError))?,
-                            )
-                            .ok_or(error!(CloneError::CheckedMathError))?,
-                    )
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                o_scale,
-            );
-            let liquidity_fees_paid = rescale_toward_zero(
-                output_before_fees
-                    .checked_mul(liquidity_trading_fee)
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                o_scale,
-            );
-            let treasury_fees_paid = rescale_toward_zero(
-                output_before_fees
-                    .checked_mul(treasury_trading_fee)
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                o_scale,
-            );
-            let result = rescale_toward_zero(
-                output_before_fees
-                    .checked_sub(
-                        liquidity_fees_paid
-                            .checked_add(treasury_fees_paid)
-                            .ok_or(error!(CloneError::CheckedMathError))?,
-                    )
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                o_scale,
-            );
-            Ok(SwapSummary {
-                result,
-                liquidity_fees_paid,
-                treasury_fees_paid,
-            })
-        } else {
-            let (o_pool, i_pool, i_scale, o_scale) = if quantity_is_collateral {
-                (
-                    pool_collateral,
-                    pool_onasset,
-                    CLONE_TOKEN_SCALE,
-                    collateral.scale.into(),
-                )
-            } else {
-                (
-                    pool_onasset,
-                    pool_collateral,
-                    collateral.scale.into(),
-                    CLONE_TOKEN_SCALE,
-                )
-            };
-            let output_before_fees = rescale_toward_zero(
-                quantity
-                    .checked_div(
-                        Decimal::ONE
-                            .checked_sub(
-                                liquidity_trading_fee
-                                    .checked_add(treasury_trading_fee)
-                                    .ok_or(error!(CloneError::CheckedMathError))?,
-                            )
-                            .ok_or(error!(CloneError::CheckedMathError))?,
-                    )
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                o_scale,
-            );
-            // invariant / (o_pool - output_before_fees) - i_pool = result
-            let result = rescale_toward_zero(
-                invariant
-                    .checked_div(
-                        o_pool
-                            .checked_sub(output_before_fees)
-                            .ok_or(error!(CloneError::CheckedMathError))?,
-                    )
-                    .ok_or(error!(CloneError::CheckedMathError))?
-                    .checked_sub(i_pool)
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                i_scale,
-            );
-            let liquidity_fees_paid = rescale_toward_zero(
-                output_before_fees
-                    .checked_mul(liquidity_trading_fee)
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                o_scale,
-            );
-            let treasury_fees_paid = rescale_toward_zero(
-                output_before_fees
-                    .checked_mul(treasury_trading_fee)
-                    .ok_or(error!(CloneError::CheckedMathError))?,
-                o_scale,
-            );
-            Ok(SwapSummary {
-                result,
-                liquidity_fees_paid,
-                treasury_fees_paid,
-            })
-        }
+        self.collateral_ild = self
+            .collateral_ild
+            .checked_add(settlement.collateral_ild_delta)
+            .ok_or(error!(CloneError::CheckedMathError))?;
+        Ok(settlement)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.committed_collateral_liquidity == 0
-            && self.onasset_ild == 0
-            && self.collateral_ild == 0
+        self.pool_state().is_empty()
+    }
+
+    /// Emits the pool's rewards up to `slot`. Must run before its committed
+    /// liquidity changes.
+    pub fn accrue_rewards(&mut self, slot: u64) -> Result<()> {
+        let state = self
+            .rewards
+            .reward_state()
+            .accrue(self.committed_collateral_liquidity, slot)
+            .map_err(|e| error!(CloneError::from(e)))?;
+        self.rewards.unemitted_rewards = state.unemitted_rewards;
+        self.rewards.reward_index = state.reward_index.into();
+        self.rewards.last_update_slot = state.last_update_slot;
+        Ok(())
     }
 }
 
@@ -307,30 +423,128 @@ pub struct Collateral {
 }
 
 impl Collateral {
-    pub fn to_collateral_decimal<T: TryInto<i64>>(&self, value: T) -> Result<Decimal> {
-        if let Ok(num) = TryInto::<i64>::try_into(value) {
-            Ok(Decimal::new(
-                num,
-                self.scale
-                    .try_into()
-                    .map_err(|_| CloneError::IntTypeConversionError)?,
-            ))
-        } else {
-            Err(error!(CloneError::InvalidConversion))
+    pub fn collateral_params(&self) -> CollateralParams {
+        CollateralParams {
+            scale: self.scale,
+            collateralization_ratio: self.collateralization_ratio,
         }
     }
+
+    pub fn to_collateral_decimal<T: TryInto<i64>>(&self, value: T) -> Result<Decimal> {
+        self.collateral_params()
+            .to_collateral_decimal(value)
+            .map_err(|e| error!(CloneError::from(e)))
+    }
 }
 
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+
+/// Optional m-of-n admin. Once an admin key (`Clone.admin`,
+/// `CloneStaking.admin`, ...) is set to this PDA, admin instructions run
+/// through `execute_admin_transaction` after `threshold` signers approve them.
 #[account]
+#[derive(Default)]
+pub struct AdminMultisig {
+    pub bump: u8,
+    pub threshold: u8,
+    pub num_signers: u8,
+    pub signers: [Pubkey; MAX_MULTISIG_SIGNERS],
+    // Bumped whenever the signers change, invalidating pending transactions.
+    pub signer_set_version: u32,
+    pub num_transactions: u64,
+}
+
+impl AdminMultisig {
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.signers[..self.num_signers as usize]
+            .iter()
+            .position(|signer| signer == key)
+    }
+
+    pub fn set_signers(&mut self, signers: &[Pubkey], threshold: u8) -> Result<()> {
+        return_error_if_false!(
+            !signers.is_empty()
+                && signers.len() <= MAX_MULTISIG_SIGNERS
+                && threshold > 0
+                && threshold as usize <= signers.len()
+                && signers
+                    .iter()
+                    .enumerate()
+                    .all(|(index, signer)| !signers[..index].contains(signer)),
+            CloneError::InvalidMultisigSigners
+        );
+        self.signers = [Pubkey::default(); MAX_MULTISIG_SIGNERS];
+        self.signers[..signers.len()].copy_from_slice(signers);
+        self.num_signers = signers.len() as u8;
+        self.threshold = threshold;
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct TransactionAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// An instruction proposed to the `AdminMultisig`, signed by its PDA when
+/// executed.
+#[account]
+pub struct AdminTransaction {
+    pub index: u64,
+    pub signer_set_version: u32,
+    pub program_id: Pubkey,
+    pub accounts: Vec<TransactionAccount>,
+    pub data: Vec<u8>,
+    // Bit `i` is set once signer `i` approved.
+    pub approvals: u16,
+    pub executed: bool,
+}
+
+impl AdminTransaction {
+    /// Account size, discriminator included.
+    pub fn space(num_accounts: usize, data_len: usize) -> usize {
+        8 + 8 + 4 + 32 + (4 + 34 * num_accounts) + (4 + data_len) + 2 + 1
+    }
+
+    pub fn approve(&mut self, signer_index: usize) {
+        self.approvals |= 1 << signer_index;
+    }
+
+    pub fn num_approvals(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+}
+
+/// Fixed header of a `User` account. The account data continues with
+/// `borrows_capacity` borrow slots followed by `comet.positions_capacity`
+/// comet position slots, both of which grow through `realloc`.
+#[account(zero_copy)]
 pub struct User {
-    pub borrows: Vec<Borrow>,
+    pub num_borrows: u64,
+    pub borrows_capacity: u64,
     pub comet: Comet,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, AnchorDeserialize, AnchorSerialize)]
+impl User {
+    pub fn account_size(borrows_capacity: u64, positions_capacity: u64) -> usize {
+        8 + size_of::<User>()
+            + borrows_capacity as usize * size_of::<Borrow>()
+            + positions_capacity as usize * size_of::<LiquidityPosition>()
+    }
+
+    fn positions_offset(&self) -> usize {
+        8 + size_of::<User>() + self.borrows_capacity as usize * size_of::<Borrow>()
+    }
+}
+
+#[zero_copy]
+#[derive(PartialEq, Eq, Default, Debug, AnchorDeserialize, AnchorSerialize)]
 pub struct Comet {
     pub collateral_amount: u64,
-    pub positions: Vec<LiquidityPosition>,
+    pub num_positions: u64,
+    pub positions_capacity: u64,
 }
 
 impl Comet {
@@ -346,45 +560,263 @@ impl Comet {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.positions.len() == 0 && self.collateral_amount == 0
+        self.num_positions == 0 && self.collateral_amount == 0
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Copy, Debug, AnchorDeserialize, AnchorSerialize)]
+/// Read-only view of a `User` account: its header and the full capacity of
+/// its borrow and comet position regions.
+pub struct UserAccountRef<'a> {
+    pub header: Ref<'a, User>,
+    pub borrows: Ref<'a, [Borrow]>,
+    pub comet_positions: Ref<'a, [LiquidityPosition]>,
+}
+
+/// Mutable view of a `User` account, see `UserAccountRef`.
+pub struct UserAccountRefMut<'a> {
+    pub header: RefMut<'a, User>,
+    pub borrows: RefMut<'a, [Borrow]>,
+    pub comet_positions: RefMut<'a, [LiquidityPosition]>,
+}
+
+/// Appending requires a free slot, which callers reserve beforehand through
+/// `reserve_user_capacity`.
+impl<'a> UserAccountRefMut<'a> {
+    pub fn append_borrow(&mut self, borrow: Borrow) {
+        let num_borrows = self.header.num_borrows as usize;
+        self.borrows[num_borrows] = borrow;
+        self.header.num_borrows += 1;
+    }
+
+    pub fn remove_borrow(&mut self, index: usize) {
+        let num_borrows = self.header.num_borrows as usize;
+        self.borrows.copy_within(index + 1..num_borrows, index);
+        self.borrows[num_borrows - 1] = Borrow::default();
+        self.header.num_borrows -= 1;
+    }
+
+    pub fn add_comet_position(&mut self, position: LiquidityPosition) {
+        let num_positions = self.header.comet.num_positions as usize;
+        self.comet_positions[num_positions] = position;
+        self.header.comet.num_positions += 1;
+    }
+
+    pub fn remove_comet_position(&mut self, index: usize) {
+        let num_positions = self.header.comet.num_positions as usize;
+        self.comet_positions
+            .copy_within(index + 1..num_positions, index);
+        self.comet_positions[num_positions - 1] = LiquidityPosition::default();
+        self.header.comet.num_positions -= 1;
+    }
+}
+
+pub trait UserAccountLoader {
+    fn load_user(&self) -> Result<UserAccountRef<'_>>;
+    fn load_user_mut(&self) -> Result<UserAccountRefMut<'_>>;
+    /// Reallocates the account to the given capacities, moving the comet
+    /// positions region accordingly. Capacities can't drop below the number
+    /// of open positions. Rent is left to the caller.
+    fn resize_user(&self, borrows_capacity: u64, positions_capacity: u64) -> Result<()>;
+}
+
+fn check_user_data(data: &[u8]) -> Result<()> {
+    return_error_if_false!(
+        data.len() >= 8 + size_of::<User>(),
+        ErrorCode::AccountDidNotDeserialize
+    );
+    return_error_if_false!(
+        data[..8] == User::discriminator(),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let header: &User = bytemuck::from_bytes(&data[8..8 + size_of::<User>()]);
+    return_error_if_false!(
+        data.len() >= User::account_size(header.borrows_capacity, header.comet.positions_capacity),
+        ErrorCode::AccountDidNotDeserialize
+    );
+    Ok(())
+}
+
+impl<'info> UserAccountLoader for AccountLoader<'info, User> {
+    fn load_user(&self) -> Result<UserAccountRef<'_>> {
+        let data = self.as_ref().try_borrow_data()?;
+        check_user_data(&data)?;
+
+        let (header, rest) = Ref::map_split(data, |data| data[8..].split_at(size_of::<User>()));
+        let header = Ref::map(header, |header| bytemuck::from_bytes::<User>(header));
+        let borrows_len = header.borrows_capacity as usize * size_of::<Borrow>();
+        let positions_len =
+            header.comet.positions_capacity as usize * size_of::<LiquidityPosition>();
+        let (borrows, comet_positions) = Ref::map_split(rest, |rest| {
+            let (borrows, positions) = rest.split_at(borrows_len);
+            (
+                bytemuck::cast_slice::<u8, Borrow>(borrows),
+                bytemuck::cast_slice::<u8, LiquidityPosition>(&positions[..positions_len]),
+            )
+        });
+
+        Ok(UserAccountRef {
+            header,
+            borrows,
+            comet_positions,
+        })
+    }
+
+    fn load_user_mut(&self) -> Result<UserAccountRefMut<'_>> {
+        return_error_if_false!(self.as_ref().is_writable, ErrorCode::AccountNotMutable);
+        let data = self.as_ref().try_borrow_mut_data()?;
+        check_user_data(&data)?;
+
+        let (header, rest) =
+            RefMut::map_split(data, |data| data[8..].split_at_mut(size_of::<User>()));
+        let header = RefMut::map(header, |header| bytemuck::from_bytes_mut::<User>(header));
+        let borrows_len = header.borrows_capacity as usize * size_of::<Borrow>();
+        let positions_len =
+            header.comet.positions_capacity as usize * size_of::<LiquidityPosition>();
+        let (borrows, comet_positions) = RefMut::map_split(rest, |rest| {
+            let (borrows, positions) = rest.split_at_mut(borrows_len);
+            (
+                bytemuck::cast_slice_mut::<u8, Borrow>(borrows),
+                bytemuck::cast_slice_mut::<u8, LiquidityPosition>(&mut positions[..positions_len]),
+            )
+        });
+
+        Ok(UserAccountRefMut {
+            header,
+            borrows,
+            comet_positions,
+        })
+    }
+
+    fn resize_user(&self, borrows_capacity: u64, positions_capacity: u64) -> Result<()> {
+        let account = self.as_ref();
+        let (old_positions_offset, new_positions_offset, positions_len) = {
+            let user = self.load_user()?;
+            return_error_if_false!(
+                borrows_capacity >= user.header.num_borrows
+                    && positions_capacity >= user.header.comet.num_positions,
+                CloneError::RequireAllPositionsClosed
+            );
+            let positions_len = positions_capacity.min(user.header.comet.positions_capacity)
+                as usize
+                * size_of::<LiquidityPosition>();
+            (
+                user.header.positions_offset(),
+                8 + size_of::<User>() + borrows_capacity as usize * size_of::<Borrow>(),
+                positions_len,
+            )
+        };
+        let new_size = User::account_size(borrows_capacity, positions_capacity);
+
+        if new_size > account.data_len() {
+            account.realloc(new_size, true)?;
+        }
+        {
+            let mut data = account.try_borrow_mut_data()?;
+            data.copy_within(
+                old_positions_offset..old_positions_offset + positions_len,
+                new_positions_offset,
+            );
+            // Clear slots uncovered by the move so new slots start out zeroed.
+            if new_positions_offset > old_positions_offset {
+                data[old_positions_offset..new_positions_offset].fill(0);
+            }
+            data[new_positions_offset + positions_len..].fill(0);
+
+            let header: &mut User = bytemuck::from_bytes_mut(&mut data[8..8 + size_of::<User>()]);
+            header.borrows_capacity = borrows_capacity;
+            header.comet.positions_capacity = positions_capacity;
+        }
+        if new_size < account.data_len() {
+            account.realloc(new_size, false)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[zero_copy]
+#[derive(PartialEq, Eq, Debug, AnchorDeserialize, AnchorSerialize)]
 pub struct LiquidityPosition {
-    pub pool_index: u8,
     pub committed_collateral_liquidity: u64,
     pub collateral_ild_rebate: i64,
     pub onasset_ild_rebate: i64,
+    /// Pool reward index the position last accrued rewards at.
+    pub reward_index: RewardIndex,
+    pub unclaimed_rewards: u64,
+    pub pool_index: u8,
+    /// Non-zero if anyone may compound the position's LP rewards, see
+    /// `compound_lp_rewards`.
+    pub auto_compound: u8,
+    pub padding: [u8; 6],
 }
 
 impl Default for LiquidityPosition {
     fn default() -> Self {
         Self {
-            pool_index: u8::MAX.into(),
             committed_collateral_liquidity: 0,
             collateral_ild_rebate: 0,
             onasset_ild_rebate: 0,
+            reward_index: RewardIndex::default(),
+            unclaimed_rewards: 0,
+            pool_index: u8::MAX.into(),
+            auto_compound: 0,
+            padding: [0; 6],
         }
     }
 }
 
+// Safety: `repr(C)` with explicit padding, so every bit pattern is valid and
+// there are no uninitialized bytes.
+unsafe impl bytemuck::Zeroable for LiquidityPosition {}
+unsafe impl bytemuck::Pod for LiquidityPosition {}
+
 impl LiquidityPosition {
+    pub fn position_state(&self) -> PositionState {
+        PositionState {
+            committed_collateral_liquidity: self.committed_collateral_liquidity,
+            collateral_ild_rebate: self.collateral_ild_rebate,
+            onasset_ild_rebate: self.onasset_ild_rebate,
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.committed_collateral_liquidity == 0
             && self.collateral_ild_rebate == 0
             && self.onasset_ild_rebate == 0
+            && self.unclaimed_rewards == 0
+    }
+
+    /// Moves the rewards earned since the last accrual into
+    /// `unclaimed_rewards`. `pool` must have accrued its rewards first.
+    pub fn accrue_rewards(&mut self, pool: &Pool) -> Result<()> {
+        let accrued = calculate_accrued_rewards(
+            self.committed_collateral_liquidity,
+            pool.rewards.reward_index.into(),
+            self.reward_index.into(),
+        )
+        .map_err(|e| error!(CloneError::from(e)))?;
+        self.unclaimed_rewards = self
+            .unclaimed_rewards
+            .checked_add(accrued)
+            .ok_or(error!(CloneError::CheckedMathError))?;
+        self.reward_index = pool.rewards.reward_index;
+        Ok(())
     }
 }
 
 #[zero_copy]
 #[derive(PartialEq, Eq, Debug, AnchorDeserialize, AnchorSerialize)]
 pub struct Borrow {
-    pub pool_index: u8,
     pub borrowed_onasset: u64,
     pub collateral_amount: u64,
+    pub pool_index: u8,
+    pub padding: [u8; 7],
 }
 
+// Safety: see `LiquidityPosition`.
+unsafe impl bytemuck::Zeroable for Borrow {}
+unsafe impl bytemuck::Pod for Borrow {}
+
 impl Borrow {
     pub fn is_empty(&self) -> bool {
         self.borrowed_onasset == 0 && self.collateral_amount == 0
@@ -394,9 +826,41 @@ impl Borrow {
 impl Default for Borrow {
     fn default() -> Self {
         Self {
-            pool_index: u8::MAX,
             borrowed_onasset: 0,
             collateral_amount: 0,
+            pool_index: u8::MAX,
+            padding: [0; 7],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admin_transaction_space() {
+        for (num_accounts, data_len) in [(0, 0), (1, 9), (3, 40)] {
+            let admin_transaction = AdminTransaction {
+                index: u64::MAX,
+                signer_set_version: 1,
+                program_id: Pubkey::new_unique(),
+                accounts: vec![
+                    TransactionAccount {
+                        pubkey: Pubkey::new_unique(),
+                        is_signer: true,
+                        is_writable: false,
+                    };
+                    num_accounts
+                ],
+                data: vec![1; data_len],
+                approvals: 3,
+                executed: false,
+            };
+            assert_eq!(
+                AdminTransaction::space(num_accounts, data_len),
+                8 + admin_transaction.try_to_vec().unwrap().len()
+            );
         }
     }
 }
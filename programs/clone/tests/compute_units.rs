@@ -0,0 +1,326 @@
+//! Compute unit benchmarks of the zero-copy account layouts against the
+//! legacy Borsh layouts they replaced.
+//!
+//! Requires the BPF build of the program (`anchor build`) and a build of the
+//! last Borsh version saved next to it as `clone_legacy.so`. That version is
+//! the parent of the commit adding `migrate_to_zero_copy`:
+//!
+//! ```text
+//! legacy=$(git log --diff-filter=A --format=%H -- \
+//!     programs/clone/src/instructions/migrate_to_zero_copy.rs)
+//! git worktree add ../clone-legacy "$legacy~1"
+//! (cd ../clone-legacy && anchor build -p clone)
+//! cp ../clone-legacy/target/deploy/clone.so target/deploy/clone_legacy.so
+//! cargo test-sbf -p clone --test compute_units -- --ignored --nocapture
+//! ```
+//!
+//! CI doesn't run the benchmarks, which are `#[ignore]`d for needing both
+//! builds. Run them by hand when an account layout changes.
+
+use anchor_lang::{
+    AccountSerialize, AnchorSerialize, Discriminator, InstructionData, ToAccountMetas,
+};
+use clone::states::*;
+use clone::{CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+
+const BENCHMARK_SLOT: u64 = 1_000;
+const NUM_BENCHMARK_POOLS: usize = 20;
+/// Size every account was created with under the Borsh layouts.
+const LEGACY_ACCOUNT_SPACE: usize = 10240;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Layout {
+    /// The last Borsh version of the program, loaded from `clone_legacy.so`.
+    Legacy,
+    ZeroCopy,
+}
+
+fn zero_copy_account_data<T: Discriminator + bytemuck::Pod>(value: &T) -> Vec<u8> {
+    let mut data = T::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(value));
+    data
+}
+
+fn put<T: AnchorSerialize>(data: &mut Vec<u8>, value: T) {
+    value.serialize(data).unwrap();
+}
+
+/// Borsh encoding of the pre zero-copy `Pools`, `Oracles` and `User`
+/// accounts, padded to the space they were created with.
+fn legacy_account_data<T: Discriminator>(write: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut data = T::discriminator().to_vec();
+    write(&mut data);
+    data.resize(LEGACY_ACCOUNT_SPACE, 0);
+    data
+}
+
+fn legacy_pools_data(pools: &Pools) -> Vec<u8> {
+    legacy_account_data::<Pools>(|data| {
+        put(data, pools.num_pools as u32);
+        for pool in pools.pools[..pools.num_pools as usize].iter() {
+            put(data, pool.underlying_asset_token_account);
+            put(data, pool.committed_collateral_liquidity);
+            put(data, pool.collateral_ild);
+            put(data, pool.onasset_ild);
+            put(data, pool.treasury_trading_fee_bps);
+            put(data, pool.liquidity_trading_fee_bps);
+            put(data, pool.asset_info.onasset_mint);
+            put(data, pool.asset_info.oracle_info_index);
+            put(data, pool.asset_info.il_health_score_coefficient);
+            put(data, pool.asset_info.position_health_score_coefficient);
+            put(data, pool.asset_info.min_overcollateral_ratio);
+            put(data, pool.asset_info.max_liquidation_overcollateral_ratio);
+            put(data, pool.status);
+        }
+    })
+}
+
+fn legacy_oracles_data(oracles: &Oracles) -> Vec<u8> {
+    legacy_account_data::<Oracles>(|data| {
+        put(data, oracles.num_oracles as u32);
+        for oracle in oracles.oracles[..oracles.num_oracles as usize].iter() {
+            put(data, oracle.source);
+            put(data, oracle.address);
+            put(data, oracle.price);
+            put(data, oracle.expo);
+            put(data, oracle.status);
+            put(data, oracle.last_update_slot);
+            put(data, oracle.rescale_factor);
+        }
+    })
+}
+
+fn legacy_user_data(collateral_amount: u64, positions: &[LiquidityPosition]) -> Vec<u8> {
+    legacy_account_data::<User>(|data| {
+        // No borrows.
+        put(data, 0u32);
+        put(data, collateral_amount);
+        put(data, positions.len() as u32);
+        for position in positions.iter() {
+            put(data, position.pool_index);
+            put(data, position.committed_collateral_liquidity);
+            put(data, position.collateral_ild_rebate);
+            put(data, position.onasset_ild_rebate);
+        }
+    })
+}
+
+fn program_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: clone::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &clone::ID).0
+}
+
+async fn setup(layout: Layout, num_positions: usize) -> (ProgramTestContext, Keypair) {
+    let program_name = match layout {
+        Layout::Legacy => "clone_legacy",
+        Layout::ZeroCopy => "clone",
+    };
+    let mut program_test = ProgramTest::new(program_name, clone::ID, None);
+    program_test.set_compute_max_units(1_400_000);
+    let mut context = program_test.start_with_context().await;
+    context.warp_to_slot(BENCHMARK_SLOT).unwrap();
+
+    let user = Keypair::new();
+
+    let clone_state = Clone {
+        admin: context.payer.pubkey(),
+        collateral: Collateral {
+            oracle_info_index: 0,
+            collateralization_ratio: 100,
+            scale: 7,
+            ..Collateral::default()
+        },
+        ..Clone::default()
+    };
+    let mut clone_data = Vec::new();
+    clone_state.try_serialize(&mut clone_data).unwrap();
+
+    // Oracle 0 prices the collateral, oracles 1..=20 price each pool's onAsset.
+    let mut oracles: Oracles = bytemuck::Zeroable::zeroed();
+    for _ in 0..=NUM_BENCHMARK_POOLS {
+        oracles
+            .append_oracle(OracleInfo {
+                address: Pubkey::new_unique(),
+                price: 100_000_000,
+                expo: 8,
+                last_update_slot: BENCHMARK_SLOT,
+                status: Status::Active,
+                ..OracleInfo::default()
+            })
+            .unwrap();
+    }
+
+    let mut pools: Pools = bytemuck::Zeroable::zeroed();
+    for index in 0..NUM_BENCHMARK_POOLS {
+        pools
+            .append_pool(Pool {
+                underlying_asset_token_account: Pubkey::new_unique(),
+                committed_collateral_liquidity: 1_000_000_000_000,
+                asset_info: AssetInfo {
+                    onasset_mint: Pubkey::new_unique(),
+                    il_health_score_coefficient: 100,
+                    position_health_score_coefficient: 100,
+                    min_overcollateral_ratio: 150,
+                    max_liquidation_overcollateral_ratio: 200,
+                    oracle_info_index: (index + 1) as u8,
+                    ..AssetInfo::default()
+                },
+                treasury_trading_fee_bps: 10,
+                liquidity_trading_fee_bps: 20,
+                status: Status::Active,
+                ..Pool::default()
+            })
+            .unwrap();
+    }
+
+    let collateral_amount = 1_000_000_000_000;
+    let positions: Vec<LiquidityPosition> = (0..num_positions)
+        .map(|index| LiquidityPosition {
+            committed_collateral_liquidity: 1_000_000_000,
+            pool_index: index as u8,
+            ..LiquidityPosition::default()
+        })
+        .collect();
+
+    let (pools_data, oracles_data, user_account_data) = match layout {
+        Layout::Legacy => (
+            legacy_pools_data(&pools),
+            legacy_oracles_data(&oracles),
+            legacy_user_data(collateral_amount, &positions),
+        ),
+        Layout::ZeroCopy => {
+            let mut user_account: User = bytemuck::Zeroable::zeroed();
+            user_account.comet.collateral_amount = collateral_amount;
+            user_account.comet.num_positions = num_positions as u64;
+            user_account.comet.positions_capacity = num_positions as u64;
+            let mut user_account_data = zero_copy_account_data(&user_account);
+            for position in positions.iter() {
+                user_account_data.extend_from_slice(bytemuck::bytes_of(position));
+            }
+            (
+                zero_copy_account_data(&pools),
+                zero_copy_account_data(&oracles),
+                user_account_data,
+            )
+        }
+    };
+
+    context.set_account(
+        &pda(&[CLONE_PROGRAM_SEED.as_ref()]),
+        &program_account(clone_data).into(),
+    );
+    context.set_account(
+        &pda(&[POOLS_SEED.as_ref()]),
+        &program_account(pools_data).into(),
+    );
+    context.set_account(
+        &pda(&[ORACLES_SEED.as_ref()]),
+        &program_account(oracles_data).into(),
+    );
+    context.set_account(
+        &pda(&[USER_SEED.as_ref(), user.pubkey().as_ref()]),
+        &program_account(user_account_data).into(),
+    );
+
+    (context, user)
+}
+
+async fn add_liquidity_to_comet_units(layout: Layout, num_positions: usize) -> u64 {
+    let (mut context, user) = setup(layout, num_positions).await;
+
+    let user_account = pda(&[USER_SEED.as_ref(), user.pubkey().as_ref()]);
+    let instruction = match layout {
+        // The Borsh version took neither a payer nor bounds.
+        Layout::Legacy => {
+            let mut data = clone::instruction::AddLiquidityToComet::discriminator().to_vec();
+            put(&mut data, 0u8);
+            put(&mut data, 1_000_000u64);
+            Instruction {
+                program_id: clone::ID,
+                accounts: vec![
+                    AccountMeta::new_readonly(user.pubkey(), true),
+                    AccountMeta::new(user_account, false),
+                    AccountMeta::new(pda(&[CLONE_PROGRAM_SEED.as_ref()]), false),
+                    AccountMeta::new(pda(&[POOLS_SEED.as_ref()]), false),
+                    AccountMeta::new(pda(&[ORACLES_SEED.as_ref()]), false),
+                ],
+                data,
+            }
+        }
+        Layout::ZeroCopy => Instruction {
+            program_id: clone::ID,
+            accounts: clone::accounts::AddLiquidityToComet {
+                user: user.pubkey(),
+                payer: context.payer.pubkey(),
+                user_account,
+                clone: pda(&[CLONE_PROGRAM_SEED.as_ref()]),
+                pools: pda(&[POOLS_SEED.as_ref()]),
+                oracles: pda(&[ORACLES_SEED.as_ref()]),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone::instruction::AddLiquidityToComet {
+                pool_index: 0,
+                collateral_amount: 1_000_000,
+                bounds: Default::default(),
+            }
+            .data(),
+        },
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(
+        matches!(simulation.result, Some(Ok(()))),
+        "{:?}",
+        simulation.result
+    );
+    simulation.simulation_details.unwrap().units_consumed
+}
+
+#[tokio::test]
+#[ignore = "requires BPF builds of the current and the legacy clone program"]
+async fn benchmark_add_liquidity_to_comet_against_borsh_layouts() {
+    for num_positions in [1, NUM_BENCHMARK_POOLS] {
+        let legacy_units = add_liquidity_to_comet_units(Layout::Legacy, num_positions).await;
+        let zero_copy_units = add_liquidity_to_comet_units(Layout::ZeroCopy, num_positions).await;
+        println!(
+            "add_liquidity_to_comet, {num_positions} comet positions: \
+             Borsh {legacy_units} CU, zero-copy {zero_copy_units} CU, saved {} CU",
+            legacy_units as i64 - zero_copy_units as i64
+        );
+        // Borsh decodes and re-encodes every pool, oracle and position, so
+        // the saving grows with the account contents.
+        assert!(
+            zero_copy_units < legacy_units,
+            "zero-copy used {zero_copy_units} CU, Borsh {legacy_units} CU"
+        );
+    }
+}
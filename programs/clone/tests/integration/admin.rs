@@ -0,0 +1,691 @@
+use crate::fixture::*;
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use clone::error::CloneError;
+use clone::instructions::{CloneParameters, MigrationTarget, PoolParameters};
+use clone::states::{
+    AdminMultisig, AssetInfo, OracleInfo, OracleSource, Oracles, Pool, Pools, Status, NUM_AUTH,
+    NUM_POOLS,
+};
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer,
+};
+use std::mem::size_of;
+
+/// Size every `Pools` and `Oracles` account had before the zero-copy layouts.
+const LEGACY_ACCOUNT_SPACE: usize = 10240;
+
+#[tokio::test]
+async fn initialize_clone_rejects_fees_out_of_range() {
+    let mut env = TestEnv::start().await;
+    let create_vault = env.create_token_account_ix(&clone_address(), &env.collateral_mint);
+    env.process(&[create_vault], &[]).await.unwrap();
+    // A zero flash loan fee would make flash loans free.
+    for (liquidator_fee_bps, flash_loan_fee_bps) in [
+        (10_000, FLASH_LOAN_FEE_BPS),
+        (LIQUIDATOR_FEE_BPS, 0),
+        (LIQUIDATOR_FEE_BPS, 10_000),
+    ] {
+        let initialize = env.initialize_clone_ix(
+            liquidator_fee_bps,
+            LIQUIDATOR_FEE_BPS,
+            LIQUIDATOR_FEE_BPS,
+            flash_loan_fee_bps,
+        );
+        let result = env.process(&[initialize], &[]).await;
+        assert_clone_error(result, CloneError::InvalidValueRange);
+    }
+}
+
+#[tokio::test]
+async fn initialize_sets_up_protocol_accounts() {
+    let mut env = TestEnv::new().await;
+
+    let clone_state = env.clone_state().await;
+    assert_eq!(clone_state.admin, env.admin.pubkey());
+    assert_eq!(clone_state.treasury_address, env.treasury.pubkey());
+    assert_eq!(clone_state.collateral.mint, env.collateral_mint);
+    assert_eq!(clone_state.collateral.vault, env.vault());
+    assert_eq!(clone_state.collateral.scale, COLLATERAL_SCALE);
+    assert_eq!(
+        clone_state.collateral.oracle_info_index,
+        COLLATERAL_ORACLE_INDEX
+    );
+    assert_eq!(clone_state.borrow_liquidator_fee_bps, LIQUIDATOR_FEE_BPS);
+    assert_eq!(clone_state.flash_loan_fee_bps, FLASH_LOAN_FEE_BPS);
+
+    let oracles = env.oracles_state().await;
+    assert_eq!(oracles.num_oracles, env.oracles.len() as u64);
+    for (oracle, address) in oracles.oracles.iter().zip(env.oracles.iter()) {
+        assert_eq!(oracle.address, *address);
+        assert_eq!(oracle.status, Status::Active);
+    }
+
+    let pools = env.pools_state().await;
+    assert_eq!(pools.num_pools, 2);
+    for (index, pool) in pools.pools[..2].iter().enumerate() {
+        assert_eq!(pool.status, Status::Active);
+        assert_eq!(pool.asset_info.onasset_mint, env.onasset_mint(index as u8));
+        assert_eq!(pool.asset_info.oracle_info_index, index as u8 + 1);
+        assert_eq!(pool.liquidity_trading_fee_bps, LIQUIDITY_TRADING_FEE_BPS);
+        assert_eq!(pool.treasury_trading_fee_bps, TREASURY_TRADING_FEE_BPS);
+        assert_eq!(
+            pool.underlying_asset_token_account,
+            token_address(&clone_address(), &env.underlying_mint(index as u8))
+        );
+    }
+}
+
+#[tokio::test]
+async fn update_clone_parameters_manages_auth_list() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let auth = Pubkey::new_unique();
+
+    let add_auth =
+        env.update_clone_parameters_ix(&admin, CloneParameters::AddAuth { address: auth });
+    env.process(&[add_auth.clone()], &[]).await.unwrap();
+    assert!(env.clone_state().await.auth.contains(&auth));
+
+    let result = env.process(&[add_auth], &[]).await;
+    assert_clone_error(result, CloneError::AuthAlreadyExists);
+
+    let fill_auth: Vec<_> = (1..NUM_AUTH)
+        .map(|_| {
+            env.update_clone_parameters_ix(
+                &admin,
+                CloneParameters::AddAuth {
+                    address: Pubkey::new_unique(),
+                },
+            )
+        })
+        .collect();
+    env.process(&fill_auth, &[]).await.unwrap();
+    let one_too_many = env.update_clone_parameters_ix(
+        &admin,
+        CloneParameters::AddAuth {
+            address: Pubkey::new_unique(),
+        },
+    );
+    let result = env.process(&[one_too_many], &[]).await;
+    assert_clone_error(result, CloneError::AuthArrayFull);
+
+    let remove_auth =
+        env.update_clone_parameters_ix(&admin, CloneParameters::RemoveAuth { address: auth });
+    env.process(&[remove_auth.clone()], &[]).await.unwrap();
+    assert!(!env.clone_state().await.auth.contains(&auth));
+
+    let result = env.process(&[remove_auth], &[]).await;
+    assert_clone_error(result, CloneError::AuthNotFound);
+}
+
+#[tokio::test]
+async fn update_clone_parameters_requires_admin() {
+    let mut env = TestEnv::new().await;
+    let outsider = Keypair::new();
+
+    let update = env.update_clone_parameters_ix(
+        &outsider.pubkey(),
+        CloneParameters::NonAuthLiquidationsEnabled { value: true },
+    );
+    let result = env.process(&[update], &[&outsider]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+
+    let admin = env.admin.pubkey();
+    let treasury = Pubkey::new_unique();
+    let updates = [
+        env.update_clone_parameters_ix(
+            &admin,
+            CloneParameters::NonAuthLiquidationsEnabled { value: true },
+        ),
+        env.update_clone_parameters_ix(
+            &admin,
+            CloneParameters::BorrowLiquidationFee { value: 250 },
+        ),
+        env.update_clone_parameters_ix(
+            &admin,
+            CloneParameters::CometCollateralLiquidationFee { value: 300 },
+        ),
+        env.update_clone_parameters_ix(
+            &admin,
+            CloneParameters::CometOnassetLiquidationFee { value: 350 },
+        ),
+        env.update_clone_parameters_ix(
+            &admin,
+            CloneParameters::CollateralizationRatio { value: 90 },
+        ),
+        env.update_clone_parameters_ix(
+            &admin,
+            CloneParameters::TreasuryAddress { address: treasury },
+        ),
+    ];
+    env.process(&updates, &[]).await.unwrap();
+
+    let clone_state = env.clone_state().await;
+    assert!(clone_state.non_auth_liquidations_enabled);
+    assert_eq!(clone_state.borrow_liquidator_fee_bps, 250);
+    assert_eq!(clone_state.comet_collateral_ild_liquidator_fee_bps, 300);
+    assert_eq!(clone_state.comet_onasset_ild_liquidator_fee_bps, 350);
+    assert_eq!(clone_state.collateral.collateralization_ratio, 90);
+    assert_eq!(clone_state.treasury_address, treasury);
+}
+
+#[tokio::test]
+async fn admin_handover_needs_acceptance() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let new_admin = env.create_user(0).await;
+
+    let propose = env.update_clone_parameters_ix(
+        &admin,
+        CloneParameters::PendingAdmin {
+            address: new_admin.pubkey(),
+        },
+    );
+    env.process(&[propose], &[]).await.unwrap();
+    let update = env.update_clone_parameters_ix(
+        &new_admin.pubkey(),
+        CloneParameters::FlashLoanFee { value: 9 },
+    );
+    let result = env.process(&[update.clone()], &[&new_admin]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+
+    let outsider = env.create_user(0).await;
+    let accept = env.accept_admin_ix(&outsider.pubkey());
+    let result = env.process(&[accept], &[&outsider]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+
+    let accept = env.accept_admin_ix(&new_admin.pubkey());
+    env.process(&[accept, update], &[&new_admin]).await.unwrap();
+    let clone_state = env.clone_state().await;
+    assert_eq!(clone_state.admin, new_admin.pubkey());
+    assert_eq!(clone_state.pending_admin, Pubkey::default());
+    assert_eq!(clone_state.flash_loan_fee_bps, 9);
+
+    let update = env.update_clone_parameters_ix(&admin, CloneParameters::FlashLoanFee { value: 0 });
+    let result = env.process(&[update], &[]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+}
+
+#[tokio::test]
+async fn migrate_clone_from_before_pending_admin() {
+    let mut env = TestEnv::new().await;
+    let account = env.account(&clone_address()).await.unwrap();
+    let legacy_account = Account {
+        data: account.data[..8 + 472].to_vec(),
+        ..account.clone()
+    };
+    env.context
+        .set_account(&clone_address(), &legacy_account.clone().into());
+
+    let admin = env.admin.pubkey();
+    let update = env.update_clone_parameters_ix(&admin, CloneParameters::FlashLoanFee { value: 9 });
+    let result = env.process(&[update.clone()], &[]).await;
+    assert_anchor_error(result, ErrorCode::AccountDidNotDeserialize);
+
+    // Anyone may grow it, the admin may be a PDA without lamports to spare.
+    let outsider = env.create_user(0).await;
+    let migrate =
+        env.migrate_account_layout_ix(&outsider.pubkey(), &clone_address(), MigrationTarget::Clone);
+    env.process(&[migrate.clone()], &[&outsider]).await.unwrap();
+    let migrated = env.account(&clone_address()).await.unwrap();
+    assert_eq!(migrated.data, account.data);
+    assert_eq!(env.clone_state().await.pending_admin, Pubkey::default());
+
+    env.process(&[update], &[]).await.unwrap();
+    let result = env.process(&[migrate], &[&outsider]).await;
+    assert_clone_error(result, CloneError::AccountAlreadyMigrated);
+
+    // Pools are written in their current layout by `migrate_to_zero_copy`.
+    let migrate = env.migrate_account_layout_ix(&admin, &pools_address(), MigrationTarget::Pools);
+    let result = env.process(&[migrate], &[]).await;
+    assert_clone_error(result, CloneError::AccountAlreadyMigrated);
+}
+
+#[tokio::test]
+async fn update_pool_parameters_checks_permissions() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let auth = Keypair::new();
+    let outsider = Keypair::new();
+
+    let add_auth = env.update_clone_parameters_ix(
+        &admin,
+        CloneParameters::AddAuth {
+            address: auth.pubkey(),
+        },
+    );
+    env.process(&[add_auth], &[]).await.unwrap();
+
+    let freeze = PoolParameters::Status {
+        value: Status::Frozen,
+    };
+    let update = env.update_pool_parameters_ix(&outsider.pubkey(), PYTH_POOL, freeze);
+    let result = env.process(&[update], &[&outsider]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    // Auths may only freeze pools.
+    let update = env.update_pool_parameters_ix(
+        &auth.pubkey(),
+        PYTH_POOL,
+        PoolParameters::TreasuryTradingFee { value: 0 },
+    );
+    let result = env.process(&[update], &[&auth]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let update = env.update_pool_parameters_ix(&auth.pubkey(), PYTH_POOL, freeze);
+    env.process(&[update], &[&auth]).await.unwrap();
+    assert_eq!(env.pools_state().await.pools[0].status, Status::Frozen);
+
+    let update = env.update_pool_parameters_ix(
+        &auth.pubkey(),
+        PYTH_POOL,
+        PoolParameters::Status {
+            value: Status::Active,
+        },
+    );
+    let result = env.process(&[update], &[&auth]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+}
+
+#[tokio::test]
+async fn update_pool_parameters_validates_values() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+
+    let update = env.update_pool_parameters_ix(
+        &admin,
+        2,
+        PoolParameters::Status {
+            value: Status::Frozen,
+        },
+    );
+    let result = env.process(&[update], &[]).await;
+    assert_clone_error(result, CloneError::PoolNotFound);
+
+    let update = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::MaxLiquidationOvercollateralRatio {
+            value: MIN_OVERCOLLATERAL_RATIO,
+        },
+    );
+    let result = env.process(&[update], &[]).await;
+    assert_clone_error(result, CloneError::InvalidOvercollateralizationRatios);
+
+    let updates = [
+        env.update_pool_parameters_ix(
+            &admin,
+            PYTH_POOL,
+            PoolParameters::LiquidityTradingFee { value: 20 },
+        ),
+        env.update_pool_parameters_ix(
+            &admin,
+            PYTH_POOL,
+            PoolParameters::TreasuryTradingFee { value: 5 },
+        ),
+        env.update_pool_parameters_ix(
+            &admin,
+            PYTH_POOL,
+            PoolParameters::MinOvercollateralRatio { value: 120 },
+        ),
+        env.update_pool_parameters_ix(
+            &admin,
+            PYTH_POOL,
+            PoolParameters::MaxLiquidationOvercollateralRatio { value: 180 },
+        ),
+        env.update_pool_parameters_ix(
+            &admin,
+            PYTH_POOL,
+            PoolParameters::IlHealthScoreCoefficient { value: 150 },
+        ),
+        env.update_pool_parameters_ix(
+            &admin,
+            PYTH_POOL,
+            PoolParameters::PositionHealthScoreCoefficient { value: 50 },
+        ),
+        env.update_pool_parameters_ix(
+            &admin,
+            PYTH_POOL,
+            PoolParameters::OracleInfoIndex { value: 2 },
+        ),
+    ];
+    env.process(&updates, &[]).await.unwrap();
+
+    let pool = env.pools_state().await.pools[0];
+    assert_eq!(pool.liquidity_trading_fee_bps, 20);
+    assert_eq!(pool.treasury_trading_fee_bps, 5);
+    assert_eq!(pool.asset_info.min_overcollateral_ratio, 120);
+    assert_eq!(pool.asset_info.max_liquidation_overcollateral_ratio, 180);
+    assert_eq!(pool.asset_info.il_health_score_coefficient, 150);
+    assert_eq!(pool.asset_info.position_health_score_coefficient, 50);
+    assert_eq!(pool.asset_info.oracle_info_index, 2);
+}
+
+#[tokio::test]
+async fn add_pool_validates_and_fills_pool_array() {
+    let mut env = TestEnv::new().await;
+    let onasset_mint = env.onasset_mint(PYTH_POOL);
+    let underlying_mint = env.underlying_mint(PYTH_POOL);
+
+    let add_pool = env.add_pool_ix(&onasset_mint, &underlying_mint, 100, 200, 1);
+    let result = env.process(&[add_pool], &[]).await;
+    assert_clone_error(result, CloneError::InvalidOvercollateralizationRatios);
+
+    let add_pool = env.add_pool_ix(&onasset_mint, &underlying_mint, 150, 150, 1);
+    let result = env.process(&[add_pool], &[]).await;
+    assert_clone_error(result, CloneError::InvalidOvercollateralizationRatios);
+
+    // The pool array has no uniqueness requirement, so reuse pool 0's mints.
+    let add_pool = env.add_pool_ix(
+        &onasset_mint,
+        &underlying_mint,
+        MIN_OVERCOLLATERAL_RATIO,
+        MAX_LIQUIDATION_OVERCOLLATERAL_RATIO,
+        1,
+    );
+    let mut num_pools = 2;
+    while num_pools < NUM_POOLS {
+        let batch = vec![add_pool.clone(); (NUM_POOLS - num_pools).min(4)];
+        env.process(&batch, &[]).await.unwrap();
+        num_pools += batch.len();
+    }
+    assert_eq!(env.pools_state().await.num_pools, NUM_POOLS as u64);
+
+    let result = env.process(&[add_pool], &[]).await;
+    assert_clone_error(result, CloneError::PoolArrayFull);
+}
+
+#[tokio::test]
+async fn remove_pool_requires_deprecation_and_sweeps_underlying() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let user = env.create_user(0).await;
+    let wrapped = underlying(5);
+
+    let instructions = [
+        env.mint_tokens_ix(
+            &env.underlying_mint(SWITCHBOARD_POOL),
+            &user.pubkey(),
+            wrapped,
+        ),
+        env.wrap_asset_ix(&user.pubkey(), SWITCHBOARD_POOL, wrapped),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    let remove_pool = env.remove_pool_ix(&admin, SWITCHBOARD_POOL);
+    let result = env.process(&[remove_pool.clone()], &[]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+
+    let deprecate = env.update_pool_parameters_ix(
+        &admin,
+        SWITCHBOARD_POOL,
+        PoolParameters::Status {
+            value: Status::Deprecation,
+        },
+    );
+    env.process(&[deprecate, remove_pool], &[]).await.unwrap();
+
+    assert_eq!(env.pools_state().await.num_pools, 1);
+    let treasury_account = token_address(
+        &env.treasury.pubkey(),
+        &env.underlying_mint(SWITCHBOARD_POOL),
+    );
+    assert_eq!(env.token_balance(&treasury_account).await, wrapped);
+
+    let remove_pool = env.remove_pool_ix(&admin, SWITCHBOARD_POOL);
+    let result = env.process(&[remove_pool], &[]).await;
+    assert_clone_error(result, CloneError::PoolNotFound);
+}
+
+#[tokio::test]
+async fn create_token_metadata_requires_admin() {
+    let mut env = TestEnv::new().await;
+    let outsider = Keypair::new();
+    let mint = env.onasset_mint(PYTH_POOL);
+
+    // The success path CPIs into Metaplex, which isn't loaded offline.
+    let create_metadata = env.create_token_metadata_ix(&outsider.pubkey(), &mint);
+    let result = env.process(&[create_metadata], &[&outsider]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+}
+
+async fn admin_multisig_state(env: &mut TestEnv) -> AdminMultisig {
+    let account = env.account(&admin_multisig_address()).await.unwrap();
+    AdminMultisig::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[tokio::test]
+async fn admin_multisig_executes_approved_transactions() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let multisig = admin_multisig_address();
+    let mut signers = Vec::new();
+    for _ in 0..3 {
+        signers.push(env.create_user(0).await);
+    }
+    let keys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+
+    for (invalid_signers, threshold) in [
+        (keys.clone(), 0),
+        (keys.clone(), 4),
+        (vec![keys[0], keys[0]], 1),
+        (vec![], 0),
+    ] {
+        let initialize = env.initialize_admin_multisig_ix(invalid_signers, threshold);
+        let result = env.process(&[initialize], &[]).await;
+        assert_clone_error(result, CloneError::InvalidMultisigSigners);
+    }
+    let instructions = [
+        env.initialize_admin_multisig_ix(keys.clone(), 2),
+        env.update_clone_parameters_ix(&admin, CloneParameters::PendingAdmin { address: multisig }),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+    // The handover only completes once the multisig accepts it.
+    assert_eq!(env.clone_state().await.admin, admin);
+
+    let accept = env.accept_admin_ix(&multisig);
+    let outsider = env.create_user(0).await;
+    let propose = env.propose_admin_transaction_ix(&outsider.pubkey(), 0, &accept);
+    let result = env.process(&[propose], &[&outsider]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let propose = env.propose_admin_transaction_ix(&keys[0], 0, &accept);
+    env.process(&[propose], &[&signers[0]]).await.unwrap();
+    let execute = env.execute_admin_transaction_ix(0, &accept);
+    let result = env.process(&[execute.clone()], &[]).await;
+    assert_clone_error(result, CloneError::NotEnoughApprovals);
+    let approve = env.approve_admin_transaction_ix(&keys[1], 0);
+    env.process(&[approve], &[&signers[1]]).await.unwrap();
+    env.process(&[execute], &[]).await.unwrap();
+    let clone = env.clone_state().await;
+    assert_eq!(
+        (clone.admin, clone.pending_admin),
+        (multisig, Pubkey::default())
+    );
+
+    let payload =
+        env.update_clone_parameters_ix(&multisig, CloneParameters::FlashLoanFee { value: 9 });
+    let instructions = [
+        env.propose_admin_transaction_ix(&keys[0], 1, &payload),
+        env.approve_admin_transaction_ix(&keys[1], 1),
+    ];
+    env.process(&instructions, &[&signers[0], &signers[1]])
+        .await
+        .unwrap();
+    let execute = env.execute_admin_transaction_ix(1, &payload);
+    env.process(&[execute.clone()], &[]).await.unwrap();
+    assert_eq!(env.clone_state().await.flash_loan_fee_bps, 9);
+    let result = env.process(&[execute], &[]).await;
+    assert_clone_error(result, CloneError::AdminTransactionExecuted);
+
+    // The former admin key no longer controls the protocol.
+    let update = env.update_clone_parameters_ix(&admin, CloneParameters::FlashLoanFee { value: 0 });
+    let result = env.process(&[update], &[]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+
+    // The signers can only change through the multisig, which drops
+    // transactions proposed under the old signers.
+    let update_multisig = env.update_admin_multisig_ix(vec![keys[0]], 1);
+    let result = env.process(&[update_multisig.clone()], &[]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+    let pending =
+        env.update_clone_parameters_ix(&multisig, CloneParameters::FlashLoanFee { value: 1 });
+    let instructions = [
+        env.propose_admin_transaction_ix(&keys[0], 2, &update_multisig),
+        env.propose_admin_transaction_ix(&keys[0], 3, &pending),
+        env.approve_admin_transaction_ix(&keys[2], 2),
+    ];
+    env.process(&instructions, &[&signers[0], &signers[2]])
+        .await
+        .unwrap();
+    let execute = env.execute_admin_transaction_ix(2, &update_multisig);
+    env.process(&[execute], &[]).await.unwrap();
+    let admin_multisig = admin_multisig_state(&mut env).await;
+    assert_eq!(admin_multisig.signers[..1], [keys[0]]);
+    assert_eq!(
+        (admin_multisig.num_signers, admin_multisig.threshold),
+        (1, 1)
+    );
+
+    let execute = env.execute_admin_transaction_ix(3, &pending);
+    let result = env.process(&[execute], &[]).await;
+    assert_clone_error(result, CloneError::StaleAdminTransaction);
+    assert_eq!(env.clone_state().await.flash_loan_fee_bps, 9);
+}
+
+#[tokio::test]
+async fn admin_multisig_accepts_staking_admin() {
+    let mut env = TestEnv::new().await;
+    env.configure_staking(0, onasset(100), 20, 10).await;
+    let multisig = admin_multisig_address();
+    let signer = env.create_user(0).await;
+    let instructions = [
+        env.initialize_admin_multisig_ix(vec![signer.pubkey()], 1),
+        env.update_staking_params_ix(clone_staking::Parameters::PendingAdmin {
+            pending_admin: multisig,
+        }),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+
+    let accept = env.accept_staking_admin_ix(&multisig);
+    let instructions = [
+        env.propose_admin_transaction_ix(&signer.pubkey(), 0, &accept),
+        env.execute_admin_transaction_ix(0, &accept),
+    ];
+    env.process(&instructions, &[&signer]).await.unwrap();
+
+    let account = env.account(&staking_address()).await.unwrap();
+    let clone_staking =
+        clone_staking::states::CloneStaking::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(clone_staking.admin, multisig);
+    assert_eq!(clone_staking.pending_admin, Pubkey::default());
+}
+
+#[tokio::test]
+async fn migrate_legacy_pools_and_oracles() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let legacy_account = |mut data: Vec<u8>| {
+        data.resize(LEGACY_ACCOUNT_SPACE, 0);
+        Account {
+            lamports: Rent::default().minimum_balance(LEGACY_ACCOUNT_SPACE),
+            data,
+            owner: clone::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    };
+
+    // One pool and one oracle in the legacy Borsh layouts.
+    let underlying = Pubkey::new_unique();
+    let onasset_mint = Pubkey::new_unique();
+    let mut data = Pools::discriminator().to_vec();
+    data.extend(1u32.to_le_bytes());
+    data.extend(underlying.to_bytes());
+    data.extend(collateral(500).to_le_bytes());
+    data.extend((-7i64).to_le_bytes());
+    data.extend(9i64.to_le_bytes());
+    data.extend(30u16.to_le_bytes());
+    data.extend(20u16.to_le_bytes());
+    data.extend(onasset_mint.to_bytes());
+    data.push(1);
+    for ratio in [100u16, 200, 150, 170] {
+        data.extend(ratio.to_le_bytes());
+    }
+    data.push(Status::Active as u8);
+    env.context
+        .set_account(&pools_address(), &legacy_account(data).into());
+
+    let oracle_address = Pubkey::new_unique();
+    let mut data = Oracles::discriminator().to_vec();
+    data.extend(1u32.to_le_bytes());
+    data.push(OracleSource::SWITCHBOARD as u8);
+    data.extend(oracle_address.to_bytes());
+    data.extend(12_345i64.to_le_bytes());
+    data.push(8);
+    data.push(Status::Active as u8);
+    data.extend(42u64.to_le_bytes());
+    data.push(2);
+    env.context
+        .set_account(&oracles_address(), &legacy_account(data).into());
+
+    // Pools grows past the legacy size, the signer topping up its rent.
+    let migrate = env.migrate_to_zero_copy_ix(&admin, &pools_address(), MigrationTarget::Pools);
+    env.process(&[migrate.clone()], &[]).await.unwrap();
+    let pools = env.pools_state().await;
+    assert_eq!(pools.num_pools, 1);
+    assert_eq!(
+        pools.pools[0],
+        Pool {
+            underlying_asset_token_account: underlying,
+            committed_collateral_liquidity: collateral(500),
+            collateral_ild: -7,
+            onasset_ild: 9,
+            asset_info: AssetInfo {
+                onasset_mint,
+                oracle_info_index: 1,
+                il_health_score_coefficient: 100,
+                position_health_score_coefficient: 200,
+                min_overcollateral_ratio: 150,
+                max_liquidation_overcollateral_ratio: 170,
+                ..AssetInfo::default()
+            },
+            treasury_trading_fee_bps: 30,
+            liquidity_trading_fee_bps: 20,
+            status: Status::Active,
+            ..Pool::default()
+        }
+    );
+    assert!(8 + size_of::<Pools>() > LEGACY_ACCOUNT_SPACE);
+    assert_eq!(
+        env.lamports(&pools_address()).await,
+        Rent::default().minimum_balance(8 + size_of::<Pools>())
+    );
+
+    // Oracles shrinks, the signer receiving the freed rent.
+    let migrate = env.migrate_to_zero_copy_ix(&admin, &oracles_address(), MigrationTarget::Oracles);
+    env.process(&[migrate], &[]).await.unwrap();
+    let oracles = env.oracles_state().await;
+    assert_eq!(oracles.num_oracles, 1);
+    assert_eq!(
+        oracles.oracles[0],
+        OracleInfo {
+            address: oracle_address,
+            price: 12_345,
+            last_update_slot: 42,
+            source: OracleSource::SWITCHBOARD,
+            expo: 8,
+            status: Status::Active,
+            rescale_factor: 2,
+            ..OracleInfo::default()
+        }
+    );
+    assert_eq!(
+        env.lamports(&oracles_address()).await,
+        Rent::default().minimum_balance(8 + size_of::<Oracles>())
+    );
+
+    let result = env.process(&[migrate], &[]).await;
+    assert_clone_error(result, CloneError::AccountAlreadyMigrated);
+}
@@ -0,0 +1,274 @@
+use crate::fixture::*;
+use clone::error::CloneError;
+use clone::instructions::{BorrowBounds, PoolParameters};
+use clone::states::Status;
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn borrow_position_lifecycle() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(collateral(2_000)).await;
+    let authority = user.pubkey();
+
+    // 1,000 collateral against 50 onAssets at 10.00 is a 200% ratio.
+    let borrow =
+        env.initialize_borrow_position_ix(&authority, PYTH_POOL, onasset(50), collateral(1_000));
+    env.process_with_prices(borrow, &[&user]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        onasset(50)
+    );
+    assert_eq!(env.collateral_balance(&authority).await, collateral(1_000));
+    let vault = env.vault();
+    assert_eq!(env.token_balance(&vault).await, collateral(1_000));
+
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.header.num_borrows, 1);
+    assert_eq!(state.borrows[0].pool_index, PYTH_POOL);
+    assert_eq!(state.borrows[0].borrowed_onasset, onasset(50));
+    assert_eq!(state.borrows[0].collateral_amount, collateral(1_000));
+
+    // 70 onAssets would drop the ratio to ~143%, below the 150% minimum.
+    let borrow_more = env.borrow_more_ix(&authority, PYTH_POOL, 0, onasset(20));
+    let result = env.process_with_prices(borrow_more, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidMintCollateralRatio);
+
+    let borrow_more = env.borrow_more_ix(&authority, PYTH_POOL, 0, onasset(10));
+    env.process_with_prices(borrow_more, &[&user])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        onasset(60)
+    );
+
+    let add_collateral = env.add_collateral_to_borrow_ix(&authority, 0, collateral(500));
+    env.process(&[add_collateral], &[&user]).await.unwrap();
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.borrows[0].collateral_amount, collateral(1_500));
+
+    let withdraw = env.withdraw_collateral_from_borrow_ix(&authority, 0, collateral(1_000));
+    let result = env.process_with_prices(withdraw, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidMintCollateralRatio);
+
+    let withdraw = env.withdraw_collateral_from_borrow_ix(&authority, 0, collateral(500));
+    env.process_with_prices(withdraw, &[&user]).await.unwrap();
+    assert_eq!(env.collateral_balance(&authority).await, collateral(1_000));
+
+    let pay_debt = env.pay_borrow_debt_ix(&authority, &authority, PYTH_POOL, 0, onasset(61));
+    let result = env.process(&[pay_debt], &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAccountBalance);
+
+    let pay_debt = env.pay_borrow_debt_ix(&authority, &authority, PYTH_POOL, 0, onasset(60));
+    env.process(&[pay_debt], &[&user]).await.unwrap();
+    assert_eq!(env.onasset_balance(&authority, PYTH_POOL).await, 0);
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.borrows[0].borrowed_onasset, 0);
+
+    // Withdrawing the remaining collateral closes the position.
+    let withdraw = env.withdraw_collateral_from_borrow_ix(&authority, 0, collateral(1_000));
+    env.process_with_prices(withdraw, &[&user]).await.unwrap();
+    assert_eq!(env.collateral_balance(&authority).await, collateral(2_000));
+    assert_eq!(env.user_state(&authority).await.header.num_borrows, 0);
+}
+
+#[tokio::test]
+async fn borrow_position_on_switchboard_pool() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(collateral(1_000)).await;
+    let authority = user.pubkey();
+
+    // 20.00 per onAsset, 10 onAssets against 300 collateral is a 150% ratio.
+    let borrow = env.initialize_borrow_position_ix(
+        &authority,
+        SWITCHBOARD_POOL,
+        onasset(10),
+        collateral(299),
+    );
+    let result = env.process_with_prices(borrow, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidMintCollateralRatio);
+
+    let borrow = env.initialize_borrow_position_ix(
+        &authority,
+        SWITCHBOARD_POOL,
+        onasset(10),
+        collateral(300),
+    );
+    env.process_with_prices(borrow, &[&user]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, SWITCHBOARD_POOL).await,
+        onasset(10)
+    );
+}
+
+#[tokio::test]
+async fn borrow_instructions_validate_inputs() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let user = env.create_user(collateral(1_000)).await;
+    let authority = user.pubkey();
+
+    let borrow = env.initialize_borrow_position_ix(&authority, PYTH_POOL, onasset(1), 0);
+    let result = env.process_with_prices(borrow, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let add_collateral = env.add_collateral_to_borrow_ix(&authority, 0, collateral(1));
+    let result = env.process(&[add_collateral], &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidInputPositionIndex);
+
+    let borrow =
+        env.initialize_borrow_position_ix(&authority, PYTH_POOL, onasset(10), collateral(500));
+    env.process_with_prices(borrow, &[&user]).await.unwrap();
+
+    let pay_debt = env.pay_borrow_debt_ix(&authority, &authority, PYTH_POOL, 1, onasset(1));
+    let result = env.process(&[pay_debt], &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidInputPositionIndex);
+
+    let borrow_more = env.borrow_more_ix(&authority, PYTH_POOL, 0, 0);
+    let result = env.process_with_prices(borrow_more, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let withdraw = env.withdraw_collateral_from_borrow_ix(&authority, 0, 0);
+    let result = env.process_with_prices(withdraw, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let freeze = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::Status {
+            value: Status::Frozen,
+        },
+    );
+    env.process(&[freeze], &[]).await.unwrap();
+
+    let borrow =
+        env.initialize_borrow_position_ix(&authority, PYTH_POOL, onasset(1), collateral(100));
+    let result = env.process_with_prices(borrow, &[&user]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+
+    let pay_debt = env.pay_borrow_debt_ix(&authority, &authority, PYTH_POOL, 0, onasset(1));
+    let result = env.process(&[pay_debt], &[&user]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+
+    let borrow = env.initialize_borrow_position_ix(&authority, 2, onasset(1), collateral(100));
+    let result = env.process_with_prices(borrow, &[&user]).await;
+    assert_clone_error(result, CloneError::PoolNotFound);
+}
+
+#[tokio::test]
+async fn anyone_can_pay_down_borrow_debt() {
+    let mut env = TestEnv::new().await;
+    let borrower = env.create_user(collateral(1_000)).await;
+    let payer = env.create_user(collateral(1_000)).await;
+
+    for user in [&borrower, &payer] {
+        let borrow = env.initialize_borrow_position_ix(
+            &user.pubkey(),
+            PYTH_POOL,
+            onasset(10),
+            collateral(500),
+        );
+        env.process_with_prices(borrow, &[user]).await.unwrap();
+    }
+
+    let pay_debt = env.pay_borrow_debt_ix(
+        &payer.pubkey(),
+        &borrower.pubkey(),
+        PYTH_POOL,
+        0,
+        onasset(4),
+    );
+    env.process(&[pay_debt], &[&payer]).await.unwrap();
+
+    assert_eq!(
+        env.onasset_balance(&payer.pubkey(), PYTH_POOL).await,
+        onasset(6)
+    );
+    let state = env.user_state(&borrower.pubkey()).await;
+    assert_eq!(state.borrows[0].borrowed_onasset, onasset(6));
+}
+
+#[tokio::test]
+async fn borrow_instructions_respect_bounds() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(collateral(2_000)).await;
+    let authority = user.pubkey();
+    env.warp_slots(10).await;
+    let slot = env.current_slot().await;
+
+    let borrow = |env: &TestEnv, bounds| {
+        env.initialize_borrow_position_with_bounds_ix(
+            &authority,
+            PYTH_POOL,
+            onasset(50),
+            collateral(1_000),
+            bounds,
+        )
+    };
+    let rejected = [
+        (
+            BorrowBounds {
+                expiry_slot: Some(slot - 1),
+                ..BorrowBounds::default()
+            },
+            CloneError::TransactionExpired,
+        ),
+        (
+            BorrowBounds {
+                max_oracle_price: Some(PYTH_PRICE as u64 - 1),
+                ..BorrowBounds::default()
+            },
+            CloneError::SlippageToleranceExceeded,
+        ),
+        // 1,000 collateral against 50 onAssets at 10.00 is a 200% ratio.
+        (
+            BorrowBounds {
+                min_collateral_ratio: Some(200_000_001),
+                ..BorrowBounds::default()
+            },
+            CloneError::SlippageToleranceExceeded,
+        ),
+    ];
+    for (bounds, error) in rejected {
+        let result = env
+            .process_with_prices(borrow(&env, bounds), &[&user])
+            .await;
+        assert_clone_error(result, error);
+    }
+
+    let bounds = BorrowBounds {
+        expiry_slot: Some(slot),
+        max_oracle_price: Some(PYTH_PRICE as u64),
+        min_collateral_ratio: Some(200_000_000),
+    };
+    env.process_with_prices(borrow(&env, bounds), &[&user])
+        .await
+        .unwrap();
+
+    // 60 onAssets would leave a ~167% ratio.
+    let bounds = BorrowBounds {
+        min_collateral_ratio: Some(170_000_000),
+        ..BorrowBounds::default()
+    };
+    let borrow_more = env.borrow_more_with_bounds_ix(&authority, PYTH_POOL, 0, onasset(10), bounds);
+    let result = env.process_with_prices(borrow_more, &[&user]).await;
+    assert_clone_error(result, CloneError::SlippageToleranceExceeded);
+
+    let bounds = BorrowBounds {
+        expiry_slot: Some(slot),
+        min_collateral_ratio: Some(160_000_000),
+        ..BorrowBounds::default()
+    };
+    let borrow_more = env.borrow_more_with_bounds_ix(&authority, PYTH_POOL, 0, onasset(10), bounds);
+    env.process_with_prices(borrow_more.clone(), &[&user])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        onasset(60)
+    );
+
+    env.warp_slots(1).await;
+    let result = env.process_with_prices(borrow_more, &[&user]).await;
+    assert_clone_error(result, CloneError::TransactionExpired);
+}
@@ -0,0 +1,601 @@
+use crate::fixture::*;
+use clone::error::CloneError;
+use clone::instructions::{CometBounds, PaymentType, PoolParameters};
+use clone::states::Status;
+use solana_sdk::signer::Signer;
+
+#[tokio::test]
+async fn comet_collateral_and_liquidity() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(collateral(2_000)).await;
+    let authority = user.pubkey();
+
+    let add_collateral = env.add_collateral_to_comet_ix(&authority, collateral(1_000));
+    env.process(&[add_collateral], &[&user]).await.unwrap();
+    assert_eq!(env.collateral_balance(&authority).await, collateral(1_000));
+    let vault = env.vault();
+    assert_eq!(env.token_balance(&vault).await, collateral(1_000));
+
+    let add_liquidity = env.add_liquidity_to_comet_ix(&authority, PYTH_POOL, collateral(500));
+    env.process_with_prices(add_liquidity, &[&user])
+        .await
+        .unwrap();
+
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.header.comet.collateral_amount, collateral(1_000));
+    assert_eq!(state.header.comet.num_positions, 1);
+    assert_eq!(state.comet_positions[0].pool_index, PYTH_POOL);
+    assert_eq!(
+        state.comet_positions[0].committed_collateral_liquidity,
+        collateral(500)
+    );
+    assert_eq!(
+        env.pools_state().await.pools[0].committed_collateral_liquidity,
+        collateral(500)
+    );
+
+    // Committing 1,100 against 1,000 of collateral leaves no health.
+    let add_liquidity = env.add_liquidity_to_comet_ix(&authority, PYTH_POOL, collateral(600));
+    let result = env.process_with_prices(add_liquidity, &[&user]).await;
+    assert_clone_error(result, CloneError::HealthScoreTooLow);
+
+    let withdraw = env.withdraw_collateral_from_comet_ix(&authority, collateral(600));
+    let result = env.process_with_prices(withdraw, &[&user]).await;
+    assert_clone_error(result, CloneError::HealthScoreTooLow);
+
+    let withdraw = env.withdraw_collateral_from_comet_ix(&authority, 0);
+    let result = env.process_with_prices(withdraw, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let add_liquidity = env.add_liquidity_to_comet_ix(&authority, PYTH_POOL, 0);
+    let result = env.process_with_prices(add_liquidity, &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let add_liquidity = env.add_liquidity_to_comet_ix(&authority, 2, collateral(1));
+    let result = env.process_with_prices(add_liquidity, &[&user]).await;
+    assert_clone_error(result, CloneError::PoolNotFound);
+
+    let withdraw = env.withdraw_collateral_from_comet_ix(&authority, collateral(400));
+    env.process_with_prices(withdraw, &[&user]).await.unwrap();
+    assert_eq!(env.collateral_balance(&authority).await, collateral(1_400));
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.header.comet.collateral_amount, collateral(600));
+}
+
+#[tokio::test]
+async fn add_liquidity_requires_an_active_pool() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let user = env.create_user(collateral(1_000)).await;
+    let authority = user.pubkey();
+    let add_collateral = env.add_collateral_to_comet_ix(&authority, collateral(1_000));
+    env.process(&[add_collateral], &[&user]).await.unwrap();
+
+    let freeze = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::Status {
+            value: Status::Liquidation,
+        },
+    );
+    env.process(&[freeze], &[]).await.unwrap();
+
+    let add_liquidity = env.add_liquidity_to_comet_ix(&authority, PYTH_POOL, collateral(100));
+    let result = env.process_with_prices(add_liquidity, &[&user]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+}
+
+#[tokio::test]
+async fn collect_rewards_and_pay_onasset_ild() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = lp.pubkey();
+
+    let buy = env.swap_ix(&trader.pubkey(), PYTH_POOL, collateral(100), true, true, 0);
+    env.process_with_prices(buy, &[&trader]).await.unwrap();
+    let pool = env.pools_state().await.pools[0];
+    assert_eq!(pool.collateral_ild, -(collateral(100) as i64));
+    let onasset_ild_share = pool.onasset_ild as u64;
+
+    // As the only LP, the comet is owed all of the collateral paid in.
+    let collect = env.collect_lp_rewards_ix(&authority, PYTH_POOL, 0);
+    env.process(&[collect], &[&lp]).await.unwrap();
+    assert_eq!(env.collateral_balance(&authority).await, collateral(100));
+    let state = env.user_state(&authority).await;
+    assert_eq!(
+        state.comet_positions[0].collateral_ild_rebate,
+        -(collateral(100) as i64)
+    );
+
+    // With the rewards collected there is no collateral debt to pay.
+    let pay = env.pay_impermanent_loss_debt_ix(
+        &authority,
+        &authority,
+        PYTH_POOL,
+        0,
+        collateral(1),
+        PaymentType::Collateral,
+    );
+    let result = env.process(&[pay], &[&lp]).await;
+    assert_clone_error(result, CloneError::InvalidPaymentType);
+
+    let collateral_mint = env.collateral_mint;
+    let fund = env.mint_tokens_ix(&collateral_mint, &authority, collateral(1_000));
+    env.process(&[fund], &[]).await.unwrap();
+    let borrow =
+        env.initialize_borrow_position_ix(&authority, PYTH_POOL, onasset(20), collateral(1_000));
+    env.process_with_prices(borrow, &[&lp]).await.unwrap();
+
+    let pay = env.pay_impermanent_loss_debt_ix(
+        &authority,
+        &authority,
+        PYTH_POOL,
+        0,
+        0,
+        PaymentType::Onasset,
+    );
+    let result = env.process(&[pay], &[&lp]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    // Only the outstanding share is burned.
+    let pay = env.pay_impermanent_loss_debt_ix(
+        &authority,
+        &authority,
+        PYTH_POOL,
+        0,
+        onasset(20),
+        PaymentType::Onasset,
+    );
+    env.process(&[pay.clone()], &[&lp]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        onasset(20) - onasset_ild_share
+    );
+    let state = env.user_state(&authority).await;
+    assert_eq!(
+        state.comet_positions[0].onasset_ild_rebate,
+        onasset_ild_share as i64
+    );
+
+    let result = env.process(&[pay], &[&lp]).await;
+    assert_clone_error(result, CloneError::InvalidPaymentType);
+}
+
+#[tokio::test]
+async fn pay_collateral_ild() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(20))
+        .await;
+    let authority = lp.pubkey();
+
+    let sell = env.swap_ix(&trader.pubkey(), PYTH_POOL, onasset(10), true, false, 0);
+    env.process_with_prices(sell, &[&trader]).await.unwrap();
+    let collateral_ild_share = env.pools_state().await.pools[0].collateral_ild as u64;
+    assert!(collateral_ild_share > 0);
+
+    // Only the owner may pay from their wallet.
+    let pay = env.pay_impermanent_loss_debt_ix(
+        &trader.pubkey(),
+        &authority,
+        PYTH_POOL,
+        0,
+        collateral(1),
+        PaymentType::CollateralFromWallet,
+    );
+    let result = env.process(&[pay], &[&trader]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let pay = env.pay_impermanent_loss_debt_ix(
+        &authority,
+        &authority,
+        PYTH_POOL,
+        0,
+        collateral(1_000),
+        PaymentType::Collateral,
+    );
+    env.process(&[pay], &[&lp]).await.unwrap();
+    let state = env.user_state(&authority).await;
+    assert_eq!(
+        state.header.comet.collateral_amount,
+        collateral(1_000) - collateral_ild_share
+    );
+    assert_eq!(
+        state.comet_positions[0].collateral_ild_rebate,
+        collateral_ild_share as i64
+    );
+}
+
+#[tokio::test]
+async fn withdraw_liquidity_and_remove_position() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(200))
+        .await;
+    let authority = lp.pubkey();
+    let add_liquidity =
+        env.add_liquidity_to_comet_ix(&authority, SWITCHBOARD_POOL, collateral(200));
+    env.process_with_prices(add_liquidity, &[&lp])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.user_state(&authority).await.header.comet.num_positions,
+        2
+    );
+
+    let remove = env.remove_comet_position_ix(&authority, 1);
+    let result = env.process(&[remove.clone()], &[&lp]).await;
+    assert_clone_error(result, CloneError::CometNotEmpty);
+
+    let withdraw = env.withdraw_liquidity_from_comet_ix(&authority, 1, 0);
+    let result = env.process_with_prices(withdraw, &[&lp]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    // Withdrawals are capped at the committed liquidity.
+    let withdraw = env.withdraw_liquidity_from_comet_ix(&authority, 1, collateral(1_000));
+    env.process_with_prices(withdraw.clone(), &[&lp])
+        .await
+        .unwrap();
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.comet_positions[1].committed_collateral_liquidity, 0);
+    assert_eq!(
+        env.pools_state().await.pools[1].committed_collateral_liquidity,
+        0
+    );
+
+    let result = env.process_with_prices(withdraw, &[&lp]).await;
+    assert_clone_error(result, CloneError::NoLiquidityToWithdraw);
+
+    env.process(&[remove], &[&lp]).await.unwrap();
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.header.comet.num_positions, 1);
+    assert_eq!(state.comet_positions[0].pool_index, PYTH_POOL);
+
+    let remove = env.remove_comet_position_ix(&authority, 1);
+    let result = env.process(&[remove], &[&lp]).await;
+    assert_clone_error(result, CloneError::InvalidInputPositionIndex);
+
+    let freeze = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::Status {
+            value: Status::Frozen,
+        },
+    );
+    env.process(&[freeze], &[]).await.unwrap();
+    let collect = env.collect_lp_rewards_ix(&authority, PYTH_POOL, 0);
+    let result = env.process(&[collect], &[&lp]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+}
+
+#[tokio::test]
+async fn comet_instructions_respect_bounds() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let (authority, trader_key) = (lp.pubkey(), trader.pubkey());
+    env.warp_slots(10).await;
+    let slot = env.current_slot().await;
+    // The balanced pool trades at the oracle price of 10.00.
+    let pool_price = PYTH_PRICE as u64;
+
+    let rejected = [
+        (
+            CometBounds {
+                expiry_slot: Some(slot - 1),
+                ..CometBounds::default()
+            },
+            CloneError::TransactionExpired,
+        ),
+        (
+            CometBounds {
+                max_pool_price: Some(pool_price - 1),
+                ..CometBounds::default()
+            },
+            CloneError::SlippageToleranceExceeded,
+        ),
+        (
+            CometBounds {
+                min_pool_price: Some(pool_price + 1),
+                ..CometBounds::default()
+            },
+            CloneError::SlippageToleranceExceeded,
+        ),
+        // A perfect health score of 100.
+        (
+            CometBounds {
+                min_health_score: Some(10_000_000_000),
+                ..CometBounds::default()
+            },
+            CloneError::SlippageToleranceExceeded,
+        ),
+    ];
+    for (bounds, error) in rejected {
+        let add_liquidity = env.add_liquidity_to_comet_with_bounds_ix(
+            &authority,
+            PYTH_POOL,
+            collateral(100),
+            bounds,
+        );
+        let result = env.process_with_prices(add_liquidity, &[&lp]).await;
+        assert_clone_error(result, error);
+    }
+    let bounds = CometBounds {
+        expiry_slot: Some(slot),
+        min_pool_price: Some(pool_price),
+        max_pool_price: Some(pool_price),
+        max_collateral_ild: Some(0),
+        max_onasset_ild: Some(0),
+        min_health_score: Some(1),
+    };
+    let add_liquidity =
+        env.add_liquidity_to_comet_with_bounds_ix(&authority, PYTH_POOL, collateral(100), bounds);
+    env.process_with_prices(add_liquidity, &[&lp])
+        .await
+        .unwrap();
+
+    // The trader buys onAssets, leaving the comet with onAsset ILD, then pays
+    // back what it bought.
+    let buy = env.swap_ix(&trader_key, PYTH_POOL, collateral(100), true, true, 0);
+    env.process_with_prices(buy, &[&trader]).await.unwrap();
+    let onasset_ild_share = env.pools_state().await.pools[0].onasset_ild as u64;
+    let bought = env.onasset_balance(&trader_key, PYTH_POOL).await;
+    let remaining_ild = onasset_ild_share - bought;
+
+    let pay = |env: &TestEnv, max_onasset_ild| {
+        env.pay_impermanent_loss_debt_with_bounds_ix(
+            &trader_key,
+            &authority,
+            PYTH_POOL,
+            0,
+            bought,
+            PaymentType::Onasset,
+            CometBounds {
+                max_onasset_ild: Some(max_onasset_ild),
+                ..CometBounds::default()
+            },
+        )
+    };
+    let result = env
+        .process(&[pay(&env, remaining_ild - 1)], &[&trader])
+        .await;
+    assert_clone_error(result, CloneError::SlippageToleranceExceeded);
+    env.process(&[pay(&env, remaining_ild)], &[&trader])
+        .await
+        .unwrap();
+
+    // Withdrawing moves the pool's ILD onto the position without adding to it.
+    let withdraw = |env: &TestEnv, max_onasset_ild| {
+        env.withdraw_liquidity_from_comet_with_bounds_ix(
+            &authority,
+            0,
+            collateral(300),
+            CometBounds {
+                max_onasset_ild: Some(max_onasset_ild),
+                ..CometBounds::default()
+            },
+        )
+    };
+    let result = env
+        .process_with_prices(withdraw(&env, remaining_ild - 1), &[&lp])
+        .await;
+    assert_clone_error(result, CloneError::SlippageToleranceExceeded);
+    env.process_with_prices(withdraw(&env, remaining_ild), &[&lp])
+        .await
+        .unwrap();
+    let state = env.user_state(&authority).await;
+    assert_eq!(
+        state.comet_positions[0].committed_collateral_liquidity,
+        collateral(300)
+    );
+}
+
+#[tokio::test]
+async fn pool_rewards_accrue_to_liquidity_providers() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let reward_mint = env.create_mint(&admin, 6).await;
+    let other_mint = env.create_mint(&admin, 6).await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(300))
+        .await;
+    let other_lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(100))
+        .await;
+    let setup = [
+        env.create_token_account_ix(&admin, &reward_mint),
+        env.create_token_account_ix(&admin, &other_mint),
+        env.create_token_account_ix(&clone_address(), &reward_mint),
+        env.create_token_account_ix(&clone_address(), &other_mint),
+    ];
+    env.process(&setup, &[]).await.unwrap();
+    let setup = [
+        env.create_token_account_ix(&lp.pubkey(), &reward_mint),
+        env.create_token_account_ix(&other_lp.pubkey(), &reward_mint),
+        env.mint_tokens_ix(&reward_mint, &admin, 1_000_000),
+        env.mint_tokens_ix(&other_mint, &admin, 1_000_000),
+    ];
+    env.process(&setup, &[]).await.unwrap();
+
+    let claim = env.claim_pool_rewards_ix(&lp.pubkey(), &reward_mint, 0);
+    let result = env.process(&[claim], &[&lp]).await;
+    assert_clone_error(result, CloneError::InvalidRewardMint);
+
+    // 100 reward tokens a slot.
+    let fund = env.fund_pool_rewards_ix(PYTH_POOL, &reward_mint, 100, 1_000_000);
+    env.process(&[fund], &[]).await.unwrap();
+    let vault = token_address(&clone_address(), &reward_mint);
+    assert_eq!(env.token_balance(&vault).await, 1_000_000);
+
+    // The reward mint is fixed once funded.
+    let fund = env.fund_pool_rewards_ix(PYTH_POOL, &other_mint, 100, 1_000);
+    let result = env.process(&[fund], &[]).await;
+    assert_clone_error(result, CloneError::InvalidRewardMint);
+
+    // Rewards split 3:1 with the committed liquidity.
+    env.warp_slots(10).await;
+    let claim = env.claim_pool_rewards_ix(&lp.pubkey(), &reward_mint, 0);
+    env.process(&[claim], &[&lp]).await.unwrap();
+    let claim_other = env.claim_pool_rewards_ix(&other_lp.pubkey(), &reward_mint, 0);
+    env.process(&[claim_other.clone()], &[&other_lp])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&token_address(&lp.pubkey(), &reward_mint))
+            .await,
+        750
+    );
+    assert_eq!(
+        env.token_balance(&token_address(&other_lp.pubkey(), &reward_mint))
+            .await,
+        250
+    );
+    let result = env.process(&[claim_other.clone()], &[&other_lp]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    // Rewards earned before withdrawing stay claimable, and keep the
+    // position from being removed.
+    env.warp_slots(10).await;
+    let withdraw = env.withdraw_liquidity_from_comet_ix(&other_lp.pubkey(), 0, collateral(100));
+    env.process_with_prices(withdraw, &[&other_lp])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.user_state(&other_lp.pubkey()).await.comet_positions[0].unclaimed_rewards,
+        250
+    );
+    let remove = env.remove_comet_position_ix(&other_lp.pubkey(), 0);
+    let result = env.process(&[remove.clone()], &[&other_lp]).await;
+    assert_clone_error(result, CloneError::CometNotEmpty);
+    env.process(&[claim_other], &[&other_lp]).await.unwrap();
+    env.process(&[remove], &[&other_lp]).await.unwrap();
+    assert_eq!(
+        env.token_balance(&token_address(&other_lp.pubkey(), &reward_mint))
+            .await,
+        500
+    );
+
+    // The remaining provider earns the whole emission.
+    env.warp_slots(10).await;
+    let claim = env.claim_pool_rewards_ix(&lp.pubkey(), &reward_mint, 0);
+    env.process(&[claim], &[&lp]).await.unwrap();
+    assert_eq!(
+        env.token_balance(&token_address(&lp.pubkey(), &reward_mint))
+            .await,
+        2_500
+    );
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    assert_eq!(pool.rewards.reward_mint, reward_mint);
+    assert_eq!(pool.rewards.unemitted_rewards, 1_000_000 - 3_000);
+}
+
+#[tokio::test]
+async fn compound_lp_rewards_into_liquidity() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let authority = lp.pubkey();
+    let keeper = env.create_user(0).await;
+    let trader = env.create_user(collateral(1_000)).await;
+
+    let buy = env.swap_ix(&trader.pubkey(), PYTH_POOL, collateral(100), true, true, 0);
+    env.process_with_prices(buy, &[&trader]).await.unwrap();
+
+    // Keepers need the user to opt in.
+    let compound = env.compound_lp_rewards_ix(&keeper.pubkey(), &authority, 0);
+    let result = env.process_with_prices(compound.clone(), &[&keeper]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+    let opt_in = env.set_auto_compound_ix(&authority, 0, true);
+    env.process(&[opt_in], &[&lp]).await.unwrap();
+
+    // Rewards are priced with fresh oracle prices only.
+    env.warp_slots(1).await;
+    let result = env.process(&[compound.clone()], &[&keeper]).await;
+    assert_clone_error(result, CloneError::OutdatedOracle);
+
+    // The collateral paid in becomes committed liquidity.
+    env.process_with_prices(compound.clone(), &[&keeper])
+        .await
+        .unwrap();
+    let state = env.user_state(&authority).await;
+    assert_eq!(
+        state.comet_positions[0].committed_collateral_liquidity,
+        collateral(600)
+    );
+    assert_eq!(
+        env.pools_state().await.pools[0].committed_collateral_liquidity,
+        collateral(600)
+    );
+    let result = env.process_with_prices(compound.clone(), &[&keeper]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    // OnAssets sold into the pool compound at the oracle price of 10.
+    let seller = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(10))
+        .await;
+    let sell = env.swap_ix(&seller.pubkey(), PYTH_POOL, onasset(5), true, false, 0);
+    env.process_with_prices(sell, &[&seller]).await.unwrap();
+    let pool = env.pools_state().await.pools[0];
+    let position = env.user_state(&authority).await.comet_positions[0];
+    let onasset_reward = position.onasset_ild_rebate - pool.onasset_ild;
+    assert!(onasset_reward > 0);
+
+    let opt_out = env.set_auto_compound_ix(&authority, 0, false);
+    env.process(&[opt_out], &[&lp]).await.unwrap();
+    let result = env.process_with_prices(compound.clone(), &[&keeper]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let compound = env.compound_lp_rewards_ix(&authority, &authority, 0);
+    env.process_with_prices(compound, &[&lp]).await.unwrap();
+    let state = env.user_state(&authority).await;
+    assert_eq!(
+        state.comet_positions[0].committed_collateral_liquidity,
+        collateral(600) + onasset_reward as u64
+    );
+}
+
+#[tokio::test]
+async fn net_lp_rewards_against_ild() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let authority = lp.pubkey();
+    let trader = env.create_user(collateral(1_000)).await;
+
+    let buy = env.swap_ix(&trader.pubkey(), PYTH_POOL, collateral(100), true, true, 0);
+    env.process_with_prices(buy, &[&trader]).await.unwrap();
+    let onasset_ild = env.pools_state().await.pools[0].onasset_ild;
+    assert!(onasset_ild > 0);
+
+    // The onAssets owed are worth 10 collateral each, less than the 100
+    // collateral of rewards.
+    let event_counter = env.clone_state().await.event_counter;
+    let net = env.net_lp_rewards_ix(&authority, 0);
+    env.process_with_prices(net.clone(), &[&lp]).await.unwrap();
+    let position = env.user_state(&authority).await.comet_positions[0];
+    assert_eq!(position.onasset_ild_rebate, onasset_ild);
+    assert_eq!(position.collateral_ild_rebate, -onasset_ild);
+    assert_eq!(env.clone_state().await.event_counter, event_counter + 1);
+
+    let result = env.process_with_prices(net, &[&lp]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    // What's left of the rewards can still be collected.
+    let collect = env.collect_lp_rewards_ix(&authority, PYTH_POOL, 0);
+    env.process(&[collect], &[&lp]).await.unwrap();
+    assert_eq!(
+        env.collateral_balance(&authority).await,
+        collateral(100) - onasset_ild as u64
+    );
+}
@@ -0,0 +1,675 @@
+use crate::fixture::*;
+use anchor_lang::error::{ErrorCode, ERROR_CODE_OFFSET};
+use anchor_lang::{AccountDeserialize, Discriminator, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use clone_staking::states::{CloneStaking, Tier, MAX_TIERS};
+use clone_staking::LEGACY_USER_ACCOUNT_SPACE;
+use depository_token::DepositoryTokenError;
+use pyth_sdk_solana::state::PriceAccount;
+use solana_sdk::{
+    account::Account, instruction::Instruction, pubkey::Pubkey, rent::Rent, signer::Signer,
+    system_program,
+};
+use std::mem::size_of;
+
+// clone-staking keeps its error enum private.
+const CANNOT_WITHDRAW_BEFORE_STAKING_PERIOD: u32 = ERROR_CODE_OFFSET;
+const INVALID_STAKE_INPUT: u32 = ERROR_CODE_OFFSET + 1;
+const UNBONDING_QUEUE_FULL: u32 = ERROR_CODE_OFFSET + 4;
+const STAKE_LOCKED: u32 = ERROR_CODE_OFFSET + 5;
+const DELEGATE_LIST_FULL: u32 = ERROR_CODE_OFFSET + 6;
+const INVALID_TIER_SCHEDULE: u32 = ERROR_CODE_OFFSET + 7;
+const USER_ACCOUNT_NOT_EMPTY: u32 = ERROR_CODE_OFFSET + 8;
+const ACCOUNT_ALREADY_MIGRATED: u32 = ERROR_CODE_OFFSET + 9;
+
+const DEPOSITORY_SEED: u8 = 7;
+const DEPOSITORY_RATIO: u64 = 10;
+
+fn depository_settings_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            &[DEPOSITORY_SEED],
+            depository_token::SETTINGS_SEED.as_bytes(),
+        ],
+        &depository_token::ID,
+    )
+    .0
+}
+
+fn depository_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: depository_token::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+async fn user_staking_state(env: &mut TestEnv, user: &Pubkey) -> clone_staking::states::User {
+    let account = env.account(&user_staking_address(user)).await.unwrap();
+    clone_staking::states::User::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+fn tier(stake_requirement: u64, lp_trading_fee_bps: u16, treasury_trading_fee_bps: u16) -> Tier {
+    Tier {
+        stake_requirement,
+        lp_trading_fee_bps,
+        treasury_trading_fee_bps,
+    }
+}
+
+async fn staking_tiers(env: &mut TestEnv) -> Vec<(u64, u16, u16)> {
+    let account = env.account(&staking_address()).await.unwrap();
+    let clone_staking =
+        clone_staking::states::CloneStaking::try_deserialize(&mut account.data.as_slice()).unwrap();
+    clone_staking.tiers[..clone_staking.num_tiers as usize]
+        .iter()
+        .map(|tier| {
+            (
+                tier.stake_requirement,
+                tier.lp_trading_fee_bps,
+                tier.treasury_trading_fee_bps,
+            )
+        })
+        .collect()
+}
+
+async fn staked_tokens(env: &mut TestEnv, user: &Pubkey) -> u64 {
+    user_staking_state(env, user).await.staked_tokens
+}
+
+#[tokio::test]
+async fn unstake_and_withdraw_after_staking_period() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let cln_token_mint = env.configure_staking(10, onasset(100), 5, 2).await;
+    let cln_account = token_address(&authority, &cln_token_mint);
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(100)),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+
+    let stake = env.add_stake_ix(&authority, &cln_token_mint, onasset(60));
+    env.process(&[stake], &[&user]).await.unwrap();
+    assert_eq!(env.token_balance(&cln_account).await, onasset(40));
+    assert_eq!(staked_tokens(&mut env, &authority).await, onasset(60));
+
+    let unstake_too_much = env.request_unstake_ix(&authority, onasset(61));
+    let result = env.process(&[unstake_too_much], &[&user]).await;
+    assert_custom_error(result, INVALID_STAKE_INPUT);
+
+    // Unbonding tokens leave the stake right away.
+    let unstake = env.request_unstake_ix(&authority, onasset(10));
+    env.process(&[unstake], &[&user]).await.unwrap();
+    assert_eq!(staked_tokens(&mut env, &authority).await, onasset(50));
+
+    let withdraw = env.withdraw_stake_ix(&authority, &cln_token_mint);
+    let result = env.process(&[withdraw.clone()], &[&user]).await;
+    assert_custom_error(result, CANNOT_WITHDRAW_BEFORE_STAKING_PERIOD);
+
+    // Topping up doesn't restart the unbonding of the first lot.
+    env.warp_slots(5).await;
+    let instructions = [
+        env.add_stake_ix(&authority, &cln_token_mint, onasset(10)),
+        env.request_unstake_ix(&authority, onasset(20)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+    assert_eq!(staked_tokens(&mut env, &authority).await, onasset(40));
+
+    env.warp_slots(6).await;
+    env.process(&[withdraw.clone()], &[&user]).await.unwrap();
+    assert_eq!(env.token_balance(&cln_account).await, onasset(40));
+    let result = env.process(&[withdraw.clone()], &[&user]).await;
+    assert_custom_error(result, CANNOT_WITHDRAW_BEFORE_STAKING_PERIOD);
+
+    env.warp_slots(5).await;
+    env.process(&[withdraw], &[&user]).await.unwrap();
+    assert_eq!(env.token_balance(&cln_account).await, onasset(60));
+    let user_staking = user_staking_state(&mut env, &authority).await;
+    assert_eq!(user_staking.staked_tokens, onasset(40));
+    assert_eq!(user_staking.num_unbonding_lots, 0);
+
+    let unstake = env.request_unstake_ix(&authority, onasset(1));
+    let fill_queue = vec![unstake.clone(); clone_staking::states::MAX_UNBONDING_LOTS];
+    env.process(&fill_queue, &[&user]).await.unwrap();
+    let result = env.process(&[unstake], &[&user]).await;
+    assert_custom_error(result, UNBONDING_QUEUE_FULL);
+}
+
+#[tokio::test]
+async fn staking_rewards_are_shared_by_stake() {
+    let mut env = TestEnv::new().await;
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let collateral_mint = env.collateral_mint;
+    let first = env.create_user(0).await;
+    let second = env.create_user(0).await;
+    for (staker, amount) in [(&first, onasset(60)), (&second, onasset(20))] {
+        let authority = staker.pubkey();
+        let instructions = [
+            env.create_token_account_ix(&authority, &cln_token_mint),
+            env.mint_tokens_ix(&cln_token_mint, &authority, amount),
+            env.add_stake_ix(&authority, &cln_token_mint, amount),
+        ];
+        env.process(&instructions, &[staker]).await.unwrap();
+    }
+
+    // Anything sent to the reward vault is distributed by stake.
+    let deposit = env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(100));
+    env.process(&[deposit], &[]).await.unwrap();
+    for (staker, rewards) in [(&first, collateral(75)), (&second, collateral(25))] {
+        let claim = env.claim_staking_rewards_ix(&staker.pubkey());
+        env.process(&[claim], &[staker]).await.unwrap();
+        assert_eq!(env.collateral_balance(&staker.pubkey()).await, rewards);
+    }
+    let claim = env.claim_staking_rewards_ix(&first.pubkey());
+    let result = env.process(&[claim], &[&first]).await;
+    assert_custom_error(result, INVALID_STAKE_INPUT);
+
+    // Unstaking settles the rewards earned so far, and later deposits only
+    // go to the remaining stake.
+    let deposit = env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(80));
+    env.process(&[deposit], &[]).await.unwrap();
+    let unstake = env.request_unstake_ix(&first.pubkey(), onasset(60));
+    env.process(&[unstake], &[&first]).await.unwrap();
+    assert_eq!(
+        user_staking_state(&mut env, &first.pubkey())
+            .await
+            .unclaimed_rewards,
+        collateral(60)
+    );
+    let deposit = env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(20));
+    env.process(&[deposit], &[]).await.unwrap();
+
+    for (staker, rewards) in [(&first, collateral(135)), (&second, collateral(65))] {
+        let claim = env.claim_staking_rewards_ix(&staker.pubkey());
+        env.process(&[claim], &[staker]).await.unwrap();
+        assert_eq!(env.collateral_balance(&staker.pubkey()).await, rewards);
+    }
+    let staking_reward_vault = env.staking_reward_vault();
+    assert_eq!(env.token_balance(&staking_reward_vault).await, 0);
+}
+
+#[tokio::test]
+async fn vote_escrow_weighs_rewards_by_remaining_lock() {
+    let mut env = TestEnv::new().await;
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let params = env.update_vote_escrow_ix(100);
+    env.process(&[params], &[]).await.unwrap();
+    let collateral_mint = env.collateral_mint;
+    let first = env.create_user(0).await;
+    let second = env.create_user(0).await;
+    let slot = env.current_slot().await;
+    for (staker, lock_end_slot) in [(&first, slot + 100), (&second, slot + 50)] {
+        let authority = staker.pubkey();
+        let instructions = [
+            env.create_token_account_ix(&authority, &cln_token_mint),
+            env.mint_tokens_ix(&cln_token_mint, &authority, onasset(100)),
+            env.add_stake_ix(&authority, &cln_token_mint, onasset(100)),
+            env.lock_stake_ix(&authority, lock_end_slot),
+        ];
+        env.process(&instructions, &[staker]).await.unwrap();
+    }
+    assert_eq!(
+        user_staking_state(&mut env, &second.pubkey())
+            .await
+            .reward_weight,
+        onasset(50)
+    );
+
+    // Locks can only be extended.
+    let shorten = env.lock_stake_ix(&first.pubkey(), slot + 99);
+    let result = env.process(&[shorten], &[&first]).await;
+    assert_custom_error(result, INVALID_STAKE_INPUT);
+    let unstake = env.request_unstake_ix(&first.pubkey(), onasset(1));
+    let result = env.process(&[unstake], &[&first]).await;
+    assert_custom_error(result, STAKE_LOCKED);
+
+    let deposit = env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(150));
+    env.process(&[deposit], &[]).await.unwrap();
+    for (staker, rewards) in [(&first, collateral(100)), (&second, collateral(50))] {
+        let claim = env.claim_staking_rewards_ix(&staker.pubkey());
+        env.process(&[claim], &[staker]).await.unwrap();
+        assert_eq!(env.collateral_balance(&staker.pubkey()).await, rewards);
+    }
+
+    // Once the second lock runs out, anyone can drop its weight.
+    env.warp_slots(50).await;
+    let instructions = [
+        env.refresh_reward_weight_ix(&first.pubkey()),
+        env.refresh_reward_weight_ix(&second.pubkey()),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+    assert_eq!(
+        user_staking_state(&mut env, &second.pubkey())
+            .await
+            .reward_weight,
+        0
+    );
+    let deposit = env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(10));
+    env.process(&[deposit], &[]).await.unwrap();
+    let claim = env.claim_staking_rewards_ix(&first.pubkey());
+    env.process(&[claim.clone()], &[&first]).await.unwrap();
+    assert_eq!(
+        env.collateral_balance(&first.pubkey()).await,
+        collateral(110)
+    );
+
+    // Without a refresh, stake earns on its decayed effective stake and what
+    // its stale weight would have earned beyond that is distributed again.
+    env.warp_slots(25).await;
+    let deposit = env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(10));
+    env.process(&[deposit], &[]).await.unwrap();
+    env.process(&[claim.clone()], &[&first]).await.unwrap();
+    assert_eq!(
+        env.collateral_balance(&first.pubkey()).await,
+        collateral(115)
+    );
+    env.process(&[claim], &[&first]).await.unwrap();
+    assert_eq!(
+        env.collateral_balance(&first.pubkey()).await,
+        collateral(120)
+    );
+
+    // Turning the mode off needs no staker accounts. Rewards distributed
+    // before the change are still capped under vote escrow, where the first
+    // lock has run out, so they go back to the vault.
+    env.warp_slots(25).await;
+    let deposit = env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(10));
+    let params = env.update_vote_escrow_ix(0);
+    env.process(&[deposit, params], &[]).await.unwrap();
+    let (first_key, second_key) = (first.pubkey(), second.pubkey());
+    let refresh = env.refresh_reward_weight_ix(&first_key);
+    env.process(&[refresh], &[]).await.unwrap();
+    let first_staking = user_staking_state(&mut env, &first_key).await;
+    assert_eq!(first_staking.unclaimed_rewards, 0);
+    assert_eq!(first_staking.reward_weight, onasset(100));
+
+    // Weights follow as accounts are touched, then earn under the new mode.
+    let refresh = env.refresh_reward_weight_ix(&second_key);
+    env.process(&[refresh], &[]).await.unwrap();
+    assert_eq!(
+        user_staking_state(&mut env, &second_key)
+            .await
+            .reward_weight,
+        onasset(100)
+    );
+    let staking_account = env.account(&staking_address()).await.unwrap();
+    let clone_staking =
+        CloneStaking::try_deserialize(&mut staking_account.data.as_slice()).unwrap();
+    assert_eq!(clone_staking.total_reward_weight, onasset(200));
+    assert_eq!(clone_staking.vote_escrow_epoch, 2);
+    let claim = env.claim_staking_rewards_ix(&first_key);
+    env.process(&[claim], &[&first]).await.unwrap();
+    assert_eq!(env.collateral_balance(&first_key).await, collateral(130));
+}
+
+#[tokio::test]
+async fn delegates_are_bounded_and_unique() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.add_stake_ix(&authority, &cln_token_mint, 0),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    let delegates: Vec<Pubkey> = (0..clone_staking::states::MAX_DELEGATES)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    let instructions: Vec<Instruction> = delegates
+        .iter()
+        .map(|delegate| env.add_delegate_ix(&authority, delegate))
+        .collect();
+    env.process(&instructions, &[&user]).await.unwrap();
+    let add_another = env.add_delegate_ix(&authority, &Pubkey::new_unique());
+    let result = env.process(&[add_another], &[&user]).await;
+    assert_custom_error(result, DELEGATE_LIST_FULL);
+
+    let remove = env.remove_delegate_ix(&authority, &delegates[0]);
+    env.process(&[remove.clone()], &[&user]).await.unwrap();
+    let result = env.process(&[remove], &[&user]).await;
+    assert_custom_error(result, INVALID_STAKE_INPUT);
+    let add_again = env.add_delegate_ix(&authority, &delegates[1]);
+    let result = env.process(&[add_again], &[&user]).await;
+    assert_custom_error(result, INVALID_STAKE_INPUT);
+
+    let user_staking = user_staking_state(&mut env, &authority).await;
+    assert_eq!(
+        user_staking.delegates[..user_staking.num_delegates as usize],
+        delegates[1..]
+    );
+}
+
+#[tokio::test]
+async fn tier_schedule_is_validated() {
+    let mut env = TestEnv::new().await;
+    env.configure_staking(0, onasset(100), 20, 10).await;
+    let schedule = vec![tier(100, 20, 10), tier(200, 10, 10), tier(300, 5, 0)];
+    let replace = env.replace_tiers_ix(schedule);
+    env.process(&[replace], &[]).await.unwrap();
+    let expected = vec![(100, 20, 10), (200, 10, 10), (300, 5, 0)];
+    assert_eq!(staking_tiers(&mut env).await, expected);
+
+    for schedule in [
+        vec![tier(200, 20, 10), tier(100, 10, 5)],
+        vec![tier(100, 20, 10), tier(100, 10, 5)],
+        vec![tier(100, 10, 5), tier(200, 20, 5)],
+        vec![tier(100, 10, 5), tier(200, 10, 6)],
+        vec![tier(100, 9_000, 1_000)],
+    ] {
+        let replace = env.replace_tiers_ix(schedule);
+        let result = env.process(&[replace], &[]).await;
+        assert_custom_error(result, INVALID_TIER_SCHEDULE);
+    }
+    let too_many_tiers = vec![tier(100, 20, 10); clone_staking::states::MAX_TIERS + 1];
+    let replace = env.replace_tiers_ix(too_many_tiers);
+    let result = env.process(&[replace], &[]).await;
+    assert_custom_error(result, INVALID_STAKE_INPUT);
+
+    // Single tier updates must keep the schedule valid.
+    let out_of_order = env.update_staking_params_ix(clone_staking::Parameters::Tier {
+        num_tiers: 3,
+        index: 1,
+        stake_requirement: 50,
+        lp_trading_fee_bps: 10,
+        treasury_trading_fee_bps: 10,
+    });
+    let result = env.process(&[out_of_order], &[]).await;
+    assert_custom_error(result, INVALID_TIER_SCHEDULE);
+    let past_the_end = env.update_staking_params_ix(clone_staking::Parameters::Tier {
+        num_tiers: 3,
+        index: 3,
+        stake_requirement: 400,
+        lp_trading_fee_bps: 0,
+        treasury_trading_fee_bps: 0,
+    });
+    let result = env.process(&[past_the_end], &[]).await;
+    assert_custom_error(result, INVALID_STAKE_INPUT);
+    assert_eq!(staking_tiers(&mut env).await, expected);
+
+    let add_tier = env.update_staking_params_ix(clone_staking::Parameters::Tier {
+        num_tiers: 4,
+        index: 3,
+        stake_requirement: 400,
+        lp_trading_fee_bps: 0,
+        treasury_trading_fee_bps: 0,
+    });
+    env.process(&[add_tier], &[]).await.unwrap();
+    assert_eq!(staking_tiers(&mut env).await.len(), 4);
+}
+
+#[tokio::test]
+async fn staking_admin_handover_takes_two_steps() {
+    let mut env = TestEnv::new().await;
+    env.configure_staking(0, onasset(100), 20, 10).await;
+    let new_admin = env.create_user(0).await;
+    let new_admin_key = new_admin.pubkey();
+    let params = clone_staking::Parameters::Staking {
+        staking_period_slots: 5,
+    };
+    let mut new_admin_update = env.update_staking_params_ix(params);
+    new_admin_update.accounts[0].pubkey = new_admin_key;
+
+    let accept = env.accept_staking_admin_ix(&new_admin_key);
+    let result = env.process(&[accept.clone()], &[&new_admin]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+
+    let propose = env.update_staking_params_ix(clone_staking::Parameters::PendingAdmin {
+        pending_admin: new_admin_key,
+    });
+    env.process(&[propose], &[]).await.unwrap();
+    let result = env
+        .process(&[new_admin_update.clone()], &[&new_admin])
+        .await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+
+    env.process(&[accept], &[&new_admin]).await.unwrap();
+    env.process(&[new_admin_update], &[&new_admin])
+        .await
+        .unwrap();
+    let old_admin_update = env.update_staking_params_ix(params);
+    let result = env.process(&[old_admin_update], &[]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+}
+
+#[tokio::test]
+async fn close_empty_staking_account() {
+    let mut env = TestEnv::new().await;
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let collateral_mint = env.collateral_mint;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(10)),
+        env.add_stake_ix(&authority, &cln_token_mint, onasset(10)),
+        env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(10)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    // Staked, unbonding and unclaimed tokens all keep the account open.
+    let close = env.close_staking_user_account_ix(&authority);
+    let result = env.process(&[close.clone()], &[&user]).await;
+    assert_custom_error(result, USER_ACCOUNT_NOT_EMPTY);
+    let unstake = env.request_unstake_ix(&authority, onasset(10));
+    env.process(&[unstake], &[&user]).await.unwrap();
+    let result = env.process(&[close.clone()], &[&user]).await;
+    assert_custom_error(result, USER_ACCOUNT_NOT_EMPTY);
+    let withdraw = env.withdraw_stake_ix(&authority, &cln_token_mint);
+    env.process(&[withdraw], &[&user]).await.unwrap();
+    let result = env.process(&[close.clone()], &[&user]).await;
+    assert_custom_error(result, USER_ACCOUNT_NOT_EMPTY);
+
+    let claim = env.claim_staking_rewards_ix(&authority);
+    env.process(&[claim], &[&user]).await.unwrap();
+    assert_eq!(env.collateral_balance(&authority).await, collateral(10));
+    env.process(&[close], &[&user]).await.unwrap();
+    assert!(env
+        .account(&user_staking_address(&authority))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn migrate_staking_accounts_from_before_rewards() {
+    let mut env = TestEnv::new().await;
+    let cln_token_mint = env.configure_staking(10, onasset(100), 5, 2).await;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(60)),
+        env.add_stake_ix(&authority, &cln_token_mint, onasset(60)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    // Before rewards, the staking account ended after the tiers and the user
+    // account held the staked tokens and the slot they unlock at.
+    let staking_account = env.account(&staking_address()).await.unwrap();
+    let legacy_len = 8 + 3 * 32 + 8 + 2 + MAX_TIERS * 12;
+    let mut data = staking_account.data[..legacy_len].to_vec();
+    data.resize(staking_account.data.len(), 0);
+    let legacy_staking_account = Account {
+        data,
+        ..staking_account
+    };
+    env.context
+        .set_account(&staking_address(), &legacy_staking_account.into());
+
+    let user_account = user_staking_address(&authority);
+    let min_slot_withdrawal = env.current_slot().await + 100;
+    let user_staking_account = env.account(&user_account).await.unwrap();
+    let data = [
+        &clone_staking::states::User::discriminator()[..],
+        &onasset(60).to_le_bytes(),
+        &min_slot_withdrawal.to_le_bytes(),
+    ]
+    .concat();
+    let legacy_user_account = Account {
+        lamports: Rent::default().minimum_balance(LEGACY_USER_ACCOUNT_SPACE),
+        data,
+        ..user_staking_account
+    };
+    env.context
+        .set_account(&user_account, &legacy_user_account.into());
+
+    let stake = env.add_stake_ix(&authority, &cln_token_mint, 0);
+    let result = env.process(&[stake.clone()], &[&user]).await;
+    assert_anchor_error(result, ErrorCode::AccountDidNotDeserialize);
+    let migrate = env.migrate_staking_user_account_ix(&authority, &authority);
+    let result = env.process(&[migrate.clone()], &[&user]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintHasOne);
+
+    let set_reward_vault = env.set_reward_vault_ix();
+    env.process(&[set_reward_vault], &[]).await.unwrap();
+    env.process(&[migrate.clone()], &[&user]).await.unwrap();
+    let result = env.process(&[migrate], &[&user]).await;
+    assert_custom_error(result, ACCOUNT_ALREADY_MIGRATED);
+
+    let user_state = user_staking_state(&mut env, &authority).await;
+    assert_eq!(user_state.staked_tokens, onasset(60));
+    assert_eq!(user_state.min_slot_withdrawal, min_slot_withdrawal);
+    assert_eq!(user_state.reward_weight, onasset(60));
+    let staking_account = env.account(&staking_address()).await.unwrap();
+    let clone_staking =
+        CloneStaking::try_deserialize(&mut staking_account.data.as_slice()).unwrap();
+    assert_eq!(clone_staking.total_reward_weight, onasset(60));
+    assert_eq!(clone_staking.reward_vault, env.staking_reward_vault());
+
+    // Migrated stake earns rewards and still waits for its original unlock.
+    let collateral_mint = env.collateral_mint;
+    let instructions = [
+        env.mint_tokens_ix(&collateral_mint, &staking_address(), collateral(10)),
+        stake,
+        env.request_unstake_ix(&authority, onasset(60)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+    let user_state = user_staking_state(&mut env, &authority).await;
+    assert_eq!(user_state.unclaimed_rewards, collateral(10));
+    assert_eq!(
+        user_state.unbonding_lots[0].unlock_slot,
+        min_slot_withdrawal
+    );
+}
+
+#[tokio::test]
+async fn depository_token_mint_and_redeem() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let settings = depository_settings_address();
+    let depositing_token_mint = env.underlying_mint(PYTH_POOL);
+    let depository_token_mint = env.create_mint(&settings, ONASSET_SCALE).await;
+    let depositing_token_account = token_address(&settings, &depositing_token_mint);
+    let user_depositing_account = token_address(&authority, &depositing_token_mint);
+    let user_depository_account = token_address(&authority, &depository_token_mint);
+
+    let admin = env.admin.pubkey();
+    let instructions = [
+        env.create_token_account_ix(&settings, &depositing_token_mint),
+        env.create_token_account_ix(&authority, &depository_token_mint),
+        depository_ix(
+            depository_token::accounts::Initialize {
+                payer: admin,
+                settings,
+                depository_token_mint,
+                depositing_token_account,
+                system_program: system_program::ID,
+            },
+            depository_token::instruction::Initialize {
+                unique_seed: DEPOSITORY_SEED,
+                ratio: DEPOSITORY_RATIO,
+                depositing_token_mint,
+            },
+        ),
+        env.mint_asset_ix(&authority, underlying(100)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    let mint_ix = |mint_amount| {
+        depository_ix(
+            depository_token::accounts::MintDepositoryToken {
+                user: authority,
+                settings,
+                depository_token_mint,
+                depositing_token_account,
+                user_depositing_token_account: user_depositing_account,
+                user_depository_token_account: user_depository_account,
+                token_program: spl_token::ID,
+            },
+            depository_token::instruction::MintDepositoryToken { mint_amount },
+        )
+    };
+    let mint = mint_ix(underlying(5));
+    env.process(&[mint], &[&user]).await.unwrap();
+    assert_eq!(
+        env.token_balance(&user_depository_account).await,
+        underlying(5)
+    );
+    assert_eq!(
+        env.token_balance(&depositing_token_account).await,
+        underlying(50)
+    );
+
+    let redeem = depository_ix(
+        depository_token::accounts::RedeemDepositoryToken {
+            user: authority,
+            settings,
+            depository_token_mint,
+            depositing_token_account,
+            user_depositing_token_account: user_depositing_account,
+            user_depository_token_account: user_depository_account,
+            token_program: spl_token::ID,
+        },
+        depository_token::instruction::RedeemDepositoryToken {
+            redeem_amount: underlying(2),
+        },
+    );
+    env.process(&[redeem], &[&user]).await.unwrap();
+    assert_eq!(
+        env.token_balance(&user_depository_account).await,
+        underlying(3)
+    );
+    assert_eq!(
+        env.token_balance(&user_depositing_account).await,
+        underlying(70)
+    );
+
+    let mint_nothing = mint_ix(0);
+    let result = env.process(&[mint_nothing], &[&user]).await;
+    assert_custom_error(
+        result,
+        ERROR_CODE_OFFSET + DepositoryTokenError::MustBeNonZero as u32,
+    );
+}
+
+#[tokio::test]
+async fn faucet_mints_underlying_asset() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let underlying_account = token_address(&authority, &env.underlying_mint(PYTH_POOL));
+
+    for _ in 0..2 {
+        let mint = env.mint_asset_ix(&authority, underlying(3));
+        env.process(&[mint], &[&user]).await.unwrap();
+    }
+    assert_eq!(env.token_balance(&underlying_account).await, underlying(6));
+}
+
+#[tokio::test]
+async fn pyth_mock_sets_price() {
+    let mut env = TestEnv::new().await;
+    let oracle = env.create_pyth_oracle(PYTH_PRICE, PYTH_EXPO).await;
+    env.set_pyth_price(&oracle, 1_234_000_000).await;
+
+    let account = env.account(&oracle).await.unwrap();
+    let price_account: PriceAccount =
+        bytemuck::pod_read_unaligned(&account.data[..size_of::<PriceAccount>()]);
+    assert_eq!(price_account.agg.price, 1_234_000_000);
+    assert_eq!(price_account.prev_price, 1_234_000_000);
+    assert_eq!(price_account.expo, PYTH_EXPO);
+}
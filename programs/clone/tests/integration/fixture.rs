@@ -0,0 +1,743 @@
+//! Local deployment of the protocol shared by every integration test.
+//!
+//! Oracles are mocked in-process: Pyth feeds are price accounts owned and
+//! written by the `pyth` test program, Switchboard aggregators are written
+//! directly into the bank with the layout `update_prices` reads.
+
+use anchor_lang::error::{ErrorCode, ERROR_CODE_OFFSET};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use clone::error::CloneError;
+use clone::instructions::UpdateOracleParameters;
+use clone::states::{Borrow, LiquidityPosition, OracleSource, Oracles, Pools, User};
+use clone::{CLONE_PROGRAM_SEED, ORACLES_SEED, POOLS_SEED, USER_SEED};
+use pyth_sdk_solana::state::PriceAccount;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use std::mem::size_of;
+use switchboard_solana::{AggregatorAccountData, SwitchboardDecimal};
+
+pub const INITIAL_SLOT: u64 = 100;
+pub const COLLATERAL_SCALE: u8 = 7;
+pub const ONASSET_SCALE: u8 = 8;
+pub const UNDERLYING_SCALE: u8 = 9;
+pub const LIQUIDATOR_FEE_BPS: u16 = 500;
+pub const FLASH_LOAN_FEE_BPS: u16 = 30;
+pub const MIN_OVERCOLLATERAL_RATIO: u16 = 150;
+pub const MAX_LIQUIDATION_OVERCOLLATERAL_RATIO: u16 = 200;
+pub const LIQUIDITY_TRADING_FEE_BPS: u16 = 15;
+pub const TREASURY_TRADING_FEE_BPS: u16 = 10;
+
+/// Pool priced by a Pyth feed, its underlying asset is minted by the faucet.
+pub const PYTH_POOL: u8 = 0;
+/// Pool priced by a Switchboard aggregator.
+pub const SWITCHBOARD_POOL: u8 = 1;
+
+pub const COLLATERAL_ORACLE_INDEX: u8 = 0;
+pub const COLLATERAL_PRICE: i64 = 100_000_000;
+pub const PYTH_PRICE: i64 = 1_000_000_000;
+pub const PYTH_EXPO: i32 = -8;
+pub const SWITCHBOARD_MANTISSA: i128 = 2_000;
+pub const SWITCHBOARD_SCALE: u32 = 2;
+
+pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+
+pub fn collateral(units: u64) -> u64 {
+    units * 10u64.pow(COLLATERAL_SCALE.into())
+}
+
+pub fn onasset(units: u64) -> u64 {
+    units * 10u64.pow(ONASSET_SCALE.into())
+}
+
+pub fn underlying(units: u64) -> u64 {
+    units * 10u64.pow(UNDERLYING_SCALE.into())
+}
+
+pub fn clone_address() -> Pubkey {
+    Pubkey::find_program_address(&[CLONE_PROGRAM_SEED.as_ref()], &clone::ID).0
+}
+
+pub fn pools_address() -> Pubkey {
+    Pubkey::find_program_address(&[POOLS_SEED.as_ref()], &clone::ID).0
+}
+
+pub fn oracles_address() -> Pubkey {
+    Pubkey::find_program_address(&[ORACLES_SEED.as_ref()], &clone::ID).0
+}
+
+pub fn user_address(authority: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_SEED.as_ref(), authority.as_ref()], &clone::ID).0
+}
+
+pub fn faucet_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"faucet"], &mock_asset_faucet::ID).0
+}
+
+pub fn staking_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[clone_staking::CLONE_STAKING_SEED.as_ref()],
+        &clone_staking::ID,
+    )
+    .0
+}
+
+pub fn user_staking_address(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[clone_staking::USER_SEED.as_ref(), user.as_ref()],
+        &clone_staking::ID,
+    )
+    .0
+}
+
+pub fn admin_multisig_address() -> Pubkey {
+    Pubkey::find_program_address(&[clone::ADMIN_MULTISIG_SEED.as_ref()], &clone::ID).0
+}
+
+pub fn admin_transaction_address(index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[clone::ADMIN_TRANSACTION_SEED.as_ref(), &index.to_le_bytes()],
+        &clone::ID,
+    )
+    .0
+}
+
+pub fn governance_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[clone_governance::GOVERNANCE_SEED.as_ref()],
+        &clone_governance::ID,
+    )
+    .0
+}
+
+pub fn proposal_address(index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            clone_governance::PROPOSAL_SEED.as_ref(),
+            &index.to_le_bytes(),
+        ],
+        &clone_governance::ID,
+    )
+    .0
+}
+
+pub fn token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, mint)
+}
+
+/// Serialized Switchboard aggregator whose latest confirmed round resolved
+/// to `mantissa * 10^-scale` with `num_success` oracle responses.
+pub fn switchboard_aggregator_data(mantissa: i128, scale: u32, num_success: u32) -> Vec<u8> {
+    let mut aggregator: AggregatorAccountData = bytemuck::Zeroable::zeroed();
+    aggregator.min_oracle_results = 1;
+    aggregator.latest_confirmed_round.num_success = num_success;
+    aggregator.latest_confirmed_round.result = SwitchboardDecimal { mantissa, scale };
+
+    let mut data =
+        solana_sdk::hash::hash(b"account:AggregatorAccountData").to_bytes()[..8].to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&aggregator));
+    data
+}
+
+pub fn clone_error(error: CloneError) -> u32 {
+    ERROR_CODE_OFFSET + error as u32
+}
+
+pub fn assert_custom_error(result: Result<(), BanksClientError>, expected: u32) {
+    match result.expect_err("transaction should fail").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, expected, "expected error {}, got {}", expected, code)
+        }
+        error => panic!("expected error {}, got {:?}", expected, error),
+    }
+}
+
+pub fn assert_clone_error(result: Result<(), BanksClientError>, error: CloneError) {
+    assert_custom_error(result, clone_error(error));
+}
+
+pub fn assert_anchor_error(result: Result<(), BanksClientError>, error: ErrorCode) {
+    assert_custom_error(result, error as u32);
+}
+
+pub struct PoolAccounts {
+    pub onasset_mint: Pubkey,
+    pub underlying_mint: Pubkey,
+    pub oracle: Pubkey,
+}
+
+/// User account contents, trimmed to the open positions.
+pub struct UserState {
+    pub header: User,
+    pub borrows: Vec<Borrow>,
+    pub comet_positions: Vec<LiquidityPosition>,
+    pub data_len: usize,
+}
+
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+    /// Same key as the fee payer, which also holds every mint authority.
+    pub admin: Keypair,
+    pub treasury: Keypair,
+    pub collateral_mint: Pubkey,
+    pub cln_token_mint: Pubkey,
+    pub pools: Vec<PoolAccounts>,
+    /// Oracle addresses in the order they're registered with the program.
+    pub oracles: Vec<Pubkey>,
+    transaction_count: u64,
+}
+
+impl TestEnv {
+    /// Loads the programs and creates the mints and oracle feeds, without
+    /// initializing the clone program.
+    pub async fn start() -> Self {
+        let mut program_test = ProgramTest::new("clone", clone::ID, processor!(clone::entry));
+        program_test.add_program(
+            "clone_staking",
+            clone_staking::ID,
+            processor!(clone_staking::entry),
+        );
+        program_test.add_program(
+            "clone_governance",
+            clone_governance::ID,
+            processor!(clone_governance::entry),
+        );
+        program_test.add_program("pyth", pyth::ID, processor!(pyth::entry));
+        program_test.add_program(
+            "mock_asset_faucet",
+            mock_asset_faucet::ID,
+            processor!(mock_asset_faucet::entry),
+        );
+        program_test.add_program(
+            "depository_token",
+            depository_token::ID,
+            processor!(depository_token::entry),
+        );
+
+        let mut context = program_test.start_with_context().await;
+        context.warp_to_slot(INITIAL_SLOT).unwrap();
+        let admin = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+
+        let mut env = Self {
+            context,
+            admin,
+            treasury: Keypair::new(),
+            collateral_mint: Pubkey::default(),
+            cln_token_mint: Pubkey::default(),
+            pools: Vec::new(),
+            oracles: Vec::new(),
+            transaction_count: 0,
+        };
+
+        let admin = env.admin.pubkey();
+        env.collateral_mint = env.create_mint(&admin, COLLATERAL_SCALE).await;
+        env.cln_token_mint = env.create_mint(&admin, 8).await;
+        let collateral_oracle = env.create_pyth_oracle(COLLATERAL_PRICE, PYTH_EXPO).await;
+        env.oracles.push(collateral_oracle);
+
+        let faucet_mint = env.create_mint(&faucet_address(), UNDERLYING_SCALE).await;
+        env.process(
+            &[Instruction {
+                program_id: mock_asset_faucet::ID,
+                accounts: mock_asset_faucet::accounts::Initialize {
+                    payer: admin,
+                    faucet: faucet_address(),
+                    mint: faucet_mint,
+                    system_program: solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: mock_asset_faucet::instruction::Initialize {}.data(),
+            }],
+            &[],
+        )
+        .await
+        .unwrap();
+        let pyth_oracle = env.create_pyth_oracle(PYTH_PRICE, PYTH_EXPO).await;
+        let onasset_mint = env.create_mint(&clone_address(), ONASSET_SCALE).await;
+        env.pools.push(PoolAccounts {
+            onasset_mint,
+            underlying_mint: faucet_mint,
+            oracle: pyth_oracle,
+        });
+        env.oracles.push(pyth_oracle);
+
+        let switchboard_oracle =
+            env.create_switchboard_oracle(SWITCHBOARD_MANTISSA, SWITCHBOARD_SCALE);
+        let onasset_mint = env.create_mint(&clone_address(), ONASSET_SCALE).await;
+        let underlying_mint = env.create_mint(&admin, UNDERLYING_SCALE).await;
+        env.pools.push(PoolAccounts {
+            onasset_mint,
+            underlying_mint,
+            oracle: switchboard_oracle,
+        });
+        env.oracles.push(switchboard_oracle);
+
+        env
+    }
+
+    /// A fully initialized protocol with one Pyth and one Switchboard pool.
+    pub async fn new() -> Self {
+        let mut env = Self::start().await;
+        let admin = env.admin.pubkey();
+        let treasury = env.treasury.pubkey();
+        let collateral_mint = env.collateral_mint;
+
+        env.process(
+            &[
+                env.create_token_account_ix(&clone_address(), &collateral_mint),
+                env.initialize_clone_ix(
+                    LIQUIDATOR_FEE_BPS,
+                    LIQUIDATOR_FEE_BPS,
+                    LIQUIDATOR_FEE_BPS,
+                    FLASH_LOAN_FEE_BPS,
+                ),
+                env.initialize_pools_ix(&admin),
+                env.initialize_oracles_ix(&admin),
+                env.create_token_account_ix(&treasury, &collateral_mint),
+                // Swaps pay stakers their share of treasury fees, so staking
+                // is always set up, with no fee tiers.
+                env.create_token_account_ix(&staking_address(), &env.cln_token_mint),
+                env.create_token_account_ix(&staking_address(), &collateral_mint),
+                env.initialize_staking_ix(&env.cln_token_mint, 0),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let sources = [
+            OracleSource::PYTH,
+            OracleSource::PYTH,
+            OracleSource::SWITCHBOARD,
+        ];
+        let add_oracles: Vec<_> = env
+            .oracles
+            .iter()
+            .zip(sources)
+            .map(|(address, source)| {
+                env.update_oracles_ix(
+                    &admin,
+                    UpdateOracleParameters::Add {
+                        address: *address,
+                        source,
+                        rescale_factor: None,
+                    },
+                )
+            })
+            .collect();
+        env.process(&add_oracles, &[]).await.unwrap();
+
+        for index in 0..env.pools.len() {
+            let pool = &env.pools[index];
+            let instructions = [
+                env.create_token_account_ix(&clone_address(), &pool.onasset_mint),
+                env.create_token_account_ix(&clone_address(), &pool.underlying_mint),
+                env.create_token_account_ix(&treasury, &pool.onasset_mint),
+                env.create_token_account_ix(&treasury, &pool.underlying_mint),
+                env.add_pool_ix(
+                    &pool.onasset_mint,
+                    &pool.underlying_mint,
+                    MIN_OVERCOLLATERAL_RATIO,
+                    MAX_LIQUIDATION_OVERCOLLATERAL_RATIO,
+                    index as u8 + 1,
+                ),
+            ];
+            env.process(&instructions, &[]).await.unwrap();
+        }
+
+        env
+    }
+
+    /// Sends the instructions in one transaction signed by the fee payer and
+    /// `signers`.
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        // Identical transactions under one blockhash are deduplicated, so
+        // each carries a distinct (negligible) priority fee.
+        self.transaction_count += 1;
+        let mut all_instructions = vec![ComputeBudgetInstruction::set_compute_unit_price(
+            self.transaction_count,
+        )];
+        all_instructions.extend_from_slice(instructions);
+
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        let mut all_signers = vec![&self.context.payer];
+        for signer in signers {
+            if all_signers.iter().all(|s| s.pubkey() != signer.pubkey()) {
+                all_signers.push(signer);
+            }
+        }
+        let transaction = Transaction::new_signed_with_payer(
+            &all_instructions,
+            Some(&self.context.payer.pubkey()),
+            all_signers.as_slice(),
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// Simulates the instructions, bundled with a price update, and returns the
+    /// return data left by the last one.
+    pub async fn simulate_with_prices(
+        &mut self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<Vec<u8>, BanksClientError> {
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &[self.update_prices_ix(), instruction],
+            Some(&self.context.payer.pubkey()),
+            all_signers.as_slice(),
+            blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await?;
+        if let Some(Err(error)) = simulation.result {
+            return Err(BanksClientError::TransactionError(error));
+        }
+        Ok(simulation
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .map(|return_data| return_data.data)
+            .unwrap_or_default())
+    }
+
+    /// Refreshes every oracle in the same transaction, since instructions
+    /// reject prices that weren't updated in the current slot.
+    pub async fn process_with_prices(
+        &mut self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let update_prices = self.update_prices_ix();
+        self.process(&[update_prices, instruction], signers).await
+    }
+
+    pub async fn warp_slots(&mut self, slots: u64) {
+        let slot = self.current_slot().await;
+        self.context.warp_to_slot(slot + slots).unwrap();
+    }
+
+    pub async fn current_slot(&mut self) -> u64 {
+        self.context
+            .banks_client
+            .get_sysvar::<sysvar::clock::Clock>()
+            .await
+            .unwrap()
+            .slot
+    }
+
+    pub async fn account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.account(address)
+            .await
+            .map_or(0, |account| account.lamports)
+    }
+
+    async fn rent_exempt_lamports(&mut self, space: usize) -> u64 {
+        self.context
+            .banks_client
+            .get_rent()
+            .await
+            .unwrap()
+            .minimum_balance(space)
+    }
+
+    pub async fn token_balance(&mut self, address: &Pubkey) -> u64 {
+        let account = self.account(address).await.expect("token account exists");
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn clone_state(&mut self) -> clone::states::Clone {
+        let account = self.account(&clone_address()).await.unwrap();
+        clone::states::Clone::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn pools_state(&mut self) -> Pools {
+        let account = self.account(&pools_address()).await.unwrap();
+        bytemuck::pod_read_unaligned(&account.data[8..8 + size_of::<Pools>()])
+    }
+
+    pub async fn oracles_state(&mut self) -> Oracles {
+        let account = self.account(&oracles_address()).await.unwrap();
+        bytemuck::pod_read_unaligned(&account.data[8..8 + size_of::<Oracles>()])
+    }
+
+    pub async fn user_state(&mut self, authority: &Pubkey) -> UserState {
+        let account = self.account(&user_address(authority)).await.unwrap();
+        let data = &account.data[8..];
+        let header: User = bytemuck::pod_read_unaligned(&data[..size_of::<User>()]);
+        let borrows_start = size_of::<User>();
+        let positions_start =
+            borrows_start + header.borrows_capacity as usize * size_of::<Borrow>();
+        let borrows = data[borrows_start..positions_start]
+            .chunks_exact(size_of::<Borrow>())
+            .take(header.num_borrows as usize)
+            .map(bytemuck::pod_read_unaligned)
+            .collect();
+        let comet_positions = data[positions_start..]
+            .chunks_exact(size_of::<LiquidityPosition>())
+            .take(header.comet.num_positions as usize)
+            .map(bytemuck::pod_read_unaligned)
+            .collect();
+
+        UserState {
+            header,
+            borrows,
+            comet_positions,
+            data_len: account.data.len(),
+        }
+    }
+
+    pub async fn create_mint(&mut self, authority: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let lamports = self.rent_exempt_lamports(spl_token::state::Mint::LEN).await;
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &self.admin.pubkey(),
+                    &mint.pubkey(),
+                    lamports,
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::ID,
+                    &mint.pubkey(),
+                    authority,
+                    None,
+                    decimals,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    /// A token account at a fresh address instead of the owner's associated
+    /// token account.
+    pub async fn create_keypair_token_account(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let account = Keypair::new();
+        let lamports = self
+            .rent_exempt_lamports(spl_token::state::Account::LEN)
+            .await;
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &self.admin.pubkey(),
+                    &account.pubkey(),
+                    lamports,
+                    spl_token::state::Account::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_account3(
+                    &spl_token::ID,
+                    &account.pubkey(),
+                    mint,
+                    owner,
+                )
+                .unwrap(),
+            ],
+            &[&account],
+        )
+        .await
+        .unwrap();
+        account.pubkey()
+    }
+
+    pub fn create_token_account_ix(&self, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+        create_associated_token_account(&self.admin.pubkey(), owner, mint, &spl_token::ID)
+    }
+
+    pub fn mint_tokens_ix(&self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Instruction {
+        spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint,
+            &token_address(owner, mint),
+            &self.admin.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()
+    }
+
+    /// A funded user with token accounts for every mint, an initialized user
+    /// account and `collateral_amount` collateral in their wallet.
+    pub async fn create_user(&mut self, collateral_amount: u64) -> Keypair {
+        let user = Keypair::new();
+        let authority = user.pubkey();
+        let mut instructions = vec![
+            system_instruction::transfer(&self.admin.pubkey(), &authority, LAMPORTS_PER_SOL),
+            self.create_token_account_ix(&authority, &self.collateral_mint),
+        ];
+        for pool in self.pools.iter() {
+            instructions.push(self.create_token_account_ix(&authority, &pool.onasset_mint));
+            instructions.push(self.create_token_account_ix(&authority, &pool.underlying_mint));
+        }
+        instructions.push(self.initialize_user_ix(&authority));
+        if collateral_amount > 0 {
+            instructions.push(self.mint_tokens_ix(
+                &self.collateral_mint,
+                &authority,
+                collateral_amount,
+            ));
+        }
+        self.process(&instructions, &[]).await.unwrap();
+        user
+    }
+
+    /// A user whose comet holds `comet_collateral` and provides `liquidity`
+    /// collateral worth of liquidity to `pool_index`.
+    pub async fn create_liquidity_provider(
+        &mut self,
+        comet_collateral: u64,
+        pool_index: u8,
+        liquidity: u64,
+    ) -> Keypair {
+        let user = self.create_user(comet_collateral).await;
+        let instructions = [
+            self.update_prices_ix(),
+            self.add_collateral_to_comet_ix(&user.pubkey(), comet_collateral),
+            self.add_liquidity_to_comet_ix(&user.pubkey(), pool_index, liquidity),
+        ];
+        self.process(&instructions, &[&user]).await.unwrap();
+        user
+    }
+
+    /// A user holding `onasset_amount` onAssets of `pool_index`, borrowed
+    /// against `collateral_amount`.
+    pub async fn create_borrower(
+        &mut self,
+        collateral_amount: u64,
+        pool_index: u8,
+        onasset_amount: u64,
+    ) -> Keypair {
+        let user = self.create_user(collateral_amount).await;
+        let borrow = self.initialize_borrow_position_ix(
+            &user.pubkey(),
+            pool_index,
+            onasset_amount,
+            collateral_amount,
+        );
+        self.process_with_prices(borrow, &[&user]).await.unwrap();
+        user
+    }
+
+    pub async fn collateral_balance(&mut self, owner: &Pubkey) -> u64 {
+        let address = token_address(owner, &self.collateral_mint);
+        self.token_balance(&address).await
+    }
+
+    pub async fn onasset_balance(&mut self, owner: &Pubkey, pool_index: u8) -> u64 {
+        let address = token_address(owner, &self.pools[pool_index as usize].onasset_mint);
+        self.token_balance(&address).await
+    }
+
+    pub async fn create_pyth_oracle(&mut self, price: i64, expo: i32) -> Pubkey {
+        let price_account = Keypair::new();
+        let space = size_of::<PriceAccount>();
+        let lamports = self.rent_exempt_lamports(space).await;
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &self.admin.pubkey(),
+                    &price_account.pubkey(),
+                    lamports,
+                    space as u64,
+                    &pyth::ID,
+                ),
+                Instruction {
+                    program_id: pyth::ID,
+                    accounts: pyth::accounts::Initialize {
+                        price_account: price_account.pubkey(),
+                    }
+                    .to_account_metas(None),
+                    data: pyth::instruction::Initialize { price, expo }.data(),
+                },
+            ],
+            &[&price_account],
+        )
+        .await
+        .unwrap();
+        price_account.pubkey()
+    }
+
+    pub async fn set_pyth_price(&mut self, price_account: &Pubkey, price: i64) {
+        self.process(
+            &[Instruction {
+                program_id: pyth::ID,
+                accounts: pyth::accounts::SetPrice {
+                    price_account: *price_account,
+                }
+                .to_account_metas(None),
+                data: pyth::instruction::SetPrice { price }.data(),
+            }],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    pub fn create_switchboard_oracle(&mut self, mantissa: i128, scale: u32) -> Pubkey {
+        let aggregator = Pubkey::new_unique();
+        self.set_switchboard_result(&aggregator, mantissa, scale, 1);
+        aggregator
+    }
+
+    pub fn set_switchboard_result(
+        &mut self,
+        aggregator: &Pubkey,
+        mantissa: i128,
+        scale: u32,
+        num_success: u32,
+    ) {
+        let data = switchboard_aggregator_data(mantissa, scale, num_success);
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: SWITCHBOARD_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(aggregator, &account.into());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::fixture::*;
+use clone::error::CloneError;
+use clone::instructions::CloneParameters;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+// `TestEnv::process` puts a compute budget instruction first, so the
+// instructions it's given start at index 1.
+const FIRST_INSTRUCTION_INDEX: u8 = 1;
+
+async fn set_flash_loan_fee(env: &mut TestEnv, value: u16) {
+    let admin = env.admin.pubkey();
+    let update = env.update_clone_parameters_ix(&admin, CloneParameters::FlashLoanFee { value });
+    env.process(&[update], &[]).await.unwrap();
+}
+
+/// A protocol whose vault holds 1,000 of comet collateral.
+async fn flash_loan_env() -> (TestEnv, Keypair) {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let user = env.create_user(collateral(10)).await;
+    (env, user)
+}
+
+#[tokio::test]
+async fn flash_loan_collateral_pays_fee_to_treasury() {
+    let (mut env, user) = flash_loan_env().await;
+    let (authority, treasury, vault) = (user.pubkey(), env.treasury.pubkey(), env.vault());
+
+    let admin = env.admin.pubkey();
+    let fee_too_high =
+        env.update_clone_parameters_ix(&admin, CloneParameters::FlashLoanFee { value: 10_000 });
+    let result = env.process(&[fee_too_high], &[]).await;
+    assert_clone_error(result, CloneError::InvalidValueRange);
+    assert_eq!(
+        env.clone_state().await.flash_loan_fee_bps,
+        FLASH_LOAN_FEE_BPS
+    );
+
+    let vault_before = env.token_balance(&vault).await;
+    let treasury_before = env.collateral_balance(&treasury).await;
+    let instructions = [
+        env.flash_loan_collateral_ix(&authority, collateral(500)),
+        env.repay_flash_loan_collateral_ix(&authority, collateral(500), FIRST_INSTRUCTION_INDEX),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    // 0.30% of 500.
+    let fee = collateral(15) / 10;
+    assert_eq!(env.token_balance(&vault).await, vault_before);
+    assert_eq!(
+        env.collateral_balance(&treasury).await,
+        treasury_before + fee
+    );
+    assert_eq!(
+        env.collateral_balance(&authority).await,
+        collateral(10) - fee
+    );
+}
+
+#[tokio::test]
+async fn flash_loan_must_be_repaid_in_the_same_transaction() {
+    let (mut env, user) = flash_loan_env().await;
+    let authority = user.pubkey();
+    let borrow = env.flash_loan_collateral_ix(&authority, collateral(500));
+
+    let result = env.process(&[borrow.clone()], &[&user]).await;
+    assert_clone_error(result, CloneError::FlashLoanNotRepaid);
+
+    let repay_less =
+        env.repay_flash_loan_collateral_ix(&authority, collateral(499), FIRST_INSTRUCTION_INDEX);
+    let result = env.process(&[borrow.clone(), repay_less], &[&user]).await;
+    assert_clone_error(result, CloneError::FlashLoanNotRepaid);
+
+    // Each loan needs its own repayment, so one can't cover two loans.
+    let repay = env.repay_flash_loan_collateral_ix(
+        &authority,
+        collateral(500),
+        FIRST_INSTRUCTION_INDEX + 1,
+    );
+    let result = env
+        .process(&[borrow.clone(), borrow.clone(), repay.clone()], &[&user])
+        .await;
+    assert_clone_error(result, CloneError::FlashLoanNotRepaid);
+
+    // Repayments must point back at an earlier loan of the same amount.
+    let result = env.process(&[repay.clone()], &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidFlashLoan);
+    let result = env.process(&[repay, borrow], &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidFlashLoan);
+}
+
+#[tokio::test]
+async fn zero_flash_loan_fee_disables_flash_loans() {
+    let (mut env, user) = flash_loan_env().await;
+    let authority = user.pubkey();
+    set_flash_loan_fee(&mut env, 0).await;
+
+    let instructions = [
+        env.flash_loan_collateral_ix(&authority, collateral(500)),
+        env.repay_flash_loan_collateral_ix(&authority, collateral(500), FIRST_INSTRUCTION_INDEX),
+    ];
+    let result = env.process(&instructions, &[&user]).await;
+    assert_clone_error(result, CloneError::FlashLoansDisabled);
+    let instructions = [
+        env.flash_mint_onasset_ix(&authority, PYTH_POOL, onasset(1)),
+        env.repay_flash_mint_onasset_ix(&authority, PYTH_POOL, onasset(1), FIRST_INSTRUCTION_INDEX),
+    ];
+    let result = env.process(&instructions, &[&user]).await;
+    assert_clone_error(result, CloneError::FlashLoansDisabled);
+}
+
+#[tokio::test]
+async fn flash_mint_liquidates_borrow_position_without_inventory() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let enable = env.update_clone_parameters_ix(
+        &admin,
+        CloneParameters::NonAuthLiquidationsEnabled { value: true },
+    );
+    env.process(&[enable], &[]).await.unwrap();
+    env.create_liquidity_provider(collateral(10_000), PYTH_POOL, collateral(5_000))
+        .await;
+    let borrower = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(50))
+        .await;
+    let liquidator = env.create_user(collateral(100)).await;
+    let (borrower_key, liquidator_key) = (borrower.pubkey(), liquidator.pubkey());
+
+    // At 15.00 the position is at ~133%, below the 150% minimum.
+    let pyth_oracle = env.pools[PYTH_POOL as usize].oracle;
+    env.set_pyth_price(&pyth_oracle, 1_500_000_000).await;
+
+    let amount = onasset(10);
+    let fee = amount * FLASH_LOAN_FEE_BPS as u64 / 10_000;
+    let flash_mint = env.flash_mint_onasset_ix(&liquidator_key, PYTH_POOL, amount);
+    let liquidate =
+        env.liquidate_borrow_position_ix(&liquidator_key, &borrower_key, PYTH_POOL, 0, amount);
+    // Buys back what's owed with the collateral the liquidation paid out.
+    let swap = env.swap_ix(
+        &liquidator_key,
+        PYTH_POOL,
+        amount + fee,
+        false,
+        false,
+        collateral(250),
+    );
+
+    let repay_other_pool = env.repay_flash_mint_onasset_ix(
+        &liquidator_key,
+        SWITCHBOARD_POOL,
+        amount,
+        FIRST_INSTRUCTION_INDEX + 1,
+    );
+    let instructions = [
+        env.update_prices_ix(),
+        flash_mint.clone(),
+        liquidate.clone(),
+        swap.clone(),
+        repay_other_pool,
+    ];
+    let result = env.process(&instructions, &[&liquidator]).await;
+    assert_clone_error(result, CloneError::FlashLoanNotRepaid);
+
+    let treasury = env.treasury.pubkey();
+    let treasury_before = env.onasset_balance(&treasury, PYTH_POOL).await;
+    let repay = env.repay_flash_mint_onasset_ix(
+        &liquidator_key,
+        PYTH_POOL,
+        amount,
+        FIRST_INSTRUCTION_INDEX + 1,
+    );
+    let instructions = [env.update_prices_ix(), flash_mint, liquidate, swap, repay];
+    env.process(&instructions, &[&liquidator]).await.unwrap();
+
+    let state = env.user_state(&borrower_key).await;
+    assert_eq!(state.borrows[0].borrowed_onasset, onasset(40));
+    assert_eq!(env.onasset_balance(&liquidator_key, PYTH_POOL).await, 0);
+    // The treasury collects the swap's fees on top of the flash mint fee.
+    assert!(env.onasset_balance(&treasury, PYTH_POOL).await >= treasury_before + fee);
+}
@@ -0,0 +1,201 @@
+use crate::fixture::*;
+use anchor_lang::error::{ErrorCode, ERROR_CODE_OFFSET};
+use anchor_lang::AccountDeserialize;
+use clone::error::CloneError;
+use clone::instructions::{CloneParameters, PoolParameters};
+use clone_governance::error::GovernanceError;
+use clone_governance::states::{Proposal, ProposalAction};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+const VOTING_PERIOD_SLOTS: u64 = 10;
+const EXECUTION_DELAY_SLOTS: u64 = 5;
+// The system program's AccountAlreadyInUse, raised by a second vote record.
+const ACCOUNT_ALREADY_IN_USE: u32 = 0;
+
+fn governance_error(error: GovernanceError) -> u32 {
+    ERROR_CODE_OFFSET + error as u32
+}
+
+fn treasury_fee_action(value: u16) -> ProposalAction {
+    ProposalAction::UpdatePoolParameters {
+        index: PYTH_POOL,
+        params: PoolParameters::TreasuryTradingFee { value },
+    }
+}
+
+async fn proposal_state(env: &mut TestEnv, index: u64) -> Proposal {
+    let account = env.account(&proposal_address(index)).await.unwrap();
+    Proposal::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+async fn create_staker(env: &mut TestEnv, cln_token_mint: &Pubkey, amount: u64) -> Keypair {
+    let staker = env.create_user(0).await;
+    let authority = staker.pubkey();
+    let instructions = [
+        env.create_token_account_ix(&authority, cln_token_mint),
+        env.mint_tokens_ix(cln_token_mint, &authority, amount),
+        env.add_stake_ix(&authority, cln_token_mint, amount),
+    ];
+    env.process(&instructions, &[&staker]).await.unwrap();
+    staker
+}
+
+/// Stakers with 60 and 30 CLN, and governance as the clone admin with a
+/// quorum of 50 CLN and a proposal threshold of 10 CLN.
+async fn setup_governance(env: &mut TestEnv) -> (Pubkey, Keypair, Keypair) {
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let large = create_staker(env, &cln_token_mint, onasset(60)).await;
+    let small = create_staker(env, &cln_token_mint, onasset(30)).await;
+
+    let admin = env.admin.pubkey();
+    let instructions = [
+        env.initialize_governance_ix(
+            VOTING_PERIOD_SLOTS,
+            EXECUTION_DELAY_SLOTS,
+            onasset(50),
+            onasset(10),
+        ),
+        env.update_clone_parameters_ix(
+            &admin,
+            CloneParameters::PendingAdmin {
+                address: governance_address(),
+            },
+        ),
+        env.accept_clone_admin_ix(),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+    env.warp_slots(1).await;
+    (cln_token_mint, large, small)
+}
+
+#[tokio::test]
+async fn passed_proposal_executes_after_delay() {
+    let mut env = TestEnv::new().await;
+    let (cln_token_mint, large, small) = setup_governance(&mut env).await;
+    assert_eq!(env.clone_state().await.admin, governance_address());
+
+    let outsider = env.create_user(0).await;
+    let create = env.create_proposal_ix(&outsider.pubkey(), 0, treasury_fee_action(123));
+    let result = env.process(&[create], &[&outsider]).await;
+    assert_anchor_error(result, ErrorCode::AccountNotInitialized);
+
+    let create = env.create_proposal_ix(&small.pubkey(), 0, treasury_fee_action(123));
+    env.process(&[create], &[&small]).await.unwrap();
+
+    // Stake added after the proposal was created doesn't vote on it.
+    let late = create_staker(&mut env, &cln_token_mint, onasset(100)).await;
+    let vote = env.cast_vote_ix(&late.pubkey(), 0, false);
+    let result = env.process(&[vote], &[&late]).await;
+    assert_custom_error(
+        result,
+        governance_error(GovernanceError::InsufficientVotingPower),
+    );
+
+    // A top-up after the snapshot keeps the stake held before it.
+    let top_up = [
+        env.mint_tokens_ix(&cln_token_mint, &large.pubkey(), onasset(40)),
+        env.add_stake_ix(&large.pubkey(), &cln_token_mint, onasset(40)),
+    ];
+    env.process(&top_up, &[&large]).await.unwrap();
+    let vote = env.cast_vote_ix(&large.pubkey(), 0, true);
+    env.process(&[vote], &[&large]).await.unwrap();
+    assert_eq!(proposal_state(&mut env, 0).await.votes_for, onasset(60));
+    let vote = env.cast_vote_ix(&small.pubkey(), 0, false);
+    env.process(&[vote.clone()], &[&small]).await.unwrap();
+    let result = env.process(&[vote], &[&small]).await;
+    assert_custom_error(result, ACCOUNT_ALREADY_IN_USE);
+
+    let execute = env.execute_proposal_ix(0);
+    env.warp_slots(VOTING_PERIOD_SLOTS).await;
+    let result = env.process(&[execute.clone()], &[]).await;
+    assert_custom_error(
+        result,
+        governance_error(GovernanceError::ExecutionDelayNotElapsed),
+    );
+    let vote = env.cast_vote_ix(&late.pubkey(), 0, true);
+    let result = env.process(&[vote], &[&late]).await;
+    assert_custom_error(result, governance_error(GovernanceError::VotingClosed));
+
+    env.warp_slots(EXECUTION_DELAY_SLOTS).await;
+    env.process(&[execute.clone()], &[]).await.unwrap();
+    assert_eq!(
+        env.pools_state().await.pools[0].treasury_trading_fee_bps,
+        123
+    );
+    let result = env.process(&[execute], &[]).await;
+    assert_custom_error(
+        result,
+        governance_error(GovernanceError::ProposalAlreadyExecuted),
+    );
+
+    // The former admin key no longer controls the protocol.
+    let admin = env.admin.pubkey();
+    let params = PoolParameters::TreasuryTradingFee { value: 0 };
+    let update = env.update_pool_parameters_ix(&admin, PYTH_POOL, params);
+    let result = env.process(&[update], &[]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+}
+
+#[tokio::test]
+async fn stake_changes_after_snapshot_never_add_voting_power() {
+    let mut env = TestEnv::new().await;
+    let (cln_token_mint, large, small) = setup_governance(&mut env).await;
+    let create = env.create_proposal_ix(&small.pubkey(), 0, treasury_fee_action(123));
+    env.process(&[create], &[&small]).await.unwrap();
+
+    // The second top-up used to be counted as held before the first.
+    let authority = large.pubkey();
+    for _ in 0..2 {
+        env.warp_slots(1).await;
+        let top_up = [
+            env.mint_tokens_ix(&cln_token_mint, &authority, onasset(40)),
+            env.add_stake_ix(&authority, &cln_token_mint, onasset(40)),
+        ];
+        env.process(&top_up, &[&large]).await.unwrap();
+    }
+    // Unstaking lowers it, whatever is staked again afterwards.
+    env.warp_slots(1).await;
+    let unstake = env.request_unstake_ix(&authority, onasset(120));
+    env.process(&[unstake], &[&large]).await.unwrap();
+    env.warp_slots(1).await;
+    let top_up = [
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(40)),
+        env.add_stake_ix(&authority, &cln_token_mint, onasset(40)),
+    ];
+    env.process(&top_up, &[&large]).await.unwrap();
+
+    let vote = env.cast_vote_ix(&authority, 0, true);
+    env.process(&[vote], &[&large]).await.unwrap();
+    assert_eq!(proposal_state(&mut env, 0).await.votes_for, onasset(20));
+}
+
+#[tokio::test]
+async fn proposal_needs_quorum_and_majority() {
+    let mut env = TestEnv::new().await;
+    let (_, large, small) = setup_governance(&mut env).await;
+
+    // 30 in favor is below the quorum, 60 against outweighs 30 in favor.
+    for (index, votes) in [
+        (0, vec![(&small, true)]),
+        (1, vec![(&small, true), (&large, false)]),
+    ] {
+        let create = env.create_proposal_ix(&small.pubkey(), index, treasury_fee_action(123));
+        env.process(&[create], &[&small]).await.unwrap();
+        for (voter, approve) in votes {
+            let vote = env.cast_vote_ix(&voter.pubkey(), index, approve);
+            env.process(&[vote], &[voter]).await.unwrap();
+        }
+    }
+
+    env.warp_slots(VOTING_PERIOD_SLOTS + EXECUTION_DELAY_SLOTS)
+        .await;
+    for index in 0..2 {
+        let execute = env.execute_proposal_ix(index);
+        let result = env.process(&[execute], &[]).await;
+        assert_custom_error(result, governance_error(GovernanceError::ProposalNotPassed));
+    }
+    assert_eq!(
+        env.pools_state().await.pools[0].treasury_trading_fee_bps,
+        TREASURY_TRADING_FEE_BPS
+    );
+}
@@ -0,0 +1,1705 @@
+//! Instruction builders for every clone instruction, filled in with the
+//! accounts of the local deployment.
+
+use crate::fixture::*;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use clone::instructions::{
+    BorrowBounds, CloneParameters, CometBounds, MetadataArgs, MigrationTarget, PaymentType,
+    PoolParameters, UpdateOracleParameters,
+};
+use clone::states::TransactionAccount;
+use clone::{accounts, instruction};
+use clone_cpi::{AggregatorSwapAccounts, AggregatorSwapArgs, StakingAccounts};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signer::Signer,
+    system_program, sysvar,
+};
+
+fn clone_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: clone::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+impl TestEnv {
+    pub fn vault(&self) -> Pubkey {
+        token_address(&clone_address(), &self.collateral_mint)
+    }
+
+    /// Staking rewards are paid in collateral.
+    pub fn staking_reward_vault(&self) -> Pubkey {
+        token_address(&staking_address(), &self.collateral_mint)
+    }
+
+    // Unknown pool indices fall back to pool 0's accounts, which exist and
+    // deserialize, so the program's own index checks are what reject them.
+    fn pool(&self, pool_index: u8) -> &PoolAccounts {
+        self.pools
+            .get(pool_index as usize)
+            .unwrap_or(&self.pools[0])
+    }
+
+    pub fn onasset_mint(&self, pool_index: u8) -> Pubkey {
+        self.pool(pool_index).onasset_mint
+    }
+
+    pub fn underlying_mint(&self, pool_index: u8) -> Pubkey {
+        self.pool(pool_index).underlying_mint
+    }
+
+    fn collateral_account(&self, owner: &Pubkey) -> Pubkey {
+        token_address(owner, &self.collateral_mint)
+    }
+
+    fn onasset_account(&self, owner: &Pubkey, pool_index: u8) -> Pubkey {
+        token_address(owner, &self.onasset_mint(pool_index))
+    }
+
+    pub fn initialize_clone_ix(
+        &self,
+        comet_collateral_ild_liquidator_fee_bps: u16,
+        comet_onasset_ild_liquidator_fee_bps: u16,
+        borrow_liquidator_fee_bps: u16,
+        flash_loan_fee_bps: u16,
+    ) -> Instruction {
+        clone_ix(
+            accounts::InitializeClone {
+                admin: self.admin.pubkey(),
+                clone: clone_address(),
+                collateral_mint: self.collateral_mint,
+                collateral_vault: self.vault(),
+                rent: sysvar::rent::ID,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeClone {
+                comet_collateral_ild_liquidator_fee_bps,
+                comet_onasset_ild_liquidator_fee_bps,
+                borrow_liquidator_fee_bps,
+                treasury_address: self.treasury.pubkey(),
+                collateral_oracle_index: COLLATERAL_ORACLE_INDEX,
+                collateralization_ratio: 100,
+                flash_loan_fee_bps,
+            },
+        )
+    }
+
+    pub fn initialize_pools_ix(&self, admin: &Pubkey) -> Instruction {
+        clone_ix(
+            accounts::InitializePools {
+                admin: *admin,
+                clone: clone_address(),
+                pools: pools_address(),
+                system_program: system_program::ID,
+            },
+            instruction::InitializePools {},
+        )
+    }
+
+    pub fn initialize_oracles_ix(&self, admin: &Pubkey) -> Instruction {
+        clone_ix(
+            accounts::InitializeOracles {
+                admin: *admin,
+                clone: clone_address(),
+                oracles: oracles_address(),
+                system_program: system_program::ID,
+            },
+            instruction::InitializeOracles {},
+        )
+    }
+
+    pub fn update_clone_parameters_ix(
+        &self,
+        admin: &Pubkey,
+        params: CloneParameters,
+    ) -> Instruction {
+        clone_ix(
+            accounts::UpdateCloneParameters {
+                admin: *admin,
+                clone: clone_address(),
+            },
+            instruction::UpdateCloneParameters { params },
+        )
+    }
+
+    pub fn accept_admin_ix(&self, pending_admin: &Pubkey) -> Instruction {
+        clone_ix(
+            accounts::AcceptAdmin {
+                pending_admin: *pending_admin,
+                clone: clone_address(),
+            },
+            instruction::AcceptAdmin {},
+        )
+    }
+
+    pub fn update_pool_parameters_ix(
+        &self,
+        auth: &Pubkey,
+        index: u8,
+        params: PoolParameters,
+    ) -> Instruction {
+        clone_ix(
+            accounts::UpdatePoolParameters {
+                auth: *auth,
+                clone: clone_address(),
+                pools: pools_address(),
+            },
+            instruction::UpdatePoolParameters { index, params },
+        )
+    }
+
+    pub fn update_oracles_ix(&self, auth: &Pubkey, params: UpdateOracleParameters) -> Instruction {
+        clone_ix(
+            accounts::UpdateOracles {
+                auth: *auth,
+                clone: clone_address(),
+                oracles: oracles_address(),
+            },
+            instruction::UpdateOracles { params },
+        )
+    }
+
+    pub fn initialize_admin_multisig_ix(&self, signers: Vec<Pubkey>, threshold: u8) -> Instruction {
+        clone_ix(
+            accounts::InitializeAdminMultisig {
+                admin: self.admin.pubkey(),
+                clone: clone_address(),
+                admin_multisig: admin_multisig_address(),
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAdminMultisig { signers, threshold },
+        )
+    }
+
+    /// Only succeeds as a payload executed by the multisig.
+    pub fn update_admin_multisig_ix(&self, signers: Vec<Pubkey>, threshold: u8) -> Instruction {
+        clone_ix(
+            accounts::UpdateAdminMultisig {
+                admin_multisig: admin_multisig_address(),
+            },
+            instruction::UpdateAdminMultisig { signers, threshold },
+        )
+    }
+
+    pub fn propose_admin_transaction_ix(
+        &self,
+        proposer: &Pubkey,
+        index: u64,
+        payload: &Instruction,
+    ) -> Instruction {
+        clone_ix(
+            accounts::ProposeAdminTransaction {
+                proposer: *proposer,
+                admin_multisig: admin_multisig_address(),
+                admin_transaction: admin_transaction_address(index),
+                system_program: system_program::ID,
+            },
+            instruction::ProposeAdminTransaction {
+                instruction_program_id: payload.program_id,
+                instruction_accounts: payload
+                    .accounts
+                    .iter()
+                    .map(|meta| TransactionAccount {
+                        pubkey: meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                instruction_data: payload.data.clone(),
+            },
+        )
+    }
+
+    pub fn approve_admin_transaction_ix(&self, signer: &Pubkey, index: u64) -> Instruction {
+        clone_ix(
+            accounts::ApproveAdminTransaction {
+                signer: *signer,
+                admin_multisig: admin_multisig_address(),
+                admin_transaction: admin_transaction_address(index),
+            },
+            instruction::ApproveAdminTransaction {},
+        )
+    }
+
+    /// Passes the payload's accounts, which the multisig signs for, followed
+    /// by its program.
+    pub fn execute_admin_transaction_ix(&self, index: u64, payload: &Instruction) -> Instruction {
+        let mut instruction = clone_ix(
+            accounts::ExecuteAdminTransaction {
+                admin_multisig: admin_multisig_address(),
+                admin_transaction: admin_transaction_address(index),
+            },
+            instruction::ExecuteAdminTransaction {},
+        );
+        instruction
+            .accounts
+            .extend(payload.accounts.iter().map(|meta| AccountMeta {
+                is_signer: false,
+                ..meta.clone()
+            }));
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(payload.program_id, false));
+        instruction
+    }
+
+    pub fn initialize_user_ix(&self, authority: &Pubkey) -> Instruction {
+        clone_ix(
+            accounts::InitializeUser {
+                payer: self.admin.pubkey(),
+                user_account: user_address(authority),
+                system_program: system_program::ID,
+            },
+            instruction::InitializeUser {
+                authority: *authority,
+            },
+        )
+    }
+
+    pub fn add_pool_ix(
+        &self,
+        onasset_mint: &Pubkey,
+        underlying_asset_mint: &Pubkey,
+        min_overcollateral_ratio: u16,
+        max_liquidation_overcollateral_ratio: u16,
+        oracle_info_index: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::AddPool {
+                admin: self.admin.pubkey(),
+                clone: clone_address(),
+                pools: pools_address(),
+                onasset_mint: *onasset_mint,
+                onasset_token_account: token_address(&clone_address(), onasset_mint),
+                underlying_asset_mint: *underlying_asset_mint,
+                underlying_asset_token_account: token_address(
+                    &clone_address(),
+                    underlying_asset_mint,
+                ),
+                system_program: system_program::ID,
+            },
+            instruction::AddPool {
+                min_overcollateral_ratio,
+                max_liquidation_overcollateral_ratio,
+                liquidity_trading_fee_bps: LIQUIDITY_TRADING_FEE_BPS,
+                treasury_trading_fee_bps: TREASURY_TRADING_FEE_BPS,
+                il_health_score_coefficient: 100,
+                position_health_score_coefficient: 100,
+                oracle_info_index,
+            },
+        )
+    }
+
+    /// Refreshes every registered oracle.
+    pub fn update_prices_ix(&self) -> Instruction {
+        let indices = (0..self.oracles.len() as u8).collect();
+        self.update_prices_for_ix(indices, &self.oracles)
+    }
+
+    pub fn update_prices_for_ix(&self, oracle_indices: Vec<u8>, feeds: &[Pubkey]) -> Instruction {
+        let mut ix = clone_ix(
+            accounts::UpdatePrices {
+                oracles: oracles_address(),
+            },
+            instruction::UpdatePrices { oracle_indices },
+        );
+        ix.accounts.extend(
+            feeds
+                .iter()
+                .map(|feed| AccountMeta::new_readonly(*feed, false)),
+        );
+        ix
+    }
+
+    pub fn initialize_borrow_position_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        onasset_amount: u64,
+        collateral_amount: u64,
+    ) -> Instruction {
+        self.initialize_borrow_position_with_bounds_ix(
+            user,
+            pool_index,
+            onasset_amount,
+            collateral_amount,
+            BorrowBounds::default(),
+        )
+    }
+
+    pub fn initialize_borrow_position_with_bounds_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        onasset_amount: u64,
+        collateral_amount: u64,
+        bounds: BorrowBounds,
+    ) -> Instruction {
+        clone_ix(
+            accounts::InitializeBorrowPosition {
+                user: *user,
+                payer: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                vault: self.vault(),
+                user_collateral_token_account: self.collateral_account(user),
+                onasset_mint: self.onasset_mint(pool_index),
+                user_onasset_token_account: self.onasset_account(user, pool_index),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeBorrowPosition {
+                pool_index,
+                onasset_amount,
+                collateral_amount,
+                bounds,
+            },
+        )
+    }
+
+    pub fn add_collateral_to_borrow_ix(
+        &self,
+        user: &Pubkey,
+        borrow_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        clone_ix(
+            accounts::AddCollateralToBorrow {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                vault: self.vault(),
+                user_collateral_token_account: self.collateral_account(user),
+                token_program: spl_token::ID,
+            },
+            instruction::AddCollateralToBorrow {
+                borrow_index,
+                amount,
+            },
+        )
+    }
+
+    pub fn withdraw_collateral_from_borrow_ix(
+        &self,
+        user: &Pubkey,
+        borrow_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        clone_ix(
+            accounts::WithdrawCollateralFromBorrow {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                vault: self.vault(),
+                user_collateral_token_account: self.collateral_account(user),
+                token_program: spl_token::ID,
+            },
+            instruction::WithdrawCollateralFromBorrow {
+                borrow_index,
+                amount,
+            },
+        )
+    }
+
+    pub fn pay_borrow_debt_ix(
+        &self,
+        payer: &Pubkey,
+        user: &Pubkey,
+        pool_index: u8,
+        borrow_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        clone_ix(
+            accounts::PayBorrowDebt {
+                payer: *payer,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                payer_onasset_token_account: self.onasset_account(payer, pool_index),
+                onasset_mint: self.onasset_mint(pool_index),
+                token_program: spl_token::ID,
+            },
+            instruction::PayBorrowDebt {
+                user: *user,
+                borrow_index,
+                amount,
+            },
+        )
+    }
+
+    pub fn borrow_more_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        borrow_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        self.borrow_more_with_bounds_ix(
+            user,
+            pool_index,
+            borrow_index,
+            amount,
+            BorrowBounds::default(),
+        )
+    }
+
+    pub fn borrow_more_with_bounds_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        borrow_index: u8,
+        amount: u64,
+        bounds: BorrowBounds,
+    ) -> Instruction {
+        clone_ix(
+            accounts::BorrowMore {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                user_onasset_token_account: self.onasset_account(user, pool_index),
+                onasset_mint: self.onasset_mint(pool_index),
+                token_program: spl_token::ID,
+            },
+            instruction::BorrowMore {
+                borrow_index,
+                amount,
+                bounds,
+            },
+        )
+    }
+
+    pub fn add_collateral_to_comet_ix(&self, user: &Pubkey, collateral_amount: u64) -> Instruction {
+        clone_ix(
+            accounts::AddCollateralToComet {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                vault: self.vault(),
+                user_collateral_token_account: self.collateral_account(user),
+                token_program: spl_token::ID,
+            },
+            instruction::AddCollateralToComet { collateral_amount },
+        )
+    }
+
+    pub fn withdraw_collateral_from_comet_ix(
+        &self,
+        user: &Pubkey,
+        collateral_amount: u64,
+    ) -> Instruction {
+        clone_ix(
+            accounts::WithdrawCollateralFromComet {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                vault: self.vault(),
+                user_collateral_token_account: self.collateral_account(user),
+                token_program: spl_token::ID,
+            },
+            instruction::WithdrawCollateralFromComet { collateral_amount },
+        )
+    }
+
+    pub fn add_liquidity_to_comet_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        collateral_amount: u64,
+    ) -> Instruction {
+        self.add_liquidity_to_comet_with_bounds_ix(
+            user,
+            pool_index,
+            collateral_amount,
+            CometBounds::default(),
+        )
+    }
+
+    pub fn add_liquidity_to_comet_with_bounds_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        collateral_amount: u64,
+        bounds: CometBounds,
+    ) -> Instruction {
+        clone_ix(
+            accounts::AddLiquidityToComet {
+                user: *user,
+                payer: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                system_program: system_program::ID,
+            },
+            instruction::AddLiquidityToComet {
+                pool_index,
+                collateral_amount,
+                bounds,
+            },
+        )
+    }
+
+    pub fn withdraw_liquidity_from_comet_ix(
+        &self,
+        user: &Pubkey,
+        comet_position_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        self.withdraw_liquidity_from_comet_with_bounds_ix(
+            user,
+            comet_position_index,
+            amount,
+            CometBounds::default(),
+        )
+    }
+
+    pub fn withdraw_liquidity_from_comet_with_bounds_ix(
+        &self,
+        user: &Pubkey,
+        comet_position_index: u8,
+        amount: u64,
+        bounds: CometBounds,
+    ) -> Instruction {
+        clone_ix(
+            accounts::WithdrawLiquidityFromComet {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+            },
+            instruction::WithdrawLiquidityFromComet {
+                comet_position_index,
+                amount,
+                bounds,
+            },
+        )
+    }
+
+    pub fn liquidate_comet_collateral_ild_ix(
+        &self,
+        liquidator: &Pubkey,
+        user: &Pubkey,
+        comet_position_index: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::LiquidateCometCollateralIld {
+                liquidator: *liquidator,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                collateral_mint: self.collateral_mint,
+                liquidator_collateral_token_account: self.collateral_account(liquidator),
+                vault: self.vault(),
+                token_program: spl_token::ID,
+            },
+            instruction::LiquidateCometCollateralIld {
+                user: *user,
+                comet_position_index,
+            },
+        )
+    }
+
+    pub fn liquidate_comet_onasset_ild_ix(
+        &self,
+        liquidator: &Pubkey,
+        user: &Pubkey,
+        pool_index: u8,
+        comet_position_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        clone_ix(
+            accounts::LiquidateCometOnassetIld {
+                liquidator: *liquidator,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                onasset_mint: self.onasset_mint(pool_index),
+                liquidator_onasset_token_account: self.onasset_account(liquidator, pool_index),
+                liquidator_collateral_token_account: self.collateral_account(liquidator),
+                vault: self.vault(),
+                token_program: spl_token::ID,
+            },
+            instruction::LiquidateCometOnassetIld {
+                user: *user,
+                comet_position_index,
+                amount,
+            },
+        )
+    }
+
+    pub fn liquidate_borrow_position_ix(
+        &self,
+        liquidator: &Pubkey,
+        user: &Pubkey,
+        pool_index: u8,
+        borrow_index: u8,
+        amount: u64,
+    ) -> Instruction {
+        clone_ix(
+            accounts::LiquidateBorrowPosition {
+                liquidator: *liquidator,
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                user_account: user_address(user),
+                onasset_mint: self.onasset_mint(pool_index),
+                vault: self.vault(),
+                liquidator_collateral_token_account: self.collateral_account(liquidator),
+                liquidator_onasset_token_account: self.onasset_account(liquidator, pool_index),
+                token_program: spl_token::ID,
+            },
+            instruction::LiquidateBorrowPosition {
+                user: *user,
+                borrow_index,
+                amount,
+            },
+        )
+    }
+
+    pub fn collect_lp_rewards_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        comet_position_index: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::CollectLpRewards {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                collateral_vault: self.vault(),
+                onasset_mint: self.onasset_mint(pool_index),
+                user_collateral_token_account: self.collateral_account(user),
+                user_onasset_token_account: self.onasset_account(user, pool_index),
+                token_program: spl_token::ID,
+            },
+            instruction::CollectLpRewards {
+                comet_position_index,
+            },
+        )
+    }
+
+    pub fn compound_lp_rewards_ix(
+        &self,
+        signer: &Pubkey,
+        user: &Pubkey,
+        comet_position_index: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::CompoundLpRewards {
+                signer: *signer,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+            },
+            instruction::CompoundLpRewards {
+                user: *user,
+                comet_position_index,
+            },
+        )
+    }
+
+    pub fn net_lp_rewards_ix(&self, user: &Pubkey, comet_position_index: u8) -> Instruction {
+        clone_ix(
+            accounts::NetLpRewards {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+            },
+            instruction::NetLpRewards {
+                comet_position_index,
+            },
+        )
+    }
+
+    pub fn set_auto_compound_ix(
+        &self,
+        user: &Pubkey,
+        comet_position_index: u8,
+        enabled: bool,
+    ) -> Instruction {
+        clone_ix(
+            accounts::SetAutoCompound {
+                user: *user,
+                user_account: user_address(user),
+            },
+            instruction::SetAutoCompound {
+                comet_position_index,
+                enabled,
+            },
+        )
+    }
+
+    pub fn fund_pool_rewards_ix(
+        &self,
+        pool_index: u8,
+        reward_mint: &Pubkey,
+        emission_rate: u64,
+        amount: u64,
+    ) -> Instruction {
+        let admin = self.admin.pubkey();
+        clone_ix(
+            accounts::FundPoolRewards {
+                admin,
+                clone: clone_address(),
+                pools: pools_address(),
+                reward_mint: *reward_mint,
+                admin_reward_token_account: token_address(&admin, reward_mint),
+                reward_vault: token_address(&clone_address(), reward_mint),
+                token_program: spl_token::ID,
+            },
+            instruction::FundPoolRewards {
+                pool_index,
+                emission_rate,
+                amount,
+            },
+        )
+    }
+
+    pub fn claim_pool_rewards_ix(
+        &self,
+        user: &Pubkey,
+        reward_mint: &Pubkey,
+        comet_position_index: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::ClaimPoolRewards {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                reward_mint: *reward_mint,
+                reward_vault: token_address(&clone_address(), reward_mint),
+                user_reward_token_account: token_address(user, reward_mint),
+                token_program: spl_token::ID,
+            },
+            instruction::ClaimPoolRewards {
+                comet_position_index,
+            },
+        )
+    }
+
+    pub fn pay_impermanent_loss_debt_ix(
+        &self,
+        payer: &Pubkey,
+        user: &Pubkey,
+        pool_index: u8,
+        comet_position_index: u8,
+        amount: u64,
+        payment_type: PaymentType,
+    ) -> Instruction {
+        self.pay_impermanent_loss_debt_with_bounds_ix(
+            payer,
+            user,
+            pool_index,
+            comet_position_index,
+            amount,
+            payment_type,
+            CometBounds::default(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn pay_impermanent_loss_debt_with_bounds_ix(
+        &self,
+        payer: &Pubkey,
+        user: &Pubkey,
+        pool_index: u8,
+        comet_position_index: u8,
+        amount: u64,
+        payment_type: PaymentType,
+        bounds: CometBounds,
+    ) -> Instruction {
+        clone_ix(
+            accounts::PayImpermanentLossDebt {
+                payer: *payer,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: Some(oracles_address()),
+                collateral_mint: self.collateral_mint,
+                collateral_vault: self.vault(),
+                onasset_mint: self.onasset_mint(pool_index),
+                payer_collateral_token_account: self.collateral_account(payer),
+                payer_onasset_token_account: self.onasset_account(payer, pool_index),
+                token_program: spl_token::ID,
+            },
+            instruction::PayImpermanentLossDebt {
+                user: *user,
+                comet_position_index,
+                amount,
+                payment_type,
+                bounds,
+            },
+        )
+    }
+
+    pub fn close_user_account_ix(&self, user: &Pubkey, destination: &Pubkey) -> Instruction {
+        clone_ix(
+            accounts::CloseUserAccount {
+                user: *user,
+                user_account: user_address(user),
+                destination: *destination,
+                system_program: system_program::ID,
+            },
+            instruction::CloseUserAccount {},
+        )
+    }
+
+    pub fn compact_user_account_ix(&self, user: &Pubkey) -> Instruction {
+        clone_ix(
+            accounts::CompactUserAccount {
+                user: *user,
+                user_account: user_address(user),
+            },
+            instruction::CompactUserAccount {},
+        )
+    }
+
+    pub fn wrap_asset_ix(&self, user: &Pubkey, pool_index: u8, amount: u64) -> Instruction {
+        let asset_mint = self.underlying_mint(pool_index);
+        clone_ix(
+            accounts::WrapAsset {
+                user: *user,
+                clone: clone_address(),
+                pools: pools_address(),
+                underlying_asset_token_account: token_address(&clone_address(), &asset_mint),
+                asset_mint,
+                user_asset_token_account: token_address(user, &asset_mint),
+                onasset_mint: self.onasset_mint(pool_index),
+                user_onasset_token_account: self.onasset_account(user, pool_index),
+                token_program: spl_token::ID,
+            },
+            instruction::WrapAsset { amount, pool_index },
+        )
+    }
+
+    pub fn unwrap_onasset_ix(&self, user: &Pubkey, pool_index: u8, amount: u64) -> Instruction {
+        let asset_mint = self.underlying_mint(pool_index);
+        clone_ix(
+            accounts::UnwrapOnAsset {
+                user: *user,
+                clone: clone_address(),
+                pools: pools_address(),
+                underlying_asset_token_account: token_address(&clone_address(), &asset_mint),
+                asset_mint,
+                user_asset_token_account: token_address(user, &asset_mint),
+                onasset_mint: self.onasset_mint(pool_index),
+                user_onasset_token_account: self.onasset_account(user, pool_index),
+                token_program: spl_token::ID,
+            },
+            instruction::UnwrapOnasset { amount, pool_index },
+        )
+    }
+
+    pub fn remove_comet_position_ix(&self, user: &Pubkey, comet_position_index: u8) -> Instruction {
+        clone_ix(
+            accounts::RemoveCometPosition {
+                user: *user,
+                user_account: user_address(user),
+                pools: pools_address(),
+            },
+            instruction::RemoveCometPosition {
+                comet_position_index,
+            },
+        )
+    }
+
+    /// Swap without a staking account, so the pool's own fees apply.
+    pub fn swap_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+        result_threshold: u64,
+    ) -> Instruction {
+        self.swap_with_staking_ix(
+            user,
+            pool_index,
+            quantity,
+            quantity_is_input,
+            quantity_is_collateral,
+            result_threshold,
+            false,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_with_staking_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+        result_threshold: u64,
+        with_staking: bool,
+    ) -> Instruction {
+        let treasury = self.treasury.pubkey();
+        let onasset_mint = self.onasset_mint(pool_index);
+        clone_ix(
+            accounts::Swap {
+                user: *user,
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                user_collateral_token_account: self.collateral_account(user),
+                user_onasset_token_account: token_address(user, &onasset_mint),
+                onasset_mint,
+                collateral_mint: self.collateral_mint,
+                collateral_vault: self.vault(),
+                treasury_onasset_token_account: token_address(&treasury, &onasset_mint),
+                treasury_collateral_token_account: self.collateral_account(&treasury),
+                token_program: spl_token::ID,
+                clone_staking: Some(staking_address()),
+                user_staking_account: with_staking.then(|| user_staking_address(user)),
+                clone_staking_program: Some(clone_staking::ID),
+                staking_reward_vault: Some(self.staking_reward_vault()),
+            },
+            instruction::Swap {
+                pool_index,
+                quantity,
+                quantity_is_input,
+                quantity_is_collateral,
+                result_threshold,
+            },
+        )
+    }
+
+    /// `aggregator_swap` accounts for `user`'s associated token accounts and
+    /// both treasury accounts.
+    pub fn aggregator_swap_accounts(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+    ) -> AggregatorSwapAccounts {
+        let treasury = self.treasury.pubkey();
+        let onasset_mint = self.onasset_mint(pool_index);
+        AggregatorSwapAccounts {
+            user: *user,
+            user_collateral_token_account: self.collateral_account(user),
+            user_onasset_token_account: token_address(user, &onasset_mint),
+            onasset_mint,
+            collateral_mint: self.collateral_mint,
+            collateral_vault: self.vault(),
+            treasury_onasset_token_account: Some(token_address(&treasury, &onasset_mint)),
+            treasury_collateral_token_account: Some(self.collateral_account(&treasury)),
+            token_program: spl_token::ID,
+            staking: Some(StakingAccounts {
+                clone_staking: staking_address(),
+                user_staking_account: None,
+                clone_staking_program: clone_staking::ID,
+                staking_reward_vault: Some(self.staking_reward_vault()),
+            }),
+        }
+    }
+
+    /// `aggregator_swap` built from the program's own Anchor types, to check
+    /// the layout `clone-cpi` publishes against.
+    pub fn aggregator_swap_ix(
+        &self,
+        swap_accounts: &AggregatorSwapAccounts,
+        args: AggregatorSwapArgs,
+    ) -> Instruction {
+        clone_ix(
+            accounts::AggregatorSwap {
+                user: swap_accounts.user,
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                user_collateral_token_account: swap_accounts.user_collateral_token_account,
+                user_onasset_token_account: swap_accounts.user_onasset_token_account,
+                onasset_mint: swap_accounts.onasset_mint,
+                collateral_mint: swap_accounts.collateral_mint,
+                collateral_vault: swap_accounts.collateral_vault,
+                treasury_onasset_token_account: swap_accounts.treasury_onasset_token_account,
+                treasury_collateral_token_account: swap_accounts.treasury_collateral_token_account,
+                token_program: swap_accounts.token_program,
+                clone_staking: swap_accounts.staking.map(|staking| staking.clone_staking),
+                user_staking_account: swap_accounts
+                    .staking
+                    .and_then(|staking| staking.user_staking_account),
+                clone_staking_program: swap_accounts
+                    .staking
+                    .map(|staking| staking.clone_staking_program),
+                staking_reward_vault: swap_accounts
+                    .staking
+                    .and_then(|staking| staking.staking_reward_vault),
+            },
+            instruction::AggregatorSwap {
+                pool_index: args.pool_index,
+                quantity: args.quantity,
+                quantity_is_input: args.quantity_is_input,
+                quantity_is_collateral: args.quantity_is_collateral,
+                result_threshold: args.result_threshold,
+            },
+        )
+    }
+
+    pub fn swap_route_ix(
+        &self,
+        user: &Pubkey,
+        input_pool_index: u8,
+        output_pool_index: u8,
+        quantity: u64,
+        result_threshold: u64,
+        with_staking: bool,
+    ) -> Instruction {
+        let treasury = self.treasury.pubkey();
+        let input_onasset_mint = self.onasset_mint(input_pool_index);
+        let output_onasset_mint = self.onasset_mint(output_pool_index);
+        clone_ix(
+            accounts::SwapRoute {
+                user: *user,
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                user_input_onasset_token_account: token_address(user, &input_onasset_mint),
+                user_output_onasset_token_account: token_address(user, &output_onasset_mint),
+                input_onasset_mint,
+                output_onasset_mint,
+                collateral_mint: self.collateral_mint,
+                collateral_vault: self.vault(),
+                treasury_output_onasset_token_account: token_address(
+                    &treasury,
+                    &output_onasset_mint,
+                ),
+                treasury_collateral_token_account: self.collateral_account(&treasury),
+                token_program: spl_token::ID,
+                clone_staking: Some(staking_address()),
+                user_staking_account: with_staking.then(|| user_staking_address(user)),
+                clone_staking_program: Some(clone_staking::ID),
+                staking_reward_vault: Some(self.staking_reward_vault()),
+            },
+            instruction::SwapRoute {
+                input_pool_index,
+                output_pool_index,
+                quantity,
+                result_threshold,
+            },
+        )
+    }
+
+    pub fn flash_loan_collateral_ix(&self, user: &Pubkey, amount: u64) -> Instruction {
+        clone_ix(
+            accounts::FlashLoanCollateral {
+                user: *user,
+                clone: clone_address(),
+                collateral_mint: self.collateral_mint,
+                collateral_vault: self.vault(),
+                user_collateral_token_account: self.collateral_account(user),
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: spl_token::ID,
+            },
+            instruction::FlashLoanCollateral { amount },
+        )
+    }
+
+    pub fn repay_flash_loan_collateral_ix(
+        &self,
+        payer: &Pubkey,
+        amount: u64,
+        borrow_instruction_index: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::RepayFlashLoanCollateral {
+                payer: *payer,
+                clone: clone_address(),
+                collateral_mint: self.collateral_mint,
+                collateral_vault: self.vault(),
+                payer_collateral_token_account: self.collateral_account(payer),
+                treasury_collateral_token_account: self.collateral_account(&self.treasury.pubkey()),
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: spl_token::ID,
+            },
+            instruction::RepayFlashLoanCollateral {
+                amount,
+                borrow_instruction_index,
+            },
+        )
+    }
+
+    pub fn flash_mint_onasset_ix(&self, user: &Pubkey, pool_index: u8, amount: u64) -> Instruction {
+        clone_ix(
+            accounts::FlashMintOnasset {
+                user: *user,
+                clone: clone_address(),
+                pools: pools_address(),
+                onasset_mint: self.onasset_mint(pool_index),
+                user_onasset_token_account: self.onasset_account(user, pool_index),
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: spl_token::ID,
+            },
+            instruction::FlashMintOnasset { pool_index, amount },
+        )
+    }
+
+    pub fn repay_flash_mint_onasset_ix(
+        &self,
+        payer: &Pubkey,
+        pool_index: u8,
+        amount: u64,
+        borrow_instruction_index: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::RepayFlashMintOnasset {
+                payer: *payer,
+                clone: clone_address(),
+                pools: pools_address(),
+                onasset_mint: self.onasset_mint(pool_index),
+                payer_onasset_token_account: self.onasset_account(payer, pool_index),
+                treasury_onasset_token_account: self
+                    .onasset_account(&self.treasury.pubkey(), pool_index),
+                instructions_sysvar: sysvar::instructions::ID,
+                token_program: spl_token::ID,
+            },
+            instruction::RepayFlashMintOnasset {
+                pool_index,
+                amount,
+                borrow_instruction_index,
+            },
+        )
+    }
+
+    pub fn quote_swap_ix(
+        &self,
+        user: &Pubkey,
+        pool_index: u8,
+        quantity: u64,
+        quantity_is_input: bool,
+        quantity_is_collateral: bool,
+        with_staking: bool,
+    ) -> Instruction {
+        clone_ix(
+            accounts::QuoteSwap {
+                user: *user,
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+                clone_staking: with_staking.then(staking_address),
+                user_staking_account: with_staking.then(|| user_staking_address(user)),
+                clone_staking_program: with_staking.then_some(clone_staking::ID),
+                staking_reward_vault: with_staking.then(|| self.staking_reward_vault()),
+            },
+            instruction::QuoteSwap {
+                pool_index,
+                quantity,
+                quantity_is_input,
+                quantity_is_collateral,
+            },
+        )
+    }
+
+    pub fn get_user_health_ix(
+        &self,
+        user: &Pubkey,
+        position_offset: u8,
+        borrow_offset: u8,
+        limit: u8,
+    ) -> Instruction {
+        clone_ix(
+            accounts::GetUserHealth {
+                user: *user,
+                user_account: user_address(user),
+                clone: clone_address(),
+                pools: pools_address(),
+                oracles: oracles_address(),
+            },
+            instruction::GetUserHealth {
+                position_offset,
+                borrow_offset,
+                limit,
+            },
+        )
+    }
+
+    pub fn create_token_metadata_ix(&self, admin: &Pubkey, mint: &Pubkey) -> Instruction {
+        let metaplex_program = mpl_token_metadata::ID;
+        let metadata = Pubkey::find_program_address(
+            &[b"metadata", metaplex_program.as_ref(), mint.as_ref()],
+            &metaplex_program,
+        )
+        .0;
+        clone_ix(
+            accounts::CreateTokenMetadata {
+                admin: *admin,
+                clone: clone_address(),
+                mint: *mint,
+                metaplex_program,
+                metadata,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::CreateTokenMetadata {
+                metadata_args: MetadataArgs {
+                    name: "onAsset".to_string(),
+                    symbol: "onA".to_string(),
+                    uri: String::new(),
+                },
+            },
+        )
+    }
+
+    pub fn remove_pool_ix(&self, admin: &Pubkey, pool_index: u8) -> Instruction {
+        let underlying_asset_mint = self.underlying_mint(pool_index);
+        clone_ix(
+            accounts::RemovePool {
+                admin: *admin,
+                clone: clone_address(),
+                pools: pools_address(),
+                underlying_asset_mint,
+                underlying_asset_token_account: token_address(
+                    &clone_address(),
+                    &underlying_asset_mint,
+                ),
+                treasury_asset_token_account: token_address(
+                    &self.treasury.pubkey(),
+                    &underlying_asset_mint,
+                ),
+                token_program: spl_token::ID,
+            },
+            instruction::RemovePool { pool_index },
+        )
+    }
+
+    pub fn migrate_to_zero_copy_ix(
+        &self,
+        signer: &Pubkey,
+        account: &Pubkey,
+        target: MigrationTarget,
+    ) -> Instruction {
+        clone_ix(
+            accounts::MigrateToZeroCopy {
+                signer: *signer,
+                clone: clone_address(),
+                account: *account,
+                system_program: system_program::ID,
+            },
+            instruction::MigrateToZeroCopy { target },
+        )
+    }
+
+    pub fn migrate_account_layout_ix(
+        &self,
+        signer: &Pubkey,
+        account: &Pubkey,
+        target: MigrationTarget,
+    ) -> Instruction {
+        clone_ix(
+            accounts::MigrateAccountLayout {
+                signer: *signer,
+                clone: clone_address(),
+                account: *account,
+                system_program: system_program::ID,
+            },
+            instruction::MigrateAccountLayout { target },
+        )
+    }
+}
+
+// Companion programs.
+impl TestEnv {
+    pub fn mint_asset_ix(&self, minter: &Pubkey, amount: u64) -> Instruction {
+        let mint = self.underlying_mint(PYTH_POOL);
+        Instruction {
+            program_id: mock_asset_faucet::ID,
+            accounts: mock_asset_faucet::accounts::MintAsset {
+                minter: *minter,
+                faucet: faucet_address(),
+                mint,
+                token_account: token_address(minter, &mint),
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: mock_asset_faucet::instruction::MintAsset { amount }.data(),
+        }
+    }
+
+    pub fn initialize_staking_ix(
+        &self,
+        cln_token_mint: &Pubkey,
+        staking_period_slots: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::Initialize {
+                admin: self.admin.pubkey(),
+                clone_staking: staking_address(),
+                cln_token_mint: *cln_token_mint,
+                cln_token_vault: token_address(&staking_address(), cln_token_mint),
+                reward_mint: self.collateral_mint,
+                reward_vault: self.staking_reward_vault(),
+                rent: sysvar::rent::ID,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::Initialize {
+                staking_period_slots,
+            }
+            .data(),
+        }
+    }
+
+    pub fn update_staking_params_ix(&self, params: clone_staking::Parameters) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::UpdateStakingParams {
+                admin: self.admin.pubkey(),
+                clone_staking: staking_address(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::UpdateStakingParams { params }.data(),
+        }
+    }
+
+    pub fn update_vote_escrow_ix(&self, max_lock_slots: u64) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::UpdateVoteEscrow {
+                admin: self.admin.pubkey(),
+                clone_staking: staking_address(),
+                reward_vault: self.staking_reward_vault(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::UpdateVoteEscrow { max_lock_slots }.data(),
+        }
+    }
+
+    pub fn replace_tiers_ix(&self, tiers: Vec<clone_staking::states::Tier>) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::ReplaceTiers {
+                admin: self.admin.pubkey(),
+                clone_staking: staking_address(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::ReplaceTiers { tiers }.data(),
+        }
+    }
+
+    pub fn accept_staking_admin_ix(&self, pending_admin: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::AcceptAdmin {
+                pending_admin: *pending_admin,
+                clone_staking: staking_address(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::AcceptAdmin {}.data(),
+        }
+    }
+
+    pub fn add_stake_ix(&self, user: &Pubkey, cln_token_mint: &Pubkey, amount: u64) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::AddStake {
+                user: *user,
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                cln_token_mint: *cln_token_mint,
+                cln_token_vault: token_address(&staking_address(), cln_token_mint),
+                reward_vault: self.staking_reward_vault(),
+                user_cln_token_account: token_address(user, cln_token_mint),
+                rent: sysvar::rent::ID,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::AddStake { amount }.data(),
+        }
+    }
+
+    pub fn request_unstake_ix(&self, user: &Pubkey, amount: u64) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::RequestUnstake {
+                user: *user,
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                reward_vault: self.staking_reward_vault(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::RequestUnstake { amount }.data(),
+        }
+    }
+
+    pub fn lock_stake_ix(&self, user: &Pubkey, lock_end_slot: u64) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::LockStake {
+                user: *user,
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                reward_vault: self.staking_reward_vault(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::LockStake { lock_end_slot }.data(),
+        }
+    }
+
+    pub fn refresh_reward_weight_ix(&self, user: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::RefreshRewardWeight {
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                reward_vault: self.staking_reward_vault(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::RefreshRewardWeight { user: *user }.data(),
+        }
+    }
+
+    pub fn add_delegate_ix(&self, user: &Pubkey, delegate: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::AddDelegate {
+                user: *user,
+                user_account: user_staking_address(user),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::AddDelegate {
+                delegate: *delegate,
+            }
+            .data(),
+        }
+    }
+
+    pub fn remove_delegate_ix(&self, user: &Pubkey, delegate: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::RemoveDelegate {
+                user: *user,
+                user_account: user_staking_address(user),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::RemoveDelegate {
+                delegate: *delegate,
+            }
+            .data(),
+        }
+    }
+
+    pub fn withdraw_stake_ix(&self, user: &Pubkey, cln_token_mint: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::WithdrawStake {
+                user: *user,
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                cln_token_mint: *cln_token_mint,
+                cln_token_vault: token_address(&staking_address(), cln_token_mint),
+                user_cln_token_account: token_address(user, cln_token_mint),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::WithdrawStake {}.data(),
+        }
+    }
+
+    pub fn claim_staking_rewards_ix(&self, user: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::ClaimRewards {
+                user: *user,
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                reward_vault: self.staking_reward_vault(),
+                user_reward_token_account: self.collateral_account(user),
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::ClaimRewards {}.data(),
+        }
+    }
+
+    pub fn close_staking_user_account_ix(&self, user: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::CloseUserAccount {
+                user: *user,
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                reward_vault: self.staking_reward_vault(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::CloseUserAccount {}.data(),
+        }
+    }
+
+    pub fn set_reward_vault_ix(&self) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::SetRewardVault {
+                admin: self.admin.pubkey(),
+                clone_staking: staking_address(),
+                reward_mint: self.collateral_mint,
+                reward_vault: self.staking_reward_vault(),
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::SetRewardVault {}.data(),
+        }
+    }
+
+    pub fn migrate_staking_user_account_ix(&self, payer: &Pubkey, user: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: clone_staking::ID,
+            accounts: clone_staking::accounts::MigrateUserAccount {
+                payer: *payer,
+                user_account: user_staking_address(user),
+                clone_staking: staking_address(),
+                reward_vault: self.staking_reward_vault(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone_staking::instruction::MigrateUserAccount { user: *user }.data(),
+        }
+    }
+
+    /// Sets the staking period and a single fee tier, returning the CLN mint.
+    pub async fn configure_staking(
+        &mut self,
+        staking_period_slots: u64,
+        stake_requirement: u64,
+        lp_trading_fee_bps: u16,
+        treasury_trading_fee_bps: u16,
+    ) -> Pubkey {
+        let instructions = [
+            self.update_staking_params_ix(clone_staking::Parameters::Staking {
+                staking_period_slots,
+            }),
+            self.update_staking_params_ix(clone_staking::Parameters::Tier {
+                num_tiers: 1,
+                index: 0,
+                stake_requirement,
+                lp_trading_fee_bps,
+                treasury_trading_fee_bps,
+            }),
+        ];
+        self.process(&instructions, &[]).await.unwrap();
+        self.cln_token_mint
+    }
+
+    pub fn initialize_governance_ix(
+        &self,
+        voting_period_slots: u64,
+        execution_delay_slots: u64,
+        quorum: u64,
+        proposal_threshold: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: clone_governance::ID,
+            accounts: clone_governance::accounts::Initialize {
+                payer: self.admin.pubkey(),
+                governance: governance_address(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone_governance::instruction::Initialize {
+                voting_period_slots,
+                execution_delay_slots,
+                quorum,
+                proposal_threshold,
+            }
+            .data(),
+        }
+    }
+
+    pub fn accept_clone_admin_ix(&self) -> Instruction {
+        Instruction {
+            program_id: clone_governance::ID,
+            accounts: clone_governance::accounts::AcceptCloneAdmin {
+                governance: governance_address(),
+                clone_program: clone::ID,
+                clone: clone_address(),
+            }
+            .to_account_metas(None),
+            data: clone_governance::instruction::AcceptCloneAdmin {}.data(),
+        }
+    }
+
+    pub fn create_proposal_ix(
+        &self,
+        proposer: &Pubkey,
+        index: u64,
+        action: clone_governance::states::ProposalAction,
+    ) -> Instruction {
+        Instruction {
+            program_id: clone_governance::ID,
+            accounts: clone_governance::accounts::CreateProposal {
+                proposer: *proposer,
+                proposer_staking_account: user_staking_address(proposer),
+                governance: governance_address(),
+                proposal: proposal_address(index),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone_governance::instruction::CreateProposal { action }.data(),
+        }
+    }
+
+    pub fn cast_vote_ix(&self, voter: &Pubkey, index: u64, approve: bool) -> Instruction {
+        let proposal = proposal_address(index);
+        let vote_record = Pubkey::find_program_address(
+            &[
+                clone_governance::VOTE_SEED.as_ref(),
+                proposal.as_ref(),
+                voter.as_ref(),
+            ],
+            &clone_governance::ID,
+        )
+        .0;
+        Instruction {
+            program_id: clone_governance::ID,
+            accounts: clone_governance::accounts::CastVote {
+                voter: *voter,
+                voter_staking_account: user_staking_address(voter),
+                proposal,
+                vote_record,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: clone_governance::instruction::CastVote { approve }.data(),
+        }
+    }
+
+    pub fn execute_proposal_ix(&self, index: u64) -> Instruction {
+        Instruction {
+            program_id: clone_governance::ID,
+            accounts: clone_governance::accounts::ExecuteProposal {
+                governance: governance_address(),
+                proposal: proposal_address(index),
+                clone_program: clone::ID,
+                clone: clone_address(),
+                pools: Some(pools_address()),
+                oracles: Some(oracles_address()),
+            }
+            .to_account_metas(None),
+            data: clone_governance::instruction::ExecuteProposal {}.data(),
+        }
+    }
+}
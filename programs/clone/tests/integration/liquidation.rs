@@ -0,0 +1,429 @@
+use crate::fixture::*;
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use clone::error::CloneError;
+use clone::instructions::{
+    BorrowHealth, CloneParameters, PoolParameters, PositionHealth, UserHealth,
+    MAX_USER_HEALTH_ENTRIES,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+async fn enable_non_auth_liquidations(env: &mut TestEnv) {
+    let admin = env.admin.pubkey();
+    let enable = env.update_clone_parameters_ix(
+        &admin,
+        CloneParameters::NonAuthLiquidationsEnabled { value: true },
+    );
+    env.process(&[enable], &[]).await.unwrap();
+}
+
+/// Doubles the position term of pool 0, which pushes a comet with half of
+/// its collateral committed and any impermanent loss below zero health.
+async fn raise_position_coefficient(env: &mut TestEnv) {
+    let admin = env.admin.pubkey();
+    let update = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::PositionHealthScoreCoefficient { value: 200 },
+    );
+    env.process(&[update], &[]).await.unwrap();
+}
+
+async fn user_health(env: &mut TestEnv, user: &Pubkey) -> UserHealth {
+    let get_health = env.get_user_health_ix(user, 0, 0, MAX_USER_HEALTH_ENTRIES);
+    let data = env.simulate_with_prices(get_health, &[]).await.unwrap();
+    UserHealth::try_from_slice(&data).unwrap()
+}
+
+#[tokio::test]
+async fn liquidate_borrow_position() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let borrower = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(50))
+        .await;
+    let liquidator = env
+        .create_borrower(collateral(10_000), PYTH_POOL, onasset(60))
+        .await;
+    let (borrower_key, liquidator_key) = (borrower.pubkey(), liquidator.pubkey());
+
+    let liquidate =
+        env.liquidate_borrow_position_ix(&liquidator_key, &borrower_key, PYTH_POOL, 0, onasset(10));
+    let result = env
+        .process_with_prices(liquidate.clone(), &[&liquidator])
+        .await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let add_auth = env.update_clone_parameters_ix(
+        &admin,
+        CloneParameters::AddAuth {
+            address: liquidator_key,
+        },
+    );
+    env.process(&[add_auth], &[]).await.unwrap();
+
+    let result = env
+        .process_with_prices(liquidate.clone(), &[&liquidator])
+        .await;
+    assert_clone_error(result, CloneError::BorrowPositionUnableToLiquidate);
+
+    let no_amount =
+        env.liquidate_borrow_position_ix(&liquidator_key, &borrower_key, PYTH_POOL, 0, 0);
+    let result = env.process_with_prices(no_amount, &[&liquidator]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    // At 15.00 the position is at ~133%, below the 150% minimum.
+    let pyth_oracle = env.pools[PYTH_POOL as usize].oracle;
+    env.set_pyth_price(&pyth_oracle, 1_500_000_000).await;
+
+    // Liquidating nearly everything would leave the position far above the
+    // maximum liquidation ratio of 200%.
+    let overshoot =
+        env.liquidate_borrow_position_ix(&liquidator_key, &borrower_key, PYTH_POOL, 0, onasset(49));
+    let result = env.process_with_prices(overshoot, &[&liquidator]).await;
+    assert_clone_error(result, CloneError::InvalidMintCollateralRatio);
+
+    let collateral_before = env.collateral_balance(&liquidator_key).await;
+    env.process_with_prices(liquidate, &[&liquidator])
+        .await
+        .unwrap();
+
+    // 10 onAssets at 15.00 plus the 5% liquidator fee.
+    let reward = collateral(1_575) / 10;
+    assert_eq!(
+        env.collateral_balance(&liquidator_key).await,
+        collateral_before + reward
+    );
+    assert_eq!(
+        env.onasset_balance(&liquidator_key, PYTH_POOL).await,
+        onasset(50)
+    );
+    let state = env.user_state(&borrower_key).await;
+    assert_eq!(state.borrows[0].borrowed_onasset, onasset(40));
+    assert_eq!(
+        state.borrows[0].collateral_amount,
+        collateral(1_000) - reward
+    );
+}
+
+#[tokio::test]
+async fn liquidate_borrow_position_checks_index() {
+    let mut env = TestEnv::new().await;
+    enable_non_auth_liquidations(&mut env).await;
+    let borrower = env.create_user(collateral(1_000)).await;
+    let liquidator = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(10))
+        .await;
+
+    let liquidate = env.liquidate_borrow_position_ix(
+        &liquidator.pubkey(),
+        &borrower.pubkey(),
+        PYTH_POOL,
+        0,
+        onasset(1),
+    );
+    let result = env.process_with_prices(liquidate, &[&liquidator]).await;
+    assert_clone_error(result, CloneError::InvalidInputPositionIndex);
+}
+
+#[tokio::test]
+async fn liquidate_comet_onasset_ild() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let liquidator = env
+        .create_borrower(collateral(10_000), PYTH_POOL, onasset(60))
+        .await;
+    let (lp_key, liquidator_key) = (lp.pubkey(), liquidator.pubkey());
+
+    // Buying onAssets leaves the pool, and so the only LP, owing onAssets.
+    let buy = env.swap_ix(&trader.pubkey(), PYTH_POOL, collateral(100), true, true, 0);
+    env.process_with_prices(buy, &[&trader]).await.unwrap();
+    let onasset_ild_share = env.pools_state().await.pools[0].onasset_ild as u64;
+    assert!(onasset_ild_share > 0);
+
+    let liquidate =
+        env.liquidate_comet_onasset_ild_ix(&liquidator_key, &lp_key, PYTH_POOL, 0, onasset(100));
+    let result = env
+        .process_with_prices(liquidate.clone(), &[&liquidator])
+        .await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    enable_non_auth_liquidations(&mut env).await;
+    let result = env
+        .process_with_prices(liquidate.clone(), &[&liquidator])
+        .await;
+    assert_clone_error(result, CloneError::NotSubjectToLiquidation);
+
+    raise_position_coefficient(&mut env).await;
+    let collateral_before = env.collateral_balance(&liquidator_key).await;
+    env.process_with_prices(liquidate, &[&liquidator])
+        .await
+        .unwrap();
+
+    // The liquidator burns the whole share and is paid its value at 10.00
+    // plus the 5% fee.
+    let reward = onasset_ild_share * 105 / 100;
+    assert_eq!(
+        env.onasset_balance(&liquidator_key, PYTH_POOL).await,
+        onasset(60) - onasset_ild_share
+    );
+    assert_eq!(
+        env.collateral_balance(&liquidator_key).await,
+        collateral_before + reward
+    );
+
+    let state = env.user_state(&lp_key).await;
+    assert_eq!(
+        state.header.comet.collateral_amount,
+        collateral(1_000) - reward
+    );
+    assert_eq!(state.comet_positions[0].committed_collateral_liquidity, 0);
+    assert_eq!(
+        env.pools_state().await.pools[0].committed_collateral_liquidity,
+        0
+    );
+}
+
+#[tokio::test]
+async fn liquidate_comet_collateral_ild() {
+    let mut env = TestEnv::new().await;
+    enable_non_auth_liquidations(&mut env).await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(20))
+        .await;
+    let liquidator = env.create_user(0).await;
+    let (lp_key, liquidator_key) = (lp.pubkey(), liquidator.pubkey());
+
+    // Selling onAssets leaves the pool owing collateral.
+    let sell = env.swap_ix(&trader.pubkey(), PYTH_POOL, onasset(10), true, false, 0);
+    env.process_with_prices(sell, &[&trader]).await.unwrap();
+    let collateral_ild_share = env.pools_state().await.pools[0].collateral_ild as u64;
+    assert!(collateral_ild_share > 0);
+
+    let liquidate = env.liquidate_comet_collateral_ild_ix(&liquidator_key, &lp_key, 0);
+    let result = env
+        .process_with_prices(liquidate.clone(), &[&liquidator])
+        .await;
+    assert_clone_error(result, CloneError::NotSubjectToLiquidation);
+
+    raise_position_coefficient(&mut env).await;
+    env.process_with_prices(liquidate, &[&liquidator])
+        .await
+        .unwrap();
+
+    // The share is settled from the comet's collateral, plus a 5% reward
+    // for the liquidator.
+    let reward = collateral_ild_share * 5 / 100;
+    assert_eq!(env.collateral_balance(&liquidator_key).await, reward);
+    let state = env.user_state(&lp_key).await;
+    assert_eq!(
+        state.header.comet.collateral_amount,
+        collateral(1_000) - collateral_ild_share - reward
+    );
+    assert_eq!(state.comet_positions[0].committed_collateral_liquidity, 0);
+}
+
+#[tokio::test]
+async fn comet_liquidations_check_position_index() {
+    let mut env = TestEnv::new().await;
+    enable_non_auth_liquidations(&mut env).await;
+    let lp = env.create_user(collateral(1_000)).await;
+    let liquidator: Keypair = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(10))
+        .await;
+
+    let liquidate = env.liquidate_comet_collateral_ild_ix(&liquidator.pubkey(), &lp.pubkey(), 0);
+    let result = env.process_with_prices(liquidate, &[&liquidator]).await;
+    assert_clone_error(result, CloneError::InvalidInputPositionIndex);
+
+    let liquidate = env.liquidate_comet_onasset_ild_ix(
+        &liquidator.pubkey(),
+        &lp.pubkey(),
+        PYTH_POOL,
+        0,
+        onasset(1),
+    );
+    let result = env.process_with_prices(liquidate, &[&liquidator]).await;
+    assert_clone_error(result, CloneError::InvalidInputPositionIndex);
+}
+
+#[tokio::test]
+async fn user_health_reports_borrow_liquidation_price() {
+    let mut env = TestEnv::new().await;
+    enable_non_auth_liquidations(&mut env).await;
+    let borrower = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(50))
+        .await;
+    let liquidator = env
+        .create_borrower(collateral(10_000), PYTH_POOL, onasset(60))
+        .await;
+    let (borrower_key, liquidator_key) = (borrower.pubkey(), liquidator.pubkey());
+
+    let health = user_health(&mut env, &borrower_key).await;
+    assert_eq!(health.scale, 8);
+    assert_eq!(health.score, 10_000_000_000);
+    assert!(health.positions.is_empty());
+    assert_eq!(health.borrows.len(), 1);
+    let borrow = health.borrows[0];
+    assert_eq!(borrow.pool_index, PYTH_POOL);
+    // 1,000 of collateral against 50 onAssets at 10.00 is 200%, and falls to
+    // the 150% minimum at 13.33.
+    assert_eq!(borrow.collateral_ratio, Some(200_000_000));
+    assert_eq!(borrow.liquidation_price, Some(1_333_333_333));
+
+    let liquidate =
+        env.liquidate_borrow_position_ix(&liquidator_key, &borrower_key, PYTH_POOL, 0, onasset(1));
+    let pyth_oracle = env.pools[PYTH_POOL as usize].oracle;
+    env.set_pyth_price(&pyth_oracle, 1_333_333_333).await;
+    let result = env
+        .process_with_prices(liquidate.clone(), &[&liquidator])
+        .await;
+    assert_clone_error(result, CloneError::BorrowPositionUnableToLiquidate);
+
+    env.set_pyth_price(&pyth_oracle, 1_333_333_334).await;
+    env.process_with_prices(liquidate, &[&liquidator])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn user_health_reports_comet_liquidation_price() {
+    let mut env = TestEnv::new().await;
+    enable_non_auth_liquidations(&mut env).await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let liquidator = env
+        .create_borrower(collateral(10_000), PYTH_POOL, onasset(60))
+        .await;
+    let (lp_key, liquidator_key) = (lp.pubkey(), liquidator.pubkey());
+
+    // Half of the collateral is committed and there is no impermanent loss.
+    let health = user_health(&mut env, &lp_key).await;
+    assert_eq!(health.score, 5_000_000_000);
+    assert_eq!(health.effective_collateral, 100_000_000_000);
+    assert_eq!(health.total_il_term, 0);
+    assert_eq!(health.total_position_term, 50_000_000_000);
+    assert_eq!(health.positions.len(), 1);
+    assert_eq!(health.positions[0].position_term, 50_000_000_000);
+    assert_eq!(health.positions[0].liquidation_price, None);
+    assert!(health.borrows.is_empty());
+
+    let buy = env.swap_ix(&trader.pubkey(), PYTH_POOL, collateral(100), true, true, 0);
+    env.process_with_prices(buy, &[&trader]).await.unwrap();
+    let health = user_health(&mut env, &lp_key).await;
+    let position = health.positions[0];
+    assert!(position.il_term > 0);
+    assert_eq!(health.total_il_term, position.il_term);
+    let liquidation_price = position.liquidation_price.unwrap();
+    assert!(liquidation_price > PYTH_PRICE as u64);
+
+    let liquidate =
+        env.liquidate_comet_onasset_ild_ix(&liquidator_key, &lp_key, PYTH_POOL, 0, onasset(100));
+    let pyth_oracle = env.pools[PYTH_POOL as usize].oracle;
+    env.set_pyth_price(&pyth_oracle, liquidation_price as i64 - 100)
+        .await;
+    let result = env
+        .process_with_prices(liquidate.clone(), &[&liquidator])
+        .await;
+    assert_clone_error(result, CloneError::NotSubjectToLiquidation);
+
+    env.set_pyth_price(&pyth_oracle, liquidation_price as i64 + 100)
+        .await;
+    env.process_with_prices(liquidate, &[&liquidator])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn user_health_requires_fresh_prices() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    env.warp_slots(1).await;
+    let get_health = env.get_user_health_ix(&lp.pubkey(), 0, 0, MAX_USER_HEALTH_ENTRIES);
+    let result = env.process(&[get_health], &[]).await;
+    assert_clone_error(result, CloneError::OutdatedOracle);
+}
+
+#[test]
+fn user_health_at_the_entry_limit_fits_in_return_data() {
+    let position = PositionHealth {
+        pool_index: u8::MAX,
+        il_term: i64::MIN,
+        position_term: i64::MIN,
+        liquidation_price: Some(u64::MAX),
+    };
+    let health = UserHealth {
+        positions: vec![position; MAX_USER_HEALTH_ENTRIES as usize],
+        ..UserHealth::default()
+    };
+    assert!(health.try_to_vec().unwrap().len() <= 1024);
+    let health = UserHealth {
+        positions: vec![position; MAX_USER_HEALTH_ENTRIES as usize + 1],
+        ..UserHealth::default()
+    };
+    assert!(health.try_to_vec().unwrap().len() > 1024);
+
+    let borrow = BorrowHealth {
+        pool_index: u8::MAX,
+        collateral_ratio: Some(u64::MAX),
+        liquidation_price: Some(u64::MAX),
+    };
+    let health = UserHealth {
+        borrows: vec![borrow; MAX_USER_HEALTH_ENTRIES as usize],
+        ..UserHealth::default()
+    };
+    assert!(health.try_to_vec().unwrap().len() <= 1024);
+}
+
+#[tokio::test]
+async fn user_health_pages_positions_then_borrows() {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let lp_key = lp.pubkey();
+    let mint = env.mint_tokens_ix(&env.collateral_mint, &lp_key, collateral(1_000));
+    env.process(&[mint], &[]).await.unwrap();
+    let borrow =
+        env.initialize_borrow_position_ix(&lp_key, PYTH_POOL, onasset(50), collateral(1_000));
+    env.process_with_prices(borrow, &[&lp]).await.unwrap();
+
+    let page = |position_offset, borrow_offset, limit| {
+        env.get_user_health_ix(&lp_key, position_offset, borrow_offset, limit)
+    };
+    let (first, second, rest) = (page(0, 0, 1), page(1, 0, 1), page(1, 1, 1));
+    let all = page(0, 0, MAX_USER_HEALTH_ENTRIES);
+    let too_many = page(0, 0, MAX_USER_HEALTH_ENTRIES + 1);
+
+    let data = env.simulate_with_prices(all, &[]).await.unwrap();
+    let health = UserHealth::try_from_slice(&data).unwrap();
+    assert_eq!((health.num_positions, health.num_borrows), (1, 1));
+    assert_eq!((health.positions.len(), health.borrows.len()), (1, 1));
+
+    let data = env.simulate_with_prices(first, &[]).await.unwrap();
+    let first = UserHealth::try_from_slice(&data).unwrap();
+    assert_eq!(first.positions, health.positions);
+    assert!(first.borrows.is_empty());
+    assert_eq!(first.score, health.score);
+
+    let data = env.simulate_with_prices(second, &[]).await.unwrap();
+    let second = UserHealth::try_from_slice(&data).unwrap();
+    assert!(second.positions.is_empty());
+    assert_eq!(second.borrows, health.borrows);
+
+    let data = env.simulate_with_prices(rest, &[]).await.unwrap();
+    let rest = UserHealth::try_from_slice(&data).unwrap();
+    assert!(rest.positions.is_empty() && rest.borrows.is_empty());
+
+    let result = env.process_with_prices(too_many, &[]).await;
+    assert_clone_error(result, CloneError::InvalidValueRange);
+}
@@ -0,0 +1,21 @@
+//! Integration tests for the clone program together with clone-staking,
+//! clone-governance, depository-token, pyth and mock-asset-faucet.
+//!
+//! Every program is registered as a native processor and oracles are mocked
+//! locally, so the suite needs neither a BPF build nor network access:
+//! `cargo test -p clone --test integration`.
+
+mod fixture;
+mod ix;
+
+mod admin;
+mod borrow;
+mod comet;
+mod companion_programs;
+mod flash_loan;
+mod governance;
+mod liquidation;
+mod oracles;
+mod swap;
+mod user;
+mod wrap;
@@ -0,0 +1,242 @@
+use crate::fixture::*;
+use clone::error::CloneError;
+use clone::instructions::{CloneParameters, UpdateOracleParameters};
+use clone::states::{OracleSource, Status, NUM_ORACLES};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+#[tokio::test]
+async fn update_prices_reads_pyth_and_switchboard_feeds() {
+    let mut env = TestEnv::new().await;
+    let update_prices = env.update_prices_ix();
+    env.process(&[update_prices], &[]).await.unwrap();
+
+    let slot = env.current_slot().await;
+    let oracles = env.oracles_state().await;
+    assert_eq!(oracles.oracles[0].price, COLLATERAL_PRICE);
+    assert_eq!(oracles.oracles[0].expo, 8);
+    assert_eq!(oracles.oracles[1].price, PYTH_PRICE);
+    assert_eq!(oracles.oracles[1].expo, 8);
+    assert_eq!(oracles.oracles[2].price, SWITCHBOARD_MANTISSA as i64);
+    assert_eq!(oracles.oracles[2].expo, SWITCHBOARD_SCALE as u8);
+    for oracle in oracles.oracles[..3].iter() {
+        assert_eq!(oracle.last_update_slot, slot);
+    }
+
+    let pyth_oracle = env.pools[PYTH_POOL as usize].oracle;
+    let switchboard_oracle = env.pools[SWITCHBOARD_POOL as usize].oracle;
+    env.set_pyth_price(&pyth_oracle, 1_250_000_000).await;
+    env.set_switchboard_result(&switchboard_oracle, 1_875, 2, 3);
+    let update_prices = env.update_prices_ix();
+    env.process(&[update_prices], &[]).await.unwrap();
+
+    let oracles = env.oracles_state().await;
+    assert_eq!(oracles.oracles[1].price, 1_250_000_000);
+    assert_eq!(oracles.oracles[2].price, 1_875);
+}
+
+#[tokio::test]
+async fn update_prices_rejects_mismatched_feeds() {
+    let mut env = TestEnv::new().await;
+    let oracles = env.oracles.clone();
+
+    let update_prices = env.update_prices_for_ix(vec![1], &[oracles[2]]);
+    let result = env.process(&[update_prices], &[]).await;
+    assert_clone_error(result, CloneError::IncorrectOracleAddress);
+
+    let update_prices = env.update_prices_for_ix(vec![3], &[Pubkey::new_unique()]);
+    let result = env.process(&[update_prices], &[]).await;
+    assert_clone_error(result, CloneError::InvalidOracleIndex);
+}
+
+#[tokio::test]
+async fn update_prices_fails_on_unreadable_feeds() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let oracles = env.oracles.clone();
+
+    // A Switchboard round without any oracle responses has no result.
+    env.set_switchboard_result(&oracles[2], SWITCHBOARD_MANTISSA, SWITCHBOARD_SCALE, 0);
+    let update_prices = env.update_prices_for_ix(vec![2], &[oracles[2]]);
+    let result = env.process(&[update_prices], &[]).await;
+    assert_clone_error(result, CloneError::FailedToLoadSwitchboard);
+
+    // Point each feed at the other oracle type's account layout.
+    let swap_sources = [
+        env.update_oracles_ix(
+            &admin,
+            UpdateOracleParameters::Modify {
+                index: 1,
+                address: None,
+                source: Some(OracleSource::SWITCHBOARD),
+                status: None,
+            },
+        ),
+        env.update_oracles_ix(
+            &admin,
+            UpdateOracleParameters::Modify {
+                index: 2,
+                address: None,
+                source: Some(OracleSource::PYTH),
+                status: None,
+            },
+        ),
+    ];
+    env.process(&swap_sources, &[]).await.unwrap();
+
+    let update_prices = env.update_prices_for_ix(vec![1], &[oracles[1]]);
+    let result = env.process(&[update_prices], &[]).await;
+    assert_clone_error(result, CloneError::FailedToLoadSwitchboard);
+
+    let update_prices = env.update_prices_for_ix(vec![2], &[oracles[2]]);
+    let result = env.process(&[update_prices], &[]).await;
+    assert_clone_error(result, CloneError::FailedToLoadPyth);
+}
+
+#[tokio::test]
+async fn stale_prices_are_rejected() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(collateral(1_000)).await;
+
+    let borrow =
+        env.initialize_borrow_position_ix(&user.pubkey(), PYTH_POOL, onasset(10), collateral(500));
+    let result = env.process(&[borrow.clone()], &[&user]).await;
+    assert_clone_error(result, CloneError::OutdatedOracle);
+
+    // Prices refreshed in an earlier slot are just as stale.
+    let update_prices = env.update_prices_ix();
+    env.process(&[update_prices], &[]).await.unwrap();
+    env.warp_slots(1).await;
+    let result = env.process(&[borrow.clone()], &[&user]).await;
+    assert_clone_error(result, CloneError::OutdatedOracle);
+
+    env.process_with_prices(borrow, &[&user]).await.unwrap();
+}
+
+#[tokio::test]
+async fn update_oracles_checks_permissions() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let auth = Keypair::new();
+
+    let add_auth = env.update_clone_parameters_ix(
+        &admin,
+        CloneParameters::AddAuth {
+            address: auth.pubkey(),
+        },
+    );
+    env.process(&[add_auth], &[]).await.unwrap();
+
+    let add_oracle = env.update_oracles_ix(
+        &auth.pubkey(),
+        UpdateOracleParameters::Add {
+            address: Pubkey::new_unique(),
+            source: OracleSource::PYTH,
+            rescale_factor: None,
+        },
+    );
+    let result = env.process(&[add_oracle], &[&auth]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let modify_address = env.update_oracles_ix(
+        &auth.pubkey(),
+        UpdateOracleParameters::Modify {
+            index: 1,
+            address: Some(Pubkey::new_unique()),
+            source: None,
+            status: None,
+        },
+    );
+    let result = env.process(&[modify_address], &[&auth]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let freeze = env.update_oracles_ix(
+        &auth.pubkey(),
+        UpdateOracleParameters::Modify {
+            index: 1,
+            address: None,
+            source: None,
+            status: Some(Status::Frozen),
+        },
+    );
+    env.process(&[freeze], &[&auth]).await.unwrap();
+    assert_eq!(env.oracles_state().await.oracles[1].status, Status::Frozen);
+
+    let remove = env.update_oracles_ix(&auth.pubkey(), UpdateOracleParameters::Remove { index: 2 });
+    let result = env.process(&[remove], &[&auth]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+}
+
+#[tokio::test]
+async fn update_oracles_manages_oracle_array() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+
+    let remove = env.update_oracles_ix(&admin, UpdateOracleParameters::Remove { index: 3 });
+    let result = env.process(&[remove], &[]).await;
+    assert_clone_error(result, CloneError::InvalidOracleIndex);
+
+    let modify = env.update_oracles_ix(
+        &admin,
+        UpdateOracleParameters::Modify {
+            index: 3,
+            address: None,
+            source: None,
+            status: Some(Status::Frozen),
+        },
+    );
+    let result = env.process(&[modify], &[]).await;
+    assert_clone_error(result, CloneError::InvalidOracleIndex);
+
+    let added = Pubkey::new_unique();
+    let add = env.update_oracles_ix(
+        &admin,
+        UpdateOracleParameters::Add {
+            address: added,
+            source: OracleSource::SWITCHBOARD,
+            rescale_factor: Some(2),
+        },
+    );
+    env.process(&[add], &[]).await.unwrap();
+    let oracles = env.oracles_state().await;
+    assert_eq!(oracles.num_oracles, 4);
+    assert_eq!(oracles.oracles[3].address, added);
+    assert_eq!(oracles.oracles[3].source, OracleSource::SWITCHBOARD);
+    assert_eq!(oracles.oracles[3].rescale_factor, 2);
+
+    let remove = env.update_oracles_ix(&admin, UpdateOracleParameters::Remove { index: 1 });
+    env.process(&[remove], &[]).await.unwrap();
+    let oracles = env.oracles_state().await;
+    assert_eq!(oracles.num_oracles, 3);
+    assert_eq!(oracles.oracles[1].address, env.oracles[2]);
+    assert_eq!(oracles.oracles[2].address, added);
+
+    let mut num_oracles = 3;
+    while num_oracles < NUM_ORACLES {
+        let batch: Vec<_> = (0..(NUM_ORACLES - num_oracles).min(8))
+            .map(|_| {
+                env.update_oracles_ix(
+                    &admin,
+                    UpdateOracleParameters::Add {
+                        address: Pubkey::new_unique(),
+                        source: OracleSource::PYTH,
+                        rescale_factor: None,
+                    },
+                )
+            })
+            .collect();
+        env.process(&batch, &[]).await.unwrap();
+        num_oracles += batch.len();
+    }
+    assert_eq!(env.oracles_state().await.num_oracles, NUM_ORACLES as u64);
+
+    let add = env.update_oracles_ix(
+        &admin,
+        UpdateOracleParameters::Add {
+            address: Pubkey::new_unique(),
+            source: OracleSource::PYTH,
+            rescale_factor: None,
+        },
+    );
+    let result = env.process(&[add], &[]).await;
+    assert_clone_error(result, CloneError::OracleArrayFull);
+}
@@ -0,0 +1,1113 @@
+use crate::fixture::*;
+use anchor_lang::error::ErrorCode;
+use anchor_lang::AnchorDeserialize;
+use anchor_spl::token::spl_token;
+use clone::error::CloneError;
+use clone::instructions::{PoolParameters, SwapQuote};
+use clone::states::{Curve, Status};
+use clone_cpi::{
+    aggregator_swap_instruction, AggregatorSwapAccounts, AggregatorSwapArgs, StakingAccounts,
+    SwapResult,
+};
+use rust_decimal::Decimal;
+use solana_program_test::BanksClientError;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+struct ExpectedSwap {
+    result: u64,
+    treasury_fees: u64,
+}
+
+/// The swap the program will compute against the current pool and oracle
+/// state, dynamic fee included, optionally with a staking tier's fees in
+/// place of the pool's.
+async fn expected_swap(
+    env: &mut TestEnv,
+    quantity: u64,
+    quantity_is_input: bool,
+    quantity_is_collateral: bool,
+    tier_fees_bps: Option<(i64, i64)>,
+) -> ExpectedSwap {
+    let update_prices = env.update_prices_ix();
+    env.process(&[update_prices], &[]).await.unwrap();
+    let collateral = env.clone_state().await.collateral;
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    let oracles = env.oracles_state().await;
+    let quantity = if quantity_is_collateral {
+        collateral.to_collateral_decimal(quantity).unwrap()
+    } else {
+        Decimal::new(quantity as i64, ONASSET_SCALE.into())
+    };
+    let (liquidity_trading_fee, treasury_trading_fee) = pool
+        .calculate_trading_fees(
+            &oracles.oracles[1],
+            &oracles.oracles[0],
+            &collateral,
+            tier_fees_bps.map(|(lp, _)| Decimal::new(lp, 4)),
+            tier_fees_bps.map(|(_, treasury)| Decimal::new(treasury, 4)),
+        )
+        .unwrap();
+    let summary = pool
+        .calculate_swap(
+            oracles.oracles[1].get_price().unwrap(),
+            oracles.oracles[0].get_price().unwrap(),
+            quantity,
+            quantity_is_input,
+            quantity_is_collateral,
+            &collateral,
+            Some(liquidity_trading_fee),
+            Some(treasury_trading_fee),
+        )
+        .unwrap();
+    ExpectedSwap {
+        result: summary.result.mantissa() as u64,
+        treasury_fees: summary.treasury_fees_paid.mantissa() as u64,
+    }
+}
+
+async fn treasury_onasset_balance(env: &mut TestEnv) -> u64 {
+    let treasury = env.treasury.pubkey();
+    env.onasset_balance(&treasury, PYTH_POOL).await
+}
+
+async fn treasury_collateral_balance(env: &mut TestEnv) -> u64 {
+    let treasury = env.treasury.pubkey();
+    env.collateral_balance(&treasury).await
+}
+
+#[tokio::test]
+async fn swap_collateral_for_onasset() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+
+    let expected = expected_swap(&mut env, collateral(100), true, true, None).await;
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        collateral(100),
+        true,
+        true,
+        expected.result + 1,
+    );
+    let result = env.process_with_prices(swap, &[&trader]).await;
+    assert_clone_error(result, CloneError::SlippageToleranceExceeded);
+
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        collateral(100),
+        true,
+        true,
+        expected.result,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(env.collateral_balance(&authority).await, collateral(900));
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        expected.result
+    );
+    assert_eq!(
+        treasury_onasset_balance(&mut env).await,
+        expected.treasury_fees
+    );
+
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    assert_eq!(
+        pool.onasset_ild,
+        (expected.result + expected.treasury_fees) as i64
+    );
+    assert_eq!(pool.collateral_ild, -(collateral(100) as i64));
+}
+
+#[tokio::test]
+async fn swap_along_stableswap_curve() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    let lp = env
+        .create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+    let constant_product = expected_swap(&mut env, collateral(100), true, true, None).await;
+
+    let stableswap = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::Curve {
+            value: Curve::StableSwap,
+        },
+    );
+    let amplification = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::AmplificationCoefficient { value: 1_000 },
+    );
+    // Either change would move the value of the committed liquidity.
+    for update in [&amplification, &stableswap] {
+        let result = env.process(&[update.clone()], &[]).await;
+        assert_clone_error(result, CloneError::PoolHasLiquidity);
+    }
+    let withdraw = env.withdraw_liquidity_from_comet_ix(&lp.pubkey(), 0, collateral(500));
+    env.process_with_prices(withdraw, &[&lp]).await.unwrap();
+
+    let result = env.process(&[stableswap.clone()], &[]).await;
+    assert_clone_error(result, CloneError::InvalidValueRange);
+    env.process(&[amplification, stableswap], &[])
+        .await
+        .unwrap();
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    assert_eq!(pool.curve, Curve::StableSwap);
+    assert_eq!(pool.asset_info.amplification_coefficient, 1_000);
+
+    let add_liquidity = env.add_liquidity_to_comet_ix(&lp.pubkey(), PYTH_POOL, collateral(500));
+    env.process_with_prices(add_liquidity, &[&lp])
+        .await
+        .unwrap();
+
+    let expected = expected_swap(&mut env, collateral(100), true, true, None).await;
+    assert!(expected.result > constant_product.result);
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        collateral(100),
+        true,
+        true,
+        expected.result,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        expected.result
+    );
+
+    // ILD records the amounts moved whichever curve priced them.
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    assert_eq!(
+        pool.onasset_ild,
+        (expected.result + expected.treasury_fees) as i64
+    );
+    assert_eq!(pool.collateral_ild, -(collateral(100) as i64));
+}
+
+#[tokio::test]
+async fn swap_charges_dynamic_fee() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+    expected_swap(&mut env, collateral(100), true, true, None).await;
+
+    // A 2% move in the onAsset's price.
+    let pyth_oracle = env.pools[PYTH_POOL as usize].oracle;
+    env.set_pyth_price(&pyth_oracle, 1_020_000_000).await;
+    let base = expected_swap(&mut env, collateral(100), true, true, None).await;
+    let oracles = env.oracles_state().await;
+    assert_eq!(oracles.oracles[1].volatility_bps, 200);
+
+    let max_trading_fee = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::MaxTradingFee { value: 10_000 },
+    );
+    let result = env.process(&[max_trading_fee], &[]).await;
+    assert_clone_error(result, CloneError::InvalidValueRange);
+    let max_trading_fee = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::MaxTradingFee { value: 200 },
+    );
+    let volatility_fee = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::VolatilityFeeCoefficient { value: 50 },
+    );
+    let imbalance_fee = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::ImbalanceFeeCoefficient { value: 100 },
+    );
+    env.process(&[volatility_fee, imbalance_fee, max_trading_fee], &[])
+        .await
+        .unwrap();
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    assert_eq!(pool.dynamic_fee.volatility_fee_coefficient, 50);
+    assert_eq!(pool.dynamic_fee.imbalance_fee_coefficient, 100);
+    assert_eq!(pool.dynamic_fee.max_trading_fee_bps, 200);
+
+    // The extra fee goes to liquidity providers, the treasury's is unchanged.
+    let expected = expected_swap(&mut env, collateral(100), true, true, None).await;
+    assert!(expected.result < base.result);
+    assert_eq!(expected.treasury_fees, base.treasury_fees);
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        collateral(100),
+        true,
+        true,
+        expected.result,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        expected.result
+    );
+    assert_eq!(
+        treasury_onasset_balance(&mut env).await,
+        expected.treasury_fees
+    );
+
+    // The skew the swap left behind raises the next swap's fee to the cap.
+    let after_swap = expected_swap(&mut env, collateral(100), true, true, None).await;
+    let collateral = env.clone_state().await.collateral;
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    let oracles = env.oracles_state().await;
+    let (liquidity_trading_fee, _) = pool
+        .calculate_trading_fees(
+            &oracles.oracles[1],
+            &oracles.oracles[0],
+            &collateral,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(liquidity_trading_fee, Decimal::new(190, 4));
+    assert!(after_swap.result < expected.result);
+}
+
+#[tokio::test]
+async fn swap_for_exact_onasset_output() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+
+    // For exact output swaps the threshold caps the input.
+    let expected = expected_swap(&mut env, onasset(5), false, false, None).await;
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        onasset(5),
+        false,
+        false,
+        expected.result - 1,
+    );
+    let result = env.process_with_prices(swap, &[&trader]).await;
+    assert_clone_error(result, CloneError::SlippageToleranceExceeded);
+
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        onasset(5),
+        false,
+        false,
+        expected.result,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(env.onasset_balance(&authority, PYTH_POOL).await, onasset(5));
+    assert_eq!(
+        env.collateral_balance(&authority).await,
+        collateral(1_000) - expected.result
+    );
+    assert_eq!(
+        treasury_onasset_balance(&mut env).await,
+        expected.treasury_fees
+    );
+}
+
+#[tokio::test]
+async fn swap_onasset_for_collateral() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(20))
+        .await;
+    let authority = trader.pubkey();
+
+    let expected = expected_swap(&mut env, onasset(10), true, false, None).await;
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        onasset(10),
+        true,
+        false,
+        expected.result,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        onasset(10)
+    );
+    assert_eq!(env.collateral_balance(&authority).await, expected.result);
+    assert_eq!(
+        treasury_collateral_balance(&mut env).await,
+        expected.treasury_fees
+    );
+
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    assert_eq!(pool.onasset_ild, -(onasset(10) as i64));
+    assert_eq!(
+        pool.collateral_ild,
+        (expected.result + expected.treasury_fees) as i64
+    );
+}
+
+#[tokio::test]
+async fn swap_validates_inputs() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+
+    let swap = env.swap_ix(&authority, PYTH_POOL, 0, true, true, 0);
+    let result = env.process_with_prices(swap, &[&trader]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let swap = env.swap_ix(&authority, SWITCHBOARD_POOL, collateral(10), true, true, 0);
+    let result = env.process_with_prices(swap, &[&trader]).await;
+    assert_clone_error(result, CloneError::PoolEmpty);
+
+    let swap = env.swap_ix(&authority, 5, collateral(10), true, true, 0);
+    let result = env.process_with_prices(swap, &[&trader]).await;
+    assert_clone_error(result, CloneError::InvalidInputPositionIndex);
+
+    let freeze = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::Status {
+            value: Status::Frozen,
+        },
+    );
+    env.process(&[freeze], &[]).await.unwrap();
+    let swap = env.swap_ix(&authority, PYTH_POOL, collateral(10), true, true, 0);
+    let result = env.process_with_prices(swap, &[&trader]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+}
+
+#[tokio::test]
+async fn swap_applies_staking_tier_fees() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(100)),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+    let stake = env.add_stake_ix(&authority, &cln_token_mint, onasset(100));
+    env.process(&[stake], &[&trader]).await.unwrap();
+
+    let expected = expected_swap(&mut env, collateral(100), true, true, Some((5, 2))).await;
+    let default_fees = expected_swap(&mut env, collateral(100), true, true, None).await;
+    assert!(expected.result > default_fees.result);
+
+    let swap = env.swap_with_staking_ix(
+        &authority,
+        PYTH_POOL,
+        collateral(100),
+        true,
+        true,
+        expected.result,
+        true,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        expected.result
+    );
+    assert_eq!(
+        treasury_onasset_balance(&mut env).await,
+        expected.treasury_fees
+    );
+}
+
+#[tokio::test]
+async fn swap_shares_collateral_treasury_fees_with_stakers() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(20))
+        .await;
+    let authority = trader.pubkey();
+
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let instructions = [
+        env.update_staking_params_ix(clone_staking::Parameters::Rewards {
+            treasury_fee_share_bps: 5_000,
+        }),
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(100)),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+    let stake = env.add_stake_ix(&authority, &cln_token_mint, onasset(100));
+    env.process(&[stake], &[&trader]).await.unwrap();
+
+    let expected = expected_swap(&mut env, onasset(10), true, false, Some((5, 2))).await;
+    let swap = env.swap_with_staking_ix(
+        &authority,
+        PYTH_POOL,
+        onasset(10),
+        true,
+        false,
+        expected.result,
+        true,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    let staking_share = expected.treasury_fees / 2;
+    assert_eq!(
+        treasury_collateral_balance(&mut env).await,
+        expected.treasury_fees - staking_share
+    );
+    let staking_reward_vault = env.staking_reward_vault();
+    assert_eq!(
+        env.token_balance(&staking_reward_vault).await,
+        staking_share
+    );
+
+    let claim = env.claim_staking_rewards_ix(&authority);
+    env.process(&[claim], &[&trader]).await.unwrap();
+    assert_eq!(
+        env.collateral_balance(&authority).await,
+        expected.result + staking_share
+    );
+}
+
+#[tokio::test]
+async fn stakers_share_treasury_fees_of_every_swap() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+    let share = env.update_staking_params_ix(clone_staking::Parameters::Rewards {
+        treasury_fee_share_bps: 5_000,
+    });
+    env.process(&[share], &[]).await.unwrap();
+    let staking_reward_vault = env.staking_reward_vault();
+
+    // The reward vault must be the staking program's.
+    let mut swap = env.swap_ix(&authority, PYTH_POOL, collateral(100), true, true, 0);
+    let vault_index = swap.accounts.len() - 1;
+    swap.accounts[vault_index].pubkey = token_address(&env.treasury.pubkey(), &env.collateral_mint);
+    let result = env.process_with_prices(swap, &[&trader]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintAddress);
+
+    // Stakers are paid their share of onAsset fees in collateral, without
+    // the trader staking. Buying lifts the pool's price above the oracle's,
+    // so the share is valued at oracle prices.
+    let expected = expected_swap(&mut env, collateral(100), true, true, None).await;
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        collateral(100),
+        true,
+        true,
+        expected.result,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    let staking_share = expected.treasury_fees / 2;
+    assert_eq!(
+        treasury_onasset_balance(&mut env).await,
+        expected.treasury_fees - staking_share
+    );
+    let staking_collateral = staking_share * (PYTH_PRICE / COLLATERAL_PRICE) as u64
+        / 10u64.pow((ONASSET_SCALE - COLLATERAL_SCALE).into());
+    assert_eq!(
+        env.token_balance(&staking_reward_vault).await,
+        staking_collateral
+    );
+
+    // Aggregator swaps pay stakers as well.
+    let bought = env.onasset_balance(&authority, PYTH_POOL).await;
+    let expected = expected_swap(&mut env, bought / 2, true, false, None).await;
+    let swap_accounts = env.aggregator_swap_accounts(&authority, PYTH_POOL);
+    let args = AggregatorSwapArgs {
+        pool_index: PYTH_POOL,
+        quantity: bought / 2,
+        quantity_is_input: true,
+        quantity_is_collateral: false,
+        result_threshold: expected.result,
+    };
+    let sell = aggregator_swap_instruction(&swap_accounts, args);
+    env.process_with_prices(sell, &[&trader]).await.unwrap();
+    let staking_rewards = staking_collateral + expected.treasury_fees / 2;
+    assert_eq!(
+        env.token_balance(&staking_reward_vault).await,
+        staking_rewards
+    );
+
+    // Without the staking accounts the treasury keeps all fees.
+    let treasury_collateral = treasury_collateral_balance(&mut env).await;
+    let expected = expected_swap(&mut env, bought / 2, true, false, None).await;
+    let sell = aggregator_swap_instruction(
+        &AggregatorSwapAccounts {
+            staking: None,
+            ..swap_accounts
+        },
+        AggregatorSwapArgs {
+            result_threshold: expected.result,
+            ..args
+        },
+    );
+    env.process_with_prices(sell, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.token_balance(&staking_reward_vault).await,
+        staking_rewards
+    );
+    assert_eq!(
+        treasury_collateral_balance(&mut env).await,
+        treasury_collateral + expected.treasury_fees
+    );
+}
+
+#[tokio::test]
+async fn swap_without_a_qualifying_tier_uses_pool_fees() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(99)),
+    ];
+    env.process(&instructions, &[]).await.unwrap();
+    let stake = env.add_stake_ix(&authority, &cln_token_mint, onasset(99));
+    env.process(&[stake], &[&trader]).await.unwrap();
+
+    let expected = expected_swap(&mut env, collateral(100), true, true, None).await;
+    let swap = env.swap_with_staking_ix(
+        &authority,
+        PYTH_POOL,
+        collateral(100),
+        true,
+        true,
+        expected.result,
+        true,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        expected.result
+    );
+}
+
+async fn quote_swap(env: &mut TestEnv, quote: Instruction) -> Result<SwapQuote, BanksClientError> {
+    let data = env.simulate_with_prices(quote, &[]).await?;
+    Ok(SwapQuote::try_from_slice(&data).unwrap())
+}
+
+#[tokio::test]
+async fn quote_swap_applies_tier_of_vote_escrowed_stake() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+
+    let cln_token_mint = env.configure_staking(0, onasset(50), 5, 2).await;
+    let instructions = [
+        env.update_vote_escrow_ix(100),
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(100)),
+        env.add_stake_ix(&authority, &cln_token_mint, onasset(100)),
+    ];
+    env.process(&instructions, &[&trader]).await.unwrap();
+    let tier_fees = expected_swap(&mut env, collateral(100), true, true, Some((5, 2))).await;
+    let pool_fees = expected_swap(&mut env, collateral(100), true, true, None).await;
+    let quote_ix = env.quote_swap_ix(&authority, PYTH_POOL, collateral(100), true, true, true);
+
+    // Unlocked stake doesn't count.
+    let quote = quote_swap(&mut env, quote_ix.clone()).await.unwrap();
+    assert_eq!(quote.result, pool_fees.result);
+
+    let lock_end_slot = env.current_slot().await + 100;
+    let lock = env.lock_stake_ix(&authority, lock_end_slot);
+    env.process(&[lock], &[&trader]).await.unwrap();
+    let quote = quote_swap(&mut env, quote_ix.clone()).await.unwrap();
+    assert_eq!(quote.result, tier_fees.result);
+
+    // Half way through the lock the effective stake drops below the tier.
+    env.warp_slots(51).await;
+    let pool_fees = expected_swap(&mut env, collateral(100), true, true, None).await;
+    let quote = quote_swap(&mut env, quote_ix).await.unwrap();
+    assert_eq!(quote.result, pool_fees.result);
+}
+
+#[tokio::test]
+async fn quote_swap_applies_tier_of_delegating_staker() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+    let staker = env.create_user(0).await;
+    let staker_authority = staker.pubkey();
+
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let instructions = [
+        env.create_token_account_ix(&staker_authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &staker_authority, onasset(100)),
+        env.add_stake_ix(&staker_authority, &cln_token_mint, onasset(100)),
+    ];
+    env.process(&instructions, &[&staker]).await.unwrap();
+
+    // The trader passes the staker's account in place of their own.
+    let mut quote_ix = env.quote_swap_ix(&authority, PYTH_POOL, collateral(100), true, true, true);
+    for account in quote_ix.accounts.iter_mut() {
+        if account.pubkey == user_staking_address(&authority) {
+            account.pubkey = user_staking_address(&staker_authority);
+        }
+    }
+    let result = quote_swap(&mut env, quote_ix.clone()).await.map(|_| ());
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let add_delegate = env.add_delegate_ix(&staker_authority, &authority);
+    env.process(&[add_delegate], &[&staker]).await.unwrap();
+    let quote = quote_swap(&mut env, quote_ix.clone()).await.unwrap();
+    let expected = expected_swap(&mut env, collateral(100), true, true, Some((5, 2))).await;
+    assert_eq!(quote.result, expected.result);
+
+    let remove_delegate = env.remove_delegate_ix(&staker_authority, &authority);
+    env.process(&[remove_delegate], &[&staker]).await.unwrap();
+    let result = quote_swap(&mut env, quote_ix).await.map(|_| ());
+    assert_clone_error(result, CloneError::Unauthorized);
+}
+
+#[tokio::test]
+async fn quote_swap_matches_swap() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+    // Stakers take half of the treasury fees, paid out of the pool.
+    let cln_token_mint = env.cln_token_mint;
+    let instructions = [
+        env.update_staking_params_ix(clone_staking::Parameters::Rewards {
+            treasury_fee_share_bps: 5_000,
+        }),
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.add_stake_ix(&authority, &cln_token_mint, 0),
+    ];
+    env.process(&instructions, &[&trader]).await.unwrap();
+
+    let quote_ix = env.quote_swap_ix(&authority, PYTH_POOL, onasset(5), false, false, true);
+    let quote = quote_swap(&mut env, quote_ix).await.unwrap();
+    let expected = expected_swap(&mut env, onasset(5), false, false, None).await;
+    assert_eq!(quote.result, expected.result);
+    assert_eq!(quote.treasury_fees_paid, expected.treasury_fees);
+    assert!(quote.liquidity_fees_paid > 0);
+
+    // Quoting leaves the pool untouched.
+    let pool = env.pools_state().await.pools[PYTH_POOL as usize];
+    assert_eq!(pool.onasset_ild, 0);
+    assert_eq!(pool.collateral_ild, 0);
+
+    let swap = env.swap_ix(
+        &authority,
+        PYTH_POOL,
+        onasset(5),
+        false,
+        false,
+        quote.result,
+    );
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.collateral_balance(&authority).await,
+        collateral(1_000) - quote.result
+    );
+    let staking_reward_vault = env.staking_reward_vault();
+    assert!(env.token_balance(&staking_reward_vault).await > 0);
+
+    // The quoted price is the pool's price after the trade.
+    let collateral_params = env.clone_state().await.collateral;
+    let oracles = env.oracles_state().await;
+    let (pool_collateral, pool_onasset) = env.pools_state().await.pools[PYTH_POOL as usize]
+        .calculate_jit_pool(
+            oracles.oracles[1].get_price().unwrap(),
+            oracles.oracles[0].get_price().unwrap(),
+            &collateral_params,
+        )
+        .unwrap();
+    let pool_price = (pool_collateral / pool_onasset)
+        .round_dp_with_strategy(quote.pool_scale, rust_decimal::RoundingStrategy::ToZero);
+    assert_eq!(quote.pool_price, pool_price.mantissa() as u64);
+    assert!(pool_price > Decimal::TEN);
+}
+
+#[tokio::test]
+async fn quote_swap_applies_staking_tier_and_validates_inputs() {
+    let mut env = TestEnv::new().await;
+    let admin = env.admin.pubkey();
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(collateral(1_000)).await;
+    let authority = trader.pubkey();
+
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(100)),
+        env.add_stake_ix(&authority, &cln_token_mint, onasset(100)),
+    ];
+    env.process(&instructions, &[&trader]).await.unwrap();
+
+    let quote_ix = env.quote_swap_ix(&authority, PYTH_POOL, collateral(100), true, true, true);
+    let quote = quote_swap(&mut env, quote_ix).await.unwrap();
+    let expected = expected_swap(&mut env, collateral(100), true, true, Some((5, 2))).await;
+    assert_eq!(quote.result, expected.result);
+    assert_eq!(quote.treasury_fees_paid, expected.treasury_fees);
+
+    let quote_ix = env.quote_swap_ix(&authority, PYTH_POOL, 0, true, true, false);
+    let result = quote_swap(&mut env, quote_ix).await.map(|_| ());
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let quote_ix = env.quote_swap_ix(
+        &authority,
+        SWITCHBOARD_POOL,
+        collateral(1),
+        true,
+        true,
+        false,
+    );
+    let result = quote_swap(&mut env, quote_ix).await.map(|_| ());
+    assert_clone_error(result, CloneError::PoolEmpty);
+
+    let freeze = env.update_pool_parameters_ix(
+        &admin,
+        PYTH_POOL,
+        PoolParameters::Status {
+            value: Status::Frozen,
+        },
+    );
+    env.process(&[freeze], &[]).await.unwrap();
+    let quote_ix = env.quote_swap_ix(&authority, PYTH_POOL, collateral(1), true, true, false);
+    let result = quote_swap(&mut env, quote_ix).await.map(|_| ());
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+}
+
+/// Liquidity in both pools and a trader holding 20 of pool 0's onAsset.
+async fn route_env() -> (TestEnv, Keypair) {
+    let mut env = TestEnv::new().await;
+    let lp = env
+        .create_liquidity_provider(collateral(2_000), PYTH_POOL, collateral(500))
+        .await;
+    let add_liquidity =
+        env.add_liquidity_to_comet_ix(&lp.pubkey(), SWITCHBOARD_POOL, collateral(500));
+    env.process_with_prices(add_liquidity, &[&lp])
+        .await
+        .unwrap();
+    let trader = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(20))
+        .await;
+    (env, trader)
+}
+
+/// Output of selling `quantity` of pool 0's onAsset and buying pool 1's
+/// onAsset with the proceeds, as two separate quotes.
+async fn expected_route_output(
+    env: &mut TestEnv,
+    user: &Pubkey,
+    quantity: u64,
+    with_staking: bool,
+) -> (SwapQuote, SwapQuote) {
+    let sell = env.quote_swap_ix(user, PYTH_POOL, quantity, true, false, with_staking);
+    let sell = quote_swap(env, sell).await.unwrap();
+    let buy = env.quote_swap_ix(
+        user,
+        SWITCHBOARD_POOL,
+        sell.result,
+        true,
+        true,
+        with_staking,
+    );
+    let buy = quote_swap(env, buy).await.unwrap();
+    (sell, buy)
+}
+
+#[tokio::test]
+async fn swap_route_between_onassets() {
+    let (mut env, trader) = route_env().await;
+    let authority = trader.pubkey();
+    let treasury = env.treasury.pubkey();
+    let event_counter = env.clone_state().await.event_counter;
+    let treasury_collateral = env.collateral_balance(&treasury).await;
+    let (sell, buy) = expected_route_output(&mut env, &authority, onasset(5), false).await;
+
+    let route = env.swap_route_ix(
+        &authority,
+        PYTH_POOL,
+        SWITCHBOARD_POOL,
+        onasset(5),
+        buy.result,
+        false,
+    );
+    env.process_with_prices(route, &[&trader]).await.unwrap();
+
+    assert_eq!(
+        env.onasset_balance(&authority, PYTH_POOL).await,
+        onasset(15)
+    );
+    assert_eq!(
+        env.onasset_balance(&authority, SWITCHBOARD_POOL).await,
+        buy.result
+    );
+    assert_eq!(
+        env.collateral_balance(&treasury).await,
+        treasury_collateral + sell.treasury_fees_paid
+    );
+    assert_eq!(
+        env.onasset_balance(&treasury, SWITCHBOARD_POOL).await,
+        buy.treasury_fees_paid
+    );
+    // One event id per hop.
+    assert_eq!(env.clone_state().await.event_counter, event_counter + 2);
+
+    let pools = env.pools_state().await;
+    assert_eq!(pools.pools[0].onasset_ild, -(onasset(5) as i64));
+    assert_eq!(
+        pools.pools[0].collateral_ild,
+        (sell.result + sell.treasury_fees_paid) as i64
+    );
+    assert_eq!(
+        pools.pools[1].onasset_ild,
+        (buy.result + buy.treasury_fees_paid) as i64
+    );
+    assert_eq!(pools.pools[1].collateral_ild, -(sell.result as i64));
+}
+
+#[tokio::test]
+async fn swap_route_applies_staking_tier_on_every_hop() {
+    let (mut env, trader) = route_env().await;
+    let authority = trader.pubkey();
+    let cln_token_mint = env.configure_staking(0, onasset(100), 5, 2).await;
+    let instructions = [
+        env.create_token_account_ix(&authority, &cln_token_mint),
+        env.mint_tokens_ix(&cln_token_mint, &authority, onasset(100)),
+        env.add_stake_ix(&authority, &cln_token_mint, onasset(100)),
+    ];
+    env.process(&instructions, &[&trader]).await.unwrap();
+
+    let (_, without_tier) = expected_route_output(&mut env, &authority, onasset(5), false).await;
+    let (_, with_tier) = expected_route_output(&mut env, &authority, onasset(5), true).await;
+    assert!(with_tier.result > without_tier.result);
+
+    let route = env.swap_route_ix(
+        &authority,
+        PYTH_POOL,
+        SWITCHBOARD_POOL,
+        onasset(5),
+        with_tier.result,
+        true,
+    );
+    env.process_with_prices(route, &[&trader]).await.unwrap();
+    assert_eq!(
+        env.onasset_balance(&authority, SWITCHBOARD_POOL).await,
+        with_tier.result
+    );
+}
+
+#[tokio::test]
+async fn swap_route_validates_inputs() {
+    let (mut env, trader) = route_env().await;
+    let admin = env.admin.pubkey();
+    let authority = trader.pubkey();
+
+    for (input_pool_index, output_pool_index) in [(PYTH_POOL, PYTH_POOL), (PYTH_POOL, 2)] {
+        let route = env.swap_route_ix(
+            &authority,
+            input_pool_index,
+            output_pool_index,
+            onasset(1),
+            0,
+            false,
+        );
+        let result = env.process_with_prices(route, &[&trader]).await;
+        assert_clone_error(result, CloneError::InvalidSwapRoute);
+    }
+
+    let route = env.swap_route_ix(&authority, PYTH_POOL, SWITCHBOARD_POOL, 0, 0, false);
+    let result = env.process_with_prices(route, &[&trader]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let (_, buy) = expected_route_output(&mut env, &authority, onasset(5), false).await;
+    let route = env.swap_route_ix(
+        &authority,
+        PYTH_POOL,
+        SWITCHBOARD_POOL,
+        onasset(5),
+        buy.result + 1,
+        false,
+    );
+    let result = env.process_with_prices(route, &[&trader]).await;
+    assert_clone_error(result, CloneError::SlippageToleranceExceeded);
+
+    let freeze = env.update_pool_parameters_ix(
+        &admin,
+        SWITCHBOARD_POOL,
+        PoolParameters::Status {
+            value: Status::Frozen,
+        },
+    );
+    env.process(&[freeze], &[]).await.unwrap();
+    let route = env.swap_route_ix(
+        &authority,
+        PYTH_POOL,
+        SWITCHBOARD_POOL,
+        onasset(5),
+        0,
+        false,
+    );
+    let result = env.process_with_prices(route, &[&trader]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+}
+
+#[tokio::test]
+async fn aggregator_swap_layout_matches_program() {
+    let env = TestEnv::new().await;
+    let user = Pubkey::new_unique();
+    let args = AggregatorSwapArgs {
+        pool_index: SWITCHBOARD_POOL,
+        quantity: onasset(3),
+        quantity_is_input: false,
+        quantity_is_collateral: false,
+        result_threshold: collateral(40),
+    };
+    let with_both_treasuries = env.aggregator_swap_accounts(&user, SWITCHBOARD_POOL);
+    let with_one_treasury = AggregatorSwapAccounts {
+        treasury_collateral_token_account: None,
+        ..with_both_treasuries
+    };
+    let with_user_staking = AggregatorSwapAccounts {
+        staking: with_both_treasuries.staking.map(|staking| StakingAccounts {
+            user_staking_account: Some(user_staking_address(&user)),
+            ..staking
+        }),
+        ..with_both_treasuries
+    };
+    let without_staking = AggregatorSwapAccounts {
+        staking: None,
+        ..with_both_treasuries
+    };
+    for swap_accounts in [
+        with_both_treasuries,
+        with_one_treasury,
+        with_user_staking,
+        without_staking,
+    ] {
+        assert_eq!(
+            aggregator_swap_instruction(&swap_accounts, args),
+            env.aggregator_swap_ix(&swap_accounts, args)
+        );
+    }
+}
+
+#[tokio::test]
+async fn aggregator_swap_with_any_owned_token_accounts() {
+    let mut env = TestEnv::new().await;
+    env.create_liquidity_provider(collateral(1_000), PYTH_POOL, collateral(500))
+        .await;
+    let trader = env.create_user(0).await;
+    let authority = trader.pubkey();
+    let collateral_mint = env.collateral_mint;
+    let onasset_mint = env.onasset_mint(PYTH_POOL);
+    let collateral_account = env
+        .create_keypair_token_account(&authority, &collateral_mint)
+        .await;
+    let onasset_account = env
+        .create_keypair_token_account(&authority, &onasset_mint)
+        .await;
+    let fund = spl_token::instruction::mint_to(
+        &spl_token::ID,
+        &collateral_mint,
+        &collateral_account,
+        &env.admin.pubkey(),
+        &[],
+        collateral(100),
+    )
+    .unwrap();
+    env.process(&[fund], &[]).await.unwrap();
+
+    // Buying onAssets only pays fees in onAssets.
+    let swap_accounts = AggregatorSwapAccounts {
+        user_collateral_token_account: collateral_account,
+        user_onasset_token_account: onasset_account,
+        treasury_collateral_token_account: None,
+        ..env.aggregator_swap_accounts(&authority, PYTH_POOL)
+    };
+    let args = AggregatorSwapArgs {
+        pool_index: PYTH_POOL,
+        quantity: collateral(100),
+        quantity_is_input: true,
+        quantity_is_collateral: true,
+        result_threshold: 0,
+    };
+    let swap = aggregator_swap_instruction(&swap_accounts, args);
+    let data = env
+        .simulate_with_prices(swap.clone(), &[&trader])
+        .await
+        .unwrap();
+    let result = SwapResult::try_from_slice(&data).unwrap();
+    let expected = expected_swap(&mut env, collateral(100), true, true, None).await;
+    assert_eq!(
+        result,
+        SwapResult {
+            input: collateral(100),
+            output: expected.result,
+            liquidity_fees_paid: result.liquidity_fees_paid,
+            treasury_fees_paid: expected.treasury_fees,
+        }
+    );
+    assert!(result.liquidity_fees_paid > 0);
+
+    env.process_with_prices(swap, &[&trader]).await.unwrap();
+    assert_eq!(env.token_balance(&collateral_account).await, 0);
+    assert_eq!(env.token_balance(&onasset_account).await, result.output);
+    assert_eq!(
+        treasury_onasset_balance(&mut env).await,
+        result.treasury_fees_paid
+    );
+
+    // Selling needs the collateral treasury account.
+    let sell = aggregator_swap_instruction(
+        &swap_accounts,
+        AggregatorSwapArgs {
+            quantity: result.output,
+            quantity_is_collateral: false,
+            ..args
+        },
+    );
+    let result = env.process_with_prices(sell, &[&trader]).await;
+    assert_clone_error(result, CloneError::ExpectedAccountNotFound);
+
+    // Token accounts must belong to the signer.
+    let someone_else = env.create_user(collateral(10)).await;
+    let swap = aggregator_swap_instruction(
+        &AggregatorSwapAccounts {
+            user: someone_else.pubkey(),
+            ..swap_accounts
+        },
+        AggregatorSwapArgs {
+            quantity: collateral(10),
+            ..args
+        },
+    );
+    let result = env.process_with_prices(swap, &[&someone_else]).await;
+    assert_anchor_error(result, ErrorCode::ConstraintTokenOwner);
+}
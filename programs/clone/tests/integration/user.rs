@@ -0,0 +1,216 @@
+use crate::fixture::*;
+use anchor_lang::Discriminator;
+use clone::error::CloneError;
+use clone::instructions::MigrationTarget;
+use clone::states::{Borrow, LiquidityPosition, User};
+use solana_sdk::{account::Account, rent::Rent, signature::Keypair, signer::Signer};
+
+/// Size every user account had before the zero-copy layout.
+const LEGACY_ACCOUNT_SPACE: usize = 10240;
+
+/// A user account in the legacy Borsh layout: a borrow vector followed by the
+/// comet's collateral and position vector.
+fn legacy_user_data(
+    borrows: &[(u8, u64, u64)],
+    comet_collateral: u64,
+    positions: &[(u8, u64, i64, i64)],
+) -> Vec<u8> {
+    let mut data = User::discriminator().to_vec();
+    data.extend((borrows.len() as u32).to_le_bytes());
+    for (pool_index, borrowed_onasset, collateral_amount) in borrows {
+        data.push(*pool_index);
+        data.extend(borrowed_onasset.to_le_bytes());
+        data.extend(collateral_amount.to_le_bytes());
+    }
+    data.extend(comet_collateral.to_le_bytes());
+    data.extend((positions.len() as u32).to_le_bytes());
+    for (pool_index, committed, collateral_rebate, onasset_rebate) in positions {
+        data.push(*pool_index);
+        data.extend(committed.to_le_bytes());
+        data.extend(collateral_rebate.to_le_bytes());
+        data.extend(onasset_rebate.to_le_bytes());
+    }
+    data.resize(LEGACY_ACCOUNT_SPACE, 0);
+    data
+}
+
+#[tokio::test]
+async fn user_account_grows_with_positions() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(collateral(2_000)).await;
+    let authority = user.pubkey();
+
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.data_len, User::account_size(0, 0));
+
+    let instructions = [
+        env.update_prices_ix(),
+        env.initialize_borrow_position_ix(&authority, PYTH_POOL, onasset(10), collateral(500)),
+        env.initialize_borrow_position_ix(
+            &authority,
+            SWITCHBOARD_POOL,
+            onasset(5),
+            collateral(500),
+        ),
+        env.add_collateral_to_comet_ix(&authority, collateral(500)),
+        env.add_liquidity_to_comet_ix(&authority, PYTH_POOL, collateral(100)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.header.borrows_capacity, 2);
+    assert_eq!(state.header.comet.positions_capacity, 1);
+    assert_eq!(state.data_len, User::account_size(2, 1));
+    assert_eq!(state.borrows[1].pool_index, SWITCHBOARD_POOL);
+    assert_eq!(state.borrows[1].borrowed_onasset, onasset(5));
+    assert_eq!(
+        state.comet_positions[0].committed_collateral_liquidity,
+        collateral(100)
+    );
+}
+
+#[tokio::test]
+async fn compact_and_close_user_account() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(collateral(1_000)).await;
+    let authority = user.pubkey();
+    let user_account = user_address(&authority);
+
+    let borrow =
+        env.initialize_borrow_position_ix(&authority, PYTH_POOL, onasset(10), collateral(500));
+    env.process_with_prices(borrow, &[&user]).await.unwrap();
+    let instructions = [
+        env.pay_borrow_debt_ix(&authority, &authority, PYTH_POOL, 0, onasset(10)),
+        env.update_prices_ix(),
+        env.withdraw_collateral_from_borrow_ix(&authority, 0, collateral(500)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    // Closing the borrow leaves its slot allocated until compaction.
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.header.num_borrows, 0);
+    assert_eq!(state.data_len, User::account_size(1, 0));
+
+    let account_lamports = env.lamports(&user_account).await;
+    let user_lamports = env.lamports(&authority).await;
+    let compact = env.compact_user_account_ix(&authority);
+    env.process(&[compact], &[&user]).await.unwrap();
+
+    let state = env.user_state(&authority).await;
+    assert_eq!(state.header.borrows_capacity, 0);
+    assert_eq!(state.data_len, User::account_size(0, 0));
+    let refund = account_lamports - env.lamports(&user_account).await;
+    assert!(refund > 0);
+    assert_eq!(env.lamports(&authority).await, user_lamports + refund);
+
+    let destination = Keypair::new().pubkey();
+    let account_lamports = env.lamports(&user_account).await;
+    let close = env.close_user_account_ix(&authority, &destination);
+    env.process(&[close], &[&user]).await.unwrap();
+    assert!(env.account(&user_account).await.is_none());
+    assert_eq!(env.lamports(&destination).await, account_lamports);
+}
+
+#[tokio::test]
+async fn close_user_account_requires_all_positions_closed() {
+    let mut env = TestEnv::new().await;
+    let borrower = env
+        .create_borrower(collateral(1_000), PYTH_POOL, onasset(10))
+        .await;
+    let comet_user = env.create_user(collateral(1_000)).await;
+    let add_collateral = env.add_collateral_to_comet_ix(&comet_user.pubkey(), collateral(1_000));
+    env.process(&[add_collateral], &[&comet_user])
+        .await
+        .unwrap();
+
+    for user in [&borrower, &comet_user] {
+        let destination = Keypair::new().pubkey();
+        let close = env.close_user_account_ix(&user.pubkey(), &destination);
+        let result = env.process(&[close], &[user]).await;
+        assert_clone_error(result, CloneError::RequireAllPositionsClosed);
+        assert!(env.account(&user_address(&user.pubkey())).await.is_some());
+    }
+}
+
+#[tokio::test]
+async fn migrate_legacy_user_account() {
+    let mut env = TestEnv::new().await;
+    let authority = Keypair::new();
+    let other = env.create_user(0).await;
+    let user_account = user_address(&authority.pubkey());
+
+    let data = legacy_user_data(
+        &[(PYTH_POOL, onasset(10), collateral(500))],
+        collateral(1_000),
+        &[
+            (PYTH_POOL, collateral(200), -5, 7),
+            (SWITCHBOARD_POOL, collateral(100), 3, -2),
+        ],
+    );
+    let legacy_account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: clone::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    env.context
+        .set_account(&user_account, &legacy_account.into());
+
+    let target = MigrationTarget::User {
+        authority: authority.pubkey(),
+    };
+    let migrate = env.migrate_to_zero_copy_ix(&other.pubkey(), &user_account, target);
+    let result = env.process(&[migrate], &[&other]).await;
+    assert_clone_error(result, CloneError::Unauthorized);
+
+    let other_account = user_address(&other.pubkey());
+    let migrate = env.migrate_to_zero_copy_ix(&authority.pubkey(), &other_account, target);
+    let result = env.process(&[migrate], &[&authority]).await;
+    assert_clone_error(result, CloneError::ExpectedAccountNotFound);
+
+    let migrate = env.migrate_to_zero_copy_ix(&authority.pubkey(), &user_account, target);
+    env.process(&[migrate.clone()], &[&authority])
+        .await
+        .unwrap();
+
+    let state = env.user_state(&authority.pubkey()).await;
+    assert_eq!(state.data_len, User::account_size(1, 2));
+    assert_eq!(state.header.comet.collateral_amount, collateral(1_000));
+    assert_eq!(
+        state.borrows,
+        vec![Borrow {
+            pool_index: PYTH_POOL,
+            borrowed_onasset: onasset(10),
+            collateral_amount: collateral(500),
+            ..Borrow::default()
+        }]
+    );
+    assert_eq!(
+        state.comet_positions,
+        vec![
+            LiquidityPosition {
+                pool_index: PYTH_POOL,
+                committed_collateral_liquidity: collateral(200),
+                collateral_ild_rebate: -5,
+                onasset_ild_rebate: 7,
+                ..LiquidityPosition::default()
+            },
+            LiquidityPosition {
+                pool_index: SWITCHBOARD_POOL,
+                committed_collateral_liquidity: collateral(100),
+                collateral_ild_rebate: 3,
+                onasset_ild_rebate: -2,
+                ..LiquidityPosition::default()
+            },
+        ]
+    );
+
+    // The freed rent goes to the signer.
+    let expected_lamports = Rent::default().minimum_balance(User::account_size(1, 2));
+    assert_eq!(env.lamports(&user_account).await, expected_lamports);
+    assert!(env.lamports(&authority.pubkey()).await > 0);
+
+    let result = env.process(&[migrate], &[&authority]).await;
+    assert_clone_error(result, CloneError::AccountAlreadyMigrated);
+}
@@ -0,0 +1,89 @@
+use crate::fixture::*;
+use clone::error::CloneError;
+use clone::instructions::PoolParameters;
+use clone::states::Status;
+use solana_sdk::signer::Signer;
+
+async fn set_status(env: &mut TestEnv, status: Status) {
+    let admin = env.admin.pubkey();
+    let update =
+        env.update_pool_parameters_ix(&admin, PYTH_POOL, PoolParameters::Status { value: status });
+    env.process(&[update], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn wrap_and_unwrap_underlying_asset() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let underlying_account = token_address(&authority, &env.underlying_mint(PYTH_POOL));
+    let pool_underlying_account = token_address(&clone_address(), &env.underlying_mint(PYTH_POOL));
+
+    let mint = env.mint_asset_ix(&authority, underlying(10));
+    env.process(&[mint], &[&user]).await.unwrap();
+    assert_eq!(env.token_balance(&underlying_account).await, underlying(10));
+
+    // The underlying asset has one more decimal than the onAsset.
+    let wrap = env.wrap_asset_ix(&authority, PYTH_POOL, underlying(5));
+    env.process(&[wrap], &[&user]).await.unwrap();
+    assert_eq!(env.token_balance(&underlying_account).await, underlying(5));
+    assert_eq!(env.onasset_balance(&authority, PYTH_POOL).await, onasset(5));
+    assert_eq!(
+        env.token_balance(&pool_underlying_account).await,
+        underlying(5)
+    );
+
+    let unwrap = env.unwrap_onasset_ix(&authority, PYTH_POOL, onasset(2));
+    env.process(&[unwrap], &[&user]).await.unwrap();
+    assert_eq!(env.token_balance(&underlying_account).await, underlying(7));
+    assert_eq!(env.onasset_balance(&authority, PYTH_POOL).await, onasset(3));
+    assert_eq!(
+        env.token_balance(&pool_underlying_account).await,
+        underlying(3)
+    );
+}
+
+#[tokio::test]
+async fn wrap_and_unwrap_validate_inputs() {
+    let mut env = TestEnv::new().await;
+    let user = env.create_user(0).await;
+    let authority = user.pubkey();
+    let instructions = [
+        env.mint_asset_ix(&authority, underlying(10)),
+        env.wrap_asset_ix(&authority, PYTH_POOL, underlying(5)),
+    ];
+    env.process(&instructions, &[&user]).await.unwrap();
+
+    let wrap = env.wrap_asset_ix(&authority, PYTH_POOL, 0);
+    let result = env.process(&[wrap], &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let unwrap = env.unwrap_onasset_ix(&authority, PYTH_POOL, 0);
+    let result = env.process(&[unwrap], &[&user]).await;
+    assert_clone_error(result, CloneError::InvalidTokenAmount);
+
+    let wrap = env.wrap_asset_ix(&authority, 2, underlying(1));
+    let result = env.process(&[wrap], &[&user]).await;
+    assert_clone_error(result, CloneError::PoolNotFound);
+
+    let unwrap = env.unwrap_onasset_ix(&authority, 2, onasset(1));
+    let result = env.process(&[unwrap], &[&user]).await;
+    assert_clone_error(result, CloneError::PoolNotFound);
+
+    set_status(&mut env, Status::Frozen).await;
+    let wrap = env.wrap_asset_ix(&authority, PYTH_POOL, underlying(1));
+    let result = env.process(&[wrap], &[&user]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+    let unwrap = env.unwrap_onasset_ix(&authority, PYTH_POOL, onasset(1));
+    let result = env.process(&[unwrap], &[&user]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+
+    // A deprecated pool only lets holders unwrap.
+    set_status(&mut env, Status::Deprecation).await;
+    let wrap = env.wrap_asset_ix(&authority, PYTH_POOL, underlying(1));
+    let result = env.process(&[wrap], &[&user]).await;
+    assert_clone_error(result, CloneError::StatusPreventsAction);
+    let unwrap = env.unwrap_onasset_ix(&authority, PYTH_POOL, onasset(1));
+    env.process(&[unwrap], &[&user]).await.unwrap();
+    assert_eq!(env.onasset_balance(&authority, PYTH_POOL).await, onasset(4));
+}